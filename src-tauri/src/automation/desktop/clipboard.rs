@@ -0,0 +1,82 @@
+//! System clipboard content
+//!
+//! The clipboard holds one piece of data offered in several formats at once,
+//! like rich text copied from a browser that also carries a plain-text
+//! version, so content is read and written as a whole.
+
+/// Clipboard data in the formats the automation understands
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ClipboardContent {
+    pub text: Option<String>,
+    /// HTML fragment, without any platform-specific framing
+    pub html: Option<String>,
+    /// PNG-encoded image
+    pub png: Option<Vec<u8>>,
+}
+
+impl ClipboardContent {
+    pub fn text(text: impl Into<String>) -> Self {
+        Self {
+            text: Some(text.into()),
+            ..Default::default()
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.text.is_none() && self.html.is_none() && self.png.is_none()
+    }
+}
+
+/// Wrap an HTML fragment in the Windows "HTML Format" header
+///
+/// The header gives byte offsets of the whole document and of the fragment
+/// between the `StartFragment`/`EndFragment` comments.
+#[cfg(any(target_os = "windows", test))]
+pub(crate) fn encode_cf_html(fragment: &str) -> String {
+    const HEADER: &str = "Version:0.9\r\nStartHTML:0000000000\r\nEndHTML:0000000000\r\n\
+        StartFragment:0000000000\r\nEndFragment:0000000000\r\n";
+    const PREFIX: &str = "<html><body>\r\n<!--StartFragment-->";
+    const SUFFIX: &str = "<!--EndFragment-->\r\n</body></html>";
+
+    let start_html = HEADER.len();
+    let start_fragment = start_html + PREFIX.len();
+    let end_fragment = start_fragment + fragment.len();
+    let end_html = end_fragment + SUFFIX.len();
+
+    format!(
+        "Version:0.9\r\nStartHTML:{:010}\r\nEndHTML:{:010}\r\nStartFragment:{:010}\r\nEndFragment:{:010}\r\n{}{}{}",
+        start_html, end_html, start_fragment, end_fragment, PREFIX, fragment, SUFFIX
+    )
+}
+
+/// Fragment of a Windows "HTML Format" document, per its header offsets
+#[cfg(any(target_os = "windows", test))]
+pub(crate) fn decode_cf_html(data: &[u8]) -> Option<String> {
+    let text = String::from_utf8_lossy(data);
+    let offset = |name: &str| -> Option<usize> {
+        let line = text.lines().find_map(|line| line.strip_prefix(name))?;
+        line.trim().parse().ok()
+    };
+
+    let start = offset("StartFragment:")?;
+    let end = offset("EndFragment:")?.min(data.len());
+    if start > end {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&data[start..end]).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cf_html_round_trip() {
+        let fragment = "<b>Grüße</b> & <i>more</i>";
+        let encoded = encode_cf_html(fragment);
+
+        assert!(encoded.starts_with("Version:0.9\r\nStartHTML:0000000"));
+        assert_eq!(decode_cf_html(encoded.as_bytes()).as_deref(), Some(fragment));
+        assert_eq!(decode_cf_html(b"<b>no header</b>"), None);
+    }
+}
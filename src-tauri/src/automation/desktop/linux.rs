@@ -1,290 +1,597 @@
 #![cfg(target_os = "linux")]
 
-use crate::automation::{AutomationError, AutomationResult, ClickType, InputMethod, Rect};
+use crate::automation::keys::{Key, KeyCombo};
+use crate::automation::{AutomationError, AutomationResult, ClickType, InputMethod, MouseButton, Rect};
 use crate::element::UIElement;
 use async_trait::async_trait;
-use super::DesktopAutomation;
-use std::collections::HashMap;
-use std::process::Command;
+use super::x11::{self, X11Display, X11Window};
+use super::{
+    assemble_tree, selection, ClipboardContent, DesktopAutomation, ElementTreeNode, WindowInfo, WindowState,
+    MAX_TREE_NODES,
+};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use atspi::{
     connection::AccessibilityConnection,
-    proxy::accessible::AccessibleProxy,
-    AccessibilityBus,
-    Role,
+    proxy::accessible::{AccessibleProxy, ObjectRefExt},
+    proxy::component::ComponentProxy,
+    CoordType, ObjectRef, Role, State,
 };
-use zbus::Connection;
+use tokio::sync::OnceCell;
+use zbus::proxy::CacheProperties;
+
+/// Bus name and path of the AT-SPI desktop root
+const REGISTRY_BUS: &str = "org.a11y.atspi.Registry";
+const ROOT_PATH: &str = "/org/a11y/atspi/accessible/root";
+
+/// Path AT-SPI returns instead of an object when there is none
+const NULL_PATH: &str = "/org/a11y/atspi/null";
 
-/// Linux automation using AT-SPI for accessibility and xdotool for input
+/// Maximum nesting followed when hit-testing or walking up the tree
+const MAX_TREE_DEPTH: usize = 64;
+
+/// Maximum depth searched below each application when finding elements
+const MAX_SEARCH_DEPTH: usize = 16;
+
+/// Linux automation using AT-SPI for accessibility and XTEST for input
 pub struct LinuxAutomation {
-    connection: Option<AccessibilityConnection>,
+    connection: OnceCell<AccessibilityConnection>,
+    display: OnceCell<Arc<X11Display>>,
 }
 
-unsafe impl Send for LinuxAutomation {}
-unsafe impl Sync for LinuxAutomation {}
-
 impl LinuxAutomation {
     pub fn new() -> Self {
         // Initialize AT-SPI connection lazily
-        Self { connection: None }
+        Self {
+            connection: OnceCell::new(),
+            display: OnceCell::new(),
+        }
+    }
+
+    /// Connection to the X server for input and screen capture
+    async fn display(&self) -> AutomationResult<&Arc<X11Display>> {
+        self.display.get_or_try_init(|| async { X11Display::connect().map(Arc::new) }).await
+    }
+
+    /// Run requests to the X display in a blocking task, as they wait for
+    /// the server and input pauses between events
+    async fn with_display<T, F>(&self, f: F) -> AutomationResult<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&X11Display) -> AutomationResult<T> + Send + 'static,
+    {
+        let display = Arc::clone(self.display().await?);
+        tokio::task::spawn_blocking(move || f(&display))
+            .await
+            .map_err(|e| AutomationError::ExecutionFailed(format!("X11 request failed: {}", e)))?
     }
 
-    async fn ensure_connection(&mut self) -> AutomationResult<&AccessibilityConnection> {
-        if self.connection.is_none() {
-            let conn = AccessibilityConnection::new()
+    /// Connection to the accessibility bus
+    async fn connection(&self) -> AutomationResult<&zbus::Connection> {
+        let connection = self
+            .connection
+            .get_or_try_init(|| async {
+                AccessibilityConnection::new().await.map_err(|e| {
+                    AutomationError::ExecutionFailed(format!("Failed to connect to AT-SPI: {}", e))
+                })
+            })
+            .await?;
+        Ok(connection.connection())
+    }
+
+    async fn accessible<'a>(
+        conn: &zbus::Connection,
+        object: &'a ObjectRef,
+    ) -> AutomationResult<AccessibleProxy<'a>> {
+        object
+            .as_accessible_proxy(conn)
+            .await
+            .map_err(|e| AutomationError::ExecutionFailed(format!("AT-SPI error: {}", e)))
+    }
+
+    async fn component<'a>(
+        conn: &zbus::Connection,
+        object: &'a ObjectRef,
+    ) -> AutomationResult<ComponentProxy<'a>> {
+        let build = async {
+            ComponentProxy::builder(conn)
+                .destination(object.name.as_str())?
+                .path(object.path.as_str())?
+                .cache_properties(CacheProperties::No)
+                .build()
                 .await
-                .map_err(|e| AutomationError::ExecutionFailed(format!("Failed to connect to AT-SPI: {}", e)))?;
-            self.connection = Some(conn);
-        }
-        Ok(self.connection.as_ref().unwrap())
+        };
+        build
+            .await
+            .map_err(|e| AutomationError::ExecutionFailed(format!("AT-SPI error: {}", e)))
     }
 
-    /// Get element at screen position using AT-SPI
-    async fn get_element_at_position(&mut self, x: i32, y: i32) -> AutomationResult<UIElement> {
-        // Use xdotool to get window at position, then query AT-SPI
-        let output = Command::new("xdotool")
-            .args(["getmouselocation", "--shell"])
-            .output()
-            .map_err(|e| AutomationError::ExecutionFailed(format!("xdotool failed: {}", e)))?;
+    /// Applications registered on the accessibility bus
+    async fn applications(conn: &zbus::Connection) -> AutomationResult<Vec<ObjectRef>> {
+        let root = AccessibleProxy::builder(conn)
+            .destination(REGISTRY_BUS)
+            .and_then(|b| b.path(ROOT_PATH))
+            .map_err(|e| AutomationError::ExecutionFailed(format!("AT-SPI error: {}", e)))?
+            .cache_properties(CacheProperties::No)
+            .build()
+            .await
+            .map_err(|e| AutomationError::ExecutionFailed(format!("AT-SPI error: {}", e)))?;
+
+        root.get_children()
+            .await
+            .map_err(|e| AutomationError::ExecutionFailed(format!("Failed to list applications: {}", e)))
+    }
 
-        if !output.status.success() {
-            return Err(AutomationError::ExecutionFailed("xdotool getmouselocation failed".to_string()));
+    fn is_null(object: &ObjectRef) -> bool {
+        object.path.as_str() == NULL_PATH
+    }
+
+    /// Screen bounds of an accessible, if it implements `Component`
+    async fn extents(conn: &zbus::Connection, object: &ObjectRef) -> Option<Rect> {
+        let component = Self::component(conn, object).await.ok()?;
+        let (x, y, width, height) = component.get_extents(CoordType::Screen).await.ok()?;
+        Some(Rect { x, y, width, height })
+    }
+
+    /// Showing top-level window containing a point, preferring the active one
+    async fn window_at(&self, conn: &zbus::Connection, x: i32, y: i32) -> AutomationResult<ObjectRef> {
+        let mut candidate = None;
+
+        for app in Self::applications(conn).await? {
+            let Ok(app_proxy) = Self::accessible(conn, &app).await else {
+                continue;
+            };
+            let Ok(windows) = app_proxy.get_children().await else {
+                continue;
+            };
+
+            for window in windows {
+                let Ok(proxy) = Self::accessible(conn, &window).await else {
+                    continue;
+                };
+                let Ok(states) = proxy.get_state().await else {
+                    continue;
+                };
+                if !states.contains(State::Showing) {
+                    continue;
+                }
+                let Some(bounds) = Self::extents(conn, &window).await else {
+                    continue;
+                };
+                if !rect_contains(&bounds, x, y) {
+                    continue;
+                }
+
+                if states.contains(State::Active) {
+                    return Ok(window);
+                }
+                candidate.get_or_insert(window);
+            }
         }
 
-        // Get window ID at position
-        let output = Command::new("xdotool")
-            .args(["mousemove", &x.to_string(), &y.to_string()])
-            .output();
+        candidate.ok_or_else(|| {
+            AutomationError::ElementNotFound(format!("No accessible window at ({}, {})", x, y))
+        })
+    }
 
-        let window_output = Command::new("xdotool")
-            .args(["getwindowfocus"])
-            .output()
-            .map_err(|e| AutomationError::ExecutionFailed(format!("xdotool failed: {}", e)))?;
+    /// Get the deepest accessible element at a screen position using AT-SPI
+    async fn get_element_at_position(&self, x: i32, y: i32) -> AutomationResult<UIElement> {
+        let conn = self.connection().await?;
+        let mut current = self.window_at(conn, x, y).await?;
+
+        for _ in 0..MAX_TREE_DEPTH {
+            let Ok(component) = Self::component(conn, &current).await else {
+                break;
+            };
+            match component.get_accessible_at_point(x, y, CoordType::Screen).await {
+                Ok(child) if !Self::is_null(&child) && child != current => current = child,
+                _ => break,
+            }
+        }
 
-        let window_id = String::from_utf8_lossy(&window_output.stdout).trim().to_string();
+        self.describe_accessible(conn, &current).await
+    }
 
-        // Get window name
-        let name_output = Command::new("xdotool")
-            .args(["getwindowname", &window_id])
-            .output()
-            .ok();
+    /// Build a `UIElement` from an accessible: role, name, states, bounds,
+    /// application, process and the path of ancestors from the application down
+    async fn describe_accessible(
+        &self,
+        conn: &zbus::Connection,
+        object: &ObjectRef,
+    ) -> AutomationResult<UIElement> {
+        let accessible = Self::accessible(conn, object).await?;
+        let mut element = Self::accessible_element(conn, object, &accessible).await;
+
+        if let Ok(app) = accessible.get_application().await {
+            if let Ok(app_proxy) = Self::accessible(conn, &app).await {
+                if let Ok(app_name) = app_proxy.name().await {
+                    element.attributes.insert("application".to_string(), app_name);
+                }
+            }
+        }
 
-        let window_name = name_output
-            .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
-            .unwrap_or_else(|| "Unknown".to_string());
+        if let Some(pid) = Self::process_id(conn, object).await {
+            element.attributes.insert("pid".to_string(), pid.to_string());
+            element.process_name = process_name(pid);
+        }
 
-        // Create element with available information
-        let element = UIElement::new(
-            window_name.clone(),
-            "Window".to_string(),
-            Rect { x, y, width: 100, height: 50 },
-        );
+        let (path, window_title) = self.ancestor_path(conn, object).await;
+        element.window_title = window_title;
+        element.attributes.insert("path".to_string(), path);
 
         Ok(element)
     }
 
-    /// Perform mouse click using xdotool
-    fn perform_click(&self, x: i32, y: i32, click_type: &ClickType) -> AutomationResult<()> {
-        // Move mouse to position
-        Command::new("xdotool")
-            .args(["mousemove", &x.to_string(), &y.to_string()])
-            .output()
-            .map_err(|e| AutomationError::ExecutionFailed(format!("xdotool mousemove failed: {}", e)))?;
-
-        // Perform click
-        let button = match click_type {
-            ClickType::Single | ClickType::Double => "1",
-            ClickType::Right => "3",
-        };
+    /// Role, name, identifiers, attributes, states and bounds of an accessible
+    async fn accessible_element(
+        conn: &zbus::Connection,
+        object: &ObjectRef,
+        accessible: &AccessibleProxy<'_>,
+    ) -> UIElement {
+        let name = accessible.name().await.unwrap_or_default();
+        let role = accessible.get_role().await.unwrap_or(Role::Unknown);
+        let bounds = Self::extents(conn, object).await.unwrap_or(Rect {
+            x: 0,
+            y: 0,
+            width: 0,
+            height: 0,
+        });
+
+        let mut element = UIElement::new(name, role.name().to_string(), bounds);
+
+        if let Ok(id) = accessible.accessible_id().await {
+            if !id.is_empty() {
+                element.automation_id = Some(id);
+            }
+        }
 
-        let click_count = match click_type {
-            ClickType::Double => "2",
-            _ => "1",
-        };
+        let attributes = accessible.get_attributes().await.unwrap_or_default();
+        element.class_name = attributes.get("class").cloned().filter(|c| !c.is_empty());
+        element.attributes.extend(attributes);
 
-        Command::new("xdotool")
-            .args(["click", "--repeat", click_count, button])
-            .output()
-            .map_err(|e| AutomationError::ExecutionFailed(format!("xdotool click failed: {}", e)))?;
+        if let Ok(states) = accessible.get_state().await {
+            let states: Vec<String> = states.iter().map(String::from).collect();
+            element.attributes.insert("states".to_string(), states.join(","));
+        }
+        if let Ok(description) = accessible.description().await {
+            if !description.is_empty() {
+                element.attributes.insert("description".to_string(), description);
+            }
+        }
+        element
+            .attributes
+            .insert("atspiObject".to_string(), format!("{}:{}", object.name, object.path));
 
-        Ok(())
+        element
     }
 
-    /// Type text using xdotool
-    fn type_text(&self, text: &str) -> AutomationResult<()> {
-        Command::new("xdotool")
-            .args(["type", "--clearmodifiers", text])
-            .output()
-            .map_err(|e| AutomationError::ExecutionFailed(format!("xdotool type failed: {}", e)))?;
+    /// Window or application whose tree is inspected
+    async fn tree_root(&self, conn: &zbus::Connection, window: Option<&str>) -> AutomationResult<ObjectRef> {
+        let wanted = window.map(str::to_lowercase);
+
+        for app in Self::applications(conn).await? {
+            let Ok(app_proxy) = Self::accessible(conn, &app).await else {
+                continue;
+            };
+            if let Some(wanted) = &wanted {
+                let app_name = app_proxy.name().await.unwrap_or_default();
+                if app_name.to_lowercase().contains(wanted.as_str()) {
+                    return Ok(app);
+                }
+            }
 
-        Ok(())
-    }
+            let Ok(windows) = app_proxy.get_children().await else {
+                continue;
+            };
+            for candidate in windows {
+                let Ok(proxy) = Self::accessible(conn, &candidate).await else {
+                    continue;
+                };
+                let found = match &wanted {
+                    Some(wanted) => proxy
+                        .name()
+                        .await
+                        .map(|name| name.to_lowercase().contains(wanted.as_str()))
+                        .unwrap_or(false),
+                    None => proxy
+                        .get_state()
+                        .await
+                        .map(|states| states.contains(State::Active))
+                        .unwrap_or(false),
+                };
+                if found {
+                    return Ok(candidate);
+                }
+            }
+        }
 
-    /// Get window title at position
-    fn get_window_title_at(&self, x: i32, y: i32) -> Option<String> {
-        // Move mouse temporarily and get window
-        let _ = Command::new("xdotool")
-            .args(["mousemove", &x.to_string(), &y.to_string()])
-            .output();
+        Err(AutomationError::ElementNotFound(match window {
+            Some(window) => format!("No window or application matching '{}'", window),
+            None => "No active window".to_string(),
+        }))
+    }
 
-        let output = Command::new("xdotool")
-            .args(["getactivewindow", "getwindowname"])
-            .output()
-            .ok()?;
+    /// Walk the tree below `root` breadth-first, `depth` levels deep
+    async fn element_tree(
+        &self,
+        conn: &zbus::Connection,
+        root: &ObjectRef,
+        depth: usize,
+    ) -> AutomationResult<ElementTreeNode> {
+        let root_element = self.describe_accessible(conn, root).await?;
+        let root_path = root_element.attributes.get("path").cloned().unwrap_or_default();
+
+        // Elements, their parent's index, and the object to expand them from
+        let mut nodes: Vec<(UIElement, Option<usize>)> = Vec::new();
+        let mut queue: VecDeque<(ObjectRef, usize, usize)> = VecDeque::new();
+        queue.push_back((root.clone(), 0, 0));
+        nodes.push((root_element.clone(), None));
+
+        while let Some((object, index, level)) = queue.pop_front() {
+            if level >= depth {
+                continue;
+            }
+            let Ok(proxy) = Self::accessible(conn, &object).await else {
+                continue;
+            };
+            let Ok(children) = proxy.get_children().await else {
+                continue;
+            };
+
+            let parent_path = if index == 0 {
+                root_path.clone()
+            } else {
+                nodes[index].0.attributes.get("path").cloned().unwrap_or_default()
+            };
+
+            for (position, child) in children.into_iter().enumerate() {
+                if nodes.len() >= MAX_TREE_NODES {
+                    log::warn!("Accessibility tree truncated at {} elements", MAX_TREE_NODES);
+                    return assemble_tree(nodes).ok_or_else(|| {
+                        AutomationError::ExecutionFailed("Empty accessibility tree".to_string())
+                    });
+                }
+                let Ok(child_proxy) = Self::accessible(conn, &child).await else {
+                    continue;
+                };
+
+                let mut element = Self::accessible_element(conn, &child, &child_proxy).await;
+                let segment = path_segment(&element.control_type, &element.name, position);
+                element
+                    .attributes
+                    .insert("path".to_string(), format!("{}/{}", parent_path, segment));
+                element.window_title = root_element.window_title.clone();
+                element.process_name = root_element.process_name.clone();
+                for key in ["application", "pid"] {
+                    if let Some(value) = root_element.attributes.get(key) {
+                        element.attributes.insert(key.to_string(), value.clone());
+                    }
+                }
 
-        if output.status.success() {
-            Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
-        } else {
-            None
+                nodes.push((element, Some(index)));
+                queue.push_back((child, nodes.len() - 1, level + 1));
+            }
         }
+
+        assemble_tree(nodes)
+            .ok_or_else(|| AutomationError::ExecutionFailed("Empty accessibility tree".to_string()))
     }
 
-    /// Get process name from window
-    fn get_process_name(&self, window_id: &str) -> Option<String> {
-        let output = Command::new("xdotool")
-            .args(["getwindowpid", window_id])
-            .output()
-            .ok()?;
+    /// Process owning an accessible, from its connection on the accessibility bus
+    async fn process_id(conn: &zbus::Connection, object: &ObjectRef) -> Option<u32> {
+        let dbus = zbus::fdo::DBusProxy::new(conn).await.ok()?;
+        let bus_name = zbus::names::BusName::try_from(object.name.as_str()).ok()?;
+        dbus.get_connection_unix_process_id(bus_name).await.ok()
+    }
+
+    /// Path like `/frame[@name='Invoice']/panel[2]/push button[@name='OK']` from
+    /// the application down to the element, and the title of its window
+    async fn ancestor_path(&self, conn: &zbus::Connection, object: &ObjectRef) -> (String, Option<String>) {
+        let mut segments = Vec::new();
+        let mut window_title = None;
+        let mut current = object.clone();
+
+        for _ in 0..MAX_TREE_DEPTH {
+            let Ok(proxy) = Self::accessible(conn, &current).await else {
+                break;
+            };
+            let role = proxy.get_role().await.unwrap_or(Role::Unknown);
+            if role == Role::Application {
+                break;
+            }
+
+            let name = proxy.name().await.unwrap_or_default();
+            if window_title.is_none() && matches!(role, Role::Frame | Role::Window | Role::Dialog) {
+                window_title = Some(name.clone()).filter(|n| !n.is_empty());
+            }
+
+            let index = if name.is_empty() {
+                proxy.get_index_in_parent().await.unwrap_or(0).max(0) as usize
+            } else {
+                0
+            };
+            segments.push(path_segment(role.name(), &name, index));
 
-        if !output.status.success() {
-            return None;
+            match proxy.parent().await {
+                Ok(parent) if !Self::is_null(&parent) => current = parent,
+                _ => break,
+            }
         }
 
-        let pid = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        segments.reverse();
+        (format!("/{}", segments.join("/")), window_title)
+    }
 
-        // Get process name from /proc
-        let cmdline = std::fs::read_to_string(format!("/proc/{}/comm", pid)).ok()?;
-        Some(cmdline.trim().to_string())
+    /// Move to a point and click
+    async fn perform_click(&self, x: i32, y: i32, click_type: &ClickType) -> AutomationResult<()> {
+        let (button, count) = match click_type {
+            ClickType::Single => (x11::BUTTON_LEFT, 1),
+            ClickType::Double => (x11::BUTTON_LEFT, 2),
+            ClickType::Right => (x11::BUTTON_RIGHT, 1),
+        };
+        self.with_display(move |display| display.click(x, y, button, count)).await
     }
 
-    /// Take screenshot using scrot or gnome-screenshot
-    fn take_screenshot(&self, rect: Option<Rect>) -> AutomationResult<Vec<u8>> {
-        let temp_path = "/tmp/batata_rpa_screenshot.png";
+    /// Find element matching criteria using AT-SPI
+    async fn find_element_by_criteria(
+        &self,
+        criteria: &HashMap<&str, &str>,
+        deadline: Option<Instant>,
+    ) -> Option<UIElement> {
+        self.find_all_by_criteria(criteria, 1, false, deadline).await.into_iter().next()
+    }
 
-        let result = match rect {
-            Some(r) => {
-                // Use scrot with geometry
-                Command::new("scrot")
-                    .args([
-                        "-a", &format!("{},{},{},{}", r.x, r.y, r.width, r.height),
-                        temp_path
-                    ])
-                    .output()
-            }
-            None => {
-                // Full screen
-                Command::new("scrot")
-                    .args([temp_path])
-                    .output()
+    /// Find up to `limit` elements matching criteria, searching the showing
+    /// part of every application's accessibility tree breadth-first
+    ///
+    /// Values must match exactly when `exact` is set, and may match partially
+    /// otherwise. The walk stops early, returning what was found so far, once
+    /// `deadline` has passed.
+    async fn find_all_by_criteria(
+        &self,
+        criteria: &HashMap<&str, &str>,
+        limit: usize,
+        exact: bool,
+        deadline: Option<Instant>,
+    ) -> Vec<UIElement> {
+        let applications = match self.connection().await {
+            Ok(conn) => Self::applications(conn).await.map(|apps| (conn, apps)),
+            Err(e) => Err(e),
+        };
+        let (conn, applications) = match applications {
+            Ok(found) => found,
+            Err(e) => {
+                log::debug!("AT-SPI unavailable, searching windows only: {}", e);
+                return self.find_windows_by_name(criteria, limit, exact).await;
             }
         };
 
-        match result {
-            Ok(output) if output.status.success() => {
-                let data = std::fs::read(temp_path)
-                    .map_err(|e| AutomationError::ExecutionFailed(format!("Failed to read screenshot: {}", e)))?;
-                let _ = std::fs::remove_file(temp_path);
-                Ok(data)
+        let mut found = Vec::new();
+        let mut queue: VecDeque<(ObjectRef, usize)> =
+            applications.into_iter().map(|app| (app, 0)).collect();
+
+        while let Some((object, depth)) = queue.pop_front() {
+            if found.len() >= limit || deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                break;
             }
-            Ok(output) => {
-                // Try gnome-screenshot as fallback
-                let fallback = Command::new("gnome-screenshot")
-                    .args(["-f", temp_path])
-                    .output();
-
-                match fallback {
-                    Ok(out) if out.status.success() => {
-                        let data = std::fs::read(temp_path)
-                            .map_err(|e| AutomationError::ExecutionFailed(format!("Failed to read screenshot: {}", e)))?;
-                        let _ = std::fs::remove_file(temp_path);
-                        Ok(data)
+            let Ok(proxy) = Self::accessible(conn, &object).await else {
+                continue;
+            };
+
+            // Applications themselves are not elements
+            if depth > 0 {
+                if Self::matches_criteria(&proxy, criteria, exact).await {
+                    if let Ok(element) = self.describe_accessible(conn, &object).await {
+                        found.push(element);
                     }
-                    _ => Err(AutomationError::ExecutionFailed(
-                        "Screenshot failed. Please install scrot or gnome-screenshot".to_string()
-                    ))
+                }
+
+                // Hidden subtrees are skipped
+                match proxy.get_state().await {
+                    Ok(states) if states.contains(State::Showing) => {}
+                    _ => continue,
+                }
+            }
+
+            if depth < MAX_SEARCH_DEPTH {
+                if let Ok(children) = proxy.get_children().await {
+                    queue.extend(children.into_iter().map(|child| (child, depth + 1)));
                 }
             }
-            Err(e) => Err(AutomationError::ExecutionFailed(format!("Screenshot command failed: {}", e)))
         }
-    }
 
-    /// Find element matching criteria using AT-SPI
-    async fn find_element_by_criteria(&mut self, criteria: &HashMap<&str, &str>) -> Option<UIElement> {
-        // This is a simplified search - full AT-SPI traversal would be more complex
-        // For now, use xdotool to search by window name
-
-        if let Some(name) = criteria.get("name").or(criteria.get("title")) {
-            let output = Command::new("xdotool")
-                .args(["search", "--name", name])
-                .output()
-                .ok()?;
-
-            if output.status.success() {
-                let window_ids: Vec<&str> = std::str::from_utf8(&output.stdout)
-                    .ok()?
-                    .trim()
-                    .lines()
-                    .collect();
-
-                if let Some(window_id) = window_ids.first() {
-                    // Get window geometry
-                    let geo_output = Command::new("xdotool")
-                        .args(["getwindowgeometry", "--shell", window_id])
-                        .output()
-                        .ok()?;
-
-                    let geo_str = String::from_utf8_lossy(&geo_output.stdout);
-                    let (x, y, width, height) = parse_geometry(&geo_str)?;
-
-                    // Get window name
-                    let name_output = Command::new("xdotool")
-                        .args(["getwindowname", window_id])
-                        .output()
-                        .ok()?;
-
-                    let window_name = String::from_utf8_lossy(&name_output.stdout).trim().to_string();
-
-                    let mut element = UIElement::new(
-                        window_name,
-                        "Window".to_string(),
-                        Rect { x, y, width, height },
-                    );
-
-                    element.window_title = Some(window_id.to_string());
-
-                    if let Some(process_name) = self.get_process_name(window_id) {
-                        element.process_name = Some(process_name);
-                    }
+        found
+    }
 
-                    return Some(element);
+    async fn matches_criteria(
+        proxy: &AccessibleProxy<'_>,
+        criteria: &HashMap<&str, &str>,
+        exact: bool,
+    ) -> bool {
+        for (key, expected_value) in criteria {
+            let actual_value = match key.to_lowercase().as_str() {
+                "name" | "title" => proxy.name().await.ok(),
+                "automationid" | "id" => proxy.accessible_id().await.ok(),
+                "classname" | "class" => proxy
+                    .get_attributes()
+                    .await
+                    .ok()
+                    .and_then(|attributes| attributes.get("class").cloned()),
+                "role" | "controltype" | "type" => {
+                    proxy.get_role().await.ok().map(|role| role.name().to_string())
                 }
+                _ => None,
+            };
+
+            match actual_value {
+                Some(actual) if super::criterion_matches(&actual, expected_value, exact) => continue,
+                _ => return false,
             }
         }
+        true
+    }
 
-        None
+    /// Find top-level windows by title over X11, when AT-SPI is not available
+    async fn find_windows_by_name(
+        &self,
+        criteria: &HashMap<&str, &str>,
+        limit: usize,
+        exact: bool,
+    ) -> Vec<UIElement> {
+        let Some(name) = criteria.get("name").or(criteria.get("title")) else {
+            return Vec::new();
+        };
+        let windows = match self.with_display(|display| display.windows()).await {
+            Ok(windows) => windows,
+            Err(e) => {
+                log::warn!("Failed to list windows: {}", e);
+                return Vec::new();
+            }
+        };
+
+        windows
+            .into_iter()
+            .filter(|window| super::criterion_matches(&window.title, name, exact))
+            .map(window_element)
+            .take(limit)
+            .collect()
     }
 }
 
-/// Parse xdotool geometry output
-fn parse_geometry(geo_str: &str) -> Option<(i32, i32, i32, i32)> {
-    let mut x = 0;
-    let mut y = 0;
-    let mut width = 0;
-    let mut height = 0;
-
-    for line in geo_str.lines() {
-        if line.starts_with("X=") {
-            x = line[2..].parse().ok()?;
-        } else if line.starts_with("Y=") {
-            y = line[2..].parse().ok()?;
-        } else if line.starts_with("WIDTH=") {
-            width = line[6..].parse().ok()?;
-        } else if line.starts_with("HEIGHT=") {
-            height = line[7..].parse().ok()?;
-        }
+/// Describe an X window as an element
+fn window_element(window: X11Window) -> UIElement {
+    let mut element = UIElement::new(window.title.clone(), "Window".to_string(), window.bounds);
+    element.window_title = Some(window.title);
+    element.attributes.insert("windowId".to_string(), window.id.to_string());
+
+    if let Some(pid) = window.pid {
+        element.attributes.insert("pid".to_string(), pid.to_string());
+        element.process_name = process_name(pid);
+    }
+
+    element
+}
+
+/// Command name of a process
+fn process_name(pid: u32) -> Option<String> {
+    std::fs::read_to_string(format!("/proc/{}/comm", pid))
+        .ok()
+        .map(|comm| comm.trim().to_string())
+}
+
+/// One step of an element path; unnamed elements are told apart by their position
+fn path_segment(role: &str, name: &str, index: usize) -> String {
+    if name.is_empty() {
+        format!("{}[{}]", role, index + 1)
+    } else {
+        format!("{}[@name='{}']", role, name.replace('\'', "\\'"))
     }
+}
 
-    Some((x, y, width, height))
+fn rect_contains(rect: &Rect, x: i32, y: i32) -> bool {
+    x >= rect.x && x < rect.x + rect.width && y >= rect.y && y < rect.y + rect.height
 }
 
 #[async_trait]
@@ -293,7 +600,7 @@ impl DesktopAutomation for LinuxAutomation {
         let (x, y) = element.bounds.center();
         log::info!("Linux: Click at ({}, {}) with type {:?}", x, y, click_type);
 
-        self.perform_click(x, y, &click_type)?;
+        self.perform_click(x, y, &click_type).await?;
 
         // Small delay after click
         tokio::time::sleep(std::time::Duration::from_millis(50)).await;
@@ -314,27 +621,27 @@ impl DesktopAutomation for LinuxAutomation {
         match method {
             InputMethod::Type => {
                 // Click to focus
-                self.perform_click(x, y, &ClickType::Single)?;
+                self.perform_click(x, y, &ClickType::Single).await?;
                 tokio::time::sleep(std::time::Duration::from_millis(100)).await;
 
                 // Type the text
-                self.type_text(text)?;
+                let text = text.to_string();
+                self.with_display(move |display| display.type_text(&text)).await?;
             }
             InputMethod::Set => {
                 // For Set mode, we also use typing on Linux
                 // (clipboard paste could be an alternative)
-                self.perform_click(x, y, &ClickType::Single)?;
+                self.perform_click(x, y, &ClickType::Single).await?;
                 tokio::time::sleep(std::time::Duration::from_millis(100)).await;
 
                 // Select all and replace
-                Command::new("xdotool")
-                    .args(["key", "ctrl+a"])
-                    .output()
-                    .ok();
+                self.with_display(|display| display.press_keys(&[x11::XK_CONTROL_L, x11::char_keysym('a')]))
+                    .await?;
 
                 tokio::time::sleep(std::time::Duration::from_millis(50)).await;
 
-                self.type_text(text)?;
+                let text = text.to_string();
+                self.with_display(move |display| display.type_text(&text)).await?;
             }
         }
 
@@ -359,26 +666,18 @@ impl DesktopAutomation for LinuxAutomation {
     async fn wait_element(&self, locator: &str, timeout_ms: u64) -> AutomationResult<UIElement> {
         log::info!("Linux: Wait for element '{}' with timeout {}ms", locator, timeout_ms);
 
-        let start = std::time::Instant::now();
-        let timeout = std::time::Duration::from_millis(timeout_ms);
-        let poll_interval = std::time::Duration::from_millis(100);
+        let deadline = Instant::now() + Duration::from_millis(timeout_ms);
+        let poll_interval = Duration::from_millis(100);
 
-        // Parse locator
-        let criteria: HashMap<&str, &str> = locator
-            .split(',')
-            .filter_map(|part| {
-                let mut parts = part.trim().splitn(2, ':');
-                Some((parts.next()?, parts.next()?))
-            })
-            .collect();
-
-        // Create mutable self for async operations
-        let mut self_mut = LinuxAutomation::new();
+        let criteria = super::parse_criteria(locator);
 
-        while start.elapsed() < timeout {
-            if let Some(element) = self_mut.find_element_by_criteria(&criteria).await {
+        loop {
+            if let Some(element) = self.find_element_by_criteria(&criteria, Some(deadline)).await {
                 return Ok(element);
             }
+            if Instant::now() >= deadline {
+                break;
+            }
             tokio::time::sleep(poll_interval).await;
         }
 
@@ -388,11 +687,30 @@ impl DesktopAutomation for LinuxAutomation {
         )))
     }
 
+    async fn find_elements(&self, locator: &str) -> AutomationResult<Vec<UIElement>> {
+        let criteria = super::parse_criteria(locator);
+        Ok(self.find_all_by_criteria(&criteria, super::MAX_FOUND_ELEMENTS, true, None).await)
+    }
+
+    async fn find_element(&self, locator: &str) -> AutomationResult<UIElement> {
+        let criteria = super::parse_criteria(locator);
+        self.find_element_by_criteria(&criteria, None)
+            .await
+            .ok_or_else(|| AutomationError::ElementNotFound(locator.to_string()))
+    }
+
     async fn capture_element(&self, x: i32, y: i32) -> AutomationResult<UIElement> {
         log::info!("Linux: Capture element at ({}, {})", x, y);
 
-        let mut self_mut = LinuxAutomation::new();
-        self_mut.get_element_at_position(x, y).await
+        self.get_element_at_position(x, y).await
+    }
+
+    async fn get_element_tree(&self, window: Option<&str>, depth: usize) -> AutomationResult<ElementTreeNode> {
+        log::info!("Linux: Get accessibility tree of {:?} ({} levels)", window, depth);
+
+        let conn = self.connection().await?;
+        let root = self.tree_root(conn, window).await?;
+        self.element_tree(conn, &root, depth).await
     }
 
     async fn get_element_bounds(&self, element: &UIElement) -> AutomationResult<Rect> {
@@ -401,6 +719,190 @@ impl DesktopAutomation for LinuxAutomation {
 
     async fn screenshot(&self, rect: Option<Rect>) -> AutomationResult<Vec<u8>> {
         log::info!("Linux: Take screenshot with rect {:?}", rect);
-        self.take_screenshot(rect)
+        self.with_display(move |display| display.capture(rect)).await
+    }
+
+    async fn scroll(&self, dx: i32, dy: i32, at: Option<(i32, i32)>) -> AutomationResult<()> {
+        log::info!("Linux: Scroll ({}, {}) at {:?}", dx, dy, at);
+
+        self.with_display(move |display| {
+            if let Some((x, y)) = at {
+                display.move_to(x, y)?;
+            }
+            display.scroll(dx, dy)
+        })
+        .await
+    }
+
+    async fn mouse_move(&self, x: i32, y: i32, smooth: bool) -> AutomationResult<()> {
+        log::info!("Linux: Move mouse to ({}, {})", x, y);
+
+        if !smooth {
+            return self.with_display(move |display| display.move_to(x, y)).await;
+        }
+
+        let (from_x, from_y, _) = self.with_display(|display| display.pointer_state()).await?;
+        for (x, y) in super::glide_path((from_x, from_y), (x, y)) {
+            self.with_display(move |display| display.move_to(x, y)).await?;
+            tokio::time::sleep(std::time::Duration::from_millis(super::GLIDE_STEP_MS)).await;
+        }
+        Ok(())
+    }
+
+    async fn mouse_down(&self, button: MouseButton) -> AutomationResult<()> {
+        log::info!("Linux: Press {:?} mouse button", button);
+        let button = x11_button(button);
+        self.with_display(move |display| display.button(button, true)).await
+    }
+
+    async fn mouse_up(&self, button: MouseButton) -> AutomationResult<()> {
+        log::info!("Linux: Release {:?} mouse button", button);
+        let button = x11_button(button);
+        self.with_display(move |display| display.button(button, false)).await
+    }
+
+    async fn key_down(&self, key: Key) -> AutomationResult<()> {
+        log::info!("Linux: Press key {}", key);
+        let keysym = x11::key_keysym(key)?;
+        self.with_display(move |display| display.key(keysym, true)).await
+    }
+
+    async fn key_up(&self, key: Key) -> AutomationResult<()> {
+        log::info!("Linux: Release key {}", key);
+        let keysym = x11::key_keysym(key)?;
+        self.with_display(move |display| display.key(keysym, false)).await
+    }
+
+    async fn press_keys(&self, combo: &KeyCombo) -> AutomationResult<()> {
+        log::info!("Linux: Press keys {}", combo);
+
+        let keysyms = combo
+            .keys
+            .iter()
+            .map(|&key| x11::key_keysym(key))
+            .collect::<AutomationResult<Vec<_>>>()?;
+        for i in 0..combo.repeat {
+            if i > 0 {
+                tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+            }
+            let keysyms = keysyms.clone();
+            self.with_display(move |display| display.press_keys(&keysyms)).await?;
+        }
+        Ok(())
+    }
+
+    async fn type_text(&self, text: &str, per_char_delay_ms: u64) -> AutomationResult<()> {
+        log::info!("Linux: Type {} characters", text.chars().count());
+
+        if per_char_delay_ms == 0 {
+            let text = text.to_string();
+            return self.with_display(move |display| display.type_text(&text)).await;
+        }
+
+        for ch in text.chars() {
+            self.with_display(move |display| display.type_text(ch.encode_utf8(&mut [0; 4])))
+                .await?;
+            tokio::time::sleep(std::time::Duration::from_millis(per_char_delay_ms)).await;
+        }
+        Ok(())
+    }
+
+    async fn list_windows(&self) -> AutomationResult<Vec<WindowInfo>> {
+        self.with_display(|display| {
+            let active = display.active_window();
+            Ok(display
+                .windows()?
+                .into_iter()
+                .map(|window| {
+                    let state = match display.window_state(window.id) {
+                        (true, _) => WindowState::Minimized,
+                        (false, true) => WindowState::Maximized,
+                        (false, false) => WindowState::Normal,
+                    };
+                    WindowInfo {
+                        id: window.id as u64,
+                        process: window.pid.and_then(process_name),
+                        focused: active == Some(window.id),
+                        title: window.title,
+                        pid: window.pid,
+                        bounds: window.bounds,
+                        state,
+                    }
+                })
+                .collect())
+        })
+        .await
+    }
+
+    async fn activate_window(&self, window: &WindowInfo) -> AutomationResult<()> {
+        log::info!("Linux: Activate window '{}'", window.title);
+        let id = x11_window(window)?;
+        self.with_display(move |display| display.activate(id)).await
+    }
+
+    async fn set_window_state(&self, window: &WindowInfo, state: WindowState) -> AutomationResult<()> {
+        log::info!("Linux: Set window '{}' to {:?}", window.title, state);
+
+        let id = x11_window(window)?;
+        self.with_display(move |display| match state {
+            WindowState::Minimized => display.minimize(id),
+            WindowState::Maximized => {
+                display.activate(id)?;
+                display.set_maximized(id, true)
+            }
+            WindowState::Normal => {
+                display.set_maximized(id, false)?;
+                display.activate(id)
+            }
+        })
+        .await
+    }
+
+    async fn set_window_bounds(&self, window: &WindowInfo, bounds: Rect) -> AutomationResult<()> {
+        log::info!("Linux: Move window '{}' to {:?}", window.title, bounds);
+
+        let id = x11_window(window)?;
+        // Maximized windows ignore moves until they are restored
+        let maximized = window.state == WindowState::Maximized;
+        self.with_display(move |display| {
+            if maximized {
+                display.set_maximized(id, false)?;
+            }
+            display.move_resize(id, &bounds)
+        })
+        .await
+    }
+
+    async fn close_window(&self, window: &WindowInfo) -> AutomationResult<()> {
+        log::info!("Linux: Close window '{}'", window.title);
+        let id = x11_window(window)?;
+        self.with_display(move |display| display.close(id)).await
+    }
+
+    async fn get_clipboard(&self) -> AutomationResult<ClipboardContent> {
+        tokio::task::spawn_blocking(selection::read)
+            .await
+            .map_err(|e| AutomationError::ExecutionFailed(format!("Clipboard read failed: {}", e)))?
+    }
+
+    async fn set_clipboard(&self, content: &ClipboardContent) -> AutomationResult<()> {
+        log::info!("Linux: Set clipboard");
+        let content = content.clone();
+        tokio::task::spawn_blocking(move || selection::write(content))
+            .await
+            .map_err(|e| AutomationError::ExecutionFailed(format!("Clipboard write failed: {}", e)))?
+    }
+}
+
+fn x11_window(window: &WindowInfo) -> AutomationResult<u32> {
+    u32::try_from(window.id)
+        .map_err(|_| AutomationError::InvalidParameter(format!("Invalid X11 window id: {}", window.id)))
+}
+
+fn x11_button(button: MouseButton) -> u8 {
+    match button {
+        MouseButton::Left => x11::BUTTON_LEFT,
+        MouseButton::Middle => x11::BUTTON_MIDDLE,
+        MouseButton::Right => x11::BUTTON_RIGHT,
     }
 }
@@ -1,20 +1,30 @@
 #![cfg(target_os = "macos")]
 
-use crate::automation::{AutomationError, AutomationResult, ClickType, InputMethod, Rect};
+use crate::automation::keys::Key;
+use crate::automation::{AutomationError, AutomationResult, ClickType, InputMethod, MouseButton, Rect};
 use crate::element::UIElement;
 use async_trait::async_trait;
-use super::DesktopAutomation;
+use super::{ClipboardContent, DesktopAutomation, ElementTreeNode, WindowInfo, WindowState, MAX_TREE_NODES};
 use std::collections::HashMap;
 use std::ffi::c_void;
+use std::sync::Mutex;
 
-use core_foundation::base::{CFRelease, CFTypeRef, TCFType, ToVoid};
+use core_foundation::array::{CFArrayGetCount, CFArrayGetValueAtIndex, CFArrayRef};
+use core_foundation::base::{CFRelease, CFRetain, CFTypeRef, TCFType, ToVoid};
 use core_foundation::string::{CFString, CFStringRef};
 use core_foundation::boolean::CFBoolean;
 
 use core_graphics::display::CGDisplay;
-use core_graphics::event::{CGEvent, CGEventTapLocation, CGEventType, CGMouseButton};
+use core_graphics::event::{
+    CGEvent, CGEventFlags, CGEventTapLocation, CGEventType, CGKeyCode, CGMouseButton, ScrollEventUnit,
+};
 use core_graphics::event_source::{CGEventSource, CGEventSourceStateID};
-use core_graphics::geometry::CGPoint;
+use core_graphics::geometry::{CGPoint, CGSize};
+use objc2_app_kit::{
+    NSApplicationActivationOptions, NSApplicationActivationPolicy, NSPasteboard, NSPasteboardTypeHTML,
+    NSPasteboardTypePNG, NSPasteboardTypeString, NSRunningApplication, NSWorkspace,
+};
+use objc2_foundation::{NSData, NSString};
 
 // Accessibility framework bindings
 #[link(name = "ApplicationServices", kind = "framework")]
@@ -61,6 +71,7 @@ const K_AX_ERROR_NO_VALUE: i32 = -25212;
 
 // Common attribute names
 const K_AX_ROLE_ATTRIBUTE: &str = "AXRole";
+const K_AX_SUBROLE_ATTRIBUTE: &str = "AXSubrole";
 const K_AX_TITLE_ATTRIBUTE: &str = "AXTitle";
 const K_AX_DESCRIPTION_ATTRIBUTE: &str = "AXDescription";
 const K_AX_VALUE_ATTRIBUTE: &str = "AXValue";
@@ -74,13 +85,23 @@ const K_AX_IDENTIFIER_ATTRIBUTE: &str = "AXIdentifier";
 const K_AX_WINDOW_ATTRIBUTE: &str = "AXWindow";
 const K_AX_WINDOWS_ATTRIBUTE: &str = "AXWindows";
 const K_AX_FOCUSED_WINDOW_ATTRIBUTE: &str = "AXFocusedWindow";
+const K_AX_FOCUSED_APPLICATION_ATTRIBUTE: &str = "AXFocusedApplication";
+const K_AX_MINIMIZED_ATTRIBUTE: &str = "AXMinimized";
+const K_AX_FULL_SCREEN_ATTRIBUTE: &str = "AXFullScreen";
+const K_AX_MAIN_ATTRIBUTE: &str = "AXMain";
+const K_AX_CLOSE_BUTTON_ATTRIBUTE: &str = "AXCloseButton";
 
 // Action names
 const K_AX_PRESS_ACTION: &str = "AXPress";
 const K_AX_SHOW_MENU_ACTION: &str = "AXShowMenu";
+const K_AX_RAISE_ACTION: &str = "AXRaise";
 
 pub struct MacOSAutomation {
     system_wide: AXUIElementRef,
+    /// Button held by `mouse_down`, turning moves into drags
+    pressed: Mutex<Option<MouseButton>>,
+    /// Modifiers held by `key_down`, applied to the key events that follow
+    modifiers: Mutex<CGEventFlags>,
 }
 
 unsafe impl Send for MacOSAutomation {}
@@ -89,7 +110,11 @@ unsafe impl Sync for MacOSAutomation {}
 impl MacOSAutomation {
     pub fn new() -> Self {
         let system_wide = unsafe { AXUIElementCreateSystemWide() };
-        Self { system_wide }
+        Self {
+            system_wide,
+            pressed: Mutex::new(None),
+            modifiers: Mutex::new(CGEventFlags::empty()),
+        }
     }
 
     fn check_accessibility(&self) -> AutomationResult<()> {
@@ -259,6 +284,11 @@ impl MacOSAutomation {
             element.automation_id = Some(identifier);
         }
 
+        // Secure text fields are text fields with their own subrole
+        if let Some(subrole) = self.get_attribute_string(ax_element, K_AX_SUBROLE_ATTRIBUTE) {
+            element.attributes.insert("subrole".to_string(), subrole);
+        }
+
         // Get value if available
         if let Some(value) = self.get_attribute_string(ax_element, K_AX_VALUE_ATTRIBUTE) {
             element.attributes.insert("value".to_string(), value);
@@ -327,6 +357,41 @@ impl MacOSAutomation {
         None
     }
 
+    fn event_source() -> AutomationResult<CGEventSource> {
+        CGEventSource::new(CGEventSourceStateID::HIDSystemState)
+            .map_err(|_| AutomationError::ExecutionFailed("Failed to create event source".to_string()))
+    }
+
+    fn pointer_location(&self) -> AutomationResult<CGPoint> {
+        CGEvent::new(Self::event_source()?)
+            .map(|event| event.location())
+            .map_err(|_| AutomationError::ExecutionFailed("Failed to read pointer location".to_string()))
+    }
+
+    /// Move the pointer, as a drag while a button is held
+    fn post_mouse_move(&self, point: CGPoint) -> AutomationResult<()> {
+        let pressed = *self.pressed.lock().unwrap_or_else(|e| e.into_inner());
+        let (event_type, button) = match pressed {
+            Some(MouseButton::Left) => (CGEventType::LeftMouseDragged, CGMouseButton::Left),
+            Some(MouseButton::Middle) => (CGEventType::OtherMouseDragged, CGMouseButton::Center),
+            Some(MouseButton::Right) => (CGEventType::RightMouseDragged, CGMouseButton::Right),
+            None => (CGEventType::MouseMoved, CGMouseButton::Left),
+        };
+
+        let event = CGEvent::new_mouse_event(Self::event_source()?, event_type, point, button)
+            .map_err(|_| AutomationError::ExecutionFailed("Failed to create mouse move event".to_string()))?;
+        event.post(CGEventTapLocation::HID);
+        Ok(())
+    }
+
+    /// Press or release a button at the pointer
+    fn post_button(&self, event_type: CGEventType, button: MouseButton) -> AutomationResult<()> {
+        let event = CGEvent::new_mouse_event(Self::event_source()?, event_type, self.pointer_location()?, cg_button(button))
+            .map_err(|_| AutomationError::ExecutionFailed("Failed to create mouse button event".to_string()))?;
+        event.post(CGEventTapLocation::HID);
+        Ok(())
+    }
+
     fn perform_click(&self, x: f64, y: f64, click_type: &ClickType) -> AutomationResult<()> {
         let source = CGEventSource::new(CGEventSourceStateID::HIDSystemState)
             .map_err(|_| AutomationError::ExecutionFailed("Failed to create event source".to_string()))?;
@@ -423,7 +488,7 @@ impl MacOSAutomation {
         Ok(())
     }
 
-    fn type_text(&self, text: &str) -> AutomationResult<()> {
+    fn send_text(&self, text: &str) -> AutomationResult<()> {
         let source = CGEventSource::new(CGEventSourceStateID::HIDSystemState)
             .map_err(|_| AutomationError::ExecutionFailed("Failed to create event source".to_string()))?;
 
@@ -434,6 +499,34 @@ impl MacOSAutomation {
         Ok(())
     }
 
+    /// Press or release a key with the held modifiers
+    ///
+    /// Characters without a key code are sent as Unicode text.
+    fn send_key(&self, key: Key, down: bool) -> AutomationResult<()> {
+        let mut modifiers = self.modifiers.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(flag) = modifier_flag(key) {
+            modifiers.set(flag, down);
+        }
+
+        let (code, text) = match (key, key_code(key)) {
+            (_, Some(code)) => (code, None),
+            (Key::Char(ch), None) => (0, Some(ch)),
+            (_, None) => {
+                return Err(AutomationError::InvalidParameter(format!("No key code for {}", key)))
+            }
+        };
+
+        let event = CGEvent::new_keyboard_event(Self::event_source()?, code, down)
+            .map_err(|_| AutomationError::ExecutionFailed("Failed to create keyboard event".to_string()))?;
+        if let Some(ch) = text {
+            let mut units = [0u16; 2];
+            event.set_string_from_utf16_unchecked(ch.encode_utf16(&mut units));
+        }
+        event.set_flags(*modifiers);
+        event.post(CGEventTapLocation::HID);
+        Ok(())
+    }
+
     fn type_character(&self, source: &CGEventSource, ch: char) -> AutomationResult<()> {
         let event = CGEvent::new_keyboard_event(source.clone(), 0, true)
             .map_err(|_| AutomationError::ExecutionFailed("Failed to create keyboard event".to_string()))?;
@@ -523,6 +616,7 @@ type AXValueRef = *mut c_void;
 #[link(name = "ApplicationServices", kind = "framework")]
 extern "C" {
     fn AXValueGetValue(value: AXValueRef, theType: i32, valuePtr: *mut c_void) -> bool;
+    fn AXValueCreate(theType: i32, valuePtr: *const c_void) -> AXValueRef;
 }
 
 #[async_trait]
@@ -560,7 +654,7 @@ impl DesktopAutomation for MacOSAutomation {
                 tokio::time::sleep(std::time::Duration::from_millis(100)).await;
 
                 // Type the text
-                self.type_text(text)?;
+                self.send_text(text)?;
             }
             InputMethod::Set => {
                 // Try to find and set value directly via Accessibility API
@@ -582,7 +676,7 @@ impl DesktopAutomation for MacOSAutomation {
 
                 // If set failed, fallback to typing
                 if set_result.is_err() {
-                    self.type_text(text)?;
+                    self.send_text(text)?;
                 }
             }
         }
@@ -645,14 +739,7 @@ impl DesktopAutomation for MacOSAutomation {
         let timeout = std::time::Duration::from_millis(timeout_ms);
         let poll_interval = std::time::Duration::from_millis(100);
 
-        // Parse locator - support formats like "role:AXButton,title:OK"
-        let criteria: HashMap<&str, &str> = locator
-            .split(',')
-            .filter_map(|part| {
-                let mut parts = part.trim().splitn(2, ':');
-                Some((parts.next()?, parts.next()?))
-            })
-            .collect();
+        let criteria = super::parse_criteria(locator);
 
         while start.elapsed() < timeout {
             // Search for element matching criteria
@@ -669,6 +756,13 @@ impl DesktopAutomation for MacOSAutomation {
         )))
     }
 
+    async fn find_elements(&self, locator: &str) -> AutomationResult<Vec<UIElement>> {
+        self.check_accessibility()?;
+
+        let criteria = super::parse_criteria(locator);
+        Ok(self.find_all_by_criteria(&criteria, super::MAX_FOUND_ELEMENTS, true))
+    }
+
     async fn capture_element(&self, x: i32, y: i32) -> AutomationResult<UIElement> {
         self.check_accessibility()?;
 
@@ -682,6 +776,20 @@ impl DesktopAutomation for MacOSAutomation {
         Ok(ui_element)
     }
 
+    async fn get_element_tree(&self, window: Option<&str>, depth: usize) -> AutomationResult<ElementTreeNode> {
+        self.check_accessibility()?;
+
+        log::info!("macOS: Get accessibility tree of {:?} ({} levels)", window, depth);
+
+        let root = self.tree_root(window)?;
+        let mut budget = MAX_TREE_NODES;
+        let tree = self.build_tree(root, depth, &mut budget);
+
+        unsafe { CFRelease(root as CFTypeRef) };
+
+        tree
+    }
+
     async fn get_element_bounds(&self, element: &UIElement) -> AutomationResult<Rect> {
         Ok(element.bounds.clone())
     }
@@ -711,26 +819,341 @@ impl DesktopAutomation for MacOSAutomation {
 
         Ok(png_data)
     }
+
+    async fn scroll(&self, dx: i32, dy: i32, at: Option<(i32, i32)>) -> AutomationResult<()> {
+        log::info!("macOS: Scroll ({}, {}) at {:?}", dx, dy, at);
+
+        if let Some((x, y)) = at {
+            self.post_mouse_move(CGPoint::new(x as f64, y as f64))?;
+        }
+
+        // One line per notch, matching a wheel; positive values scroll up and left
+        let event = CGEvent::new_scroll_event(
+            Self::event_source()?,
+            ScrollEventUnit::LINE,
+            2,
+            -dy,
+            -dx,
+            0,
+        )
+        .map_err(|_| AutomationError::ExecutionFailed("Failed to create scroll event".to_string()))?;
+        event.post(CGEventTapLocation::HID);
+        Ok(())
+    }
+
+    async fn mouse_move(&self, x: i32, y: i32, smooth: bool) -> AutomationResult<()> {
+        log::info!("macOS: Move mouse to ({}, {})", x, y);
+
+        if !smooth {
+            return self.post_mouse_move(CGPoint::new(x as f64, y as f64));
+        }
+
+        let from = self.pointer_location()?;
+        for (x, y) in super::glide_path((from.x as i32, from.y as i32), (x, y)) {
+            self.post_mouse_move(CGPoint::new(x as f64, y as f64))?;
+            tokio::time::sleep(std::time::Duration::from_millis(super::GLIDE_STEP_MS)).await;
+        }
+        Ok(())
+    }
+
+    async fn mouse_down(&self, button: MouseButton) -> AutomationResult<()> {
+        log::info!("macOS: Press {:?} mouse button", button);
+
+        let event_type = match button {
+            MouseButton::Left => CGEventType::LeftMouseDown,
+            MouseButton::Middle => CGEventType::OtherMouseDown,
+            MouseButton::Right => CGEventType::RightMouseDown,
+        };
+        self.post_button(event_type, button)?;
+        *self.pressed.lock().unwrap_or_else(|e| e.into_inner()) = Some(button);
+        Ok(())
+    }
+
+    async fn mouse_up(&self, button: MouseButton) -> AutomationResult<()> {
+        log::info!("macOS: Release {:?} mouse button", button);
+
+        let event_type = match button {
+            MouseButton::Left => CGEventType::LeftMouseUp,
+            MouseButton::Middle => CGEventType::OtherMouseUp,
+            MouseButton::Right => CGEventType::RightMouseUp,
+        };
+        *self.pressed.lock().unwrap_or_else(|e| e.into_inner()) = None;
+        self.post_button(event_type, button)
+    }
+    async fn key_down(&self, key: Key) -> AutomationResult<()> {
+        log::info!("macOS: Press key {}", key);
+        self.send_key(key, true)
+    }
+
+    async fn key_up(&self, key: Key) -> AutomationResult<()> {
+        log::info!("macOS: Release key {}", key);
+        self.send_key(key, false)
+    }
+
+    async fn type_text(&self, text: &str, per_char_delay_ms: u64) -> AutomationResult<()> {
+        log::info!("macOS: Type {} characters", text.chars().count());
+
+        let source = Self::event_source()?;
+        for ch in text.chars() {
+            self.type_character(&source, ch)?;
+            if per_char_delay_ms > 0 {
+                tokio::time::sleep(std::time::Duration::from_millis(per_char_delay_ms)).await;
+            }
+        }
+        Ok(())
+    }
+
+    async fn list_windows(&self) -> AutomationResult<Vec<WindowInfo>> {
+        self.check_accessibility()?;
+        Ok(self.collect_windows())
+    }
+
+    async fn activate_window(&self, window: &WindowInfo) -> AutomationResult<()> {
+        log::info!("macOS: Activate window '{}'", window.title);
+        self.with_window(window, |element| self.raise_window(window, element))
+    }
+
+    async fn set_window_state(&self, window: &WindowInfo, state: WindowState) -> AutomationResult<()> {
+        log::info!("macOS: Set window '{}' to {:?}", window.title, state);
+
+        self.with_window(window, |element| match state {
+            WindowState::Minimized => self.set_attribute_bool(element, K_AX_MINIMIZED_ATTRIBUTE, true),
+            WindowState::Maximized => {
+                self.raise_window(window, element)?;
+                self.set_attribute_bool(element, K_AX_FULL_SCREEN_ATTRIBUTE, true)
+            }
+            WindowState::Normal => {
+                if window.state == WindowState::Maximized {
+                    self.set_attribute_bool(element, K_AX_FULL_SCREEN_ATTRIBUTE, false)?;
+                }
+                self.raise_window(window, element)
+            }
+        })
+    }
+
+    async fn set_window_bounds(&self, window: &WindowInfo, bounds: Rect) -> AutomationResult<()> {
+        log::info!("macOS: Move window '{}' to {:?}", window.title, bounds);
+
+        self.with_window(window, |element| {
+            let position = CGPoint::new(bounds.x as f64, bounds.y as f64);
+            let size = CGSize::new(bounds.width as f64, bounds.height as f64);
+            // kAXValueCGPointType and kAXValueCGSizeType
+            self.set_attribute_value(element, K_AX_POSITION_ATTRIBUTE, 1, &position as *const _ as *const c_void)?;
+            self.set_attribute_value(element, K_AX_SIZE_ATTRIBUTE, 2, &size as *const _ as *const c_void)
+        })
+    }
+
+    async fn close_window(&self, window: &WindowInfo) -> AutomationResult<()> {
+        log::info!("macOS: Close window '{}'", window.title);
+
+        self.with_window(window, |element| {
+            let button = self
+                .copy_attribute(element, K_AX_CLOSE_BUTTON_ATTRIBUTE)
+                .ok_or_else(|| {
+                    AutomationError::ExecutionFailed(format!("Window '{}' cannot be closed", window.title))
+                })?;
+            let result = self.perform_action(button as AXUIElementRef, K_AX_PRESS_ACTION);
+            unsafe { CFRelease(button) };
+            result
+        })
+    }
+
+    async fn get_clipboard(&self) -> AutomationResult<ClipboardContent> {
+        unsafe {
+            let pasteboard = NSPasteboard::generalPasteboard();
+            Ok(ClipboardContent {
+                text: pasteboard.stringForType(NSPasteboardTypeString).map(|s| s.to_string()),
+                html: pasteboard.stringForType(NSPasteboardTypeHTML).map(|s| s.to_string()),
+                png: pasteboard.dataForType(NSPasteboardTypePNG).map(|data| data.bytes().to_vec()),
+            })
+        }
+    }
+
+    async fn set_clipboard(&self, content: &ClipboardContent) -> AutomationResult<()> {
+        log::info!("macOS: Set clipboard");
+
+        let written = unsafe {
+            let pasteboard = NSPasteboard::generalPasteboard();
+            pasteboard.clearContents();
+
+            let mut written = true;
+            if let Some(text) = &content.text {
+                written &= pasteboard.setString_forType(&NSString::from_str(text), NSPasteboardTypeString);
+            }
+            if let Some(html) = &content.html {
+                written &= pasteboard.setString_forType(&NSString::from_str(html), NSPasteboardTypeHTML);
+            }
+            if let Some(png) = &content.png {
+                written &= pasteboard.setData_forType(Some(&NSData::with_bytes(png)), NSPasteboardTypePNG);
+            }
+            written
+        };
+
+        if written {
+            Ok(())
+        } else {
+            Err(AutomationError::ExecutionFailed("Failed to set clipboard".to_string()))
+        }
+    }
+}
+
+fn modifier_flag(key: Key) -> Option<CGEventFlags> {
+    match key {
+        Key::Ctrl => Some(CGEventFlags::CGEventFlagControl),
+        Key::Shift => Some(CGEventFlags::CGEventFlagShift),
+        Key::Alt => Some(CGEventFlags::CGEventFlagAlternate),
+        Key::Meta => Some(CGEventFlags::CGEventFlagCommand),
+        _ => None,
+    }
+}
+
+/// Virtual key code of a key on an ANSI keyboard
+fn key_code(key: Key) -> Option<CGKeyCode> {
+    let code = match key {
+        Key::Ctrl => 59,
+        Key::Shift => 56,
+        Key::Alt => 58,
+        Key::Meta => 55,
+        Key::Enter => 36,
+        Key::Tab => 48,
+        Key::Escape => 53,
+        Key::Backspace => 51,
+        Key::Delete => 117,
+        Key::Insert => 114,
+        Key::Home => 115,
+        Key::End => 119,
+        Key::PageUp => 116,
+        Key::PageDown => 121,
+        Key::Left => 123,
+        Key::Up => 126,
+        Key::Right => 124,
+        Key::Down => 125,
+        Key::Space | Key::Char(' ') => 49,
+        Key::CapsLock => 57,
+        Key::F(n) => match n {
+            1 => 122,
+            2 => 120,
+            3 => 99,
+            4 => 118,
+            5 => 96,
+            6 => 97,
+            7 => 98,
+            8 => 100,
+            9 => 101,
+            10 => 109,
+            11 => 103,
+            12 => 111,
+            13 => 105,
+            14 => 107,
+            15 => 113,
+            16 => 106,
+            17 => 64,
+            18 => 79,
+            19 => 80,
+            20 => 90,
+            _ => return None,
+        },
+        Key::Char(ch) => match ch {
+            'a' => 0,
+            's' => 1,
+            'd' => 2,
+            'f' => 3,
+            'h' => 4,
+            'g' => 5,
+            'z' => 6,
+            'x' => 7,
+            'c' => 8,
+            'v' => 9,
+            'b' => 11,
+            'q' => 12,
+            'w' => 13,
+            'e' => 14,
+            'r' => 15,
+            'y' => 16,
+            't' => 17,
+            '1' => 18,
+            '2' => 19,
+            '3' => 20,
+            '4' => 21,
+            '6' => 22,
+            '5' => 23,
+            '=' => 24,
+            '9' => 25,
+            '7' => 26,
+            '-' => 27,
+            '8' => 28,
+            '0' => 29,
+            ']' => 30,
+            'o' => 31,
+            'u' => 32,
+            '[' => 33,
+            'i' => 34,
+            'p' => 35,
+            'l' => 37,
+            'j' => 38,
+            '\'' => 39,
+            'k' => 40,
+            ';' => 41,
+            '\\' => 42,
+            ',' => 43,
+            '/' => 44,
+            'n' => 45,
+            'm' => 46,
+            '.' => 47,
+            '`' => 50,
+            _ => return None,
+        },
+        Key::PrintScreen | Key::Menu => return None,
+    };
+    Some(code)
+}
+
+/// Windows have no stable handle, so they are identified by process and
+/// position in the application's window list
+fn window_id(pid: i32, index: usize) -> u64 {
+    ((pid as u64) << 32) | index as u64
+}
+
+fn cg_button(button: MouseButton) -> CGMouseButton {
+    match button {
+        MouseButton::Left => CGMouseButton::Left,
+        MouseButton::Middle => CGMouseButton::Center,
+        MouseButton::Right => CGMouseButton::Right,
+    }
 }
 
 impl MacOSAutomation {
     async fn find_element_by_criteria(&self, criteria: &HashMap<&str, &str>) -> Option<UIElement> {
+        self.find_all_by_criteria(criteria, 1, false).into_iter().next()
+    }
+
+    fn find_all_by_criteria(&self, criteria: &HashMap<&str, &str>, limit: usize, exact: bool) -> Vec<UIElement> {
         // Get all running applications and search for matching element
         // This is a simplified search - in production, you'd want a more sophisticated traversal
 
         // For now, we'll scan common screen positions
         let screen_bounds = CGDisplay::main().bounds();
         let step = 50.0;
+        let mut found: Vec<UIElement> = Vec::new();
 
         let mut y = 0.0;
-        while y < screen_bounds.size.height {
+        while y < screen_bounds.size.height && found.len() < limit {
             let mut x = 0.0;
-            while x < screen_bounds.size.width {
+            while x < screen_bounds.size.width && found.len() < limit {
                 if let Ok(ax_element) = self.get_element_at_position(x as f32, y as f32) {
-                    if self.element_matches_criteria(ax_element, criteria) {
+                    if self.element_matches_criteria(ax_element, criteria, exact) {
                         if let Ok(ui_element) = self.element_to_ui_element(ax_element) {
-                            unsafe { CFRelease(ax_element as CFTypeRef) };
-                            return Some(ui_element);
+                            // Large elements are hit from several scan points
+                            let b = &ui_element.bounds;
+                            let seen = found.iter().any(|f| {
+                                f.bounds.x == b.x
+                                    && f.bounds.y == b.y
+                                    && f.bounds.width == b.width
+                                    && f.bounds.height == b.height
+                            });
+                            if !seen {
+                                found.push(ui_element);
+                            }
                         }
                     }
                     unsafe { CFRelease(ax_element as CFTypeRef) };
@@ -740,10 +1163,274 @@ impl MacOSAutomation {
             y += step;
         }
 
-        None
+        found
+    }
+
+    /// Copy an attribute value; the caller releases it
+    fn copy_attribute(&self, element: AXUIElementRef, attr: &str) -> Option<CFTypeRef> {
+        let cf_attr = CFString::new(attr);
+        let mut value: CFTypeRef = std::ptr::null_mut();
+
+        let result = unsafe {
+            AXUIElementCopyAttributeValue(element, cf_attr.as_concrete_TypeRef(), &mut value)
+        };
+
+        if result != K_AX_ERROR_SUCCESS || value.is_null() {
+            return None;
+        }
+        Some(value)
+    }
+
+    /// Elements of an array attribute, each retained; the caller releases them
+    fn copy_element_array(&self, element: AXUIElementRef, attr: &str) -> Vec<AXUIElementRef> {
+        let Some(array) = self.copy_attribute(element, attr) else {
+            return Vec::new();
+        };
+
+        let array = array as CFArrayRef;
+        let count = unsafe { CFArrayGetCount(array) };
+        let elements = (0..count)
+            .map(|i| unsafe {
+                let item = CFArrayGetValueAtIndex(array, i);
+                CFRetain(item as CFTypeRef);
+                item as AXUIElementRef
+            })
+            .collect();
+
+        unsafe { CFRelease(array as CFTypeRef) };
+        elements
+    }
+
+    /// Windows of every regular application, in the order of their
+    /// `AXWindows` lists
+    fn collect_windows(&self) -> Vec<WindowInfo> {
+        let applications = unsafe { NSWorkspace::sharedWorkspace().runningApplications() };
+
+        let mut windows = Vec::new();
+        for i in 0..applications.count() {
+            let app = applications.objectAtIndex(i);
+            if unsafe { app.activationPolicy() } != NSApplicationActivationPolicy::Regular {
+                continue;
+            }
+            let pid = unsafe { app.processIdentifier() };
+            let process = unsafe { app.localizedName() }.map(|name| name.to_string());
+
+            let app_element = unsafe { AXUIElementCreateApplication(pid) };
+            let focused_window = unsafe { app.isActive() }
+                .then(|| self.copy_attribute(app_element, K_AX_FOCUSED_WINDOW_ATTRIBUTE))
+                .flatten();
+
+            for (index, element) in self.copy_element_array(app_element, K_AX_WINDOWS_ATTRIBUTE).into_iter().enumerate() {
+                let state = if self.get_attribute_bool(element, K_AX_MINIMIZED_ATTRIBUTE).unwrap_or(false) {
+                    WindowState::Minimized
+                } else if self.get_attribute_bool(element, K_AX_FULL_SCREEN_ATTRIBUTE).unwrap_or(false) {
+                    WindowState::Maximized
+                } else {
+                    WindowState::Normal
+                };
+
+                if let Ok(bounds) = self.get_element_bounds(element) {
+                    windows.push(WindowInfo {
+                        id: window_id(pid, index),
+                        title: self.get_attribute_string(element, K_AX_TITLE_ATTRIBUTE).unwrap_or_default(),
+                        pid: Some(pid as u32),
+                        process: process.clone(),
+                        bounds,
+                        state,
+                        focused: focused_window
+                            .is_some_and(|focused| unsafe { core_foundation::base::CFEqual(focused, element as CFTypeRef) } != 0),
+                    });
+                }
+                unsafe { CFRelease(element as CFTypeRef) };
+            }
+
+            unsafe {
+                if let Some(focused) = focused_window {
+                    CFRelease(focused);
+                }
+                CFRelease(app_element as CFTypeRef);
+            }
+        }
+        windows
+    }
+
+    /// Run `f` on the accessibility element of a listed window
+    ///
+    /// Window indices shift as windows open and close, so the window at the
+    /// recorded index is only used while its title still matches.
+    fn with_window<T>(
+        &self,
+        window: &WindowInfo,
+        f: impl FnOnce(AXUIElementRef) -> AutomationResult<T>,
+    ) -> AutomationResult<T> {
+        self.check_accessibility()?;
+
+        let pid = (window.id >> 32) as i32;
+        let index = (window.id & 0xffff_ffff) as usize;
+        let app_element = unsafe { AXUIElementCreateApplication(pid) };
+        let elements = self.copy_element_array(app_element, K_AX_WINDOWS_ATTRIBUTE);
+        unsafe { CFRelease(app_element as CFTypeRef) };
+
+        let title_of = |element: AXUIElementRef| self.get_attribute_string(element, K_AX_TITLE_ATTRIBUTE).unwrap_or_default();
+        let found = elements
+            .get(index)
+            .copied()
+            .filter(|&element| title_of(element) == window.title)
+            .or_else(|| elements.iter().copied().find(|&element| title_of(element) == window.title));
+
+        let result = match found {
+            Some(element) => f(element),
+            None => Err(AutomationError::ElementNotFound(format!(
+                "Window '{}' no longer exists",
+                window.title
+            ))),
+        };
+
+        for element in elements {
+            unsafe { CFRelease(element as CFTypeRef) };
+        }
+        result
+    }
+
+    /// Restore, raise and focus a window, bringing its application to the front
+    fn raise_window(&self, window: &WindowInfo, element: AXUIElementRef) -> AutomationResult<()> {
+        if window.state == WindowState::Minimized {
+            self.set_attribute_bool(element, K_AX_MINIMIZED_ATTRIBUTE, false)?;
+        }
+        self.perform_action(element, K_AX_RAISE_ACTION)?;
+        self.set_attribute_bool(element, K_AX_MAIN_ATTRIBUTE, true)?;
+
+        let pid = (window.id >> 32) as i32;
+        let app = unsafe { NSRunningApplication::runningApplicationWithProcessIdentifier(pid) }
+            .ok_or_else(|| AutomationError::ElementNotFound(format!("No application with pid {}", pid)))?;
+        unsafe { app.activateWithOptions(NSApplicationActivationOptions::NSApplicationActivateIgnoringOtherApps) };
+        Ok(())
+    }
+
+    fn set_attribute_bool(&self, element: AXUIElementRef, attr: &str, value: bool) -> AutomationResult<()> {
+        let cf_attr = CFString::new(attr);
+        let cf_value = CFBoolean::from(value);
+
+        let result = unsafe {
+            AXUIElementSetAttributeValue(element, cf_attr.as_concrete_TypeRef(), cf_value.to_void() as CFTypeRef)
+        };
+
+        if result != K_AX_ERROR_SUCCESS {
+            return Err(AutomationError::ExecutionFailed(format!(
+                "Failed to set {}, error: {}",
+                attr, result
+            )));
+        }
+        Ok(())
+    }
+
+    /// Set an attribute to an `AXValue` of the given type wrapping `value`
+    fn set_attribute_value(
+        &self,
+        element: AXUIElementRef,
+        attr: &str,
+        value_type: i32,
+        value: *const c_void,
+    ) -> AutomationResult<()> {
+        let cf_attr = CFString::new(attr);
+        let ax_value = unsafe { AXValueCreate(value_type, value) };
+        if ax_value.is_null() {
+            return Err(AutomationError::ExecutionFailed(format!("Failed to create {} value", attr)));
+        }
+
+        let result = unsafe {
+            let result = AXUIElementSetAttributeValue(element, cf_attr.as_concrete_TypeRef(), ax_value as CFTypeRef);
+            CFRelease(ax_value as CFTypeRef);
+            result
+        };
+
+        if result != K_AX_ERROR_SUCCESS {
+            return Err(AutomationError::ExecutionFailed(format!(
+                "Failed to set {}, error: {}",
+                attr, result
+            )));
+        }
+        Ok(())
+    }
+
+    /// Window whose title matches, or the whole application whose process does;
+    /// the focused window without a name
+    fn tree_root(&self, window: Option<&str>) -> AutomationResult<AXUIElementRef> {
+        let application = self.copy_attribute(self.system_wide, K_AX_FOCUSED_APPLICATION_ATTRIBUTE);
+
+        let Some(window) = window else {
+            let focused = application
+                .and_then(|app| {
+                    let focused = self.copy_attribute(app as AXUIElementRef, K_AX_FOCUSED_WINDOW_ATTRIBUTE);
+                    unsafe { CFRelease(app) };
+                    focused
+                });
+            return focused
+                .map(|w| w as AXUIElementRef)
+                .ok_or_else(|| AutomationError::ElementNotFound("No active window".to_string()));
+        };
+
+        let wanted = window.to_lowercase();
+        if let Some(app) = application {
+            let windows = self.copy_element_array(app as AXUIElementRef, K_AX_WINDOWS_ATTRIBUTE);
+            unsafe { CFRelease(app) };
+
+            let mut found = None;
+            for w in windows {
+                let matches = self
+                    .get_attribute_string(w, K_AX_TITLE_ATTRIBUTE)
+                    .map(|title| title.to_lowercase().contains(&wanted))
+                    .unwrap_or(false);
+                if matches && found.is_none() {
+                    found = Some(w);
+                } else {
+                    unsafe { CFRelease(w as CFTypeRef) };
+                }
+            }
+            if let Some(w) = found {
+                return Ok(w);
+            }
+        }
+
+        // Otherwise treat it as a process name
+        let output = std::process::Command::new("pgrep")
+            .args(["-i", "-n", window])
+            .output()
+            .map_err(|e| AutomationError::ExecutionFailed(format!("Failed to run pgrep: {}", e)))?;
+        let pid = String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .next()
+            .and_then(|line| line.trim().parse::<i32>().ok())
+            .ok_or_else(|| {
+                AutomationError::ElementNotFound(format!("No window or application matching '{}'", window))
+            })?;
+
+        Ok(unsafe { AXUIElementCreateApplication(pid) })
+    }
+
+    fn build_tree(&self, element: AXUIElementRef, depth: usize, budget: &mut usize) -> AutomationResult<ElementTreeNode> {
+        *budget = budget.saturating_sub(1);
+        let ui_element = self.element_to_ui_element(element)?;
+
+        let mut nodes = Vec::new();
+        if depth > 0 {
+            for child in self.copy_element_array(element, K_AX_CHILDREN_ATTRIBUTE) {
+                if *budget > 0 {
+                    if let Ok(node) = self.build_tree(child, depth - 1, budget) {
+                        nodes.push(node);
+                    }
+                }
+                unsafe { CFRelease(child as CFTypeRef) };
+            }
+            if *budget == 0 {
+                log::warn!("Accessibility tree truncated at {} elements", MAX_TREE_NODES);
+            }
+        }
+
+        Ok(ElementTreeNode::new(ui_element, nodes))
     }
 
-    fn element_matches_criteria(&self, element: AXUIElementRef, criteria: &HashMap<&str, &str>) -> bool {
+    fn element_matches_criteria(&self, element: AXUIElementRef, criteria: &HashMap<&str, &str>, exact: bool) -> bool {
         for (key, expected_value) in criteria {
             let ax_attr = match key.to_lowercase().as_str() {
                 "role" => K_AX_ROLE_ATTRIBUTE,
@@ -755,7 +1442,7 @@ impl MacOSAutomation {
             };
 
             if let Some(actual_value) = self.get_attribute_string(element, ax_attr) {
-                if !actual_value.to_lowercase().contains(&expected_value.to_lowercase()) {
+                if !super::criterion_matches(&actual_value, expected_value, exact) {
                     return false;
                 }
             } else {
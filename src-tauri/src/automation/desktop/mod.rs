@@ -7,9 +7,80 @@ pub mod macos;
 #[cfg(target_os = "linux")]
 pub mod linux;
 
-use crate::automation::{AutomationResult, ClickType, InputMethod, Rect};
+#[cfg(target_os = "linux")]
+pub mod x11;
+
+#[cfg(target_os = "linux")]
+mod selection;
+
+mod clipboard;
+mod window;
+
+#[cfg(test)]
+pub(crate) mod testing;
+
+pub use clipboard::ClipboardContent;
+pub use window::{WindowInfo, WindowQuery, WindowState};
+
+use crate::automation::keys::{Key, KeyCombo};
+use crate::automation::{AutomationError, AutomationResult, ClickType, InputMethod, MouseButton, Rect};
 use crate::element::UIElement;
 use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// Upper bound on elements collected by `DesktopAutomation::find_elements`
+pub const MAX_FOUND_ELEMENTS: usize = 50;
+
+/// Upper bound on elements returned by `DesktopAutomation::get_element_tree`
+pub const MAX_TREE_NODES: usize = 5000;
+
+/// Pause between pointer positions of a smooth move
+pub(crate) const GLIDE_STEP_MS: u64 = 10;
+
+/// Pointer travel per step of a smooth move, in pixels
+const GLIDE_STEP_DISTANCE: f64 = 20.0;
+
+const MAX_GLIDE_STEPS: usize = 50;
+
+/// Time the button is held before a drag starts moving, as applications
+/// often ignore a drag that starts right away
+const DRAG_HOLD_MS: u64 = 150;
+
+/// Pause between repeats of a key combination
+const KEY_REPEAT_MS: u64 = 50;
+
+/// Pause between window list checks while waiting for a window
+const WINDOW_POLL_MS: u64 = 200;
+
+/// Element with its descendants
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ElementTreeNode {
+    pub element: UIElement,
+    pub children: Vec<ElementTreeNode>,
+}
+
+impl ElementTreeNode {
+    /// Create a node, linking `parent_id` and `children` of the elements
+    pub fn new(mut element: UIElement, children: Vec<ElementTreeNode>) -> Self {
+        element.children = children.iter().map(|c| c.element.id.clone()).collect();
+        let children = children
+            .into_iter()
+            .map(|mut child| {
+                child.element.parent_id = Some(element.id.clone());
+                child
+            })
+            .collect();
+        Self { element, children }
+    }
+
+    /// Number of elements in this subtree
+    pub fn count(&self) -> usize {
+        1 + self.children.iter().map(ElementTreeNode::count).sum::<usize>()
+    }
+}
 
 #[async_trait]
 pub trait DesktopAutomation: Send + Sync {
@@ -28,11 +99,144 @@ pub trait DesktopAutomation: Send + Sync {
         timeout_ms: u64,
     ) -> AutomationResult<UIElement>;
 
+    /// Find every element currently matching a locator, without waiting
+    ///
+    /// Values must match exactly (ignoring case), so the number of elements
+    /// found tells whether the locator is unique.
+    async fn find_elements(&self, locator: &str) -> AutomationResult<Vec<UIElement>>;
+
+    /// Search once for the first element matching a locator, without waiting
+    ///
+    /// Unlike `wait_element`, a search that finishes without a match fails
+    /// right away with `ElementNotFound`.
+    async fn find_element(&self, locator: &str) -> AutomationResult<UIElement> {
+        self.find_elements(locator)
+            .await?
+            .into_iter()
+            .next()
+            .ok_or_else(|| AutomationError::ElementNotFound(locator.to_string()))
+    }
+
     async fn capture_element(&self, x: i32, y: i32) -> AutomationResult<UIElement>;
 
+    /// Element hierarchy of a window or application, `depth` levels deep
+    ///
+    /// `window` matches a window title or application name; the active window
+    /// is used when it is not given.
+    async fn get_element_tree(&self, window: Option<&str>, depth: usize) -> AutomationResult<ElementTreeNode>;
+
     async fn get_element_bounds(&self, element: &UIElement) -> AutomationResult<Rect>;
 
     async fn screenshot(&self, rect: Option<Rect>) -> AutomationResult<Vec<u8>>;
+
+    /// Scroll by wheel notches; positive `dy` scrolls down, positive `dx` right
+    ///
+    /// The pointer is moved to `at` first, otherwise the content under the
+    /// pointer scrolls.
+    async fn scroll(&self, dx: i32, dy: i32, at: Option<(i32, i32)>) -> AutomationResult<()>;
+
+    /// Move the pointer, passing through intermediate positions when `smooth`
+    async fn mouse_move(&self, x: i32, y: i32, smooth: bool) -> AutomationResult<()>;
+
+    /// Press a button at the pointer and keep it held
+    async fn mouse_down(&self, button: MouseButton) -> AutomationResult<()>;
+
+    /// Release a button held by `mouse_down`
+    async fn mouse_up(&self, button: MouseButton) -> AutomationResult<()>;
+
+    /// Press the left button at `from`, move to `to` and release it there
+    async fn drag(&self, from: (i32, i32), to: (i32, i32)) -> AutomationResult<()> {
+        self.mouse_move(from.0, from.1, false).await?;
+        self.mouse_down(MouseButton::Left).await?;
+        tokio::time::sleep(Duration::from_millis(DRAG_HOLD_MS)).await;
+
+        // Release the button even when the move fails
+        let moved = self.mouse_move(to.0, to.1, true).await;
+        if moved.is_ok() {
+            tokio::time::sleep(Duration::from_millis(DRAG_HOLD_MS)).await;
+        }
+        let released = self.mouse_up(MouseButton::Left).await;
+        moved.and(released)
+    }
+
+    /// Press a key and keep it held
+    async fn key_down(&self, key: Key) -> AutomationResult<()>;
+
+    /// Release a key held by `key_down`
+    async fn key_up(&self, key: Key) -> AutomationResult<()>;
+
+    /// Press the keys of a combination in order and release them in reverse
+    async fn press_keys(&self, combo: &KeyCombo) -> AutomationResult<()> {
+        for i in 0..combo.repeat {
+            if i > 0 {
+                tokio::time::sleep(Duration::from_millis(KEY_REPEAT_MS)).await;
+            }
+
+            let mut pressed = 0;
+            let mut result = Ok(());
+            for &key in &combo.keys {
+                result = self.key_down(key).await;
+                if result.is_err() {
+                    break;
+                }
+                pressed += 1;
+            }
+            // Release the pressed keys even when a press fails
+            for &key in combo.keys[..pressed].iter().rev() {
+                let released = self.key_up(key).await;
+                result = result.and(released);
+            }
+            result?;
+        }
+        Ok(())
+    }
+
+    /// Type text into the focused element, pausing after each character
+    async fn type_text(&self, text: &str, per_char_delay_ms: u64) -> AutomationResult<()>;
+
+    /// Top-level application windows, including minimized ones
+    async fn list_windows(&self) -> AutomationResult<Vec<WindowInfo>>;
+
+    /// Bring a window to the front and give it keyboard focus, restoring it
+    /// if it is minimized
+    async fn activate_window(&self, window: &WindowInfo) -> AutomationResult<()>;
+
+    async fn set_window_state(&self, window: &WindowInfo, state: WindowState) -> AutomationResult<()>;
+
+    /// Move and resize a window
+    async fn set_window_bounds(&self, window: &WindowInfo, bounds: Rect) -> AutomationResult<()>;
+
+    /// Ask a window to close, like its close button; the application may
+    /// still keep it open, for instance to ask about unsaved changes
+    async fn close_window(&self, window: &WindowInfo) -> AutomationResult<()>;
+
+    /// Current clipboard content in every format it is offered in
+    async fn get_clipboard(&self) -> AutomationResult<ClipboardContent>;
+
+    /// Replace the clipboard content; empty content clears the clipboard
+    async fn set_clipboard(&self, content: &ClipboardContent) -> AutomationResult<()>;
+
+    async fn find_windows(&self, query: &WindowQuery) -> AutomationResult<Vec<WindowInfo>> {
+        let windows = self.list_windows().await?;
+        Ok(windows.into_iter().filter(|w| query.matches(w)).collect())
+    }
+
+    /// Wait until a window matching the query exists
+    async fn wait_window(&self, query: &WindowQuery, timeout_ms: u64) -> AutomationResult<WindowInfo> {
+        let deadline = std::time::Instant::now() + Duration::from_millis(timeout_ms);
+        loop {
+            if let Some(window) = self.find_windows(query).await?.into_iter().next() {
+                return Ok(window);
+            }
+            if std::time::Instant::now() >= deadline {
+                return Err(AutomationError::Timeout(format!(
+                    "No {} within {}ms",
+                    query, timeout_ms
+                )));
+            }
+            tokio::time::sleep(Duration::from_millis(WINDOW_POLL_MS)).await;
+        }
+    }
 }
 
 pub fn create_automation() -> Box<dyn DesktopAutomation> {
@@ -51,3 +255,114 @@ pub fn create_automation() -> Box<dyn DesktopAutomation> {
         Box::new(linux::LinuxAutomation::new())
     }
 }
+
+/// Pointer positions of a smooth move from `from`, ending exactly at `to`
+///
+/// Steps are eased in and out so the motion looks like a hand on a mouse.
+pub(crate) fn glide_path(from: (i32, i32), to: (i32, i32)) -> Vec<(i32, i32)> {
+    let (dx, dy) = ((to.0 - from.0) as f64, (to.1 - from.1) as f64);
+    let steps = ((dx.hypot(dy) / GLIDE_STEP_DISTANCE).ceil() as usize).clamp(1, MAX_GLIDE_STEPS);
+
+    (1..=steps)
+        .map(|step| {
+            let t = step as f64 / steps as f64;
+            let eased = t * t * (3.0 - 2.0 * t);
+            (
+                from.0 + (dx * eased).round() as i32,
+                from.1 + (dy * eased).round() as i32,
+            )
+        })
+        .collect()
+}
+
+/// Parse locator criteria like "name:Button1,type:Button"
+pub(crate) fn parse_criteria(locator: &str) -> HashMap<&str, &str> {
+    locator
+        .split(',')
+        .filter_map(|part| {
+            let mut parts = part.trim().splitn(2, ':');
+            Some((parts.next()?, parts.next()?))
+        })
+        .collect()
+}
+
+/// Compare an element property with a locator value, ignoring case
+///
+/// Searches accept a partial match, but counting matches needs exact ones,
+/// otherwise "OK" would also count "OK All" and "Bookmarks".
+pub(crate) fn criterion_matches(actual: &str, expected: &str, exact: bool) -> bool {
+    let (actual, expected) = (actual.to_lowercase(), expected.to_lowercase());
+    if exact {
+        actual == expected
+    } else {
+        actual.contains(&expected)
+    }
+}
+
+/// Build a tree from elements listed breadth-first with the index of their parent
+pub(crate) fn assemble_tree(nodes: Vec<(UIElement, Option<usize>)>) -> Option<ElementTreeNode> {
+    let mut children: Vec<Vec<ElementTreeNode>> = nodes.iter().map(|_| Vec::new()).collect();
+    let mut root = None;
+
+    // Children always come after their parent, so walking backwards builds
+    // every subtree before it is attached
+    for (index, (element, parent)) in nodes.into_iter().enumerate().rev() {
+        let mut own = std::mem::take(&mut children[index]);
+        own.reverse();
+        let node = ElementTreeNode::new(element, own);
+        match parent {
+            Some(parent) => children[parent].push(node),
+            None => root = Some(node),
+        }
+    }
+
+    root
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn element(name: &str) -> UIElement {
+        UIElement::new(name.to_string(), "Pane".to_string(), Rect { x: 0, y: 0, width: 10, height: 10 })
+    }
+
+    #[test]
+    fn test_criterion_matches() {
+        assert!(criterion_matches("OK All", "ok", false));
+        assert!(!criterion_matches("OK All", "ok", true));
+        assert!(criterion_matches("OK", "ok", true));
+    }
+
+    #[test]
+    fn test_assemble_tree_keeps_order_and_links() {
+        let nodes = vec![
+            (element("window"), None),
+            (element("toolbar"), Some(0)),
+            (element("body"), Some(0)),
+            (element("save"), Some(1)),
+            (element("open"), Some(1)),
+        ];
+
+        let tree = assemble_tree(nodes).unwrap();
+        assert_eq!(tree.count(), 5);
+        let names: Vec<&str> = tree.children.iter().map(|c| c.element.name.as_str()).collect();
+        assert_eq!(names, ["toolbar", "body"]);
+
+        let toolbar = &tree.children[0];
+        assert_eq!(toolbar.children[0].element.name, "save");
+        assert_eq!(toolbar.children[1].element.name, "open");
+        assert_eq!(toolbar.element.parent_id.as_deref(), Some(tree.element.id.as_str()));
+        assert_eq!(tree.element.children.len(), 2);
+    }
+
+    #[test]
+    fn test_glide_path_ends_at_target() {
+        let path = glide_path((0, 0), (200, 100));
+        assert_eq!(path.len(), 12);
+        assert_eq!(*path.last().unwrap(), (200, 100));
+        assert!(path.windows(2).all(|w| w[0].0 <= w[1].0 && w[0].1 <= w[1].1));
+
+        assert_eq!(glide_path((5, 5), (5, 5)), vec![(5, 5)]);
+    }
+}
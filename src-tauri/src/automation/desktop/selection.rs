@@ -0,0 +1,442 @@
+#![cfg(target_os = "linux")]
+
+//! X11 `CLIPBOARD` selection
+//!
+//! X11 has no clipboard storage: the owner of the selection hands its data
+//! to whoever asks. Setting the clipboard therefore starts a thread with its
+//! own connection that answers requests until another client takes the
+//! selection over. Reading converts the selection into a property of a
+//! private window. Data larger than a single request goes through the INCR
+//! protocol in both directions.
+
+use super::ClipboardContent;
+use crate::automation::{AutomationError, AutomationResult};
+use std::time::{Duration, Instant};
+use x11rb::connection::{Connection, RequestConnection};
+use x11rb::protocol::xproto::{
+    Atom, AtomEnum, ChangeWindowAttributesAux, ConnectionExt as _, CreateWindowAux, EventMask, PropMode, Property,
+    SelectionNotifyEvent, SelectionRequestEvent, Timestamp, Window, WindowClass, SELECTION_NOTIFY_EVENT,
+};
+use x11rb::protocol::Event;
+use x11rb::rust_connection::RustConnection;
+use x11rb::wrapper::ConnectionExt as _;
+use x11rb::{CURRENT_TIME, NONE};
+
+/// How long the selection owner gets to answer a request
+const CONVERT_TIMEOUT: Duration = Duration::from_secs(2);
+
+const POLL_INTERVAL: Duration = Duration::from_millis(5);
+
+x11rb::atom_manager! {
+    Atoms: AtomsCookie {
+        CLIPBOARD,
+        TARGETS,
+        TIMESTAMP,
+        INCR,
+        UTF8_STRING,
+        TEXT_HTML: b"text/html",
+        IMAGE_PNG: b"image/png",
+        TRANSFER: b"_AUTOMATION_CLIPBOARD",
+    }
+}
+
+/// Connection with an unmapped window to exchange selection data through;
+/// selections are converted into its `TRANSFER` property
+struct Session {
+    conn: RustConnection,
+    window: Window,
+    atoms: Atoms,
+}
+
+impl Session {
+    fn open() -> AutomationResult<Self> {
+        let (conn, screen) = x11rb::connect(None)
+            .map_err(|e| AutomationError::PlatformNotSupported(format!("Cannot connect to X display: {}", e)))?;
+        let root = conn.setup().roots[screen].root;
+        let atoms = Atoms::new(&conn).map_err(x11_error)?.reply().map_err(x11_error)?;
+
+        let window = conn.generate_id().map_err(x11_error)?;
+        conn.create_window(
+            0,
+            window,
+            root,
+            0,
+            0,
+            1,
+            1,
+            0,
+            WindowClass::INPUT_ONLY,
+            0,
+            &CreateWindowAux::new().event_mask(EventMask::PROPERTY_CHANGE),
+        )
+        .map_err(x11_error)?;
+        conn.flush().map_err(x11_error)?;
+
+        Ok(Self { conn, window, atoms })
+    }
+
+    /// Targets the current owner offers, empty without an owner
+    fn targets(&self) -> AutomationResult<Vec<Atom>> {
+        let data = self.convert(self.atoms.TARGETS)?.unwrap_or_default();
+        Ok(data
+            .chunks_exact(4)
+            .map(|atom| u32::from_ne_bytes([atom[0], atom[1], atom[2], atom[3]]))
+            .collect())
+    }
+
+    /// Selection data converted to `target`, if the owner provides it
+    fn convert(&self, target: Atom) -> AutomationResult<Option<Vec<u8>>> {
+        self.conn
+            .convert_selection(self.window, self.atoms.CLIPBOARD, target, self.atoms.TRANSFER, CURRENT_TIME)
+            .map_err(x11_error)?;
+        self.conn.flush().map_err(x11_error)?;
+
+        let deadline = Instant::now() + CONVERT_TIMEOUT;
+        let notify = loop {
+            match self.next_event(deadline)? {
+                Event::SelectionNotify(event) if event.selection == self.atoms.CLIPBOARD => break event,
+                _ => continue,
+            }
+        };
+        if notify.property == NONE {
+            return Ok(None);
+        }
+
+        let reply = self.take_property()?;
+        if reply.type_ != self.atoms.INCR {
+            return Ok(Some(reply.value));
+        }
+
+        // Deleting the INCR property asks for the first chunk; each chunk
+        // arrives as a new value and an empty one ends the transfer
+        let mut data = Vec::new();
+        loop {
+            let deadline = Instant::now() + CONVERT_TIMEOUT;
+            match self.next_event(deadline)? {
+                Event::PropertyNotify(event) if event.atom == self.atoms.TRANSFER && event.state == Property::NEW_VALUE => {
+                    let chunk = self.take_property()?.value;
+                    if chunk.is_empty() {
+                        return Ok(Some(data));
+                    }
+                    data.extend_from_slice(&chunk);
+                }
+                _ => continue,
+            }
+        }
+    }
+
+    /// Current server time, read from the notification of an empty property
+    /// change, as ICCCM wants selections owned from a real timestamp
+    fn server_time(&self) -> AutomationResult<Timestamp> {
+        self.conn
+            .change_property8(PropMode::APPEND, self.window, self.atoms.TRANSFER, AtomEnum::STRING, &[])
+            .map_err(x11_error)?;
+        self.conn.flush().map_err(x11_error)?;
+
+        let deadline = Instant::now() + CONVERT_TIMEOUT;
+        loop {
+            match self.next_event(deadline)? {
+                Event::PropertyNotify(event) if event.atom == self.atoms.TRANSFER => return Ok(event.time),
+                _ => continue,
+            }
+        }
+    }
+
+    fn take_property(&self) -> AutomationResult<x11rb::protocol::xproto::GetPropertyReply> {
+        self.conn
+            .get_property(true, self.window, self.atoms.TRANSFER, AtomEnum::ANY, 0, u32::MAX / 4)
+            .map_err(x11_error)?
+            .reply()
+            .map_err(x11_error)
+    }
+
+    fn next_event(&self, deadline: Instant) -> AutomationResult<Event> {
+        loop {
+            if let Some(event) = self.conn.poll_for_event().map_err(x11_error)? {
+                return Ok(event);
+            }
+            if Instant::now() >= deadline {
+                return Err(AutomationError::Timeout("Clipboard owner did not respond".to_string()));
+            }
+            std::thread::sleep(POLL_INTERVAL);
+        }
+    }
+}
+
+/// Read the formats of the clipboard the automation understands
+pub fn read() -> AutomationResult<ClipboardContent> {
+    let session = Session::open()?;
+    let atoms = session.atoms;
+    let targets = session.targets()?;
+
+    let mut content = ClipboardContent::default();
+    let text_target = [atoms.UTF8_STRING, AtomEnum::STRING.into()]
+        .into_iter()
+        .find(|target| targets.contains(target));
+    if let Some(target) = text_target {
+        content.text = session.convert(target)?.map(|data| {
+            if target == atoms.UTF8_STRING {
+                String::from_utf8_lossy(&data).into_owned()
+            } else {
+                data.iter().map(|&byte| char::from(byte)).collect()
+            }
+        });
+    }
+    if targets.contains(&atoms.TEXT_HTML) {
+        content.html = session.convert(atoms.TEXT_HTML)?.map(|data| decode_html(&data));
+    }
+    if targets.contains(&atoms.IMAGE_PNG) {
+        content.png = session.convert(atoms.IMAGE_PNG)?;
+    }
+    Ok(content)
+}
+
+/// Take over the clipboard and serve `content` from a background thread
+/// until another client owns it; empty content just drops the selection
+pub fn write(content: ClipboardContent) -> AutomationResult<()> {
+    let session = Session::open()?;
+    let atoms = session.atoms;
+
+    if content.is_empty() {
+        session
+            .conn
+            .set_selection_owner(NONE, atoms.CLIPBOARD, CURRENT_TIME)
+            .map_err(x11_error)?;
+        session.conn.flush().map_err(x11_error)?;
+        return Ok(());
+    }
+
+    let time = session.server_time()?;
+    session
+        .conn
+        .set_selection_owner(session.window, atoms.CLIPBOARD, time)
+        .map_err(x11_error)?;
+    let owner = session
+        .conn
+        .get_selection_owner(atoms.CLIPBOARD)
+        .map_err(x11_error)?
+        .reply()
+        .map_err(x11_error)?
+        .owner;
+    if owner != session.window {
+        return Err(AutomationError::ExecutionFailed("Failed to take over the clipboard".to_string()));
+    }
+
+    std::thread::Builder::new()
+        .name("clipboard-owner".to_string())
+        .spawn(move || {
+            if let Err(e) = serve(&session, &content, time) {
+                log::warn!("Clipboard owner stopped: {}", e);
+            }
+        })
+        .map_err(|e| AutomationError::ExecutionFailed(format!("Failed to start clipboard owner: {}", e)))?;
+    Ok(())
+}
+
+/// Data sent to a requestor in chunks through the INCR protocol
+struct IncrTransfer<'a> {
+    requestor: Window,
+    property: Atom,
+    target: Atom,
+    remaining: &'a [u8],
+}
+
+/// Answer selection requests until the selection is lost and all INCR
+/// transfers are done; `time` is when the selection was acquired
+fn serve(session: &Session, content: &ClipboardContent, time: Timestamp) -> AutomationResult<()> {
+    let conn = &session.conn;
+    let atoms = session.atoms;
+    let max_bytes = conn.maximum_request_bytes().saturating_sub(64);
+    let mut transfers: Vec<IncrTransfer> = Vec::new();
+    let mut owned = true;
+
+    let latin1 = content.text.as_deref().map(latin1);
+    let mut offers: Vec<(Atom, &[u8])> = Vec::new();
+    if let (Some(text), Some(latin1)) = (&content.text, &latin1) {
+        offers.push((atoms.UTF8_STRING, text.as_bytes()));
+        offers.push((AtomEnum::STRING.into(), latin1));
+    }
+    if let Some(html) = &content.html {
+        offers.push((atoms.TEXT_HTML, html.as_bytes()));
+    }
+    if let Some(png) = &content.png {
+        offers.push((atoms.IMAGE_PNG, png.as_slice()));
+    }
+
+    while owned || !transfers.is_empty() {
+        let event = if transfers.is_empty() {
+            conn.wait_for_event().map_err(x11_error)?
+        } else {
+            match session.next_event(Instant::now() + CONVERT_TIMEOUT) {
+                Ok(event) => event,
+                Err(AutomationError::Timeout(_)) => {
+                    log::warn!("Dropping {} stalled clipboard transfers", transfers.len());
+                    transfers.clear();
+                    continue;
+                }
+                Err(e) => return Err(e),
+            }
+        };
+
+        match event {
+            Event::SelectionClear(_) => owned = false,
+            Event::SelectionRequest(request) if owned => {
+                answer(session, &request, &offers, time, max_bytes, &mut transfers)?;
+            }
+            // The requestor deleted the last chunk and waits for the next
+            Event::PropertyNotify(event) if event.state == Property::DELETE => {
+                let Some(index) = transfers
+                    .iter()
+                    .position(|t| t.requestor == event.window && t.property == event.atom)
+                else {
+                    continue;
+                };
+                let transfer = &mut transfers[index];
+                let (chunk, rest) = transfer.remaining.split_at(transfer.remaining.len().min(max_bytes));
+                // An empty chunk ends the transfer
+                conn.change_property8(PropMode::REPLACE, transfer.requestor, transfer.property, transfer.target, chunk)
+                    .map_err(x11_error)?;
+                conn.flush().map_err(x11_error)?;
+                transfer.remaining = rest;
+                if chunk.is_empty() {
+                    let transfer = transfers.swap_remove(index);
+                    let _ = conn.change_window_attributes(
+                        transfer.requestor,
+                        &ChangeWindowAttributesAux::new().event_mask(EventMask::NO_EVENT),
+                    );
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Answer one selection request, starting an INCR transfer for data larger
+/// than a single request
+fn answer<'a>(
+    session: &Session,
+    request: &SelectionRequestEvent,
+    offers: &[(Atom, &'a [u8])],
+    time: Timestamp,
+    max_bytes: usize,
+    transfers: &mut Vec<IncrTransfer<'a>>,
+) -> AutomationResult<()> {
+    let conn = &session.conn;
+    let atoms = session.atoms;
+
+    // Obsolete clients leave out the property and expect the target
+    let property = if request.property == NONE { request.target } else { request.property };
+    let answered = if request.target == atoms.TARGETS {
+        let mut targets = vec![atoms.TARGETS, atoms.TIMESTAMP];
+        targets.extend(offers.iter().map(|(target, _)| *target));
+        conn.change_property32(PropMode::REPLACE, request.requestor, property, AtomEnum::ATOM, &targets)
+            .is_ok()
+    } else if request.target == atoms.TIMESTAMP {
+        conn.change_property32(PropMode::REPLACE, request.requestor, property, AtomEnum::INTEGER, &[time])
+            .is_ok()
+    } else {
+        match offers.iter().find(|(target, _)| *target == request.target) {
+            Some((target, data)) if data.len() <= max_bytes => conn
+                .change_property8(PropMode::REPLACE, request.requestor, property, *target, data)
+                .is_ok(),
+            // Announce the size, then send chunks as the requestor deletes the property
+            Some((target, data)) => {
+                let started = conn
+                    .change_window_attributes(
+                        request.requestor,
+                        &ChangeWindowAttributesAux::new().event_mask(EventMask::PROPERTY_CHANGE),
+                    )
+                    .is_ok()
+                    && conn
+                        .change_property32(
+                            PropMode::REPLACE,
+                            request.requestor,
+                            property,
+                            atoms.INCR,
+                            &[u32::try_from(data.len()).unwrap_or(u32::MAX)],
+                        )
+                        .is_ok();
+                if started {
+                    transfers.push(IncrTransfer {
+                        requestor: request.requestor,
+                        property,
+                        target: *target,
+                        remaining: data,
+                    });
+                }
+                started
+            }
+            None => false,
+        }
+    };
+
+    let notify = SelectionNotifyEvent {
+        response_type: SELECTION_NOTIFY_EVENT,
+        sequence: 0,
+        time: request.time,
+        requestor: request.requestor,
+        selection: request.selection,
+        target: request.target,
+        property: if answered { property } else { NONE },
+    };
+    conn.send_event(false, request.requestor, EventMask::NO_EVENT, notify)
+        .map_err(x11_error)?;
+    conn.flush().map_err(x11_error)?;
+    Ok(())
+}
+
+/// Text as the ISO Latin-1 the `STRING` target is defined in; characters
+/// outside of it become '?'
+fn latin1(text: &str) -> Vec<u8> {
+    text.chars().map(|c| u8::try_from(c).unwrap_or(b'?')).collect()
+}
+
+/// Browsers offer `text/html` as UTF-16 with a byte order mark
+fn decode_html(data: &[u8]) -> String {
+    let utf16 = |bytes: &[u8], read: fn([u8; 2]) -> u16| {
+        let units: Vec<u16> = bytes.chunks_exact(2).map(|c| read([c[0], c[1]])).collect();
+        String::from_utf16_lossy(&units)
+    };
+    match data {
+        [0xff, 0xfe, rest @ ..] => utf16(rest, u16::from_le_bytes),
+        [0xfe, 0xff, rest @ ..] => utf16(rest, u16::from_be_bytes),
+        _ => String::from_utf8_lossy(data).into_owned(),
+    }
+}
+
+fn x11_error(e: impl std::fmt::Display) -> AutomationError {
+    AutomationError::ExecutionFailed(format!("X11 clipboard error: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_latin1_replaces_other_characters() {
+        assert_eq!(latin1("Café €5"), b"Caf\xe9 ?5");
+    }
+
+    /// Needs an X server, e.g. `xvfb-run cargo test -- --ignored`
+    #[test]
+    #[ignore]
+    fn test_large_content_round_trips() {
+        // Well above a single request, so both sides go through INCR
+        let png: Vec<u8> = (0..3_000_000u32).map(|i| (i % 251) as u8).collect();
+        let content = ClipboardContent {
+            text: Some("clipboard text".to_string()),
+            png: Some(png.clone()),
+            ..Default::default()
+        };
+
+        write(content).unwrap();
+        let session = Session::open().unwrap();
+        let targets = session.targets().unwrap();
+        assert!(targets.contains(&session.atoms.TIMESTAMP) && targets.contains(&AtomEnum::STRING.into()));
+
+        let read = read().unwrap();
+        assert_eq!(read.text.as_deref(), Some("clipboard text"));
+        assert_eq!(read.png.map(|p| p.len()), Some(png.len()));
+    }
+}
@@ -0,0 +1,165 @@
+//! Desktop double shared by tests
+//!
+//! [`FakeDesktop`] finds elements by name, records the input it is sent, shows
+//! a fixed screen and fails everything else with `PlatformNotSupported`.
+
+use super::{ClipboardContent, DesktopAutomation, ElementTreeNode, WindowInfo, WindowState};
+use crate::automation::keys::{Key, KeyCombo};
+use crate::automation::{AutomationError, AutomationResult, ClickType, InputMethod, MouseButton, Rect};
+use crate::element::UIElement;
+use async_trait::async_trait;
+use image::DynamicImage;
+use std::io::Cursor;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+#[derive(Default)]
+pub(crate) struct FakeDesktop {
+    /// Elements found by `name:` locators
+    pub elements: Vec<UIElement>,
+    /// Time every element search takes, like a large accessibility tree
+    pub search_delay: Duration,
+    /// Screen contents returned by `screenshot`
+    pub screen: Option<DynamicImage>,
+    /// Input received, e.g. "click 120,45", "keys Ctrl+C" or "type abc"
+    pub log: Arc<Mutex<Vec<String>>>,
+}
+
+impl FakeDesktop {
+    /// Desktop showing a button for each name
+    pub fn with_buttons(names: &[&str]) -> Self {
+        let bounds = Rect { x: 10, y: 10, width: 80, height: 20 };
+        Self {
+            elements: names
+                .iter()
+                .map(|n| UIElement::new(n.to_string(), "Button".to_string(), bounds.clone()))
+                .collect(),
+            ..Self::default()
+        }
+    }
+
+    fn record(&self, entry: String) -> AutomationResult<()> {
+        self.log.lock().unwrap().push(entry);
+        Ok(())
+    }
+}
+
+fn unsupported<T>(what: &str) -> AutomationResult<T> {
+    Err(AutomationError::PlatformNotSupported(format!("{} on the fake desktop", what)))
+}
+
+#[async_trait]
+impl DesktopAutomation for FakeDesktop {
+    async fn click(&self, element: &UIElement, _: ClickType) -> AutomationResult<()> {
+        let (x, y) = element.bounds.center();
+        self.record(format!("click {},{}", x, y))
+    }
+
+    async fn input(&self, element: &UIElement, text: &str, _: InputMethod) -> AutomationResult<()> {
+        self.record(format!("input {} {}", element.name, text))
+    }
+
+    async fn get_text(&self, _: &UIElement) -> AutomationResult<String> {
+        unsupported("get_text")
+    }
+
+    async fn get_attribute(&self, _: &UIElement, _: &str) -> AutomationResult<String> {
+        unsupported("get_attribute")
+    }
+
+    async fn wait_element(&self, locator: &str, _: u64) -> AutomationResult<UIElement> {
+        self.find_element(locator).await
+    }
+
+    async fn find_elements(&self, locator: &str) -> AutomationResult<Vec<UIElement>> {
+        tokio::time::sleep(self.search_delay).await;
+        let name = locator.strip_prefix("name:").unwrap_or_default();
+        Ok(self.elements.iter().filter(|e| e.name == name).cloned().collect())
+    }
+
+    async fn capture_element(&self, _: i32, _: i32) -> AutomationResult<UIElement> {
+        unsupported("capture_element")
+    }
+
+    async fn get_element_tree(&self, _: Option<&str>, _: usize) -> AutomationResult<ElementTreeNode> {
+        unsupported("get_element_tree")
+    }
+
+    async fn get_element_bounds(&self, element: &UIElement) -> AutomationResult<Rect> {
+        Ok(element.bounds.clone())
+    }
+
+    async fn screenshot(&self, rect: Option<Rect>) -> AutomationResult<Vec<u8>> {
+        let Some(screen) = &self.screen else {
+            return unsupported("screenshot");
+        };
+        let shot = match rect {
+            Some(r) => screen.crop_imm(r.x as u32, r.y as u32, r.width as u32, r.height as u32),
+            None => screen.clone(),
+        };
+        let mut png = Vec::new();
+        shot.write_to(&mut Cursor::new(&mut png), image::ImageFormat::Png)
+            .map_err(|e| AutomationError::ExecutionFailed(e.to_string()))?;
+        Ok(png)
+    }
+
+    async fn scroll(&self, dx: i32, dy: i32, _: Option<(i32, i32)>) -> AutomationResult<()> {
+        self.record(format!("scroll {},{}", dx, dy))
+    }
+
+    async fn mouse_move(&self, x: i32, y: i32, _: bool) -> AutomationResult<()> {
+        self.record(format!("move {},{}", x, y))
+    }
+
+    async fn mouse_down(&self, button: MouseButton) -> AutomationResult<()> {
+        self.record(format!("down {:?}", button))
+    }
+
+    async fn mouse_up(&self, button: MouseButton) -> AutomationResult<()> {
+        self.record(format!("up {:?}", button))
+    }
+
+    async fn key_down(&self, key: Key) -> AutomationResult<()> {
+        self.record(format!("key down {}", key))
+    }
+
+    async fn key_up(&self, key: Key) -> AutomationResult<()> {
+        self.record(format!("key up {}", key))
+    }
+
+    async fn press_keys(&self, combo: &KeyCombo) -> AutomationResult<()> {
+        self.record(format!("keys {}", combo))
+    }
+
+    async fn type_text(&self, text: &str, _: u64) -> AutomationResult<()> {
+        self.record(format!("type {}", text))
+    }
+
+    async fn list_windows(&self) -> AutomationResult<Vec<WindowInfo>> {
+        Ok(Vec::new())
+    }
+
+    async fn activate_window(&self, _: &WindowInfo) -> AutomationResult<()> {
+        unsupported("activate_window")
+    }
+
+    async fn set_window_state(&self, _: &WindowInfo, _: WindowState) -> AutomationResult<()> {
+        unsupported("set_window_state")
+    }
+
+    async fn set_window_bounds(&self, _: &WindowInfo, _: Rect) -> AutomationResult<()> {
+        unsupported("set_window_bounds")
+    }
+
+    async fn close_window(&self, _: &WindowInfo) -> AutomationResult<()> {
+        unsupported("close_window")
+    }
+
+    async fn get_clipboard(&self) -> AutomationResult<ClipboardContent> {
+        unsupported("get_clipboard")
+    }
+
+    async fn set_clipboard(&self, _: &ClipboardContent) -> AutomationResult<()> {
+        unsupported("set_clipboard")
+    }
+}
@@ -0,0 +1,140 @@
+//! Top-level application windows
+//!
+//! Windows are looked up by title and process name through case-insensitive
+//! regular expressions, so plain text matches any title containing it.
+
+use crate::automation::{AutomationError, AutomationResult, Rect};
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum WindowState {
+    Normal,
+    Minimized,
+    Maximized,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WindowInfo {
+    /// Native handle: the X11 window, the HWND, or the process id and window
+    /// index on macOS
+    pub id: u64,
+    pub title: String,
+    pub pid: Option<u32>,
+    /// Executable or application name of the owning process
+    pub process: Option<String>,
+    pub bounds: Rect,
+    pub state: WindowState,
+    /// Whether the window has keyboard focus
+    pub focused: bool,
+}
+
+/// Which windows to look for; an empty query matches every window
+#[derive(Debug, Clone, Default)]
+pub struct WindowQuery {
+    title: Option<Regex>,
+    process: Option<Regex>,
+    pid: Option<u32>,
+}
+
+impl WindowQuery {
+    /// Query by title and process name patterns; empty patterns are ignored
+    pub fn new(title: Option<&str>, process: Option<&str>) -> AutomationResult<Self> {
+        Ok(Self {
+            title: pattern(title)?,
+            process: pattern(process)?,
+            pid: None,
+        })
+    }
+
+    pub fn with_pid(mut self, pid: u32) -> Self {
+        self.pid = Some(pid);
+        self
+    }
+
+    /// Whether the query has no criterion and matches every window
+    pub fn is_empty(&self) -> bool {
+        self.title.is_none() && self.process.is_none() && self.pid.is_none()
+    }
+
+    pub fn matches(&self, window: &WindowInfo) -> bool {
+        self.title.as_ref().is_none_or(|re| re.is_match(&window.title))
+            && self
+                .process
+                .as_ref()
+                .is_none_or(|re| window.process.as_deref().is_some_and(|p| re.is_match(p)))
+            && self.pid.is_none_or(|pid| window.pid == Some(pid))
+    }
+}
+
+impl fmt::Display for WindowQuery {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts = Vec::new();
+        if let Some(title) = &self.title {
+            parts.push(format!("title '{}'", title));
+        }
+        if let Some(process) = &self.process {
+            parts.push(format!("process '{}'", process));
+        }
+        if let Some(pid) = self.pid {
+            parts.push(format!("pid {}", pid));
+        }
+        if parts.is_empty() {
+            f.write_str("any window")
+        } else {
+            write!(f, "window with {}", parts.join(" and "))
+        }
+    }
+}
+
+fn pattern(pattern: Option<&str>) -> AutomationResult<Option<Regex>> {
+    let Some(pattern) = pattern.map(str::trim).filter(|p| !p.is_empty()) else {
+        return Ok(None);
+    };
+    RegexBuilder::new(pattern)
+        .case_insensitive(true)
+        .build()
+        .map(Some)
+        .map_err(|e| AutomationError::InvalidParameter(format!("Invalid pattern '{}': {}", pattern, e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn window(title: &str, process: Option<&str>) -> WindowInfo {
+        WindowInfo {
+            id: 1,
+            title: title.to_string(),
+            pid: Some(42),
+            process: process.map(String::from),
+            bounds: Rect {
+                x: 0,
+                y: 0,
+                width: 800,
+                height: 600,
+            },
+            state: WindowState::Normal,
+            focused: false,
+        }
+    }
+
+    #[test]
+    fn test_query_matches_title_and_process() {
+        let notepad = window("notes.txt - Notepad", Some("notepad.exe"));
+
+        assert!(WindowQuery::new(Some("notepad"), None).unwrap().matches(&notepad));
+        assert!(WindowQuery::new(Some(r"^notes\.txt"), Some("NOTEPAD")).unwrap().matches(&notepad));
+        assert!(WindowQuery::new(Some(""), Some(" ")).unwrap().matches(&notepad));
+        assert!(!WindowQuery::new(None, Some("code")).unwrap().matches(&notepad));
+        assert!(!WindowQuery::new(None, Some("notepad")).unwrap().matches(&window("Untitled", None)));
+        assert!(!WindowQuery::default().with_pid(7).matches(&notepad));
+
+        assert!(WindowQuery::new(Some(""), Some(" ")).unwrap().is_empty());
+        assert!(!WindowQuery::default().with_pid(7).is_empty());
+        assert!(WindowQuery::new(Some("("), None).is_err());
+    }
+}
@@ -1,9 +1,11 @@
 #![cfg(target_os = "windows")]
 
-use crate::automation::{AutomationError, AutomationResult, ClickType, InputMethod, Rect};
+use crate::automation::keys::Key;
+use crate::automation::{AutomationError, AutomationResult, ClickType, InputMethod, MouseButton, Rect};
 use crate::element::UIElement;
 use async_trait::async_trait;
-use super::DesktopAutomation;
+use super::clipboard::{decode_cf_html, encode_cf_html};
+use super::{ClipboardContent, DesktopAutomation, ElementTreeNode, WindowInfo, WindowState, MAX_TREE_NODES};
 use std::collections::HashMap;
 
 use uiautomation::{
@@ -19,14 +21,35 @@ use windows::Win32::UI::Input::KeyboardAndMouse::{
     MOUSE_EVENT_FLAGS, MOUSEEVENTF_LEFTDOWN, MOUSEEVENTF_LEFTUP,
     MOUSEEVENTF_RIGHTDOWN, MOUSEEVENTF_RIGHTUP, MOUSEEVENTF_ABSOLUTE,
     MOUSEEVENTF_MOVE, MOUSEEVENTF_VIRTUALDESK,
-    KEYEVENTF_KEYUP, KEYEVENTF_UNICODE,
-    VIRTUAL_KEY,
+    MOUSEEVENTF_MIDDLEDOWN, MOUSEEVENTF_MIDDLEUP, MOUSEEVENTF_WHEEL, MOUSEEVENTF_HWHEEL,
+    KEYEVENTF_KEYUP, KEYEVENTF_UNICODE, KEYEVENTF_EXTENDEDKEY, KEYBD_EVENT_FLAGS,
+    VIRTUAL_KEY, VkKeyScanW,
 };
 use windows::Win32::UI::WindowsAndMessaging::{
-    SetCursorPos, GetSystemMetrics,
+    SetCursorPos, GetCursorPos, GetSystemMetrics,
     SM_CXSCREEN, SM_CYSCREEN, SM_CXVIRTUALSCREEN, SM_CYVIRTUALSCREEN,
+    EnumWindows, GetWindowTextW, GetWindowTextLengthW, GetWindowThreadProcessId, GetWindowRect,
+    GetForegroundWindow, SetForegroundWindow, IsWindowVisible, IsIconic, IsZoomed, IsWindow,
+    ShowWindow, SetWindowPos, PostMessageW, GetWindowLongW,
+    GWL_EXSTYLE, WS_EX_TOOLWINDOW, SW_MINIMIZE, SW_MAXIMIZE, SW_RESTORE,
+    SWP_NOZORDER, SWP_NOACTIVATE, WM_CLOSE,
 };
-use windows::Win32::Foundation::POINT;
+use windows::Win32::System::Threading::{
+    OpenProcess, QueryFullProcessImageNameW, PROCESS_NAME_WIN32, PROCESS_QUERY_LIMITED_INFORMATION,
+};
+use windows::Win32::System::DataExchange::{
+    CloseClipboard, EmptyClipboard, GetClipboardData, IsClipboardFormatAvailable, OpenClipboard,
+    RegisterClipboardFormatW, SetClipboardData,
+};
+use windows::Win32::System::Memory::{GlobalAlloc, GlobalLock, GlobalSize, GlobalUnlock, GMEM_MOVEABLE};
+use windows::Win32::Foundation::{BOOL, CloseHandle, HANDLE, HGLOBAL, HWND, LPARAM, POINT, RECT, WPARAM};
+use windows::core::{w, PWSTR};
+
+/// Standard clipboard format for UTF-16 text
+const CF_UNICODETEXT: u32 = 13;
+
+/// Attempts to open the clipboard, which other applications hold briefly
+const CLIPBOARD_OPEN_ATTEMPTS: u32 = 10;
 
 pub struct WindowsAutomation {
     automation: UIAutomation,
@@ -115,6 +138,10 @@ impl WindowsAutomation {
             ui_element.attributes.insert("focused".to_string(), has_keyboard_focus.to_string());
         }
 
+        if let Ok(true) = element.get_is_password() {
+            ui_element.attributes.insert("isPassword".to_string(), "true".to_string());
+        }
+
         // Get value if it's a value pattern element
         if let Ok(value) = self.get_element_value(element) {
             if !value.is_empty() {
@@ -293,13 +320,97 @@ impl WindowsAutomation {
         Ok(())
     }
 
-    fn type_text(&self, text: &str) -> AutomationResult<()> {
+    fn set_cursor(&self, x: i32, y: i32) -> AutomationResult<()> {
+        unsafe {
+            SetCursorPos(x, y)
+                .map_err(|e| AutomationError::ExecutionFailed(format!("Failed to set cursor position: {}", e)))
+        }
+    }
+
+    /// Send a mouse event at the current cursor position
+    fn send_mouse(&self, flags: MOUSE_EVENT_FLAGS, mouse_data: i32) -> AutomationResult<()> {
+        let input = INPUT {
+            r#type: INPUT_MOUSE,
+            Anonymous: INPUT_0 {
+                mi: MOUSEINPUT {
+                    dx: 0,
+                    dy: 0,
+                    mouseData: mouse_data as _,
+                    dwFlags: flags,
+                    time: 0,
+                    dwExtraInfo: 0,
+                },
+            },
+        };
+
+        let sent = unsafe { SendInput(&[input], std::mem::size_of::<INPUT>() as i32) };
+        if sent == 0 {
+            return Err(AutomationError::ExecutionFailed("Failed to send mouse input".to_string()));
+        }
+        Ok(())
+    }
+
+    fn send_text(&self, text: &str) -> AutomationResult<()> {
         for ch in text.chars() {
             self.type_character(ch)?;
         }
         Ok(())
     }
 
+    /// Press or release a key through its virtual-key code
+    ///
+    /// Characters without a key on the current layout are sent as Unicode.
+    fn send_key(&self, key: Key, key_up: bool) -> AutomationResult<()> {
+        let vk = match key {
+            Key::Char(ch) => match key.virtual_key_code() {
+                Some(vk) => vk,
+                None => {
+                    let mut units = [0u16; 2];
+                    let units = ch.encode_utf16(&mut units);
+                    // The low byte is the key, the high byte the shift state
+                    let scan = if units.len() == 1 { unsafe { VkKeyScanW(units[0]) } } else { -1 };
+                    if scan == -1 {
+                        return units.iter().try_for_each(|&unit| self.send_keyboard(0, unit, KEYEVENTF_UNICODE, key_up));
+                    }
+                    (scan & 0xff) as u16
+                }
+            },
+            _ => key.virtual_key_code().ok_or_else(|| {
+                AutomationError::InvalidParameter(format!("No virtual key for {}", key))
+            })?,
+        };
+
+        let extended = matches!(
+            key,
+            Key::Insert | Key::Delete | Key::Home | Key::End | Key::PageUp | Key::PageDown
+                | Key::Left | Key::Up | Key::Right | Key::Down | Key::Meta | Key::Menu | Key::PrintScreen
+        );
+        let flags = if extended { KEYEVENTF_EXTENDEDKEY } else { KEYBD_EVENT_FLAGS(0) };
+        self.send_keyboard(vk, 0, flags, key_up)
+    }
+
+    fn send_keyboard(&self, vk: u16, scan: u16, flags: KEYBD_EVENT_FLAGS, key_up: bool) -> AutomationResult<()> {
+        let flags = if key_up { flags | KEYEVENTF_KEYUP } else { flags };
+        let input = INPUT {
+            r#type: INPUT_KEYBOARD,
+            Anonymous: INPUT_0 {
+                ki: KEYBDINPUT {
+                    wVk: VIRTUAL_KEY(vk),
+                    wScan: scan,
+                    dwFlags: flags,
+                    time: 0,
+                    dwExtraInfo: 0,
+                },
+            },
+        };
+
+        let sent = unsafe { SendInput(&[input], std::mem::size_of::<INPUT>() as i32) };
+        if sent == 0 {
+            return Err(AutomationError::ExecutionFailed("Failed to send keyboard input".to_string()));
+        }
+        Ok(())
+    }
+
     fn type_character(&self, ch: char) -> AutomationResult<()> {
         let char_code = ch as u16;
 
@@ -373,7 +484,7 @@ impl WindowsAutomation {
             return None;
         }
 
-        if self.element_matches_criteria(element, criteria) {
+        if self.element_matches_criteria(element, criteria, false) {
             return Some(element.clone());
         }
 
@@ -391,7 +502,127 @@ impl WindowsAutomation {
         None
     }
 
-    fn element_matches_criteria(&self, element: &UIAElement, criteria: &HashMap<&str, &str>) -> bool {
+    /// Find up to `limit` elements whose properties match the criteria exactly
+    fn find_all_by_criteria(&self, criteria: &HashMap<&str, &str>, limit: usize) -> Vec<UIAElement> {
+        let mut found = Vec::new();
+        if let Ok(root) = self.automation.get_root_element() {
+            self.collect_elements_recursive(&root, criteria, 10, limit, &mut found);
+        }
+        found
+    }
+
+    fn collect_elements_recursive(
+        &self,
+        element: &UIAElement,
+        criteria: &HashMap<&str, &str>,
+        max_depth: u32,
+        limit: usize,
+        found: &mut Vec<UIAElement>,
+    ) {
+        if max_depth == 0 || found.len() >= limit {
+            return;
+        }
+
+        if self.element_matches_criteria(element, criteria, true) {
+            found.push(element.clone());
+        }
+
+        let Ok(walker) = self.automation.create_tree_walker() else {
+            return;
+        };
+        let mut child = walker.get_first_child(element).ok();
+
+        while let Some(ref c) = child {
+            self.collect_elements_recursive(c, criteria, max_depth - 1, limit, found);
+            child = walker.get_next_sibling(c).ok();
+        }
+    }
+
+    /// Top-level window whose title or process matches, or the focused window
+    fn tree_root(&self, window: Option<&str>) -> AutomationResult<UIAElement> {
+        let Some(window) = window else {
+            let focused = self
+                .automation
+                .get_focused_element()
+                .map_err(|e| AutomationError::ElementNotFound(format!("No active window: {}", e)))?;
+            return Ok(self.top_level_window(&focused).unwrap_or(focused));
+        };
+
+        let wanted = window.to_lowercase();
+        let root = self
+            .automation
+            .get_root_element()
+            .map_err(|e| AutomationError::ExecutionFailed(format!("Failed to get desktop root: {}", e)))?;
+        let walker = self
+            .automation
+            .create_tree_walker()
+            .map_err(|e| AutomationError::ExecutionFailed(format!("Failed to create tree walker: {}", e)))?;
+
+        let mut child = walker.get_first_child(&root).ok();
+        while let Some(c) = child {
+            let title_matches = c
+                .get_name()
+                .map(|name| name.to_lowercase().contains(&wanted))
+                .unwrap_or(false);
+            let process_matches = c
+                .get_process_id()
+                .ok()
+                .and_then(|pid| self.get_process_name(pid))
+                .map(|name| name.to_lowercase().contains(&wanted))
+                .unwrap_or(false);
+            if title_matches || process_matches {
+                return Ok(c);
+            }
+            child = walker.get_next_sibling(&c).ok();
+        }
+
+        Err(AutomationError::ElementNotFound(format!(
+            "No window or application matching '{}'",
+            window
+        )))
+    }
+
+    /// Outermost ancestor below the desktop root
+    fn top_level_window(&self, element: &UIAElement) -> Option<UIAElement> {
+        let root = self.automation.get_root_element().ok()?;
+        let walker = self.automation.create_tree_walker().ok()?;
+        let mut current = element.clone();
+
+        for _ in 0..64 {
+            let parent = walker.get_parent(&current).ok()?;
+            if self.automation.compare_elements(&parent, &root).unwrap_or(false) {
+                return Some(current);
+            }
+            current = parent;
+        }
+        None
+    }
+
+    fn build_tree(&self, element: &UIAElement, depth: usize, budget: &mut usize) -> AutomationResult<ElementTreeNode> {
+        *budget = budget.saturating_sub(1);
+        let ui_element = self.uia_element_to_ui_element(element)?;
+
+        let mut children = Vec::new();
+        if depth > 0 {
+            if let Ok(walker) = self.automation.create_tree_walker() {
+                let mut child = walker.get_first_child(element).ok();
+                while let Some(ref c) = child {
+                    if *budget == 0 {
+                        log::warn!("Accessibility tree truncated at {} elements", MAX_TREE_NODES);
+                        break;
+                    }
+                    if let Ok(node) = self.build_tree(c, depth - 1, budget) {
+                        children.push(node);
+                    }
+                    child = walker.get_next_sibling(c).ok();
+                }
+            }
+        }
+
+        Ok(ElementTreeNode::new(ui_element, children))
+    }
+
+    fn element_matches_criteria(&self, element: &UIAElement, criteria: &HashMap<&str, &str>, exact: bool) -> bool {
         for (key, expected_value) in criteria {
             let actual_value = match key.to_lowercase().as_str() {
                 "name" | "title" => element.get_name().ok(),
@@ -402,7 +633,7 @@ impl WindowsAutomation {
             };
 
             match actual_value {
-                Some(actual) if actual.to_lowercase().contains(&expected_value.to_lowercase()) => continue,
+                Some(actual) if super::criterion_matches(&actual, expected_value, exact) => continue,
                 _ => return false,
             }
         }
@@ -441,7 +672,7 @@ impl DesktopAutomation for WindowsAutomation {
                 tokio::time::sleep(std::time::Duration::from_millis(100)).await;
 
                 // Type the text
-                self.type_text(text)?;
+                self.send_text(text)?;
             }
             InputMethod::Set => {
                 // Try to set value directly via UI Automation
@@ -454,7 +685,7 @@ impl DesktopAutomation for WindowsAutomation {
                 // Try to set value directly
                 if self.set_element_value(&uia_element, text).is_err() {
                     // Fallback to typing
-                    self.type_text(text)?;
+                    self.send_text(text)?;
                 }
             }
         }
@@ -516,14 +747,7 @@ impl DesktopAutomation for WindowsAutomation {
         let timeout = std::time::Duration::from_millis(timeout_ms);
         let poll_interval = std::time::Duration::from_millis(100);
 
-        // Parse locator - support formats like "name:Button1,type:Button"
-        let criteria: HashMap<&str, &str> = locator
-            .split(',')
-            .filter_map(|part| {
-                let mut parts = part.trim().splitn(2, ':');
-                Some((parts.next()?, parts.next()?))
-            })
-            .collect();
+        let criteria = super::parse_criteria(locator);
 
         while start.elapsed() < timeout {
             if let Some(element) = self.find_element_by_criteria(&criteria) {
@@ -538,6 +762,14 @@ impl DesktopAutomation for WindowsAutomation {
         )))
     }
 
+    async fn find_elements(&self, locator: &str) -> AutomationResult<Vec<UIElement>> {
+        let criteria = super::parse_criteria(locator);
+        self.find_all_by_criteria(&criteria, super::MAX_FOUND_ELEMENTS)
+            .iter()
+            .map(|element| self.uia_element_to_ui_element(element))
+            .collect()
+    }
+
     async fn capture_element(&self, x: i32, y: i32) -> AutomationResult<UIElement> {
         log::info!("Windows: Capture element at ({}, {})", x, y);
 
@@ -545,6 +777,14 @@ impl DesktopAutomation for WindowsAutomation {
         self.uia_element_to_ui_element(&uia_element)
     }
 
+    async fn get_element_tree(&self, window: Option<&str>, depth: usize) -> AutomationResult<ElementTreeNode> {
+        log::info!("Windows: Get accessibility tree of {:?} ({} levels)", window, depth);
+
+        let root = self.tree_root(window)?;
+        let mut budget = MAX_TREE_NODES;
+        self.build_tree(&root, depth, &mut budget)
+    }
+
     async fn get_element_bounds(&self, element: &UIElement) -> AutomationResult<Rect> {
         Ok(element.bounds.clone())
     }
@@ -606,6 +846,352 @@ $bitmap.Dispose()
 
         Ok(data)
     }
+
+    async fn scroll(&self, dx: i32, dy: i32, at: Option<(i32, i32)>) -> AutomationResult<()> {
+        log::info!("Windows: Scroll ({}, {}) at {:?}", dx, dy, at);
+
+        if let Some((x, y)) = at {
+            self.set_cursor(x, y)?;
+        }
+        // A notch is WHEEL_DELTA (120); positive wheel data scrolls up
+        if dy != 0 {
+            self.send_mouse(MOUSEEVENTF_WHEEL, -dy * 120)?;
+        }
+        if dx != 0 {
+            self.send_mouse(MOUSEEVENTF_HWHEEL, dx * 120)?;
+        }
+        Ok(())
+    }
+
+    async fn mouse_move(&self, x: i32, y: i32, smooth: bool) -> AutomationResult<()> {
+        log::info!("Windows: Move mouse to ({}, {})", x, y);
+
+        if !smooth {
+            return self.set_cursor(x, y);
+        }
+
+        let mut from = POINT::default();
+        unsafe {
+            GetCursorPos(&mut from)
+                .map_err(|e| AutomationError::ExecutionFailed(format!("Failed to get cursor position: {}", e)))?;
+        }
+        for (x, y) in super::glide_path((from.x, from.y), (x, y)) {
+            self.set_cursor(x, y)?;
+            tokio::time::sleep(std::time::Duration::from_millis(super::GLIDE_STEP_MS)).await;
+        }
+        Ok(())
+    }
+
+    async fn mouse_down(&self, button: MouseButton) -> AutomationResult<()> {
+        log::info!("Windows: Press {:?} mouse button", button);
+        let flags = match button {
+            MouseButton::Left => MOUSEEVENTF_LEFTDOWN,
+            MouseButton::Middle => MOUSEEVENTF_MIDDLEDOWN,
+            MouseButton::Right => MOUSEEVENTF_RIGHTDOWN,
+        };
+        self.send_mouse(flags, 0)
+    }
+
+    async fn mouse_up(&self, button: MouseButton) -> AutomationResult<()> {
+        log::info!("Windows: Release {:?} mouse button", button);
+        let flags = match button {
+            MouseButton::Left => MOUSEEVENTF_LEFTUP,
+            MouseButton::Middle => MOUSEEVENTF_MIDDLEUP,
+            MouseButton::Right => MOUSEEVENTF_RIGHTUP,
+        };
+        self.send_mouse(flags, 0)
+    }
+    async fn key_down(&self, key: Key) -> AutomationResult<()> {
+        log::info!("Windows: Press key {}", key);
+        self.send_key(key, false)
+    }
+
+    async fn key_up(&self, key: Key) -> AutomationResult<()> {
+        log::info!("Windows: Release key {}", key);
+        self.send_key(key, true)
+    }
+
+    async fn type_text(&self, text: &str, per_char_delay_ms: u64) -> AutomationResult<()> {
+        log::info!("Windows: Type {} characters", text.chars().count());
+
+        for ch in text.chars() {
+            self.type_character(ch)?;
+            if per_char_delay_ms > 0 {
+                tokio::time::sleep(std::time::Duration::from_millis(per_char_delay_ms)).await;
+            }
+        }
+        Ok(())
+    }
+
+    async fn list_windows(&self) -> AutomationResult<Vec<WindowInfo>> {
+        let mut handles: Vec<HWND> = Vec::new();
+        unsafe {
+            EnumWindows(Some(collect_window), LPARAM(&mut handles as *mut Vec<HWND> as isize))
+                .map_err(|e| AutomationError::ExecutionFailed(format!("Failed to enumerate windows: {}", e)))?;
+        }
+
+        let foreground = unsafe { GetForegroundWindow() };
+        let windows = handles
+            .into_iter()
+            .filter_map(|hwnd| {
+                let title = window_text(hwnd);
+                if title.is_empty() {
+                    return None;
+                }
+
+                let mut pid = 0u32;
+                let mut rect = RECT::default();
+                let state = unsafe {
+                    GetWindowThreadProcessId(hwnd, Some(&mut pid));
+                    GetWindowRect(hwnd, &mut rect).ok()?;
+                    if IsIconic(hwnd).as_bool() {
+                        WindowState::Minimized
+                    } else if IsZoomed(hwnd).as_bool() {
+                        WindowState::Maximized
+                    } else {
+                        WindowState::Normal
+                    }
+                };
+
+                Some(WindowInfo {
+                    id: hwnd.0 as u64,
+                    title,
+                    pid: (pid != 0).then_some(pid),
+                    process: image_name(pid),
+                    bounds: Rect {
+                        x: rect.left,
+                        y: rect.top,
+                        width: rect.right - rect.left,
+                        height: rect.bottom - rect.top,
+                    },
+                    state,
+                    focused: hwnd == foreground,
+                })
+            })
+            .collect();
+        Ok(windows)
+    }
+
+    async fn activate_window(&self, window: &WindowInfo) -> AutomationResult<()> {
+        log::info!("Windows: Activate window '{}'", window.title);
+
+        let hwnd = window_handle(window)?;
+        unsafe {
+            if IsIconic(hwnd).as_bool() {
+                let _ = ShowWindow(hwnd, SW_RESTORE);
+            }
+        }
+        // Windows only lets the process that received the last input take
+        // the foreground, so tap Alt first to become that process
+        self.send_key(Key::Alt, false)?;
+        self.send_key(Key::Alt, true)?;
+
+        if unsafe { SetForegroundWindow(hwnd) }.as_bool() {
+            Ok(())
+        } else {
+            Err(AutomationError::ExecutionFailed(format!(
+                "Failed to activate window '{}'",
+                window.title
+            )))
+        }
+    }
+
+    async fn set_window_state(&self, window: &WindowInfo, state: WindowState) -> AutomationResult<()> {
+        log::info!("Windows: Set window '{}' to {:?}", window.title, state);
+
+        let command = match state {
+            WindowState::Normal => SW_RESTORE,
+            WindowState::Minimized => SW_MINIMIZE,
+            WindowState::Maximized => SW_MAXIMIZE,
+        };
+        // ShowWindow reports the previous visibility, not success
+        unsafe {
+            let _ = ShowWindow(window_handle(window)?, command);
+        }
+        Ok(())
+    }
+
+    async fn set_window_bounds(&self, window: &WindowInfo, bounds: Rect) -> AutomationResult<()> {
+        log::info!("Windows: Move window '{}' to {:?}", window.title, bounds);
+
+        let hwnd = window_handle(window)?;
+        unsafe {
+            // Minimized and maximized windows ignore moves until restored
+            if window.state != WindowState::Normal {
+                let _ = ShowWindow(hwnd, SW_RESTORE);
+            }
+            SetWindowPos(
+                hwnd,
+                HWND::default(),
+                bounds.x,
+                bounds.y,
+                bounds.width,
+                bounds.height,
+                SWP_NOZORDER | SWP_NOACTIVATE,
+            )
+        }
+        .map_err(|e| AutomationError::ExecutionFailed(format!("Failed to move window: {}", e)))
+    }
+
+    async fn close_window(&self, window: &WindowInfo) -> AutomationResult<()> {
+        log::info!("Windows: Close window '{}'", window.title);
+
+        unsafe { PostMessageW(window_handle(window)?, WM_CLOSE, WPARAM(0), LPARAM(0)) }
+            .map_err(|e| AutomationError::ExecutionFailed(format!("Failed to close window: {}", e)))
+    }
+
+    async fn get_clipboard(&self) -> AutomationResult<ClipboardContent> {
+        let _clipboard = ClipboardGuard::open().await?;
+        let (html_format, png_format) = clipboard_formats();
+
+        let text = clipboard_bytes(CF_UNICODETEXT).map(|data| {
+            let units: Vec<u16> = data.chunks_exact(2).map(|c| u16::from_le_bytes([c[0], c[1]])).collect();
+            let len = units.iter().position(|&c| c == 0).unwrap_or(units.len());
+            String::from_utf16_lossy(&units[..len])
+        });
+        let html = clipboard_bytes(html_format).and_then(|data| decode_cf_html(&data));
+        let png = clipboard_bytes(png_format);
+
+        Ok(ClipboardContent { text, html, png })
+    }
+
+    async fn set_clipboard(&self, content: &ClipboardContent) -> AutomationResult<()> {
+        log::info!("Windows: Set clipboard");
+
+        let _clipboard = ClipboardGuard::open().await?;
+        let (html_format, png_format) = clipboard_formats();
+
+        unsafe { EmptyClipboard() }
+            .map_err(|e| AutomationError::ExecutionFailed(format!("Failed to clear clipboard: {}", e)))?;
+
+        if let Some(text) = &content.text {
+            let bytes: Vec<u8> = text
+                .encode_utf16()
+                .chain(std::iter::once(0))
+                .flat_map(u16::to_le_bytes)
+                .collect();
+            set_clipboard_bytes(CF_UNICODETEXT, &bytes)?;
+        }
+        if let Some(html) = &content.html {
+            let mut bytes = encode_cf_html(html).into_bytes();
+            bytes.push(0);
+            set_clipboard_bytes(html_format, &bytes)?;
+        }
+        if let Some(png) = &content.png {
+            set_clipboard_bytes(png_format, png)?;
+        }
+        Ok(())
+    }
+}
+
+/// `EnumWindows` callback collecting visible top-level windows that show up
+/// in the taskbar
+unsafe extern "system" fn collect_window(hwnd: HWND, lparam: LPARAM) -> BOOL {
+    let handles = &mut *(lparam.0 as *mut Vec<HWND>);
+    let tool_window = GetWindowLongW(hwnd, GWL_EXSTYLE) as u32 & WS_EX_TOOLWINDOW.0 != 0;
+    if IsWindowVisible(hwnd).as_bool() && !tool_window {
+        handles.push(hwnd);
+    }
+    BOOL(1)
+}
+
+fn window_text(hwnd: HWND) -> String {
+    unsafe {
+        let len = GetWindowTextLengthW(hwnd);
+        if len <= 0 {
+            return String::new();
+        }
+        let mut buf = vec![0u16; len as usize + 1];
+        let copied = GetWindowTextW(hwnd, &mut buf);
+        String::from_utf16_lossy(&buf[..copied.max(0) as usize])
+    }
+}
+
+/// Executable file name of a process, like "notepad.exe"
+fn image_name(pid: u32) -> Option<String> {
+    if pid == 0 {
+        return None;
+    }
+    unsafe {
+        let process = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid).ok()?;
+        let mut buf = [0u16; 1024];
+        let mut len = buf.len() as u32;
+        let result = QueryFullProcessImageNameW(process, PROCESS_NAME_WIN32, PWSTR(buf.as_mut_ptr()), &mut len);
+        let _ = CloseHandle(process);
+        result.ok()?;
+
+        let path = String::from_utf16_lossy(&buf[..len as usize]);
+        path.rsplit('\\').next().map(String::from)
+    }
+}
+
+/// Open clipboard, closed again on drop
+struct ClipboardGuard;
+
+impl ClipboardGuard {
+    async fn open() -> AutomationResult<Self> {
+        for _ in 0..CLIPBOARD_OPEN_ATTEMPTS {
+            if unsafe { OpenClipboard(HWND::default()) }.is_ok() {
+                return Ok(Self);
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        }
+        Err(AutomationError::ExecutionFailed(
+            "Clipboard is in use by another application".to_string(),
+        ))
+    }
+}
+
+impl Drop for ClipboardGuard {
+    fn drop(&mut self) {
+        let _ = unsafe { CloseClipboard() };
+    }
+}
+
+/// Registered "HTML Format" and "PNG" clipboard formats
+fn clipboard_formats() -> (u32, u32) {
+    unsafe { (RegisterClipboardFormatW(w!("HTML Format")), RegisterClipboardFormatW(w!("PNG"))) }
+}
+
+/// Copy of the clipboard data in a format, if present; the clipboard must be open
+fn clipboard_bytes(format: u32) -> Option<Vec<u8>> {
+    unsafe {
+        IsClipboardFormatAvailable(format).ok()?;
+        let handle = GetClipboardData(format).ok()?;
+        let memory = HGLOBAL(handle.0);
+        let data = GlobalLock(memory) as *const u8;
+        if data.is_null() {
+            return None;
+        }
+        let bytes = std::slice::from_raw_parts(data, GlobalSize(memory)).to_vec();
+        let _ = GlobalUnlock(memory);
+        Some(bytes)
+    }
+}
+
+/// Put data in a format on the open clipboard, which then owns the memory
+fn set_clipboard_bytes(format: u32, bytes: &[u8]) -> AutomationResult<()> {
+    let failed = |e: windows::core::Error| AutomationError::ExecutionFailed(format!("Failed to set clipboard: {}", e));
+    unsafe {
+        let memory = GlobalAlloc(GMEM_MOVEABLE, bytes.len()).map_err(failed)?;
+        let data = GlobalLock(memory) as *mut u8;
+        if data.is_null() {
+            return Err(AutomationError::ExecutionFailed("Failed to lock clipboard memory".to_string()));
+        }
+        std::ptr::copy_nonoverlapping(bytes.as_ptr(), data, bytes.len());
+        let _ = GlobalUnlock(memory);
+        SetClipboardData(format, HANDLE(memory.0)).map_err(failed)?;
+    }
+    Ok(())
+}
+
+fn window_handle(window: &WindowInfo) -> AutomationResult<HWND> {
+    let hwnd = HWND(window.id as isize as *mut std::ffi::c_void);
+    if unsafe { IsWindow(hwnd) }.as_bool() {
+        Ok(hwnd)
+    } else {
+        Err(AutomationError::ElementNotFound(format!("Window '{}' no longer exists", window.title)))
+    }
 }
 
 fn control_type_to_string(ct: ControlType) -> String {
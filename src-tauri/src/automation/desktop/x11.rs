@@ -0,0 +1,849 @@
+#![cfg(target_os = "linux")]
+
+//! Native X11 input and screen capture
+//!
+//! Mouse and keyboard input is synthesized with the XTEST extension and the
+//! screen is read straight from the root window, so nothing like xdotool or
+//! scrot has to be installed. Works against any X server, including Xvfb.
+
+use crate::automation::keys::Key;
+use crate::automation::{AutomationError, AutomationResult, Rect};
+use image::{codecs::png::PngEncoder, ExtendedColorType, ImageEncoder};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use x11rb::connection::{Connection, RequestConnection};
+use x11rb::protocol::xproto::{
+    AtomEnum, ClientMessageEvent, ConnectionExt as _, EventMask, ImageFormat, ImageOrder, KeyButMask, Keycode, Keysym, Window,
+    BUTTON_PRESS_EVENT, BUTTON_RELEASE_EVENT, KEY_PRESS_EVENT, KEY_RELEASE_EVENT, MOTION_NOTIFY_EVENT,
+};
+use x11rb::protocol::xtest::{self, ConnectionExt as _};
+use x11rb::rust_connection::RustConnection;
+use x11rb::wrapper::ConnectionExt as _;
+
+pub const BUTTON_LEFT: u8 = 1;
+pub const BUTTON_MIDDLE: u8 = 2;
+pub const BUTTON_RIGHT: u8 = 3;
+const BUTTON_WHEEL_UP: u8 = 4;
+const BUTTON_WHEEL_DOWN: u8 = 5;
+const BUTTON_WHEEL_LEFT: u8 = 6;
+const BUTTON_WHEEL_RIGHT: u8 = 7;
+
+pub const XK_BACKSPACE: Keysym = 0xff08;
+pub const XK_TAB: Keysym = 0xff09;
+pub const XK_RETURN: Keysym = 0xff0d;
+pub const XK_ISO_LEVEL3_SHIFT: Keysym = 0xfe03;
+pub const XK_MODE_SWITCH: Keysym = 0xff7e;
+pub const XK_NUM_LOCK: Keysym = 0xff7f;
+pub const XK_ESCAPE: Keysym = 0xff1b;
+pub const XK_SHIFT_L: Keysym = 0xffe1;
+pub const XK_CONTROL_L: Keysym = 0xffe3;
+pub const XK_ALT_L: Keysym = 0xffe9;
+pub const XK_SUPER_L: Keysym = 0xffeb;
+
+/// Pause between synthesized key strokes, so clients keep up
+const KEY_DELAY: Duration = Duration::from_millis(8);
+
+/// Pause after changing the keymap before it is relied upon or restored;
+/// clients fetch the new mapping only when they see the MappingNotify
+const REMAP_DELAY: Duration = Duration::from_millis(50);
+
+x11rb::atom_manager! {
+    Atoms: AtomsCookie {
+        _NET_ACTIVE_WINDOW,
+        _NET_CLIENT_LIST,
+        _NET_CLOSE_WINDOW,
+        _NET_MOVERESIZE_WINDOW,
+        _NET_WM_NAME,
+        _NET_WM_PID,
+        _NET_WM_STATE,
+        _NET_WM_STATE_HIDDEN,
+        _NET_WM_STATE_MAXIMIZED_HORZ,
+        _NET_WM_STATE_MAXIMIZED_VERT,
+        WM_CHANGE_STATE,
+        UTF8_STRING,
+    }
+}
+
+/// `_NET_WM_STATE` client message actions
+const NET_WM_STATE_REMOVE: u32 = 0;
+const NET_WM_STATE_ADD: u32 = 1;
+
+/// ICCCM state requested through `WM_CHANGE_STATE` to minimize a window
+const ICONIC_STATE: u32 = 3;
+
+/// Source indication telling the window manager a pager sent the request,
+/// which it honors rather than treating it as an application's whim
+const SOURCE_PAGER: u32 = 2;
+
+/// Top-level client window
+#[derive(Debug, Clone)]
+pub struct X11Window {
+    pub id: Window,
+    pub title: String,
+    pub pid: Option<u32>,
+    pub bounds: Rect,
+}
+
+/// Connection to the X server used for input and capture
+pub struct X11Display {
+    conn: RustConnection,
+    root: Window,
+    screen: usize,
+    atoms: Atoms,
+    /// Keys held down through a remapped spare keycode
+    remapped_keys: Mutex<HashMap<Keysym, Keycode>>,
+}
+
+impl X11Display {
+    /// Connect to the display named by `$DISPLAY` and check for XTEST
+    pub fn connect() -> AutomationResult<Self> {
+        let (conn, screen) = x11rb::connect(None)
+            .map_err(|e| AutomationError::PlatformNotSupported(format!("Cannot connect to X display: {}", e)))?;
+
+        let has_xtest = conn
+            .extension_information(xtest::X11_EXTENSION_NAME)
+            .map_err(x11_error)?
+            .is_some();
+        if !has_xtest {
+            return Err(AutomationError::PlatformNotSupported(
+                "X server does not support the XTEST extension".to_string(),
+            ));
+        }
+
+        let atoms = Atoms::new(&conn).map_err(x11_error)?.reply().map_err(x11_error)?;
+        let root = conn.setup().roots[screen].root;
+
+        Ok(Self {
+            conn,
+            root,
+            screen,
+            atoms,
+            remapped_keys: Mutex::new(HashMap::new()),
+        })
+    }
+
+    pub fn screen_size(&self) -> (i32, i32) {
+        let screen = &self.conn.setup().roots[self.screen];
+        (screen.width_in_pixels as i32, screen.height_in_pixels as i32)
+    }
+
+    /// Pointer position with the held modifiers and buttons
+    pub fn pointer_state(&self) -> AutomationResult<(i32, i32, KeyButMask)> {
+        let reply = self.conn.query_pointer(self.root).map_err(x11_error)?.reply().map_err(x11_error)?;
+        Ok((reply.root_x as i32, reply.root_y as i32, reply.mask))
+    }
+
+    /// Whether a key producing each keysym is held down
+    pub fn keys_down(&self, keysyms: &[Keysym]) -> AutomationResult<Vec<bool>> {
+        let keymap = Keymap::load(&self.conn)?;
+        let keys = self.conn.query_keymap().map_err(x11_error)?.reply().map_err(x11_error)?.keys;
+
+        Ok(keysyms
+            .iter()
+            .map(|&keysym| {
+                keymap
+                    .lookup(keysym)
+                    .is_some_and(|(keycode, _)| keys[keycode as usize / 8] & (1 << (keycode % 8)) != 0)
+            })
+            .collect())
+    }
+
+    pub fn move_to(&self, x: i32, y: i32) -> AutomationResult<()> {
+        self.fake(MOTION_NOTIFY_EVENT, 0, x as i16, y as i16)?;
+        self.flush()
+    }
+
+    pub fn button(&self, button: u8, press: bool) -> AutomationResult<()> {
+        let event = if press { BUTTON_PRESS_EVENT } else { BUTTON_RELEASE_EVENT };
+        self.fake(event, button, 0, 0)?;
+        self.flush()
+    }
+
+    /// Move to a point and click a button `count` times
+    pub fn click(&self, x: i32, y: i32, button: u8, count: u32) -> AutomationResult<()> {
+        self.move_to(x, y)?;
+        for i in 0..count {
+            if i > 0 {
+                std::thread::sleep(Duration::from_millis(40));
+            }
+            self.button(button, true)?;
+            self.button(button, false)?;
+        }
+        Ok(())
+    }
+
+    /// Scroll by wheel notches; positive `dy` scrolls down, positive `dx` right
+    pub fn scroll(&self, dx: i32, dy: i32) -> AutomationResult<()> {
+        let vertical = if dy > 0 { BUTTON_WHEEL_DOWN } else { BUTTON_WHEEL_UP };
+        let horizontal = if dx > 0 { BUTTON_WHEEL_RIGHT } else { BUTTON_WHEEL_LEFT };
+
+        for (button, notches) in [(vertical, dy.unsigned_abs()), (horizontal, dx.unsigned_abs())] {
+            for _ in 0..notches {
+                self.button(button, true)?;
+                self.button(button, false)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Press or release the key producing `keysym`; a spare keycode remapped
+    /// for the press is restored on release
+    pub fn key(&self, keysym: Keysym, press: bool) -> AutomationResult<()> {
+        let mut held = self.remapped_keys.lock().unwrap_or_else(|e| e.into_inner());
+        let mut keymap = Keymap::load(&self.conn)?;
+
+        let keycode = match (press, held.get(&keysym)) {
+            (false, Some(&keycode)) => keycode,
+            (false, None) => {
+                keymap
+                    .lookup(keysym)
+                    .ok_or_else(|| AutomationError::InvalidParameter(format!("No key produces keysym {:#x}", keysym)))?
+                    .0
+            }
+            (true, _) => {
+                let mut remapped = Vec::new();
+                let (keycode, _) = keymap.keycode_for(&self.conn, keysym, &mut remapped)?;
+                if !remapped.is_empty() {
+                    held.insert(keysym, keycode);
+                }
+                keycode
+            }
+        };
+
+        let event = if press { KEY_PRESS_EVENT } else { KEY_RELEASE_EVENT };
+        self.fake(event, keycode, 0, 0)?;
+        self.flush()?;
+
+        if !press {
+            if let Some(keycode) = held.remove(&keysym) {
+                keymap.restore(&self.conn, &mut vec![keycode])?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Press keys in order and release them in reverse, e.g. Ctrl+Shift+S
+    pub fn press_keys(&self, keysyms: &[Keysym]) -> AutomationResult<()> {
+        let mut keymap = Keymap::load(&self.conn)?;
+        let mut remapped = Vec::new();
+        let keycodes = keysyms
+            .iter()
+            .map(|&keysym| keymap.keycode_for(&self.conn, keysym, &mut remapped))
+            .collect::<AutomationResult<Vec<_>>>();
+
+        let result = keycodes.and_then(|keycodes| {
+            for &(keycode, _) in &keycodes {
+                self.fake(KEY_PRESS_EVENT, keycode, 0, 0)?;
+            }
+            for &(keycode, _) in keycodes.iter().rev() {
+                self.fake(KEY_RELEASE_EVENT, keycode, 0, 0)?;
+            }
+            self.flush()
+        });
+
+        keymap.restore(&self.conn, &mut remapped)?;
+        result
+    }
+
+    /// Type text character by character; characters missing from the keyboard
+    /// layout are typed through temporarily remapped spare keycodes
+    pub fn type_text(&self, text: &str) -> AutomationResult<()> {
+        let mut keymap = Keymap::load(&self.conn)?;
+        let shift = keymap.lookup(XK_SHIFT_L).map(|(keycode, _)| keycode);
+        let mut remapped = Vec::new();
+
+        let result = text.chars().try_for_each(|ch| {
+            let (keycode, shifted) = keymap.keycode_for(&self.conn, char_keysym(ch), &mut remapped)?;
+            let shift = shift.filter(|_| shifted);
+
+            if let Some(shift) = shift {
+                self.fake(KEY_PRESS_EVENT, shift, 0, 0)?;
+            }
+            self.fake(KEY_PRESS_EVENT, keycode, 0, 0)?;
+            self.fake(KEY_RELEASE_EVENT, keycode, 0, 0)?;
+            if let Some(shift) = shift {
+                self.fake(KEY_RELEASE_EVENT, shift, 0, 0)?;
+            }
+            self.flush()?;
+            std::thread::sleep(KEY_DELAY);
+            Ok(())
+        });
+
+        keymap.restore(&self.conn, &mut remapped)?;
+        result
+    }
+
+    /// Capture the screen, or part of it, as PNG
+    pub fn capture(&self, rect: Option<Rect>) -> AutomationResult<Vec<u8>> {
+        let (screen_width, screen_height) = self.screen_size();
+        let rect = clip(rect, screen_width, screen_height).ok_or_else(|| {
+            AutomationError::InvalidParameter("Screenshot region is outside the screen".to_string())
+        })?;
+
+        let reply = self
+            .conn
+            .get_image(
+                ImageFormat::Z_PIXMAP,
+                self.root,
+                rect.x as i16,
+                rect.y as i16,
+                rect.width as u16,
+                rect.height as u16,
+                u32::MAX,
+            )
+            .map_err(x11_error)?
+            .reply()
+            .map_err(x11_error)?;
+
+        let format = self.pixel_format(reply.depth, reply.visual, rect.width as usize)?;
+        let rgb = format.to_rgb(&reply.data, rect.width as usize, rect.height as usize)?;
+
+        let mut png = Vec::new();
+        PngEncoder::new(&mut png)
+            .write_image(&rgb, rect.width as u32, rect.height as u32, ExtendedColorType::Rgb8)
+            .map_err(|e| AutomationError::ExecutionFailed(format!("Failed to encode screenshot: {}", e)))?;
+        Ok(png)
+    }
+
+    /// Managed top-level windows, from `_NET_CLIENT_LIST` or the root's children
+    pub fn windows(&self) -> AutomationResult<Vec<X11Window>> {
+        let clients = self
+            .conn
+            .get_property(false, self.root, self.atoms._NET_CLIENT_LIST, AtomEnum::WINDOW, 0, u32::MAX / 4)
+            .map_err(x11_error)?
+            .reply()
+            .map_err(x11_error)?;
+
+        let ids: Vec<Window> = match clients.value32() {
+            Some(ids) if clients.value_len > 0 => ids.collect(),
+            _ => self.conn.query_tree(self.root).map_err(x11_error)?.reply().map_err(x11_error)?.children,
+        };
+
+        Ok(ids.into_iter().filter_map(|id| self.window(id)).collect())
+    }
+
+    pub fn window(&self, id: Window) -> Option<X11Window> {
+        let geometry = self.conn.get_geometry(id).ok()?.reply().ok()?;
+        let origin = self.conn.translate_coordinates(id, self.root, 0, 0).ok()?.reply().ok()?;
+
+        let title = self
+            .text_property(id, self.atoms._NET_WM_NAME, self.atoms.UTF8_STRING)
+            .or_else(|| self.text_property(id, AtomEnum::WM_NAME.into(), AtomEnum::STRING.into()))
+            .unwrap_or_default();
+        let pid = self
+            .conn
+            .get_property(false, id, self.atoms._NET_WM_PID, AtomEnum::CARDINAL, 0, 1)
+            .ok()?
+            .reply()
+            .ok()
+            .and_then(|reply| reply.value32().and_then(|mut values| values.next()));
+
+        Some(X11Window {
+            id,
+            title,
+            pid,
+            bounds: Rect {
+                x: origin.dst_x as i32,
+                y: origin.dst_y as i32,
+                width: geometry.width as i32,
+                height: geometry.height as i32,
+            },
+        })
+    }
+
+    /// Window with keyboard focus, per `_NET_ACTIVE_WINDOW`
+    pub fn active_window(&self) -> Option<Window> {
+        let reply = self
+            .conn
+            .get_property(false, self.root, self.atoms._NET_ACTIVE_WINDOW, AtomEnum::WINDOW, 0, 1)
+            .ok()?
+            .reply()
+            .ok()?;
+        let id = reply.value32()?.next();
+        id.filter(|&id| id != 0)
+    }
+
+    /// Whether a window is minimized and whether it is maximized
+    pub fn window_state(&self, id: Window) -> (bool, bool) {
+        let states: Vec<u32> = self
+            .conn
+            .get_property(false, id, self.atoms._NET_WM_STATE, AtomEnum::ATOM, 0, 64)
+            .ok()
+            .and_then(|cookie| cookie.reply().ok())
+            .and_then(|reply| reply.value32().map(|values| values.collect()))
+            .unwrap_or_default();
+
+        let has = |atom: u32| states.contains(&atom);
+        (
+            has(self.atoms._NET_WM_STATE_HIDDEN),
+            has(self.atoms._NET_WM_STATE_MAXIMIZED_HORZ) && has(self.atoms._NET_WM_STATE_MAXIMIZED_VERT),
+        )
+    }
+
+    /// Raise and focus a window, mapping it again if it is minimized
+    pub fn activate(&self, id: Window) -> AutomationResult<()> {
+        self.send_wm_message(id, self.atoms._NET_ACTIVE_WINDOW, [SOURCE_PAGER, x11rb::CURRENT_TIME, 0, 0, 0])
+    }
+
+    pub fn minimize(&self, id: Window) -> AutomationResult<()> {
+        self.send_wm_message(id, self.atoms.WM_CHANGE_STATE, [ICONIC_STATE, 0, 0, 0, 0])
+    }
+
+    pub fn set_maximized(&self, id: Window, maximized: bool) -> AutomationResult<()> {
+        let action = if maximized { NET_WM_STATE_ADD } else { NET_WM_STATE_REMOVE };
+        self.send_wm_message(
+            id,
+            self.atoms._NET_WM_STATE,
+            [
+                action,
+                self.atoms._NET_WM_STATE_MAXIMIZED_HORZ,
+                self.atoms._NET_WM_STATE_MAXIMIZED_VERT,
+                SOURCE_PAGER,
+                0,
+            ],
+        )
+    }
+
+    /// Move and resize the frame of a window to root coordinates
+    pub fn move_resize(&self, id: Window, bounds: &Rect) -> AutomationResult<()> {
+        // Default gravity, with x, y, width and height all given
+        let flags = 0xf << 8 | SOURCE_PAGER << 12;
+        self.send_wm_message(
+            id,
+            self.atoms._NET_MOVERESIZE_WINDOW,
+            [
+                flags,
+                bounds.x as u32,
+                bounds.y as u32,
+                bounds.width.max(1) as u32,
+                bounds.height.max(1) as u32,
+            ],
+        )
+    }
+
+    /// Ask the window manager to close a window the way its close button does
+    pub fn close(&self, id: Window) -> AutomationResult<()> {
+        self.send_wm_message(id, self.atoms._NET_CLOSE_WINDOW, [x11rb::CURRENT_TIME, SOURCE_PAGER, 0, 0, 0])
+    }
+
+    /// Send a client message about `window` to the window manager
+    fn send_wm_message(&self, window: Window, message: u32, data: [u32; 5]) -> AutomationResult<()> {
+        let event = ClientMessageEvent::new(32, window, message, data);
+        self.conn
+            .send_event(
+                false,
+                self.root,
+                EventMask::SUBSTRUCTURE_REDIRECT | EventMask::SUBSTRUCTURE_NOTIFY,
+                event,
+            )
+            .map_err(x11_error)?;
+        self.flush()
+    }
+
+    fn text_property(&self, window: Window, property: u32, type_: u32) -> Option<String> {
+        let reply = self.conn.get_property(false, window, property, type_, 0, 1024).ok()?.reply().ok()?;
+        if reply.value.is_empty() {
+            return None;
+        }
+        Some(String::from_utf8_lossy(&reply.value).into_owned())
+    }
+
+    fn pixel_format(&self, depth: u8, visual: u32, width: usize) -> AutomationResult<PixelFormat> {
+        let setup = self.conn.setup();
+        let format = setup
+            .pixmap_formats
+            .iter()
+            .find(|f| f.depth == depth)
+            .ok_or_else(|| AutomationError::ExecutionFailed(format!("No pixmap format for depth {}", depth)))?;
+        let visual = setup.roots[self.screen]
+            .allowed_depths
+            .iter()
+            .flat_map(|d| d.visuals.iter())
+            .find(|v| v.visual_id == visual)
+            .ok_or_else(|| AutomationError::ExecutionFailed(format!("Unknown visual {:#x}", visual)))?;
+
+        let bits_per_pixel = format.bits_per_pixel as usize;
+        let pad = format.scanline_pad as usize;
+        Ok(PixelFormat {
+            bytes_per_pixel: bits_per_pixel / 8,
+            stride: (width * bits_per_pixel).div_ceil(pad) * pad / 8,
+            masks: [visual.red_mask, visual.green_mask, visual.blue_mask],
+            lsb_first: setup.image_byte_order == ImageOrder::LSB_FIRST,
+        })
+    }
+
+    fn fake(&self, event: u8, detail: u8, x: i16, y: i16) -> AutomationResult<()> {
+        self.conn
+            .xtest_fake_input(event, detail, x11rb::CURRENT_TIME, self.root, x, y, 0)
+            .map_err(x11_error)?;
+        Ok(())
+    }
+
+    fn flush(&self) -> AutomationResult<()> {
+        self.conn.sync().map_err(x11_error)
+    }
+}
+
+/// Keyboard mapping of the server, with the keycodes borrowed for remapping
+struct Keymap {
+    min_keycode: Keycode,
+    keysyms_per_keycode: usize,
+    keysyms: Vec<Keysym>,
+}
+
+impl Keymap {
+    fn load(conn: &RustConnection) -> AutomationResult<Self> {
+        let setup = conn.setup();
+        let (min_keycode, max_keycode) = (setup.min_keycode, setup.max_keycode);
+        let reply = conn
+            .get_keyboard_mapping(min_keycode, max_keycode - min_keycode + 1)
+            .map_err(x11_error)?
+            .reply()
+            .map_err(x11_error)?;
+
+        Ok(Self {
+            min_keycode,
+            keysyms_per_keycode: reply.keysyms_per_keycode as usize,
+            keysyms: reply.keysyms,
+        })
+    }
+
+    fn row(&self, keycode: Keycode) -> &[Keysym] {
+        let start = (keycode - self.min_keycode) as usize * self.keysyms_per_keycode;
+        &self.keysyms[start..start + self.keysyms_per_keycode]
+    }
+
+    fn keycodes(&self) -> impl DoubleEndedIterator<Item = Keycode> + '_ {
+        let count = self.keysyms.len() / self.keysyms_per_keycode.max(1);
+        (0..count).map(move |i| self.min_keycode + i as Keycode)
+    }
+
+    /// Keycode producing `keysym` and whether Shift is needed for it
+    fn lookup(&self, keysym: Keysym) -> Option<(Keycode, bool)> {
+        (0..2.min(self.keysyms_per_keycode)).find_map(|level| {
+            self.keycodes()
+                .find(|&keycode| self.row(keycode)[level] == keysym)
+                .map(|keycode| (keycode, level == 1))
+        })
+    }
+
+    /// Keycodes without any keysym, highest first
+    fn spare(&self) -> impl Iterator<Item = Keycode> + '_ {
+        self.keycodes().rev().filter(|&keycode| self.row(keycode).iter().all(|&s| s == 0))
+    }
+
+    /// Keycode for `keysym`, remapping a spare keycode when needed; once every
+    /// spare keycode is in use they are restored and reused
+    fn keycode_for(
+        &mut self,
+        conn: &RustConnection,
+        keysym: Keysym,
+        remapped: &mut Vec<Keycode>,
+    ) -> AutomationResult<(Keycode, bool)> {
+        if let Some(found) = self.lookup(keysym) {
+            return Ok(found);
+        }
+
+        let spare = self.spare().next();
+        let keycode = match spare {
+            Some(keycode) => keycode,
+            None if !remapped.is_empty() => {
+                self.restore(conn, remapped)?;
+                self.spare().next().ok_or_else(no_spare_keycode)?
+            }
+            None => return Err(no_spare_keycode()),
+        };
+
+        self.remap(conn, keycode, keysym)?;
+        let start = (keycode - self.min_keycode) as usize * self.keysyms_per_keycode;
+        self.keysyms[start] = keysym;
+        self.keysyms[start + 1.min(self.keysyms_per_keycode - 1)] = keysym;
+        remapped.push(keycode);
+
+        std::thread::sleep(REMAP_DELAY);
+        Ok((keycode, false))
+    }
+
+    fn remap(&self, conn: &RustConnection, keycode: Keycode, keysym: Keysym) -> AutomationResult<()> {
+        // Same keysym on both levels so a held Shift does not matter
+        let mut row = vec![0; self.keysyms_per_keycode];
+        row[0] = keysym;
+        if row.len() > 1 {
+            row[1] = keysym;
+        }
+        conn.change_keyboard_mapping(1, keycode, self.keysyms_per_keycode as u8, &row)
+            .map_err(x11_error)?;
+        conn.sync().map_err(x11_error)
+    }
+
+    /// Clear remapped keycodes again, after giving clients time to read them
+    fn restore(&mut self, conn: &RustConnection, remapped: &mut Vec<Keycode>) -> AutomationResult<()> {
+        if remapped.is_empty() {
+            return Ok(());
+        }
+        std::thread::sleep(REMAP_DELAY);
+
+        let empty = vec![0; self.keysyms_per_keycode];
+        for keycode in remapped.drain(..) {
+            conn.change_keyboard_mapping(1, keycode, self.keysyms_per_keycode as u8, &empty)
+                .map_err(x11_error)?;
+            let start = (keycode - self.min_keycode) as usize * self.keysyms_per_keycode;
+            self.keysyms[start..start + self.keysyms_per_keycode].fill(0);
+        }
+        conn.sync().map_err(x11_error)
+    }
+}
+
+/// Layout of ZPixmap image data
+struct PixelFormat {
+    bytes_per_pixel: usize,
+    stride: usize,
+    masks: [u32; 3],
+    lsb_first: bool,
+}
+
+impl PixelFormat {
+    /// Convert image data to packed 8-bit RGB
+    fn to_rgb(&self, data: &[u8], width: usize, height: usize) -> AutomationResult<Vec<u8>> {
+        if !(2..=4).contains(&self.bytes_per_pixel) || data.len() < self.stride * height {
+            return Err(AutomationError::ExecutionFailed(format!(
+                "Unsupported screen format ({} bytes per pixel)",
+                self.bytes_per_pixel
+            )));
+        }
+
+        let channels = self.masks.map(|mask| (mask.trailing_zeros(), mask >> mask.trailing_zeros()));
+        let mut rgb = Vec::with_capacity(width * height * 3);
+
+        for row in data.chunks(self.stride).take(height) {
+            for bytes in row.chunks(self.bytes_per_pixel).take(width) {
+                let pixel = bytes.iter().enumerate().fold(0u32, |pixel, (i, &b)| {
+                    let shift = if self.lsb_first { i } else { bytes.len() - 1 - i };
+                    pixel | (b as u32) << (shift * 8)
+                });
+                for (shift, max) in channels {
+                    let value = (pixel >> shift) & max;
+                    rgb.push((value * 255).checked_div(max).unwrap_or(0) as u8);
+                }
+            }
+        }
+
+        Ok(rgb)
+    }
+}
+
+/// Keysym typing a character: Latin-1 maps directly, everything else through
+/// the Unicode keysym range
+pub fn char_keysym(ch: char) -> Keysym {
+    match ch {
+        '\n' | '\r' => XK_RETURN,
+        '\t' => XK_TAB,
+        '\u{8}' => XK_BACKSPACE,
+        ' '..='~' | '\u{a0}'..='\u{ff}' => ch as Keysym,
+        _ => 0x0100_0000 | ch as Keysym,
+    }
+}
+
+/// Keysym of a key name like "ctrl", "Enter", "F5", "pagedown" or "a"
+pub fn keysym_from_name(name: &str) -> Option<Keysym> {
+    let lower = name.trim().to_lowercase();
+    let keysym = match lower.as_str() {
+        "ctrl" | "control" => XK_CONTROL_L,
+        "shift" => XK_SHIFT_L,
+        "alt" | "option" => XK_ALT_L,
+        "super" | "win" | "windows" | "meta" | "cmd" | "command" => XK_SUPER_L,
+        "enter" | "return" => XK_RETURN,
+        "tab" => XK_TAB,
+        "esc" | "escape" => XK_ESCAPE,
+        "backspace" => XK_BACKSPACE,
+        "space" => 0x0020,
+        "delete" | "del" => 0xffff,
+        "insert" | "ins" => 0xff63,
+        "home" => 0xff50,
+        "end" => 0xff57,
+        "pageup" | "pgup" => 0xff55,
+        "pagedown" | "pgdn" => 0xff56,
+        "left" => 0xff51,
+        "up" => 0xff52,
+        "right" => 0xff53,
+        "down" => 0xff54,
+        "capslock" => 0xffe5,
+        "printscreen" | "print" => 0xff61,
+        "menu" => 0xff67,
+        _ => {
+            if let Some(n) = lower.strip_prefix('f').and_then(|n| n.parse::<u32>().ok()) {
+                return (1..=24).contains(&n).then(|| 0xffbe + n - 1);
+            }
+            let mut chars = name.trim().chars();
+            return match (chars.next(), chars.next()) {
+                (Some(ch), None) => Some(char_keysym(ch)),
+                _ => None,
+            };
+        }
+    };
+    Some(keysym)
+}
+
+/// Keysym pressed for a key
+pub fn key_keysym(key: Key) -> AutomationResult<Keysym> {
+    match key {
+        Key::Char(ch) => Ok(char_keysym(ch)),
+        _ => keysym_from_name(&key.to_string())
+            .ok_or_else(|| AutomationError::InvalidParameter(format!("No keysym for key {}", key))),
+    }
+}
+
+/// Character a keysym types, if it types one
+pub fn keysym_char(keysym: Keysym) -> Option<char> {
+    match keysym {
+        0x20..=0x7e | 0xa0..=0xff => char::from_u32(keysym),
+        0x0100_0100..=0x0110_ffff => char::from_u32(keysym - 0x0100_0000),
+        // Keypad digits and operators
+        0xffb0..=0xffb9 => char::from_u32(keysym - 0xffb0 + '0' as u32),
+        0xffaa => Some('*'),
+        0xffab => Some('+'),
+        0xffad => Some('-'),
+        0xffae => Some('.'),
+        0xffaf => Some('/'),
+        _ => None,
+    }
+}
+
+/// Name of a non-character key in the form `keysym_from_name` accepts
+pub fn keysym_name(keysym: Keysym) -> Option<String> {
+    let name = match keysym {
+        XK_RETURN | 0xff8d => "Enter",
+        XK_TAB => "Tab",
+        XK_ESCAPE => "Esc",
+        XK_BACKSPACE => "Backspace",
+        0xffff | 0xff9f => "Delete",
+        0xff63 | 0xff9e => "Insert",
+        0xff50 | 0xff95 => "Home",
+        0xff57 | 0xff9c => "End",
+        0xff55 | 0xff9a => "PageUp",
+        0xff56 | 0xff9b => "PageDown",
+        0xff51 | 0xff96 => "Left",
+        0xff52 | 0xff97 => "Up",
+        0xff53 | 0xff98 => "Right",
+        0xff54 | 0xff99 => "Down",
+        0x0020 => "Space",
+        0xffe5 => "CapsLock",
+        0xff61 => "PrintScreen",
+        0xff67 => "Menu",
+        0xffbe..=0xffd5 => return Some(format!("F{}", keysym - 0xffbe + 1)),
+        _ => return None,
+    };
+    Some(name.to_string())
+}
+
+/// Modifier a keysym belongs to: "Ctrl", "Shift", "Alt" or "Super"
+///
+/// AltGr (ISO_Level3_Shift) and Mode_switch are not among them: they select
+/// other characters rather than forming shortcuts, see [`is_level3_shift`].
+pub fn keysym_modifier(keysym: Keysym) -> Option<&'static str> {
+    match keysym {
+        0xffe3 | 0xffe4 => Some("Ctrl"),
+        0xffe1 | 0xffe2 => Some("Shift"),
+        0xffe7..=0xffea => Some("Alt"),
+        0xffeb | 0xffec => Some("Super"),
+        _ => None,
+    }
+}
+
+/// Whether a keysym switches keys to their third and fourth level, like AltGr
+pub fn is_level3_shift(keysym: Keysym) -> bool {
+    matches!(keysym, XK_ISO_LEVEL3_SHIFT | XK_MODE_SWITCH)
+}
+
+/// Whether a keysym is on the numeric keypad, where Num Lock applies
+pub fn is_keypad(keysym: Keysym) -> bool {
+    (0xff80..=0xffbd).contains(&keysym)
+}
+
+/// Clip a region to the screen; the whole screen without one
+fn clip(rect: Option<Rect>, screen_width: i32, screen_height: i32) -> Option<Rect> {
+    let rect = rect.unwrap_or(Rect {
+        x: 0,
+        y: 0,
+        width: screen_width,
+        height: screen_height,
+    });
+    let x = rect.x.clamp(0, screen_width);
+    let y = rect.y.clamp(0, screen_height);
+    let width = (rect.x + rect.width).min(screen_width) - x;
+    let height = (rect.y + rect.height).min(screen_height) - y;
+
+    (width > 0 && height > 0).then_some(Rect { x, y, width, height })
+}
+
+fn x11_error(e: impl std::fmt::Display) -> AutomationError {
+    AutomationError::ExecutionFailed(format!("X11 request failed: {}", e))
+}
+
+fn no_spare_keycode() -> AutomationError {
+    AutomationError::ExecutionFailed("No spare keycode left to type the character".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keysyms_of_characters_and_names() {
+        assert_eq!(char_keysym('a'), 0x61);
+        assert_eq!(char_keysym('é'), 0xe9);
+        assert_eq!(char_keysym('€'), 0x0100_20ac);
+        assert_eq!(char_keysym('\n'), XK_RETURN);
+
+        assert_eq!(keysym_from_name("Ctrl"), Some(XK_CONTROL_L));
+        assert_eq!(keysym_from_name("F4"), Some(0xffc1));
+        assert_eq!(keysym_from_name("S"), Some(0x53));
+        assert_eq!(keysym_from_name("bogus"), None);
+    }
+
+    /// Needs an X server with XTEST, e.g. `xvfb-run cargo test -- --ignored`
+    #[test]
+    #[ignore]
+    fn test_input_and_capture_on_display() {
+        let display = X11Display::connect().expect("no X display with XTEST");
+
+        display.move_to(20, 30).unwrap();
+        let (x, y, _) = display.pointer_state().unwrap();
+        assert_eq!((x, y), (20, 30));
+
+        display.key(XK_SHIFT_L, true).unwrap();
+        assert_eq!(display.keys_down(&[XK_SHIFT_L]).unwrap(), [true]);
+        display.key(XK_SHIFT_L, false).unwrap();
+        assert_eq!(display.keys_down(&[XK_SHIFT_L]).unwrap(), [false]);
+
+        // Characters outside the layout go through a remapped spare keycode
+        display.type_text("a€").unwrap();
+        display.click(20, 30, BUTTON_LEFT, 2).unwrap();
+
+        let png = display.capture(Some(Rect { x: 0, y: 0, width: 16, height: 8 })).unwrap();
+        assert!(png.starts_with(b"\x89PNG"));
+    }
+
+    #[test]
+    fn test_bgrx_pixels_convert_to_rgb() {
+        let format = PixelFormat {
+            bytes_per_pixel: 4,
+            stride: 8,
+            masks: [0x00ff_0000, 0x0000_ff00, 0x0000_00ff],
+            lsb_first: true,
+        };
+        // Two pixels: pure red, then (0x10, 0x20, 0x30), stored as B G R X
+        let data = [0x00, 0x00, 0xff, 0x00, 0x30, 0x20, 0x10, 0x00];
+
+        let rgb = format.to_rgb(&data, 2, 1).unwrap();
+        assert_eq!(rgb, [0xff, 0x00, 0x00, 0x10, 0x20, 0x30]);
+    }
+
+    #[test]
+    fn test_clip_region_to_screen() {
+        let clipped = clip(Some(Rect { x: -10, y: 50, width: 100, height: 2000 }), 1920, 1080).unwrap();
+        assert_eq!((clipped.x, clipped.y, clipped.width, clipped.height), (0, 50, 90, 1030));
+        assert!(clip(Some(Rect { x: 2000, y: 0, width: 10, height: 10 }), 1920, 1080).is_none());
+    }
+}
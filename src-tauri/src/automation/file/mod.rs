@@ -4,6 +4,11 @@ use rust_xlsxwriter::Workbook;
 use serde::{Deserialize, Serialize};
 use std::path::Path;
 
+mod ops;
+
+pub use ops::{FileInfo, FileWaitCondition, OverwritePolicy};
+use ops::io_error;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExcelCell {
     pub row: u32,
@@ -32,49 +37,34 @@ impl FileAutomation {
     pub async fn read_file(&self, path: &str) -> AutomationResult<String> {
         tokio::fs::read_to_string(path)
             .await
-            .map_err(|e| AutomationError::ExecutionFailed(format!("Failed to read file: {}", e)))
+            .map_err(|e| io_error("read", Path::new(path), e))
     }
 
     pub async fn write_file(&self, path: &str, content: &str) -> AutomationResult<()> {
         tokio::fs::write(path, content)
             .await
-            .map_err(|e| AutomationError::ExecutionFailed(format!("Failed to write file: {}", e)))
-    }
-
-    pub async fn copy_file(&self, src: &str, dest: &str) -> AutomationResult<()> {
-        tokio::fs::copy(src, dest)
-            .await
-            .map_err(|e| AutomationError::ExecutionFailed(format!("Failed to copy file: {}", e)))?;
-        Ok(())
-    }
-
-    pub async fn move_file(&self, src: &str, dest: &str) -> AutomationResult<()> {
-        tokio::fs::rename(src, dest)
-            .await
-            .map_err(|e| AutomationError::ExecutionFailed(format!("Failed to move file: {}", e)))
+            .map_err(|e| io_error("write", Path::new(path), e))
     }
 
     pub async fn delete_file(&self, path: &str) -> AutomationResult<()> {
         tokio::fs::remove_file(path)
             .await
-            .map_err(|e| AutomationError::ExecutionFailed(format!("Failed to delete file: {}", e)))
+            .map_err(|e| io_error("delete", Path::new(path), e))
     }
 
     pub async fn create_directory(&self, path: &str) -> AutomationResult<()> {
         tokio::fs::create_dir_all(path)
             .await
-            .map_err(|e| AutomationError::ExecutionFailed(format!("Failed to create directory: {}", e)))
+            .map_err(|e| io_error("create", Path::new(path), e))
     }
 
     pub async fn list_files(&self, path: &str) -> AutomationResult<Vec<String>> {
         let mut entries = tokio::fs::read_dir(path)
             .await
-            .map_err(|e| AutomationError::ExecutionFailed(format!("Failed to list files: {}", e)))?;
+            .map_err(|e| io_error("list", Path::new(path), e))?;
 
         let mut files = Vec::new();
-        while let Some(entry) = entries.next_entry().await.map_err(|e| {
-            AutomationError::ExecutionFailed(format!("Failed to read entry: {}", e))
-        })? {
+        while let Some(entry) = entries.next_entry().await.map_err(|e| io_error("list", Path::new(path), e))? {
             if let Some(name) = entry.file_name().to_str() {
                 files.push(name.to_string());
             }
@@ -0,0 +1,731 @@
+//! File system operations
+//!
+//! Sources may use `*` and `?` wildcards in their last path component, like
+//! `reports/*.xlsx`. Copies are written next to their destination first and
+//! renamed into place, so a failed copy leaves an existing destination as it
+//! was. I/O errors name the path they occurred on.
+
+use super::FileAutomation;
+use crate::automation::{AutomationError, AutomationResult};
+use regex::RegexBuilder;
+use serde::{Deserialize, Serialize};
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+const FILE_POLL_MS: u64 = 200;
+
+/// What to do when the destination of a copy or move already exists
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OverwritePolicy {
+    Overwrite,
+    Skip,
+    #[default]
+    Fail,
+}
+
+impl FromStr for OverwritePolicy {
+    type Err = AutomationError;
+
+    fn from_str(policy: &str) -> AutomationResult<Self> {
+        match policy.trim().to_lowercase().as_str() {
+            "overwrite" | "replace" => Ok(Self::Overwrite),
+            "skip" => Ok(Self::Skip),
+            "fail" | "error" | "" => Ok(Self::Fail),
+            other => Err(AutomationError::InvalidParameter(format!("Unknown overwrite policy: {}", other))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileWaitCondition {
+    Appear,
+    Disappear,
+    /// Exists and its size stopped changing, as when a download finished
+    Stable,
+}
+
+impl FromStr for FileWaitCondition {
+    type Err = AutomationError;
+
+    fn from_str(condition: &str) -> AutomationResult<Self> {
+        match condition.trim().to_lowercase().as_str() {
+            "appear" | "exists" => Ok(Self::Appear),
+            "disappear" | "deleted" => Ok(Self::Disappear),
+            "stable" => Ok(Self::Stable),
+            other => Err(AutomationError::InvalidParameter(format!("Unknown wait condition: {}", other))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileInfo {
+    pub path: String,
+    pub exists: bool,
+    pub is_directory: bool,
+    pub size: u64,
+    /// Last modification time as RFC 3339
+    pub modified: Option<String>,
+}
+
+impl FileAutomation {
+    /// Paths matching a pattern, sorted; a path without wildcards is
+    /// returned as is whether it exists or not
+    pub async fn expand_pattern(&self, pattern: &str) -> AutomationResult<Vec<PathBuf>> {
+        let path = Path::new(pattern);
+        let Some(name) = path.file_name().and_then(|n| n.to_str()).filter(|n| is_pattern(n)) else {
+            return Ok(vec![path.to_path_buf()]);
+        };
+        let dir = match path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+            _ => PathBuf::from("."),
+        };
+
+        let mut entries = tokio::fs::read_dir(&dir)
+            .await
+            .map_err(|e| io_error("list", &dir, e))?;
+        let mut matches = Vec::new();
+        while let Some(entry) = entries.next_entry().await.map_err(|e| io_error("list", &dir, e))? {
+            if entry.file_name().to_str().is_some_and(|n| wildcard_match(name, n)) {
+                matches.push(entry.path());
+            }
+        }
+        matches.sort();
+        Ok(matches)
+    }
+
+    /// Copy files or whole directories matching `source` to `dest`
+    ///
+    /// Returns the paths written, leaving out those skipped by the policy.
+    pub async fn copy(&self, source: &str, dest: &str, policy: OverwritePolicy) -> AutomationResult<Vec<PathBuf>> {
+        let mut written = Vec::new();
+        for (from, to) in self.transfers(source, dest).await? {
+            if self.prepare_target(&from, &to, policy, false).await? {
+                copy_into_place(&from, &to).await?;
+                written.push(to);
+            }
+        }
+        Ok(written)
+    }
+
+    /// Move files or directories matching `source` to `dest`, copying and
+    /// deleting when they cannot simply be renamed across drives
+    pub async fn move_to(&self, source: &str, dest: &str, policy: OverwritePolicy) -> AutomationResult<Vec<PathBuf>> {
+        let mut written = Vec::new();
+        for (from, to) in self.transfers(source, dest).await? {
+            if !self.prepare_target(&from, &to, policy, true).await? {
+                continue;
+            }
+            match replace(&from, &to).await {
+                Ok(()) => {}
+                Err(e) if e.kind() == ErrorKind::CrossesDevices => {
+                    copy_into_place(&from, &to).await?;
+                    remove(&from, true).await?;
+                }
+                Err(e) => return Err(io_error("move", &from, e)),
+            }
+            written.push(to);
+        }
+        Ok(written)
+    }
+
+    /// Give a file or directory a new name in the same directory
+    pub async fn rename(&self, path: &str, new_name: &str, policy: OverwritePolicy) -> AutomationResult<PathBuf> {
+        if new_name.is_empty() || new_name.contains(['/', '\\']) {
+            return Err(AutomationError::InvalidParameter(format!("Invalid file name: {}", new_name)));
+        }
+        let from = Path::new(path);
+        let to = from.with_file_name(new_name);
+
+        ensure_exists(from).await?;
+        if self.prepare_target(from, &to, policy, true).await? {
+            replace(from, &to).await.map_err(|e| io_error("rename", from, e))?;
+        }
+        Ok(to)
+    }
+
+    /// Delete files or directories matching a pattern and return how many
+    /// were deleted; directories need `recursive` unless they are empty
+    pub async fn delete(&self, pattern: &str, recursive: bool) -> AutomationResult<usize> {
+        let paths = self.expand_pattern(pattern).await?;
+        if !is_pattern(pattern) {
+            ensure_exists(&paths[0]).await?;
+        }
+        for path in &paths {
+            remove(path, recursive).await?;
+        }
+        Ok(paths.len())
+    }
+
+    pub async fn file_info(&self, path: &str) -> AutomationResult<FileInfo> {
+        let metadata = match tokio::fs::metadata(path).await {
+            Ok(metadata) => metadata,
+            Err(e) if e.kind() == ErrorKind::NotFound => {
+                return Ok(FileInfo {
+                    path: path.to_string(),
+                    exists: false,
+                    is_directory: false,
+                    size: 0,
+                    modified: None,
+                })
+            }
+            Err(e) => return Err(io_error("read", Path::new(path), e)),
+        };
+
+        Ok(FileInfo {
+            path: path.to_string(),
+            exists: true,
+            is_directory: metadata.is_dir(),
+            size: metadata.len(),
+            modified: metadata
+                .modified()
+                .ok()
+                .map(|time| chrono::DateTime::<chrono::Local>::from(time).to_rfc3339()),
+        })
+    }
+
+    /// Wait for a file to appear, disappear or stop growing for `stable_ms`
+    pub async fn wait_for_file(
+        &self,
+        path: &str,
+        condition: FileWaitCondition,
+        timeout_ms: u64,
+        stable_ms: u64,
+    ) -> AutomationResult<FileInfo> {
+        let deadline = Instant::now() + Duration::from_millis(timeout_ms);
+        // Size and the time it was first seen
+        let mut last_size: Option<(u64, Instant)> = None;
+        loop {
+            let info = self.file_info(path).await?;
+            let done = match condition {
+                FileWaitCondition::Appear => info.exists,
+                FileWaitCondition::Disappear => !info.exists,
+                FileWaitCondition::Stable if !info.exists => {
+                    last_size = None;
+                    false
+                }
+                FileWaitCondition::Stable => match last_size {
+                    Some((size, since)) if size == info.size => since.elapsed() >= Duration::from_millis(stable_ms),
+                    _ => {
+                        last_size = Some((info.size, Instant::now()));
+                        false
+                    }
+                },
+            };
+            if done {
+                return Ok(info);
+            }
+            if Instant::now() >= deadline {
+                return Err(AutomationError::Timeout(format!(
+                    "File {} did not {} within {}ms",
+                    path,
+                    match condition {
+                        FileWaitCondition::Appear => "appear",
+                        FileWaitCondition::Disappear => "disappear",
+                        FileWaitCondition::Stable => "become stable",
+                    },
+                    timeout_ms
+                )));
+            }
+            tokio::time::sleep(Duration::from_millis(FILE_POLL_MS)).await;
+        }
+    }
+
+    /// Create a new uniquely named file in the system temp directory
+    pub async fn create_temp_file(&self, prefix: &str, extension: &str, content: &[u8]) -> AutomationResult<PathBuf> {
+        let extension = extension.trim_start_matches('.');
+        let mut name = format!("{}{}", prefix, uuid::Uuid::new_v4().simple());
+        if !extension.is_empty() {
+            name = format!("{}.{}", name, extension);
+        }
+        let path = std::env::temp_dir().join(name);
+
+        tokio::fs::write(&path, content)
+            .await
+            .map_err(|e| io_error("create", &path, e))?;
+        Ok(path)
+    }
+
+    /// Source and target paths of a copy or move
+    ///
+    /// A pattern or an existing directory as destination receives the
+    /// sources under their own names; several sources need a directory,
+    /// which is created when missing once a source is written to it.
+    async fn transfers(&self, source: &str, dest: &str) -> AutomationResult<Vec<(PathBuf, PathBuf)>> {
+        let sources = self.expand_pattern(source).await?;
+        if !is_pattern(source) {
+            ensure_exists(&sources[0]).await?;
+        }
+
+        let dest = Path::new(dest);
+        let into_dir = is_pattern(source) || tokio::fs::metadata(dest).await.is_ok_and(|m| m.is_dir());
+        if !into_dir {
+            return Ok(sources.into_iter().map(|from| (from, dest.to_path_buf())).collect());
+        }
+
+        Ok(sources
+            .into_iter()
+            .filter_map(|from| {
+                let to = dest.join(from.file_name()?);
+                Some((from, to))
+            })
+            .collect())
+    }
+
+    /// Check that `source` can be written to `target`, apply the overwrite
+    /// policy and create the directory of `target`; false means skip it
+    ///
+    /// An existing target is left in place, to be replaced only once the
+    /// new content is complete. When `renaming`, a name differing only in
+    /// case is a new name even where the file system ignores case.
+    async fn prepare_target(
+        &self,
+        source: &Path,
+        target: &Path,
+        policy: OverwritePolicy,
+        renaming: bool,
+    ) -> AutomationResult<bool> {
+        let source_path = canonical(source).await?;
+        let target_path = canonical(target).await?;
+        if source_path == target_path {
+            if renaming && source.file_name() != target.file_name() {
+                return Ok(true);
+            }
+            return Err(AutomationError::InvalidParameter(format!(
+                "{} is both source and destination",
+                source.display()
+            )));
+        }
+        if target_path.starts_with(&source_path) {
+            return Err(AutomationError::InvalidParameter(format!(
+                "Cannot put {} inside itself at {}",
+                source.display(),
+                target.display()
+            )));
+        }
+
+        if tokio::fs::symlink_metadata(target).await.is_ok() {
+            match policy {
+                OverwritePolicy::Overwrite => {}
+                OverwritePolicy::Skip => return Ok(false),
+                OverwritePolicy::Fail => {
+                    return Err(AutomationError::ExecutionFailed(format!(
+                        "{} already exists",
+                        target.display()
+                    )))
+                }
+            }
+        }
+
+        if let Some(parent) = target.parent().filter(|p| !p.as_os_str().is_empty()) {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .map_err(|e| io_error("create", parent, e))?;
+        }
+        Ok(true)
+    }
+}
+
+/// Map an I/O error to the automation error of its kind, naming the path;
+/// a missing file is reported as not found, so error handling can tell it
+/// apart from other failures
+pub(crate) fn io_error(action: &str, path: &Path, e: std::io::Error) -> AutomationError {
+    let message = format!("Failed to {} {}: {}", action, path.display(), e);
+    match e.kind() {
+        ErrorKind::NotFound => AutomationError::ElementNotFound(message),
+        ErrorKind::TimedOut => AutomationError::Timeout(message),
+        ErrorKind::InvalidInput | ErrorKind::InvalidData => AutomationError::InvalidParameter(message),
+        ErrorKind::Unsupported => AutomationError::PlatformNotSupported(message),
+        _ => AutomationError::ExecutionFailed(message),
+    }
+}
+
+async fn ensure_exists(path: &Path) -> AutomationResult<()> {
+    tokio::fs::symlink_metadata(path)
+        .await
+        .map(|_| ())
+        .map_err(|e| io_error("find", path, e))
+}
+
+async fn remove(path: &Path, recursive: bool) -> AutomationResult<()> {
+    let metadata = tokio::fs::symlink_metadata(path)
+        .await
+        .map_err(|e| io_error("delete", path, e))?;
+    let result = match (metadata.is_dir(), recursive) {
+        (true, true) => tokio::fs::remove_dir_all(path).await,
+        (true, false) => tokio::fs::remove_dir(path).await,
+        (false, _) => tokio::fs::remove_file(path).await,
+    };
+    result.map_err(|e| io_error("delete", path, e))
+}
+
+/// Absolute path with symbolic links resolved; the part of a target that
+/// does not exist yet is appended to its nearest existing ancestor
+async fn canonical(path: &Path) -> AutomationResult<PathBuf> {
+    let mut missing = Vec::new();
+    let mut current = path;
+    loop {
+        match tokio::fs::canonicalize(current).await {
+            Ok(resolved) => return Ok(missing.iter().rev().fold(resolved, |path, name| path.join(name))),
+            Err(e) if e.kind() == ErrorKind::NotFound => {
+                let Some(name) = current.file_name() else {
+                    return Err(io_error("find", path, e));
+                };
+                missing.push(name);
+                current = match current.parent() {
+                    Some(parent) if !parent.as_os_str().is_empty() => parent,
+                    _ => Path::new("."),
+                };
+            }
+            Err(e) => return Err(io_error("find", current, e)),
+        }
+    }
+}
+
+/// Hidden sibling of `path` for staging a copy or setting aside a replaced
+/// destination, on the same drive so it can be renamed into place
+fn sibling(path: &Path, purpose: &str) -> PathBuf {
+    let name = path.file_name().map(|n| n.to_string_lossy()).unwrap_or_default();
+    path.with_file_name(format!(".{}.{}-{}", name, purpose, uuid::Uuid::new_v4().simple()))
+}
+
+/// Copy `from` next to `to`, then rename the copy over `to`; a failed copy
+/// is cleaned up and leaves `to` untouched
+async fn copy_into_place(from: &Path, to: &Path) -> AutomationResult<()> {
+    let staged = sibling(to, "copy");
+    if let Err(e) = copy_recursive(from, &staged).await {
+        let _ = remove(&staged, true).await;
+        return Err(e);
+    }
+    if let Err(e) = replace(&staged, to).await {
+        let _ = remove(&staged, true).await;
+        return Err(io_error("replace", to, e));
+    }
+    Ok(())
+}
+
+/// Rename `from` to `to`, replacing whatever is at `to`
+///
+/// An existing directory cannot be renamed over, so it is set aside first
+/// and restored when the rename fails. A directory found at `to` that is
+/// `from` itself, under a name differing only in case, is simply renamed.
+async fn replace(from: &Path, to: &Path) -> std::io::Result<()> {
+    let existing = tokio::fs::symlink_metadata(to).await.ok();
+    if !existing.is_some_and(|m| m.is_dir()) || same_entry(from, to).await {
+        return tokio::fs::rename(from, to).await;
+    }
+
+    let aside = sibling(to, "old");
+    tokio::fs::rename(to, &aside).await?;
+    if let Err(e) = tokio::fs::rename(from, to).await {
+        let _ = tokio::fs::rename(&aside, to).await;
+        return Err(e);
+    }
+    if let Err(e) = remove(&aside, true).await {
+        log::warn!("Failed to remove replaced {}: {}", aside.display(), e);
+    }
+    Ok(())
+}
+
+async fn same_entry(a: &Path, b: &Path) -> bool {
+    match (tokio::fs::canonicalize(a).await, tokio::fs::canonicalize(b).await) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
+/// Copy a file, or a directory with everything in it
+async fn copy_recursive(from: &Path, to: &Path) -> AutomationResult<()> {
+    let mut pending = vec![(from.to_path_buf(), to.to_path_buf())];
+    while let Some((from, to)) = pending.pop() {
+        let metadata = tokio::fs::metadata(&from)
+            .await
+            .map_err(|e| io_error("copy", &from, e))?;
+        if !metadata.is_dir() {
+            tokio::fs::copy(&from, &to)
+                .await
+                .map_err(|e| io_error("copy", &from, e))?;
+            continue;
+        }
+
+        tokio::fs::create_dir_all(&to)
+            .await
+            .map_err(|e| io_error("create", &to, e))?;
+        let mut entries = tokio::fs::read_dir(&from)
+            .await
+            .map_err(|e| io_error("list", &from, e))?;
+        while let Some(entry) = entries.next_entry().await.map_err(|e| io_error("list", &from, e))? {
+            pending.push((entry.path(), to.join(entry.file_name())));
+        }
+    }
+    Ok(())
+}
+
+fn is_pattern(path: &str) -> bool {
+    Path::new(path)
+        .file_name()
+        .and_then(|n| n.to_str())
+        .is_some_and(|n| n.contains(['*', '?']))
+}
+
+/// Match a file name against `*` and `?` wildcards, ignoring case on Windows
+fn wildcard_match(pattern: &str, name: &str) -> bool {
+    let regex = pattern
+        .split('*')
+        .map(|part| part.split('?').map(regex::escape).collect::<Vec<_>>().join("."))
+        .collect::<Vec<_>>()
+        .join(".*");
+    RegexBuilder::new(&format!("^{}$", regex))
+        .case_insensitive(cfg!(target_os = "windows"))
+        .build()
+        .is_ok_and(|re| re.is_match(name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wildcard_match() {
+        assert!(wildcard_match("*.xlsx", "report.xlsx"));
+        assert!(wildcard_match("report-??.csv", "report-01.csv"));
+        assert!(wildcard_match("a.b*", "a.b"));
+        assert!(!wildcard_match("*.xlsx", "report.xlsx.bak"));
+        assert!(!wildcard_match("a.b", "axb"));
+        assert!(!wildcard_match("report-?.csv", "report-01.csv"));
+    }
+
+    #[tokio::test]
+    async fn test_copy_respects_overwrite_policy() {
+        let files = FileAutomation;
+        let dir = std::env::temp_dir().join(format!("file-ops-{}", uuid::Uuid::new_v4().simple()));
+        let src = dir.join("in");
+        std::fs::create_dir_all(&src).unwrap();
+        std::fs::write(src.join("a.txt"), "new").unwrap();
+        std::fs::write(src.join("b.txt"), "new").unwrap();
+        std::fs::write(src.join("c.log"), "new").unwrap();
+
+        let out = dir.join("out");
+        std::fs::create_dir_all(&out).unwrap();
+        std::fs::write(out.join("a.txt"), "old").unwrap();
+
+        let pattern = src.join("*.txt").to_string_lossy().into_owned();
+        let out_dir = out.to_string_lossy().into_owned();
+        assert!(files.copy(&pattern, &out_dir, OverwritePolicy::Fail).await.is_err());
+
+        let written = files.copy(&pattern, &out_dir, OverwritePolicy::Skip).await.unwrap();
+        assert_eq!(written, vec![out.join("b.txt")]);
+        assert_eq!(std::fs::read_to_string(out.join("a.txt")).unwrap(), "old");
+
+        files.copy(&pattern, &out_dir, OverwritePolicy::Overwrite).await.unwrap();
+        assert_eq!(std::fs::read_to_string(out.join("a.txt")).unwrap(), "new");
+        assert!(!out.join("c.log").exists());
+
+        let missing = files.copy(&dir.join("none.txt").to_string_lossy(), &out_dir, OverwritePolicy::Fail).await;
+        assert!(matches!(missing, Err(AutomationError::ElementNotFound(e)) if e.contains("none.txt")));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    fn scratch_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("file-ops-{}", uuid::Uuid::new_v4().simple()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[tokio::test]
+    async fn test_same_source_and_destination_keep_files() {
+        let files = FileAutomation;
+        let dir = scratch_dir();
+        std::fs::write(dir.join("a.txt"), "keep").unwrap();
+
+        let pattern = dir.join("*.txt").to_string_lossy().into_owned();
+        let into = dir.to_string_lossy().into_owned();
+        for policy in [OverwritePolicy::Overwrite, OverwritePolicy::Skip] {
+            assert!(files.copy(&pattern, &into, policy).await.is_err());
+            assert!(files.move_to(&pattern, &into, policy).await.is_err());
+        }
+        let file = dir.join("a.txt").to_string_lossy().into_owned();
+        assert!(files.move_to(&file, &file, OverwritePolicy::Overwrite).await.is_err());
+        assert!(files.rename(&file, "a.txt", OverwritePolicy::Overwrite).await.is_err());
+        assert_eq!(std::fs::read_to_string(dir.join("a.txt")).unwrap(), "keep");
+
+        // A directory copied into itself would never end
+        let sub = dir.join("sub");
+        std::fs::create_dir_all(&sub).unwrap();
+        let nested = files.copy(&into, &sub.to_string_lossy(), OverwritePolicy::Overwrite).await;
+        assert!(matches!(nested, Err(AutomationError::InvalidParameter(_))));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_failed_copy_keeps_destination() {
+        let files = FileAutomation;
+        let dir = scratch_dir();
+        // A dangling link makes the copy fail after part of it was written
+        let data = dir.join("src").join("data");
+        std::fs::create_dir_all(&data).unwrap();
+        std::fs::write(data.join("a.txt"), "new").unwrap();
+        std::os::unix::fs::symlink(dir.join("missing"), data.join("z-link")).unwrap();
+
+        let dest = dir.join("dest");
+        std::fs::create_dir_all(dest.join("data")).unwrap();
+        std::fs::write(dest.join("data").join("old.txt"), "old").unwrap();
+
+        let pattern = dir.join("src").join("*").to_string_lossy().into_owned();
+        let copied = files.copy(&pattern, &dest.to_string_lossy(), OverwritePolicy::Overwrite).await;
+        assert!(copied.is_err());
+
+        // The old destination is intact and nothing staged is left behind
+        let entries: Vec<_> = std::fs::read_dir(&dest).unwrap().flatten().map(|e| e.file_name()).collect();
+        assert_eq!(entries, vec![std::ffi::OsString::from("data")]);
+        assert_eq!(std::fs::read_to_string(dest.join("data").join("old.txt")).unwrap(), "old");
+        assert!(!dest.join("data").join("a.txt").exists());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_rejected_transfer_creates_no_directories() {
+        let files = FileAutomation;
+        let dir = scratch_dir();
+        std::fs::write(dir.join("a.txt"), "a").unwrap();
+        let nested = dir.join("out").join("deep");
+
+        let copied = files.copy(&dir.to_string_lossy(), &nested.to_string_lossy(), OverwritePolicy::Overwrite).await;
+        assert!(matches!(copied, Err(AutomationError::InvalidParameter(_))));
+        assert!(!dir.join("out").exists());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_rename_changes_case() {
+        let files = FileAutomation;
+        let dir = scratch_dir();
+        std::fs::write(dir.join("a.txt"), "a").unwrap();
+        std::fs::create_dir_all(dir.join("sub")).unwrap();
+
+        let file = dir.join("a.txt").to_string_lossy().into_owned();
+        files.rename(&file, "A.txt", OverwritePolicy::Fail).await.unwrap();
+        let sub = dir.join("sub").to_string_lossy().into_owned();
+        files.rename(&sub, "Sub", OverwritePolicy::Fail).await.unwrap();
+
+        let mut names: Vec<_> = std::fs::read_dir(&dir).unwrap().flatten().map(|e| e.file_name()).collect();
+        names.sort();
+        assert_eq!(names, ["A.txt", "Sub"]);
+        assert_eq!(std::fs::read_to_string(dir.join("A.txt")).unwrap(), "a");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_delete_pattern() {
+        let files = FileAutomation;
+        let dir = scratch_dir();
+        for name in ["a.log", "b.log", "c.txt"] {
+            std::fs::write(dir.join(name), "x").unwrap();
+        }
+        std::fs::create_dir_all(dir.join("logs.log").join("inner")).unwrap();
+
+        assert!(files.delete(&dir.join("logs.log").to_string_lossy(), false).await.is_err());
+        let pattern = dir.join("*.log").to_string_lossy().into_owned();
+        assert_eq!(files.delete(&pattern, true).await.unwrap(), 3);
+        assert_eq!(files.delete(&pattern, true).await.unwrap(), 0);
+        assert!(dir.join("c.txt").exists());
+
+        let missing = files.delete(&dir.join("none.txt").to_string_lossy(), false).await;
+        assert!(matches!(missing, Err(AutomationError::ElementNotFound(_))));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_file() {
+        let files = FileAutomation;
+        let dir = scratch_dir();
+        let path = dir.join("download.bin");
+        let file = path.to_string_lossy().into_owned();
+
+        let timeout = files.wait_for_file(&file, FileWaitCondition::Appear, 300, 0).await;
+        assert!(matches!(timeout, Err(AutomationError::Timeout(_))));
+
+        // Written in two parts, like a download in progress
+        let writer = {
+            let path = path.clone();
+            tokio::spawn(async move {
+                tokio::time::sleep(Duration::from_millis(300)).await;
+                std::fs::write(&path, b"part").unwrap();
+                tokio::time::sleep(Duration::from_millis(500)).await;
+                std::fs::write(&path, b"part and the rest").unwrap();
+            })
+        };
+        let appeared = files.wait_for_file(&file, FileWaitCondition::Appear, 2000, 0).await.unwrap();
+        assert!(appeared.exists);
+        let stable = files.wait_for_file(&file, FileWaitCondition::Stable, 5000, 600).await.unwrap();
+        assert_eq!(stable.size, 17);
+        writer.await.unwrap();
+
+        std::fs::remove_file(&path).unwrap();
+        let gone = files.wait_for_file(&file, FileWaitCondition::Disappear, 1000, 0).await.unwrap();
+        assert!(!gone.exists);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_create_temp_file() {
+        let files = FileAutomation;
+        let first = files.create_temp_file("report-", ".csv", b"a,b").await.unwrap();
+        let second = files.create_temp_file("report-", "csv", b"").await.unwrap();
+
+        assert_ne!(first, second);
+        assert_eq!(first.parent(), Some(std::env::temp_dir().as_path()));
+        let name = first.file_name().unwrap().to_string_lossy().into_owned();
+        assert!(name.starts_with("report-") && name.ends_with(".csv") && !name.ends_with("..csv"));
+        assert_eq!(std::fs::read(&first).unwrap(), b"a,b");
+
+        std::fs::remove_file(first).unwrap();
+        std::fs::remove_file(second).unwrap();
+    }
+
+    /// Needs a directory on another file system than the temp directory,
+    /// like /dev/shm; passes without checking anything when there is none
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_move_across_file_systems() {
+        use std::os::unix::fs::MetadataExt;
+
+        let files = FileAutomation;
+        let dir = scratch_dir();
+        let device = std::fs::metadata(&dir).unwrap().dev();
+        let Some(other) = [PathBuf::from("/dev/shm"), std::env::current_dir().unwrap()]
+            .into_iter()
+            .find(|d| std::fs::metadata(d).is_ok_and(|m| m.dev() != device))
+        else {
+            std::fs::remove_dir_all(&dir).unwrap();
+            return;
+        };
+
+        let source = dir.join("data");
+        std::fs::create_dir_all(source.join("inner")).unwrap();
+        std::fs::write(source.join("inner").join("a.txt"), "a").unwrap();
+        let dest = other.join(format!("file-ops-{}", uuid::Uuid::new_v4().simple()));
+
+        let moved = files
+            .move_to(&source.to_string_lossy(), &dest.to_string_lossy(), OverwritePolicy::Fail)
+            .await;
+        let content = std::fs::read_to_string(dest.join("inner").join("a.txt"));
+        let _ = std::fs::remove_dir_all(&dest);
+
+        assert_eq!(moved.unwrap(), vec![dest.clone()]);
+        assert_eq!(content.unwrap(), "a");
+        assert!(!source.exists());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -2,48 +2,185 @@
 
 use crate::automation::Rect;
 use super::HighlightConfig;
-use std::process::Command;
 
-/// Show highlight overlay on Linux using notify-send or zenity
+use x11rb::connection::{Connection, RequestConnection};
+use x11rb::protocol::shape::{self, ConnectionExt as _};
+use x11rb::protocol::xproto::{
+    AtomEnum, ClipOrdering, ConfigureWindowAux, ConnectionExt as _, CreateWindowAux, PropMode, StackMode,
+    VisualClass, Visualtype, WindowClass,
+};
+use x11rb::rust_connection::RustConnection;
+use x11rb::wrapper::ConnectionExt as _;
+use x11rb::COPY_DEPTH_FROM_PARENT;
+
+/// Show highlight overlay on Linux
+///
+/// The border is made of four override-redirect windows around the bounds, so
+/// the element itself stays visible. They ignore pointer input where the
+/// server supports the SHAPE extension. Every highlight uses its own
+/// connection, so several can be shown at once, and its windows disappear with
+/// the connection even if the highlight is cancelled.
 pub async fn show_highlight(bounds: Rect, config: HighlightConfig) -> Result<(), String> {
     log::info!(
         "Linux: Highlighting element at ({}, {}) size {}x{} for {}ms",
         bounds.x, bounds.y, bounds.width, bounds.height, config.duration_ms
     );
 
-    // Try to use notify-send for visual feedback
-    let message = format!(
-        "元素位置: ({}, {})  大小: {}x{}",
-        bounds.x, bounds.y, bounds.width, bounds.height
-    );
+    // Connecting and creating the windows are blocking round trips to the server
+    let duration = std::time::Duration::from_millis(config.duration_ms);
+    let conn = tokio::task::spawn_blocking(move || {
+        let (conn, screen_num) =
+            x11rb::connect(None).map_err(|e| format!("Failed to connect to X display: {}", e))?;
+        create_border(&conn, screen_num, &bounds, &config).map_err(|e| format!("Failed to show highlight: {}", e))?;
+        Ok::<_, String>(conn)
+    })
+    .await
+    .map_err(|e| format!("Highlight task failed: {}", e))??;
 
-    // Try notify-send first
-    let result = Command::new("notify-send")
-        .args([
-            "-t", &config.duration_ms.to_string(),
-            "元素高亮",
-            &message,
-        ])
-        .output();
-
-    if result.is_err() || !result.as_ref().unwrap().status.success() {
-        // Fallback to zenity
-        let _ = Command::new("zenity")
-            .args([
-                "--notification",
-                "--text", &message,
-            ])
-            .output();
-    }
+    tokio::time::sleep(duration).await;
+
+    // Closing the connection destroys the windows
+    drop(conn);
+    Ok(())
+}
+
+fn create_border(
+    conn: &RustConnection,
+    screen_num: usize,
+    bounds: &Rect,
+    config: &HighlightConfig,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let screen = &conn.setup().roots[screen_num];
+    let visual = screen
+        .allowed_depths
+        .iter()
+        .flat_map(|d| d.visuals.iter())
+        .find(|v| v.visual_id == screen.root_visual && v.class == VisualClass::TRUE_COLOR);
+    let pixel = visual.map(|v| color_pixel(v, config.color)).unwrap_or(screen.white_pixel);
+
+    let has_shape = conn.extension_information(shape::X11_EXTENSION_NAME)?.is_some();
+    let opacity_atom = conn.intern_atom(false, b"_NET_WM_WINDOW_OPACITY")?.reply()?.atom;
+    let opacity = (config.opacity.clamp(0.0, 1.0) * u32::MAX as f64) as u32;
 
-    // Wait for the duration
-    tokio::time::sleep(std::time::Duration::from_millis(config.duration_ms)).await;
+    for strip in border_strips(bounds, config.border_width) {
+        let window = conn.generate_id()?;
+        conn.create_window(
+            COPY_DEPTH_FROM_PARENT,
+            window,
+            screen.root,
+            strip.x as i16,
+            strip.y as i16,
+            strip.width as u16,
+            strip.height as u16,
+            0,
+            WindowClass::INPUT_OUTPUT,
+            0,
+            &CreateWindowAux::new().background_pixel(pixel).override_redirect(1),
+        )?;
 
+        // Empty input region: clicks go through to the windows below
+        if has_shape {
+            conn.shape_rectangles(shape::SO::SET, shape::SK::INPUT, ClipOrdering::UNSORTED, window, 0, 0, &[])?;
+        }
+        conn.change_property32(PropMode::REPLACE, window, opacity_atom, AtomEnum::CARDINAL, &[opacity])?;
+
+        conn.map_window(window)?;
+        conn.configure_window(window, &ConfigureWindowAux::new().stack_mode(StackMode::ABOVE))?;
+    }
+
+    conn.sync()?;
     Ok(())
 }
 
-// Note: For a true overlay window on Linux, we would need to:
-// 1. Create an X11 or Wayland overlay window
-// 2. Set it as always-on-top and transparent
-// 3. Draw a colored rectangle border
-// This requires significant platform-specific code (GTK, Qt, or raw X11/Wayland)
+/// Top, bottom, left and right edges of a border drawn just outside `bounds`
+fn border_strips(bounds: &Rect, border_width: f64) -> [Rect; 4] {
+    let w = (border_width.round() as i32).max(1);
+    let outer_width = bounds.width.max(0) + 2 * w;
+    let height = bounds.height.max(0);
+
+    [
+        Rect { x: bounds.x - w, y: bounds.y - w, width: outer_width, height: w },
+        Rect { x: bounds.x - w, y: bounds.y + height, width: outer_width, height: w },
+        Rect { x: bounds.x - w, y: bounds.y, width: w, height: height.max(1) },
+        Rect { x: bounds.x + bounds.width.max(0), y: bounds.y, width: w, height: height.max(1) },
+    ]
+}
+
+/// Pixel value of an RGB color in a TrueColor visual
+fn color_pixel(visual: &Visualtype, (r, g, b): (u8, u8, u8)) -> u32 {
+    let channel = |value: u8, mask: u32| {
+        if mask == 0 {
+            return 0;
+        }
+        let shift = mask.trailing_zeros();
+        let max = mask >> shift;
+        ((value as u32 * max / 255) << shift) & mask
+    };
+    channel(r, visual.red_mask) | channel(g, visual.green_mask) | channel(b, visual.blue_mask)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_border_surrounds_bounds() {
+        let bounds = Rect { x: 100, y: 50, width: 200, height: 30 };
+        let [top, bottom, left, right] = border_strips(&bounds, 3.0);
+
+        assert_eq!((top.x, top.y, top.width, top.height), (97, 47, 206, 3));
+        assert_eq!((bottom.x, bottom.y, bottom.width, bottom.height), (97, 80, 206, 3));
+        assert_eq!((left.x, left.y, left.width, left.height), (97, 50, 3, 30));
+        assert_eq!((right.x, right.y, right.width, right.height), (300, 50, 3, 30));
+    }
+
+    #[test]
+    fn test_color_pixel_follows_masks() {
+        let mut visual = Visualtype {
+            visual_id: 0,
+            class: VisualClass::TRUE_COLOR,
+            bits_per_rgb_value: 8,
+            colormap_entries: 256,
+            red_mask: 0xff0000,
+            green_mask: 0x00ff00,
+            blue_mask: 0x0000ff,
+        };
+        assert_eq!(color_pixel(&visual, (0x12, 0x34, 0x56)), 0x123456);
+
+        // RGB565
+        (visual.red_mask, visual.green_mask, visual.blue_mask) = (0xf800, 0x07e0, 0x001f);
+        assert_eq!(color_pixel(&visual, (255, 0, 255)), 0xf81f);
+
+        // A visual without a channel must not panic
+        visual.blue_mask = 0;
+        assert_eq!(color_pixel(&visual, (255, 255, 255)), 0xffe0);
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn test_border_windows_on_display() {
+        use x11rb::protocol::xproto::MapState;
+
+        let bounds = Rect { x: 100, y: 50, width: 200, height: 30 };
+        let config = HighlightConfig { duration_ms: 500, ..HighlightConfig::default() };
+        let highlight = tokio::spawn(show_highlight(bounds.clone(), config));
+
+        let (conn, screen_num) = x11rb::connect(None).expect("no X display");
+        let root = conn.setup().roots[screen_num].root;
+        let top_strip_shown = |conn: &RustConnection| {
+            conn.query_tree(root).unwrap().reply().unwrap().children.into_iter().any(|window| {
+                let geometry = conn.get_geometry(window).unwrap().reply().unwrap();
+                let mapped = conn.get_window_attributes(window).unwrap().reply().unwrap().map_state
+                    == MapState::VIEWABLE;
+                mapped && (geometry.x, geometry.y, geometry.width) == (bounds.x as i16 - 3, bounds.y as i16 - 3, 206)
+            })
+        };
+
+        tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+        assert!(top_strip_shown(&conn));
+
+        highlight.await.unwrap().unwrap();
+        conn.sync().unwrap();
+        assert!(!top_strip_shown(&conn));
+    }
+}
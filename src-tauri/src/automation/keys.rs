@@ -0,0 +1,262 @@
+//! Key names and key combinations
+//!
+//! Combinations are written as key names joined by `+`, like "Ctrl+Shift+S"
+//! or "Alt+F4", optionally followed by a repeat count as in "Enter×3" or
+//! "Tab*2". Desktop and browser keyboard input share this parser.
+
+use super::{AutomationError, AutomationResult};
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Key {
+    Ctrl,
+    Shift,
+    Alt,
+    /// Windows key on Windows, Command on macOS, Super on Linux
+    Meta,
+    Enter,
+    Tab,
+    Escape,
+    Backspace,
+    Delete,
+    Insert,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+    Left,
+    Up,
+    Right,
+    Down,
+    Space,
+    CapsLock,
+    PrintScreen,
+    Menu,
+    /// Function keys F1 to F24
+    F(u8),
+    /// Key typing a character; letters are stored in lower case
+    Char(char),
+}
+
+impl Key {
+    pub fn is_modifier(self) -> bool {
+        matches!(self, Key::Ctrl | Key::Shift | Key::Alt | Key::Meta)
+    }
+
+    /// Windows virtual-key code, which browsers also report as `keyCode`
+    ///
+    /// Characters other than letters, digits and space have no fixed code.
+    pub fn virtual_key_code(self) -> Option<u16> {
+        let code = match self {
+            Key::Ctrl => 0x11,
+            Key::Shift => 0x10,
+            Key::Alt => 0x12,
+            Key::Meta => 0x5b,
+            Key::Enter => 0x0d,
+            Key::Tab => 0x09,
+            Key::Escape => 0x1b,
+            Key::Backspace => 0x08,
+            Key::Delete => 0x2e,
+            Key::Insert => 0x2d,
+            Key::Home => 0x24,
+            Key::End => 0x23,
+            Key::PageUp => 0x21,
+            Key::PageDown => 0x22,
+            Key::Left => 0x25,
+            Key::Up => 0x26,
+            Key::Right => 0x27,
+            Key::Down => 0x28,
+            Key::Space | Key::Char(' ') => 0x20,
+            Key::CapsLock => 0x14,
+            Key::PrintScreen => 0x2c,
+            Key::Menu => 0x5d,
+            Key::F(n) => 0x70 + n as u16 - 1,
+            Key::Char(ch @ ('a'..='z' | '0'..='9')) => ch.to_ascii_uppercase() as u16,
+            Key::Char(_) => return None,
+        };
+        Some(code)
+    }
+}
+
+impl FromStr for Key {
+    type Err = AutomationError;
+
+    fn from_str(name: &str) -> AutomationResult<Self> {
+        let name = name.trim();
+        let key = match name.to_lowercase().as_str() {
+            "ctrl" | "control" => Key::Ctrl,
+            "shift" => Key::Shift,
+            "alt" | "option" => Key::Alt,
+            "meta" | "super" | "win" | "windows" | "cmd" | "command" => Key::Meta,
+            "enter" | "return" => Key::Enter,
+            "tab" => Key::Tab,
+            "esc" | "escape" => Key::Escape,
+            "backspace" => Key::Backspace,
+            "delete" | "del" => Key::Delete,
+            "insert" | "ins" => Key::Insert,
+            "home" => Key::Home,
+            "end" => Key::End,
+            "pageup" | "pgup" => Key::PageUp,
+            "pagedown" | "pgdn" => Key::PageDown,
+            "left" | "arrowleft" => Key::Left,
+            "up" | "arrowup" => Key::Up,
+            "right" | "arrowright" => Key::Right,
+            "down" | "arrowdown" => Key::Down,
+            "space" => Key::Space,
+            "capslock" => Key::CapsLock,
+            "printscreen" | "print" => Key::PrintScreen,
+            "menu" | "apps" => Key::Menu,
+            lower => {
+                if let Some(n) = lower.strip_prefix('f').and_then(|n| n.parse::<u8>().ok()) {
+                    if (1..=24).contains(&n) {
+                        return Ok(Key::F(n));
+                    }
+                }
+                let mut chars = name.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(ch), None) => Key::Char(ch.to_ascii_lowercase()),
+                    _ => return Err(AutomationError::InvalidParameter(format!("Unknown key: {}", name))),
+                }
+            }
+        };
+        Ok(key)
+    }
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Key::Ctrl => "Ctrl",
+            Key::Shift => "Shift",
+            Key::Alt => "Alt",
+            Key::Meta => "Meta",
+            Key::Enter => "Enter",
+            Key::Tab => "Tab",
+            Key::Escape => "Esc",
+            Key::Backspace => "Backspace",
+            Key::Delete => "Delete",
+            Key::Insert => "Insert",
+            Key::Home => "Home",
+            Key::End => "End",
+            Key::PageUp => "PageUp",
+            Key::PageDown => "PageDown",
+            Key::Left => "Left",
+            Key::Up => "Up",
+            Key::Right => "Right",
+            Key::Down => "Down",
+            Key::Space => "Space",
+            Key::CapsLock => "CapsLock",
+            Key::PrintScreen => "PrintScreen",
+            Key::Menu => "Menu",
+            Key::F(n) => return write!(f, "F{}", n),
+            Key::Char(ch) => return write!(f, "{}", ch.to_ascii_uppercase()),
+        };
+        f.write_str(name)
+    }
+}
+
+/// Keys pressed together, pressed `repeat` times in a row
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyCombo {
+    /// Keys in the order they are pressed; they are released in reverse
+    pub keys: Vec<Key>,
+    pub repeat: u32,
+}
+
+impl KeyCombo {
+    pub fn new(keys: Vec<Key>) -> Self {
+        Self { keys, repeat: 1 }
+    }
+}
+
+impl FromStr for KeyCombo {
+    type Err = AutomationError;
+
+    fn from_str(combo: &str) -> AutomationResult<Self> {
+        // A literal space after the last "+", as in "Ctrl+ ", is the space bar
+        if let Some(modifiers) = combo.trim_start().strip_suffix("+ ") {
+            if !modifiers.trim().is_empty() && !modifiers.ends_with('+') {
+                let mut combo: Self = modifiers.parse()?;
+                combo.keys.push(Key::Space);
+                return Ok(combo);
+            }
+        }
+
+        let combo = combo.trim();
+        let invalid = || AutomationError::InvalidParameter(format!("Invalid key combination: {}", combo));
+
+        // "Enter×3"; a trailing "*" without a count is the asterisk key
+        let (keys, repeat) = match combo.rsplit_once(['×', '*']) {
+            Some((keys, count)) if !keys.trim().is_empty() => match count.trim().parse::<u32>() {
+                Ok(0) => return Err(invalid()),
+                Ok(count) => (keys.trim(), count),
+                Err(_) => (combo, 1),
+            },
+            _ => (combo, 1),
+        };
+        if keys.is_empty() {
+            return Err(invalid());
+        }
+
+        // An empty part is the plus key itself, as in "Ctrl++"
+        let parts: Vec<&str> = keys.split('+').collect();
+        let mut parsed = Vec::with_capacity(parts.len());
+        let mut i = 0;
+        while i < parts.len() {
+            if parts[i].trim().is_empty() {
+                parsed.push(Key::Char('+'));
+                i += 2;
+                continue;
+            }
+            parsed.push(parts[i].parse()?);
+            i += 1;
+        }
+
+        Ok(Self { keys: parsed, repeat })
+    }
+}
+
+impl fmt::Display for KeyCombo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, key) in self.keys.iter().enumerate() {
+            if i > 0 {
+                f.write_str("+")?;
+            }
+            write!(f, "{}", key)?;
+        }
+        if self.repeat > 1 {
+            write!(f, "×{}", self.repeat)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(combo: &str) -> KeyCombo {
+        combo.parse().unwrap()
+    }
+
+    #[test]
+    fn test_parse_combos() {
+        assert_eq!(parse("Ctrl+Shift+S").keys, vec![Key::Ctrl, Key::Shift, Key::Char('s')]);
+        assert_eq!(parse("alt + f4").keys, vec![Key::Alt, Key::F(4)]);
+        assert_eq!(parse("Enter×3"), KeyCombo { keys: vec![Key::Enter], repeat: 3 });
+        assert_eq!(parse("Tab*2").repeat, 2);
+        assert_eq!(parse("Ctrl++").keys, vec![Key::Ctrl, Key::Char('+')]);
+        assert_eq!(parse("Shift+*").keys, vec![Key::Shift, Key::Char('*')]);
+        assert_eq!(parse("cmd+a").to_string(), "Meta+A");
+        assert_eq!(parse("Down×10").to_string(), "Down×10");
+        // Space as reported by a browser's KeyboardEvent.key
+        assert_eq!(parse("Ctrl+ ").keys, vec![Key::Ctrl, Key::Space]);
+        assert_eq!(parse("Ctrl+Space").keys, vec![Key::Ctrl, Key::Space]);
+        assert_eq!(parse("Ctrl++ ").keys, vec![Key::Ctrl, Key::Char('+')]);
+
+        assert!("".parse::<KeyCombo>().is_err());
+        assert!("Ctrl+Foo".parse::<KeyCombo>().is_err());
+        assert!("Enter×0".parse::<KeyCombo>().is_err());
+    }
+}
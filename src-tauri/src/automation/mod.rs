@@ -1,6 +1,9 @@
 pub mod desktop;
 pub mod file;
 pub mod highlight;
+pub mod keys;
+pub mod process;
+pub mod vision;
 pub mod web;
 
 use serde::{Deserialize, Serialize};
@@ -26,7 +29,7 @@ pub enum AutomationError {
 
 pub type AutomationResult<T> = Result<T, AutomationError>;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Rect {
     pub x: i32,
     pub y: i32,
@@ -47,6 +50,13 @@ pub enum ClickType {
     Right,
 }
 
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum MouseButton {
+    Left,
+    Middle,
+    Right,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum InputMethod {
     Type,
@@ -0,0 +1,374 @@
+//! Operating system processes
+//!
+//! Processes are listed from `/proc` on Linux, through `ps` on other Unix
+//! systems and from a ToolHelp snapshot on Windows. Names match
+//! case-insensitively and without the ".exe" suffix, so "notepad" finds
+//! "Notepad.exe".
+
+use super::{AutomationError, AutomationResult};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fmt;
+use std::time::Duration;
+
+const PROCESS_POLL_MS: u64 = 200;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProcessInfo {
+    pub pid: u32,
+    pub parent_pid: Option<u32>,
+    /// Executable name without its directory
+    pub name: String,
+}
+
+/// Process looked up by id or by executable name
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProcessTarget {
+    Pid(u32),
+    Name(String),
+}
+
+impl ProcessTarget {
+    pub fn matches(&self, process: &ProcessInfo) -> bool {
+        match self {
+            ProcessTarget::Pid(pid) => process.pid == *pid,
+            ProcessTarget::Name(name) => base_name(&process.name) == base_name(name),
+        }
+    }
+}
+
+impl fmt::Display for ProcessTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProcessTarget::Pid(pid) => write!(f, "process {}", pid),
+            ProcessTarget::Name(name) => write!(f, "process '{}'", name),
+        }
+    }
+}
+
+fn base_name(name: &str) -> String {
+    let name = name.trim().to_lowercase();
+    match name.strip_suffix(".exe") {
+        Some(stem) => stem.to_string(),
+        None => name,
+    }
+}
+
+/// Start a program without waiting for it and return its process id
+///
+/// The child is reaped in the background once it exits.
+pub fn launch(program: &str, args: &[String], working_dir: Option<&str>) -> AutomationResult<u32> {
+    let mut cmd = tokio::process::Command::new(program);
+    cmd.args(args);
+    if let Some(dir) = working_dir {
+        cmd.current_dir(dir);
+    }
+
+    let child = cmd
+        .spawn()
+        .map_err(|e| AutomationError::ExecutionFailed(format!("Failed to start '{}': {}", program, e)))?;
+    child
+        .id()
+        .ok_or_else(|| AutomationError::ExecutionFailed(format!("'{}' exited immediately", program)))
+}
+
+pub async fn find_processes(target: &ProcessTarget) -> AutomationResult<Vec<ProcessInfo>> {
+    Ok(list_processes()
+        .await?
+        .into_iter()
+        .filter(|p| target.matches(p))
+        .collect())
+}
+
+pub async fn is_running(target: &ProcessTarget) -> AutomationResult<bool> {
+    Ok(!find_processes(target).await?.is_empty())
+}
+
+/// Wait until a process is running, returning the matches, or until none is
+pub async fn wait_for(target: &ProcessTarget, running: bool, timeout_ms: u64) -> AutomationResult<Vec<ProcessInfo>> {
+    let deadline = std::time::Instant::now() + Duration::from_millis(timeout_ms);
+    loop {
+        let found = find_processes(target).await?;
+        if found.is_empty() != running {
+            return Ok(found);
+        }
+        if std::time::Instant::now() >= deadline {
+            let state = if running { "did not start" } else { "still running" };
+            return Err(AutomationError::Timeout(format!(
+                "{} {} within {}ms",
+                target, state, timeout_ms
+            )));
+        }
+        tokio::time::sleep(Duration::from_millis(PROCESS_POLL_MS)).await;
+    }
+}
+
+/// Kill a process, and with `tree` all of its descendants first
+///
+/// Returns how many processes were killed. Descendants that exit on their
+/// own in the meantime are skipped.
+pub async fn kill(pid: u32, tree: bool) -> AutomationResult<usize> {
+    let order = if tree {
+        kill_order(&list_processes().await?, pid)
+    } else {
+        vec![pid]
+    };
+
+    let mut killed = 0;
+    for &id in &order {
+        match kill_process(id).await {
+            Ok(()) => killed += 1,
+            Err(e) if id == pid => return Err(e),
+            Err(e) => log::debug!("Skipping process {}: {}", id, e),
+        }
+    }
+    Ok(killed)
+}
+
+/// Descendants of `root` children first, followed by `root` itself
+fn kill_order(processes: &[ProcessInfo], root: u32) -> Vec<u32> {
+    fn visit(processes: &[ProcessInfo], pid: u32, seen: &mut HashSet<u32>, order: &mut Vec<u32>) {
+        if !seen.insert(pid) {
+            return;
+        }
+        for child in processes.iter().filter(|p| p.parent_pid == Some(pid)) {
+            visit(processes, child.pid, seen, order);
+        }
+        order.push(pid);
+    }
+
+    let mut order = Vec::new();
+    visit(processes, root, &mut HashSet::new(), &mut order);
+    order
+}
+
+#[cfg(target_os = "linux")]
+pub async fn list_processes() -> AutomationResult<Vec<ProcessInfo>> {
+    tokio::task::spawn_blocking(read_proc)
+        .await
+        .map_err(|e| AutomationError::ExecutionFailed(format!("Failed to list processes: {}", e)))?
+}
+
+/// Processes under `/proc`; names come from the executable rather than the
+/// kernel's `comm`, which is cut off at 15 bytes
+#[cfg(target_os = "linux")]
+fn read_proc() -> AutomationResult<Vec<ProcessInfo>> {
+    let entries = std::fs::read_dir("/proc")
+        .map_err(|e| AutomationError::ExecutionFailed(format!("Failed to read /proc: {}", e)))?;
+
+    Ok(entries
+        .flatten()
+        .filter_map(|entry| {
+            let pid = entry.file_name().to_str()?.parse::<u32>().ok()?;
+            // Processes that exit while listing are skipped
+            let stat = std::fs::read_to_string(entry.path().join("stat")).ok()?;
+            let (comm, parent_pid) = parse_stat(&stat)?;
+
+            let exe = std::fs::read_link(entry.path().join("exe")).ok();
+            let cmdline = std::fs::read(entry.path().join("cmdline")).unwrap_or_default();
+            let name = exe
+                .as_deref()
+                .and_then(|path| path.file_name())
+                .map(|name| name.to_string_lossy().into_owned())
+                .or_else(|| command_name(&cmdline))
+                .unwrap_or(comm);
+
+            Some(ProcessInfo {
+                pid,
+                parent_pid: (parent_pid != 0).then_some(parent_pid),
+                name,
+            })
+        })
+        .collect())
+}
+
+/// `comm` and parent pid from `/proc/<pid>/stat`; `comm` is in parentheses
+/// and may itself contain spaces and parentheses
+#[cfg(any(target_os = "linux", test))]
+fn parse_stat(stat: &str) -> Option<(String, u32)> {
+    let open = stat.find('(')?;
+    let close = stat.rfind(')')?;
+    let comm = stat.get(open + 1..close)?.to_string();
+    // Fields after `comm`: state, then the parent pid
+    let parent_pid = stat[close + 1..].split_whitespace().nth(1)?.parse().ok()?;
+    Some((comm, parent_pid))
+}
+
+/// File name of the program in a NUL separated `/proc/<pid>/cmdline`;
+/// kernel threads have none
+#[cfg(any(target_os = "linux", test))]
+fn command_name(cmdline: &[u8]) -> Option<String> {
+    let program = cmdline.split(|&b| b == 0).next().filter(|p| !p.is_empty())?;
+    let program = String::from_utf8_lossy(program);
+    let name = program.rsplit('/').next().unwrap_or(&program);
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+#[cfg(all(unix, not(target_os = "linux")))]
+pub async fn list_processes() -> AutomationResult<Vec<ProcessInfo>> {
+    let output = tokio::process::Command::new("ps")
+        .args(["-A", "-o", "pid=", "-o", "ppid=", "-o", "comm="])
+        .output()
+        .await
+        .map_err(|e| AutomationError::ExecutionFailed(format!("Failed to run ps: {}", e)))?;
+    if !output.status.success() {
+        return Err(AutomationError::ExecutionFailed(format!(
+            "ps failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(parse_ps(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Parse `ps -o pid= -o ppid= -o comm=` lines; macOS prints full paths
+#[cfg(any(all(unix, not(target_os = "linux")), test))]
+fn parse_ps(output: &str) -> Vec<ProcessInfo> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut rest = line.trim_start();
+            let mut field = || {
+                let end = rest.find(char::is_whitespace)?;
+                let (value, tail) = rest.split_at(end);
+                rest = tail.trim_start();
+                value.parse::<u32>().ok()
+            };
+            let pid = field()?;
+            let parent_pid = field()?;
+
+            let name = rest.trim_end();
+            let name = name.rsplit('/').next().unwrap_or(name);
+            Some(ProcessInfo {
+                pid,
+                parent_pid: (parent_pid != 0).then_some(parent_pid),
+                name: name.to_string(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(unix)]
+async fn kill_process(pid: u32) -> AutomationResult<()> {
+    let output = tokio::process::Command::new("kill")
+        .args(["-KILL", &pid.to_string()])
+        .output()
+        .await
+        .map_err(|e| AutomationError::ExecutionFailed(format!("Failed to run kill: {}", e)))?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(AutomationError::ExecutionFailed(format!(
+            "Failed to kill process {}: {}",
+            pid,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )))
+    }
+}
+
+#[cfg(windows)]
+pub async fn list_processes() -> AutomationResult<Vec<ProcessInfo>> {
+    tokio::task::spawn_blocking(snapshot_processes)
+        .await
+        .map_err(|e| AutomationError::ExecutionFailed(format!("Failed to list processes: {}", e)))?
+}
+
+#[cfg(windows)]
+fn snapshot_processes() -> AutomationResult<Vec<ProcessInfo>> {
+    use windows::Win32::Foundation::CloseHandle;
+    use windows::Win32::System::Diagnostics::ToolHelp::{
+        CreateToolhelp32Snapshot, Process32FirstW, Process32NextW, PROCESSENTRY32W, TH32CS_SNAPPROCESS,
+    };
+
+    let mut processes = Vec::new();
+    unsafe {
+        let snapshot = CreateToolhelp32Snapshot(TH32CS_SNAPPROCESS, 0)
+            .map_err(|e| AutomationError::ExecutionFailed(format!("Failed to list processes: {}", e)))?;
+
+        let mut entry = PROCESSENTRY32W {
+            dwSize: std::mem::size_of::<PROCESSENTRY32W>() as u32,
+            ..Default::default()
+        };
+        let mut more = Process32FirstW(snapshot, &mut entry).is_ok();
+        while more {
+            let len = entry.szExeFile.iter().position(|&c| c == 0).unwrap_or(entry.szExeFile.len());
+            processes.push(ProcessInfo {
+                pid: entry.th32ProcessID,
+                parent_pid: (entry.th32ParentProcessID != 0).then_some(entry.th32ParentProcessID),
+                name: String::from_utf16_lossy(&entry.szExeFile[..len]),
+            });
+            more = Process32NextW(snapshot, &mut entry).is_ok();
+        }
+        let _ = CloseHandle(snapshot);
+    }
+    Ok(processes)
+}
+
+#[cfg(windows)]
+async fn kill_process(pid: u32) -> AutomationResult<()> {
+    tokio::task::spawn_blocking(move || terminate_process(pid))
+        .await
+        .map_err(|e| AutomationError::ExecutionFailed(format!("Failed to kill process {}: {}", pid, e)))?
+}
+
+#[cfg(windows)]
+fn terminate_process(pid: u32) -> AutomationResult<()> {
+    use windows::Win32::Foundation::CloseHandle;
+    use windows::Win32::System::Threading::{OpenProcess, TerminateProcess, PROCESS_TERMINATE};
+
+    unsafe {
+        let process = OpenProcess(PROCESS_TERMINATE, false, pid)
+            .map_err(|e| AutomationError::ExecutionFailed(format!("Failed to open process {}: {}", pid, e)))?;
+        let result = TerminateProcess(process, 1);
+        let _ = CloseHandle(process);
+        result.map_err(|e| AutomationError::ExecutionFailed(format!("Failed to kill process {}: {}", pid, e)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_ps_and_kill_order() {
+        let processes = parse_ps(
+            "    1     0 init\n  200     1 /Applications/Safari.app/Contents/MacOS/Safari\n  \
+             201   200 Web Content\n  202   201 helper\n  300     1 bash\n",
+        );
+        assert_eq!(processes.len(), 5);
+        assert_eq!(processes[0].parent_pid, None);
+        assert_eq!(processes[1].name, "Safari");
+        assert_eq!(processes[2].name, "Web Content");
+
+        assert_eq!(kill_order(&processes, 200), vec![202, 201, 200]);
+        assert_eq!(kill_order(&processes, 300), vec![300]);
+
+        assert!(ProcessTarget::Name("safari".into()).matches(&processes[1]));
+        assert!(ProcessTarget::Name("BASH.exe".into()).matches(&processes[4]));
+        assert!(!ProcessTarget::Name("bas".into()).matches(&processes[4]));
+    }
+
+    #[test]
+    fn test_parse_proc_entries() {
+        assert_eq!(
+            parse_stat("4242 (Web Content (1)) S 200 4242 4242 0 -1 4194560"),
+            Some(("Web Content (1)".to_string(), 200))
+        );
+        assert_eq!(parse_stat("garbage"), None);
+
+        assert_eq!(
+            command_name(b"/usr/lib/firefox/firefox-bin-with-a-long-name\0-contentproc\0"),
+            Some("firefox-bin-with-a-long-name".to_string())
+        );
+        assert_eq!(command_name(b""), None);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_lists_own_process() {
+        let processes = list_processes().await.unwrap();
+        let own = processes.iter().find(|p| p.pid == std::process::id()).unwrap();
+        let exe = std::env::current_exe().unwrap();
+        assert_eq!(own.name, exe.file_name().unwrap().to_string_lossy());
+    }
+}
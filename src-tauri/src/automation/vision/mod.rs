@@ -0,0 +1,458 @@
+//! Image template matching
+//!
+//! Locates a reference image on screen for applications without an
+//! accessibility tree (Citrix, Java Swing, games). Matching uses zero-mean
+//! normalized cross-correlation on grayscale images, so results are robust to
+//! uniform brightness changes. Large searches run coarse-to-fine: candidates are
+//! found on a downscaled pyramid level and then refined at full resolution.
+
+use crate::automation::desktop::DesktopAutomation;
+use crate::automation::{AutomationError, AutomationResult, Rect};
+use image::imageops::{self, FilterType};
+use image::GrayImage;
+use serde::{Deserialize, Serialize};
+
+/// Smallest template side kept when searching on a downscaled level
+const MIN_PYRAMID_SIDE: u32 = 8;
+
+/// Largest downscale factor used for the coarse search
+const MAX_PYRAMID_FACTOR: u32 = 8;
+
+/// How far below the threshold a coarse candidate may score and still be refined
+const COARSE_MARGIN: f32 = 0.15;
+
+/// Maximum coarse candidates refined per scale
+const MAX_CANDIDATES: usize = 200;
+
+/// Share of the smaller of two matches they may overlap and still both be kept
+const OVERLAP_LIMIT: f32 = 0.3;
+
+/// Template matching options
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct MatchOptions {
+    /// Minimum confidence (0.0 - 1.0) for a match
+    pub threshold: f32,
+    /// Screen region to search, the whole screen if not set
+    pub region: Option<Rect>,
+    /// Template scale factors to try, e.g. `[0.8, 1.0, 1.25]`
+    pub scales: Vec<f32>,
+    /// Maximum number of matches returned
+    pub max_matches: usize,
+}
+
+impl Default for MatchOptions {
+    fn default() -> Self {
+        Self {
+            threshold: 0.85,
+            region: None,
+            scales: vec![1.0],
+            max_matches: 20,
+        }
+    }
+}
+
+/// A location where the template was found
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImageMatch {
+    pub rect: Rect,
+    pub confidence: f32,
+    pub scale: f32,
+}
+
+/// Take a screenshot and find all occurrences of a PNG/JPEG template on it
+///
+/// Returned rectangles are in screen coordinates, best match first.
+pub async fn locate_on_screen(
+    automation: &dyn DesktopAutomation,
+    template: &[u8],
+    options: &MatchOptions,
+) -> AutomationResult<Vec<ImageMatch>> {
+    let screenshot = automation.screenshot(options.region.clone()).await?;
+    // Matching a full screen takes a while; keep it off the async workers
+    let (template, search) = (template.to_vec(), options.clone());
+    let mut matches = tokio::task::spawn_blocking(move || find_in_images(&screenshot, &template, &search))
+        .await
+        .map_err(|e| AutomationError::ExecutionFailed(format!("Image matching failed: {}", e)))??;
+
+    if let Some(region) = &options.region {
+        for m in &mut matches {
+            m.rect.x += region.x;
+            m.rect.y += region.y;
+        }
+    }
+
+    Ok(matches)
+}
+
+/// Find all occurrences of an encoded template in an encoded screenshot
+pub fn find_in_images(
+    screenshot: &[u8],
+    template: &[u8],
+    options: &MatchOptions,
+) -> AutomationResult<Vec<ImageMatch>> {
+    let haystack = decode_gray(screenshot)?;
+    let needle = decode_gray(template)?;
+    Ok(find_matches(&haystack, &needle, options))
+}
+
+/// Decode an image and convert it to grayscale
+pub fn decode_gray(bytes: &[u8]) -> AutomationResult<GrayImage> {
+    image::load_from_memory(bytes)
+        .map(|img| img.to_luma8())
+        .map_err(|e| AutomationError::InvalidParameter(format!("Failed to decode image: {}", e)))
+}
+
+/// Find all occurrences of `template` in `haystack`, best match first
+///
+/// Coordinates are relative to `haystack`; the search region is not applied here.
+pub fn find_matches(haystack: &GrayImage, template: &GrayImage, options: &MatchOptions) -> Vec<ImageMatch> {
+    let threshold = options.threshold.clamp(0.0, 1.0);
+    let scales: &[f32] = if options.scales.is_empty() { &[1.0] } else { &options.scales };
+
+    let mut matches = Vec::new();
+    for &scale in scales {
+        if scale <= 0.0 {
+            continue;
+        }
+
+        let width = (template.width() as f32 * scale).round() as u32;
+        let height = (template.height() as f32 * scale).round() as u32;
+        if width == 0 || height == 0 || width > haystack.width() || height > haystack.height() {
+            continue;
+        }
+
+        let scaled;
+        let needle = if width == template.width() && height == template.height() {
+            template
+        } else {
+            scaled = imageops::resize(template, width, height, FilterType::Triangle);
+            &scaled
+        };
+
+        for (x, y, confidence) in match_single_scale(haystack, needle, threshold) {
+            matches.push(ImageMatch {
+                rect: Rect {
+                    x: x as i32,
+                    y: y as i32,
+                    width: width as i32,
+                    height: height as i32,
+                },
+                confidence,
+                scale,
+            });
+        }
+    }
+
+    let mut matches = suppress_overlaps(matches);
+    matches.truncate(options.max_matches.max(1));
+    matches
+}
+
+fn match_single_scale(haystack: &GrayImage, template: &GrayImage, threshold: f32) -> Vec<(u32, u32, f32)> {
+    let min_side = template.width().min(template.height());
+    let mut factor = 1;
+    while factor < MAX_PYRAMID_FACTOR && min_side / (factor * 2) >= MIN_PYRAMID_SIDE {
+        factor *= 2;
+    }
+
+    let full = Searcher::new(haystack, template);
+    if factor == 1 {
+        return full.scan(threshold);
+    }
+
+    let small_haystack = imageops::resize(
+        haystack,
+        (haystack.width() / factor).max(1),
+        (haystack.height() / factor).max(1),
+        FilterType::Triangle,
+    );
+    let small_template = imageops::resize(
+        template,
+        (template.width() / factor).max(1),
+        (template.height() / factor).max(1),
+        FilterType::Triangle,
+    );
+
+    let mut hits = Searcher::new(&small_haystack, &small_template)
+        .scan((threshold - COARSE_MARGIN).max(0.0));
+    hits.sort_by(|a, b| b.2.total_cmp(&a.2));
+
+    // Every match scores high at several neighbouring positions; keep one of
+    // them so the limit is spent on distinct places
+    let size = (small_template.width() as i32, small_template.height() as i32);
+    let rect = |&(x, y, _): &(u32, u32, f32)| Rect {
+        x: x as i32,
+        y: y as i32,
+        width: size.0,
+        height: size.1,
+    };
+    let mut candidates: Vec<(u32, u32, f32)> = Vec::new();
+    for hit in hits {
+        if candidates.len() >= MAX_CANDIDATES {
+            break;
+        }
+        if candidates.iter().all(|c| overlap_ratio(&rect(c), &rect(&hit)) < OVERLAP_LIMIT) {
+            candidates.push(hit);
+        }
+    }
+
+    // Refine each coarse hit in its full-resolution neighbourhood
+    let max_x = haystack.width() - template.width();
+    let max_y = haystack.height() - template.height();
+    candidates
+        .into_iter()
+        .filter_map(|(cx, cy, _)| {
+            let (x0, y0) = ((cx * factor).saturating_sub(factor), (cy * factor).saturating_sub(factor));
+            let (x1, y1) = ((cx * factor + factor).min(max_x), (cy * factor + factor).min(max_y));
+
+            let mut best: Option<(u32, u32, f32)> = None;
+            for y in y0..=y1 {
+                for x in x0..=x1 {
+                    let score = full.score_at(x, y);
+                    if best.map(|b| score > b.2).unwrap_or(true) {
+                        best = Some((x, y, score));
+                    }
+                }
+            }
+            best.filter(|b| b.2 >= threshold)
+        })
+        .collect()
+}
+
+/// Normalized cross-correlation of one template over one image
+struct Searcher<'a> {
+    haystack: &'a GrayImage,
+    width: u32,
+    height: u32,
+    /// Zero-mean template pixels
+    template: Vec<f64>,
+    template_mean: f64,
+    template_norm: f64,
+    /// Integral images of pixel values and squared pixel values, (w + 1) x (h + 1)
+    sum: Vec<f64>,
+    sq_sum: Vec<f64>,
+}
+
+impl<'a> Searcher<'a> {
+    fn new(haystack: &'a GrayImage, template: &GrayImage) -> Self {
+        let pixels: Vec<f64> = template.as_raw().iter().map(|&p| p as f64).collect();
+        let template_mean = pixels.iter().sum::<f64>() / pixels.len() as f64;
+        let centered: Vec<f64> = pixels.iter().map(|p| p - template_mean).collect();
+        let template_norm = centered.iter().map(|p| p * p).sum::<f64>().sqrt();
+
+        let (w, h) = (haystack.width() as usize, haystack.height() as usize);
+        let stride = w + 1;
+        let mut sum = vec![0.0; stride * (h + 1)];
+        let mut sq_sum = vec![0.0; stride * (h + 1)];
+        let raw = haystack.as_raw();
+        for y in 0..h {
+            let (mut row, mut row_sq) = (0.0, 0.0);
+            for x in 0..w {
+                let p = raw[y * w + x] as f64;
+                row += p;
+                row_sq += p * p;
+                sum[(y + 1) * stride + x + 1] = sum[y * stride + x + 1] + row;
+                sq_sum[(y + 1) * stride + x + 1] = sq_sum[y * stride + x + 1] + row_sq;
+            }
+        }
+
+        Self {
+            haystack,
+            width: template.width(),
+            height: template.height(),
+            template: centered,
+            template_mean,
+            template_norm,
+            sum,
+            sq_sum,
+        }
+    }
+
+    /// All positions scoring at least `threshold`
+    fn scan(&self, threshold: f32) -> Vec<(u32, u32, f32)> {
+        if self.width > self.haystack.width() || self.height > self.haystack.height() {
+            return Vec::new();
+        }
+
+        let mut hits = Vec::new();
+        for y in 0..=(self.haystack.height() - self.height) {
+            for x in 0..=(self.haystack.width() - self.width) {
+                let score = self.score_at(x, y);
+                if score >= threshold {
+                    hits.push((x, y, score));
+                }
+            }
+        }
+        hits
+    }
+
+    fn window_sums(&self, x: u32, y: u32) -> (f64, f64) {
+        let stride = self.haystack.width() as usize + 1;
+        let (x0, y0) = (x as usize, y as usize);
+        let (x1, y1) = (x0 + self.width as usize, y0 + self.height as usize);
+        let area = |t: &[f64]| t[y1 * stride + x1] - t[y0 * stride + x1] - t[y1 * stride + x0] + t[y0 * stride + x0];
+        (area(&self.sum), area(&self.sq_sum))
+    }
+
+    fn score_at(&self, x: u32, y: u32) -> f32 {
+        let n = (self.width * self.height) as f64;
+        let (sum, sq_sum) = self.window_sums(x, y);
+        let variance = (sq_sum - sum * sum / n).max(0.0);
+
+        // Flat templates only match flat regions of the same brightness
+        if self.template_norm < 1e-6 {
+            return if variance < n {
+                (1.0 - ((sum / n - self.template_mean).abs() / 255.0)) as f32
+            } else {
+                0.0
+            };
+        }
+        if variance < 1e-6 {
+            return 0.0;
+        }
+
+        // The template is zero-mean, so the window mean cancels out of the cross term
+        let raw = self.haystack.as_raw();
+        let stride = self.haystack.width() as usize;
+        let mut cross = 0.0;
+        for ty in 0..self.height as usize {
+            let row = (y as usize + ty) * stride + x as usize;
+            let template_row = ty * self.width as usize;
+            for tx in 0..self.width as usize {
+                cross += raw[row + tx] as f64 * self.template[template_row + tx];
+            }
+        }
+
+        (cross / (variance.sqrt() * self.template_norm)) as f32
+    }
+}
+
+/// Keep the best match of every group of overlapping matches
+fn suppress_overlaps(mut matches: Vec<ImageMatch>) -> Vec<ImageMatch> {
+    matches.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+
+    let mut kept: Vec<ImageMatch> = Vec::new();
+    for candidate in matches {
+        if kept.iter().all(|m| overlap_ratio(&m.rect, &candidate.rect) < OVERLAP_LIMIT) {
+            kept.push(candidate);
+        }
+    }
+    kept
+}
+
+fn overlap_ratio(a: &Rect, b: &Rect) -> f32 {
+    let w = (a.x + a.width).min(b.x + b.width) - a.x.max(b.x);
+    let h = (a.y + a.height).min(b.y + b.height) - a.y.max(b.y);
+    if w <= 0 || h <= 0 {
+        return 0.0;
+    }
+
+    let intersection = (w * h) as f32;
+    let smaller = (a.width * a.height).min(b.width * b.height).max(1) as f32;
+    intersection / smaller
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::automation::desktop::testing::FakeDesktop;
+    use image::Luma;
+
+    fn pattern(width: u32, height: u32) -> GrayImage {
+        GrayImage::from_fn(width, height, |x, y| Luma([((x * 37 + y * 91) % 251) as u8]))
+    }
+
+    #[test]
+    fn test_finds_all_occurrences() {
+        let template = pattern(32, 24);
+        let mut haystack = GrayImage::from_pixel(400, 300, Luma([40]));
+        imageops::replace(&mut haystack, &template, 50, 60);
+        imageops::replace(&mut haystack, &template, 300, 200);
+
+        let matches = find_matches(&haystack, &template, &MatchOptions::default());
+        assert_eq!(matches.len(), 2);
+
+        let mut positions: Vec<(i32, i32)> = matches.iter().map(|m| (m.rect.x, m.rect.y)).collect();
+        positions.sort();
+        assert_eq!(positions, vec![(50, 60), (300, 200)]);
+        assert!(matches.iter().all(|m| m.confidence > 0.99));
+    }
+
+    #[test]
+    fn test_finds_scaled_template() {
+        let template = pattern(32, 24);
+        let larger = imageops::resize(&template, 40, 30, FilterType::Triangle);
+        let mut haystack = GrayImage::from_pixel(300, 200, Luma([40]));
+        imageops::replace(&mut haystack, &larger, 120, 90);
+
+        let options = MatchOptions {
+            scales: vec![0.8, 1.0, 1.25],
+            ..Default::default()
+        };
+        let matches = find_matches(&haystack, &template, &options);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].scale, 1.25);
+        assert_eq!(matches[0].rect, Rect { x: 120, y: 90, width: 40, height: 30 });
+    }
+
+    #[test]
+    fn test_many_hits_of_one_area_leave_room_for_others() {
+        // A shallow slope in the same direction matches the template everywhere
+        let template = GrayImage::from_fn(64, 64, |x, y| Luma([(x * 2 + y) as u8]));
+        let slope = GrayImage::from_fn(320, 320, |x, y| Luma([((x * 2 + y) / 4) as u8]));
+        let mut haystack = GrayImage::from_pixel(1000, 600, Luma([40]));
+        imageops::replace(&mut haystack, &slope, 0, 0);
+        let copies = [(400, 50), (600, 50), (400, 300), (700, 450)];
+        for (x, y) in copies {
+            imageops::replace(&mut haystack, &template, x, y);
+        }
+
+        let options = MatchOptions {
+            threshold: 0.95,
+            max_matches: 1000,
+            ..Default::default()
+        };
+        let matches = find_matches(&haystack, &template, &options);
+        for (x, y) in copies {
+            assert!(matches.iter().any(|m| (m.rect.x, m.rect.y) == (x as i32, y as i32)));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_region_search_reports_screen_coordinates() {
+        let template = pattern(32, 24);
+        let mut screen = GrayImage::from_pixel(400, 300, Luma([40]));
+        imageops::replace(&mut screen, &template, 20, 20);
+        imageops::replace(&mut screen, &template, 250, 180);
+        let desktop = FakeDesktop {
+            screen: Some(image::DynamicImage::ImageLuma8(screen)),
+            ..FakeDesktop::default()
+        };
+
+        let mut png = Vec::new();
+        template
+            .write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+            .unwrap();
+        let options = MatchOptions {
+            region: Some(Rect { x: 200, y: 150, width: 150, height: 100 }),
+            ..Default::default()
+        };
+        let matches = locate_on_screen(&desktop, &png, &options).await.unwrap();
+        assert_eq!(matches.len(), 1);
+        assert_eq!((matches[0].rect.x, matches[0].rect.y), (250, 180));
+    }
+
+    #[test]
+    fn test_no_match_below_threshold() {
+        let template = pattern(20, 20);
+        let haystack = GrayImage::from_fn(200, 200, |x, y| Luma([((x * 13 + y * 7) % 256) as u8]));
+
+        let options = MatchOptions {
+            threshold: 0.99,
+            ..Default::default()
+        };
+        assert!(find_matches(&haystack, &template, &options).is_empty());
+    }
+}
@@ -1,13 +1,19 @@
+use crate::automation::keys::{Key, KeyCombo};
 use crate::automation::{AutomationError, AutomationResult, Rect};
 use chromiumoxide::browser::{Browser, BrowserConfig};
-use chromiumoxide::cdp::browser_protocol::page::CaptureScreenshotFormat;
+use chromiumoxide::cdp::browser_protocol::input::{DispatchKeyEventParams, DispatchKeyEventType};
+use chromiumoxide::cdp::browser_protocol::page::{
+    AddScriptToEvaluateOnNewDocumentParams, CaptureScreenshotFormat, EventFrameNavigated,
+    RemoveScriptToEvaluateOnNewDocumentParams, ScriptIdentifier,
+};
+use chromiumoxide::cdp::js_protocol::runtime::{AddBindingParams, EventBindingCalled, RemoveBindingParams};
 use chromiumoxide::page::ScreenshotParams;
 use chromiumoxide::Element;
 use futures::StreamExt;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use tokio::sync::{mpsc, RwLock};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WebElement {
@@ -39,10 +45,60 @@ impl Default for BrowserOptions {
     }
 }
 
+/// Element an action was recorded on, with selectors to find it again
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordedTarget {
+    pub selector: String,
+    pub xpath: String,
+    pub tag_name: String,
+    /// Accessible name, text or placeholder of the element
+    pub label: String,
+    /// Password input, whose value is never sent by the page
+    #[serde(default)]
+    pub sensitive: bool,
+}
+
+/// User action in a recorded browser session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum WebRecordedEvent {
+    Click {
+        target: RecordedTarget,
+    },
+    /// Final value of a text field
+    Input {
+        target: RecordedTarget,
+        value: String,
+    },
+    Select {
+        target: RecordedTarget,
+        value: String,
+        label: String,
+    },
+    /// Key that does not just type text, like Enter or Ctrl+A
+    Key {
+        target: Option<RecordedTarget>,
+        key: String,
+        modifiers: Vec<String>,
+    },
+    /// The top-level document changed, whatever caused it
+    Navigate {
+        url: String,
+    },
+}
+
 pub struct BrowserSession {
     browser: Browser,
     page: chromiumoxide::Page,
     user_data_dir: Option<std::path::PathBuf>,
+    recording: Option<WebRecording>,
+}
+
+/// Listener script and event task of a session being recorded
+struct WebRecording {
+    script: ScriptIdentifier,
+    listener: tokio::task::JoinHandle<()>,
 }
 
 pub struct WebAutomation {
@@ -141,6 +197,7 @@ impl WebAutomation {
             browser,
             page,
             user_data_dir: Some(user_data_dir),
+            recording: None,
         };
         self.sessions.write().await.insert(session_id.to_string(), session);
 
@@ -394,6 +451,10 @@ impl WebAutomation {
 
         let mut sessions = self.sessions.write().await;
         if let Some(session) = sessions.remove(session_id) {
+            if let Some(recording) = &session.recording {
+                recording.listener.abort();
+            }
+
             // Close the page first
             let _ = session.page.close().await;
             // Browser will be dropped
@@ -578,89 +639,537 @@ impl WebAutomation {
         Ok(())
     }
 
-    /// Press a keyboard key in the browser
+    /// Press a key or key combination like "Ctrl+A" in the browser
     pub async fn press_key(&self, session_id: &str, key: &str) -> AutomationResult<()> {
-        log::info!("Pressing key: {}", key);
+        self.press_keys(session_id, &key.parse()?).await
+    }
+
+    /// Press a key combination in the page through CDP key events, so the
+    /// browser performs the default action of the keys
+    pub async fn press_keys(&self, session_id: &str, combo: &KeyCombo) -> AutomationResult<()> {
+        log::info!("Pressing keys: {}", combo);
 
         let sessions = self.sessions.read().await;
         let session = sessions
             .get(session_id)
             .ok_or_else(|| AutomationError::ExecutionFailed(format!("Session not found: {}", session_id)))?;
 
-        // Map common key names to JavaScript key codes
-        let (js_key, key_code, code) = match key.to_lowercase().as_str() {
-            "enter" | "return" => ("Enter", 13, "Enter"),
-            "escape" | "esc" => ("Escape", 27, "Escape"),
-            "tab" => ("Tab", 9, "Tab"),
-            "backspace" => ("Backspace", 8, "Backspace"),
-            "delete" | "del" => ("Delete", 46, "Delete"),
-            "arrowup" | "up" => ("ArrowUp", 38, "ArrowUp"),
-            "arrowdown" | "down" => ("ArrowDown", 40, "ArrowDown"),
-            "arrowleft" | "left" => ("ArrowLeft", 37, "ArrowLeft"),
-            "arrowright" | "right" => ("ArrowRight", 39, "ArrowRight"),
-            "home" => ("Home", 36, "Home"),
-            "end" => ("End", 35, "End"),
-            "pageup" => ("PageUp", 33, "PageUp"),
-            "pagedown" => ("PageDown", 34, "PageDown"),
-            "space" | " " => (" ", 32, "Space"),
-            _ => (key, 0, key), // For single characters or unknown keys
-        };
+        for _ in 0..combo.repeat {
+            let mut modifiers = 0;
+            let mut events = Vec::with_capacity(combo.keys.len() * 2);
+            for &key in &combo.keys {
+                modifiers |= cdp_modifier(key);
+                events.push(key_event(key, modifiers, true)?);
+            }
+            for &key in combo.keys.iter().rev() {
+                events.push(key_event(key, modifiers, false)?);
+                modifiers &= !cdp_modifier(key);
+            }
 
-        // Dispatch keydown, keypress, and keyup events using JavaScript
-        let script = format!(
-            r#"
-            const activeElement = document.activeElement || document.body;
-            const keyDownEvent = new KeyboardEvent('keydown', {{
-                key: '{}',
-                code: '{}',
-                keyCode: {},
-                which: {},
-                bubbles: true,
-                cancelable: true
-            }});
-            const keyPressEvent = new KeyboardEvent('keypress', {{
-                key: '{}',
-                code: '{}',
-                keyCode: {},
-                which: {},
-                bubbles: true,
-                cancelable: true
-            }});
-            const keyUpEvent = new KeyboardEvent('keyup', {{
-                key: '{}',
-                code: '{}',
-                keyCode: {},
-                which: {},
-                bubbles: true,
-                cancelable: true
-            }});
-            activeElement.dispatchEvent(keyDownEvent);
-            activeElement.dispatchEvent(keyPressEvent);
-            activeElement.dispatchEvent(keyUpEvent);
-            true
-            "#,
-            js_key, code, key_code, key_code,
-            js_key, code, key_code, key_code,
-            js_key, code, key_code, key_code
-        );
+            for event in events {
+                session
+                    .page
+                    .execute(event)
+                    .await
+                    .map_err(|e| AutomationError::ExecutionFailed(format!("Failed to press key: {}", e)))?;
+            }
+        }
 
-        session
-            .page
-            .evaluate(script)
+        log::info!("Key press completed: {}", combo);
+        Ok(())
+    }
+
+    /// Record user actions in a browser session
+    ///
+    /// A listener script is injected into the current document and every
+    /// document loaded after it. It reports clicks, committed text input,
+    /// selections and keys through a CDP binding; navigations of the main
+    /// frame are reported by the browser. Events arrive on the returned
+    /// channel until `stop_recording` is called or the browser closes.
+    pub async fn start_recording(&self, session_id: &str) -> AutomationResult<mpsc::UnboundedReceiver<WebRecordedEvent>> {
+        log::info!("Recording browser session: {}", session_id);
+
+        let mut sessions = self.sessions.write().await;
+        let session = sessions
+            .get_mut(session_id)
+            .ok_or_else(|| AutomationError::ExecutionFailed(format!("Session not found: {}", session_id)))?;
+        if session.recording.is_some() {
+            return Err(AutomationError::ExecutionFailed(format!(
+                "Session is already being recorded: {}",
+                session_id
+            )));
+        }
+
+        let page = &session.page;
+        let cdp_error = |e: chromiumoxide::error::CdpError| {
+            AutomationError::ExecutionFailed(format!("Failed to start recording: {}", e))
+        };
+
+        page.execute(AddBindingParams::new(RECORDER_BINDING)).await.map_err(cdp_error)?;
+        let mut bindings = page.event_listener::<EventBindingCalled>().await.map_err(cdp_error)?;
+        let mut navigations = page.event_listener::<EventFrameNavigated>().await.map_err(cdp_error)?;
+        let script = page
+            .evaluate_on_new_document(AddScriptToEvaluateOnNewDocumentParams::new(RECORDER_JS))
             .await
-            .map_err(|e| AutomationError::ExecutionFailed(format!("Failed to press key: {}", e)))?;
+            .map_err(cdp_error)?;
+        page.evaluate(RECORDER_JS).await.map_err(cdp_error)?;
+
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let listener = tokio::spawn(async move {
+            loop {
+                let event = tokio::select! {
+                    Some(call) = bindings.next() => {
+                        if call.name != RECORDER_BINDING {
+                            continue;
+                        }
+                        match serde_json::from_str::<WebRecordedEvent>(&call.payload) {
+                            Ok(event) => event,
+                            Err(e) => {
+                                log::warn!("Invalid recorded browser event: {}", e);
+                                continue;
+                            }
+                        }
+                    }
+                    Some(navigation) = navigations.next() => {
+                        if navigation.frame.parent_id.is_some() {
+                            continue;
+                        }
+                        WebRecordedEvent::Navigate { url: navigation.frame.url.clone() }
+                    }
+                    else => break,
+                };
+                if sender.send(event).is_err() {
+                    break;
+                }
+            }
+        });
+
+        session.recording = Some(WebRecording { script, listener });
+        Ok(receiver)
+    }
+
+    /// Stop recording a browser session and remove the listener script
+    pub async fn stop_recording(&self, session_id: &str) -> AutomationResult<()> {
+        let mut sessions = self.sessions.write().await;
+        let session = sessions
+            .get_mut(session_id)
+            .ok_or_else(|| AutomationError::ExecutionFailed(format!("Session not found: {}", session_id)))?;
+        let Some(recording) = session.recording.take() else {
+            return Ok(());
+        };
+
+        log::info!("Stopping browser recording: {}", session_id);
+        recording.listener.abort();
+
+        // The page may already be gone; the listener is stopped either way
+        let page = &session.page;
+        let _ = page
+            .execute(RemoveScriptToEvaluateOnNewDocumentParams::new(recording.script))
+            .await;
+        let _ = page.execute(RemoveBindingParams::new(RECORDER_BINDING)).await;
+        let _ = page.evaluate("window.__batataRecorder && window.__batataRecorder.stop()").await;
 
-        log::info!("Key press completed: {}", key);
         Ok(())
     }
 
-    async fn find_element(&self, page: &chromiumoxide::Page, selector: &str) -> AutomationResult<Element> {
-        page.find_element(selector)
+    /// Find a single element and describe it, without waiting
+    pub async fn find(&self, session_id: &str, selector: &str) -> AutomationResult<WebElement> {
+        let sessions = self.sessions.read().await;
+        let session = sessions
+            .get(session_id)
+            .ok_or_else(|| AutomationError::ExecutionFailed(format!("Session not found: {}", session_id)))?;
+
+        let element = self.find_element(&session.page, selector).await?;
+        self.describe_element(&element).await
+    }
+
+    /// Find and describe every element matching a selector, without waiting
+    pub async fn find_all(&self, session_id: &str, selector: &str) -> AutomationResult<Vec<WebElement>> {
+        let sessions = self.sessions.read().await;
+        let session = sessions
+            .get(session_id)
+            .ok_or_else(|| AutomationError::ExecutionFailed(format!("Session not found: {}", session_id)))?;
+
+        let query_error = |e: chromiumoxide::error::CdpError| {
+            AutomationError::ExecutionFailed(format!("Failed to query {}: {}", selector, e))
+        };
+        let elements = match xpath_selector(selector) {
+            Some(xpath) => {
+                // DOM.getSearchResults rejects an empty range, and DOM.performSearch
+                // falls back to a text search for invalid XPath, so evaluate it first
+                let count = session
+                    .page
+                    .evaluate(format!(
+                        "document.evaluate({}, document, null, XPathResult.ORDERED_NODE_SNAPSHOT_TYPE, null).snapshotLength",
+                        serde_json::to_string(xpath).unwrap_or_default()
+                    ))
+                    .await
+                    .map_err(query_error)?
+                    .into_value::<u64>()
+                    .unwrap_or(0);
+                if count == 0 {
+                    return Ok(Vec::new());
+                }
+                session.page.find_xpaths(xpath).await.map_err(query_error)?
+            }
+            None => session.page.find_elements(selector).await.map_err(query_error)?,
+        };
+
+        let mut found = Vec::with_capacity(elements.len());
+        for element in &elements {
+            found.push(self.describe_element(element).await?);
+        }
+        Ok(found)
+    }
+
+    /// Read tag, text, attributes, bounds and unique selectors of an element
+    async fn describe_element(&self, element: &Element) -> AutomationResult<WebElement> {
+        let description = element
+            .call_js_fn(DESCRIBE_ELEMENT_JS, false)
+            .await
+            .map_err(|e| AutomationError::ExecutionFailed(format!("Failed to describe element: {}", e)))?
+            .result
+            .value
+            .unwrap_or(serde_json::Value::Null);
+
+        let bounds = element
+            .bounding_box()
             .await
-            .map_err(|_| AutomationError::ElementNotFound(selector.to_string()))
+            .map(|b| Rect {
+                x: b.x.round() as i32,
+                y: b.y.round() as i32,
+                width: b.width.round() as i32,
+                height: b.height.round() as i32,
+            })
+            .unwrap_or(Rect {
+                x: 0,
+                y: 0,
+                width: 0,
+                height: 0,
+            });
+
+        let field = |name: &str| {
+            description
+                .get(name)
+                .and_then(|v| v.as_str())
+                .unwrap_or_default()
+                .to_string()
+        };
+
+        let attributes = description
+            .get("attributes")
+            .and_then(|v| v.as_object())
+            .map(|attrs| {
+                attrs
+                    .iter()
+                    .filter_map(|(k, v)| v.as_str().map(|v| (k.clone(), v.to_string())))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let text = field("text");
+        Ok(WebElement {
+            id: uuid::Uuid::new_v4().to_string(),
+            tag_name: field("tagName"),
+            xpath: field("xpath"),
+            css_selector: field("cssSelector"),
+            text: if text.is_empty() { None } else { Some(text) },
+            attributes,
+            bounds,
+        })
+    }
+
+    /// Find an element by CSS selector, or by XPath when the selector starts
+    /// with `xpath=` or `/`
+    async fn find_element(&self, page: &chromiumoxide::Page, selector: &str) -> AutomationResult<Element> {
+        let result = match xpath_selector(selector) {
+            Some(xpath) => page.find_xpath(xpath).await,
+            None => page.find_element(selector).await,
+        };
+
+        result.map_err(|_| AutomationError::ElementNotFound(selector.to_string()))
     }
 }
 
+/// Return the XPath expression of an XPath selector
+pub fn xpath_selector(selector: &str) -> Option<&str> {
+    if let Some(xpath) = selector.strip_prefix("xpath=") {
+        Some(xpath)
+    } else if selector.starts_with('/') || selector.starts_with("(/") {
+        Some(selector)
+    } else {
+        None
+    }
+}
+
+/// CDP modifier bit of a key: Alt 1, Ctrl 2, Meta 4, Shift 8
+fn cdp_modifier(key: Key) -> i64 {
+    match key {
+        Key::Alt => 1,
+        Key::Ctrl => 2,
+        Key::Meta => 4,
+        Key::Shift => 8,
+        _ => 0,
+    }
+}
+
+/// Key press or release event with the DOM `key` and `code` of a key
+fn key_event(key: Key, modifiers: i64, down: bool) -> AutomationResult<DispatchKeyEventParams> {
+    let shift = modifiers & cdp_modifier(Key::Shift) != 0;
+    let (dom_key, code) = match key {
+        Key::Ctrl => ("Control".to_string(), "ControlLeft".to_string()),
+        Key::Shift => ("Shift".to_string(), "ShiftLeft".to_string()),
+        Key::Alt => ("Alt".to_string(), "AltLeft".to_string()),
+        Key::Meta => ("Meta".to_string(), "MetaLeft".to_string()),
+        Key::Escape => ("Escape".to_string(), "Escape".to_string()),
+        Key::Left | Key::Up | Key::Right | Key::Down => {
+            let name = format!("Arrow{}", key);
+            (name.clone(), name)
+        }
+        Key::Space | Key::Char(' ') => (" ".to_string(), "Space".to_string()),
+        Key::Menu => ("ContextMenu".to_string(), "ContextMenu".to_string()),
+        Key::Char(ch) => {
+            let code = match ch {
+                'a'..='z' => format!("Key{}", ch.to_ascii_uppercase()),
+                '0'..='9' => format!("Digit{}", ch),
+                _ => String::new(),
+            };
+            let ch = if shift { ch.to_ascii_uppercase() } else { ch };
+            (ch.to_string(), code)
+        }
+        _ => (key.to_string(), key.to_string()),
+    };
+
+    // Keys typing text while no shortcut modifier is held also insert it
+    let text = match key {
+        Key::Enter => Some("\r".to_string()),
+        Key::Space | Key::Char(_) => Some(dom_key.clone()),
+        _ => None,
+    }
+    .filter(|_| modifiers & !cdp_modifier(Key::Shift) == 0);
+
+    let mut builder = DispatchKeyEventParams::builder()
+        .modifiers(modifiers)
+        .key(dom_key)
+        .code(code);
+    if let Some(vk) = key.virtual_key_code() {
+        builder = builder.windows_virtual_key_code(vk as i64);
+    }
+    builder = match (down, text) {
+        (true, Some(text)) => builder.r#type(DispatchKeyEventType::KeyDown).text(text),
+        (true, None) => builder.r#type(DispatchKeyEventType::RawKeyDown),
+        (false, _) => builder.r#type(DispatchKeyEventType::KeyUp),
+    };
+    builder
+        .build()
+        .map_err(|e| AutomationError::InvalidParameter(format!("Invalid key event: {}", e)))
+}
+
+/// Describes `this` element: tag, text, attributes and selectors that uniquely identify it
+const DESCRIBE_ELEMENT_JS: &str = r#"
+function() {
+    const el = this;
+    const cssPath = (node) => {
+        if (node.id && document.querySelectorAll('#' + CSS.escape(node.id)).length === 1) {
+            return '#' + CSS.escape(node.id);
+        }
+        const parts = [];
+        while (node && node.nodeType === Node.ELEMENT_NODE && node !== document.documentElement) {
+            if (node.id && document.querySelectorAll('#' + CSS.escape(node.id)).length === 1) {
+                parts.unshift('#' + CSS.escape(node.id));
+                break;
+            }
+            let part = node.tagName.toLowerCase();
+            const parent = node.parentElement;
+            if (parent) {
+                const siblings = Array.from(parent.children).filter(c => c.tagName === node.tagName);
+                if (siblings.length > 1) {
+                    part += ':nth-of-type(' + (siblings.indexOf(node) + 1) + ')';
+                }
+            }
+            parts.unshift(part);
+            node = parent;
+        }
+        return parts.join(' > ');
+    };
+    const xpath = (node) => {
+        const parts = [];
+        while (node && node.nodeType === Node.ELEMENT_NODE) {
+            let index = 1;
+            for (let sib = node.previousElementSibling; sib; sib = sib.previousElementSibling) {
+                if (sib.tagName === node.tagName) index++;
+            }
+            parts.unshift(node.tagName.toLowerCase() + '[' + index + ']');
+            node = node.parentElement;
+        }
+        return '/' + parts.join('/');
+    };
+    const attributes = {};
+    for (const attr of el.attributes) {
+        attributes[attr.name] = attr.value;
+    }
+    return {
+        tagName: el.tagName.toLowerCase(),
+        text: (el.innerText || el.value || '').trim().slice(0, 500),
+        cssSelector: cssPath(el),
+        xpath: xpath(el),
+        attributes,
+    };
+}
+"#;
+
+/// Name of the binding the recorder script reports events through
+const RECORDER_BINDING: &str = "__batataRecord";
+
+/// Reports user actions of a document through the recorder binding
+///
+/// Text fields are reported once their value is committed (change event, or
+/// before Enter/Tab leaves them), not per keystroke. Selectors prefer ids,
+/// test ids and names over positional paths, and are only used when unique.
+const RECORDER_JS: &str = r#"
+(() => {
+    if (window.__batataRecorder || typeof window.__batataRecord !== 'function') return;
+
+    const send = (event) => window.__batataRecord(JSON.stringify(event));
+    const count = (selector) => {
+        try {
+            return document.querySelectorAll(selector).length;
+        } catch (e) {
+            return 0;
+        }
+    };
+    const countXPath = (xpath) => {
+        try {
+            return document.evaluate('count(' + xpath + ')', document, null, XPathResult.NUMBER_TYPE, null).numberValue;
+        } catch (e) {
+            return 0;
+        }
+    };
+    const quote = (value) => JSON.stringify(value);
+    const xpathLiteral = (value) => {
+        if (!value.includes('"')) return '"' + value + '"';
+        if (!value.includes("'")) return "'" + value + "'";
+        return 'concat("' + value.split('"').join('", \'"\', "') + '")';
+    };
+
+    const cssSelector = (el) => {
+        const tag = el.tagName.toLowerCase();
+        if (el.id && count('#' + CSS.escape(el.id)) === 1) return '#' + CSS.escape(el.id);
+        for (const attr of ['data-testid', 'data-test', 'data-qa', 'name', 'aria-label', 'placeholder']) {
+            const value = el.getAttribute(attr);
+            if (value) {
+                const selector = tag + '[' + attr + '=' + quote(value) + ']';
+                if (count(selector) === 1) return selector;
+            }
+        }
+        const parts = [];
+        let node = el;
+        while (node && node.nodeType === Node.ELEMENT_NODE && node !== document.documentElement) {
+            if (node.id && count('#' + CSS.escape(node.id)) === 1) {
+                parts.unshift('#' + CSS.escape(node.id));
+                break;
+            }
+            let part = node.tagName.toLowerCase();
+            const parent = node.parentElement;
+            if (parent) {
+                const siblings = Array.from(parent.children).filter(c => c.tagName === node.tagName);
+                if (siblings.length > 1) part += ':nth-of-type(' + (siblings.indexOf(node) + 1) + ')';
+            }
+            parts.unshift(part);
+            node = parent;
+        }
+        return parts.join(' > ');
+    };
+
+    const xpath = (el) => {
+        const tag = el.tagName.toLowerCase();
+        if (el.id) {
+            const byId = '//' + tag + '[@id=' + xpathLiteral(el.id) + ']';
+            if (countXPath(byId) === 1) return byId;
+        }
+        const text = (el.innerText || '').trim();
+        if (text && text.length <= 50 && ['a', 'button', 'label', 'span', 'li', 'td', 'th'].includes(tag)) {
+            const byText = '//' + tag + '[normalize-space()=' + xpathLiteral(text.replace(/\s+/g, ' ')) + ']';
+            if (countXPath(byText) === 1) return byText;
+        }
+        const parts = [];
+        for (let node = el; node && node.nodeType === Node.ELEMENT_NODE; node = node.parentElement) {
+            let index = 1;
+            for (let sib = node.previousElementSibling; sib; sib = sib.previousElementSibling) {
+                if (sib.tagName === node.tagName) index++;
+            }
+            parts.unshift(node.tagName.toLowerCase() + '[' + index + ']');
+        }
+        return '/' + parts.join('/');
+    };
+
+    const isPassword = (el) => el.tagName === 'INPUT' && el.type === 'password';
+    const target = (el) => ({
+        selector: cssSelector(el),
+        xpath: xpath(el),
+        tagName: el.tagName.toLowerCase(),
+        label: (el.getAttribute('aria-label') || (el.innerText || '').trim() || el.getAttribute('placeholder')
+            || el.getAttribute('name') || el.getAttribute('title') || '').slice(0, 80),
+        sensitive: isPassword(el),
+    });
+
+    const isTextField = (el) => el.tagName === 'TEXTAREA' || (el.tagName === 'INPUT'
+        && !['button', 'submit', 'reset', 'checkbox', 'radio', 'file', 'image', 'hidden'].includes(el.type));
+    const reported = new WeakMap();
+    const reportValue = (el) => {
+        if (reported.get(el) === el.value) return;
+        reported.set(el, el.value);
+        // Passwords stay in the page, the recorder substitutes a variable
+        send({ type: 'input', target: target(el), value: isPassword(el) ? '' : el.value });
+    };
+
+    const onClick = (e) => {
+        if (!e.isTrusted || !(e.target instanceof Element)) return;
+        const el = e.target.closest('a, button, input, select, textarea, label, [role="button"], [role="link"], [onclick]')
+            || e.target;
+        // Selections are reported by the change event
+        if (el.tagName === 'SELECT' || el.tagName === 'OPTION') return;
+        send({ type: 'click', target: target(el) });
+    };
+    const onChange = (e) => {
+        const el = e.target;
+        if (!e.isTrusted || !(el instanceof Element)) return;
+        if (el.tagName === 'SELECT') {
+            const option = el.options[el.selectedIndex];
+            send({ type: 'select', target: target(el), value: el.value, label: option ? option.text : '' });
+        } else if (isTextField(el)) {
+            reportValue(el);
+        }
+    };
+    const onKeyDown = (e) => {
+        if (!e.isTrusted) return;
+        const el = e.target instanceof Element ? e.target : null;
+        const modifiers = [];
+        if (e.metaKey) modifiers.push('Meta');
+        if (e.ctrlKey) modifiers.push('Ctrl');
+        if (e.altKey) modifiers.push('Alt');
+        const special = ['Enter', 'Escape', 'Tab'].includes(e.key);
+        if (!special && (modifiers.length === 0 || ['Control', 'Alt', 'Meta', 'Shift'].includes(e.key))) return;
+        if (e.shiftKey) modifiers.push('Shift');
+        // The value comes first, the key may submit or leave the field
+        if (el && isTextField(el)) reportValue(el);
+        send({ type: 'key', target: el && el !== document.body ? target(el) : null, key: e.key === ' ' ? 'Space' : e.key.length === 1 ? e.key.toUpperCase() : e.key, modifiers });
+    };
+
+    document.addEventListener('click', onClick, true);
+    document.addEventListener('change', onChange, true);
+    document.addEventListener('keydown', onKeyDown, true);
+    window.__batataRecorder = {
+        stop() {
+            document.removeEventListener('click', onClick, true);
+            document.removeEventListener('change', onChange, true);
+            document.removeEventListener('keydown', onKeyDown, true);
+            delete window.__batataRecorder;
+        },
+    };
+})();
+"#;
+
 impl Default for WebAutomation {
     fn default() -> Self {
         Self::new()
@@ -0,0 +1,701 @@
+//! Portable workflow bundle format
+//!
+//! A bundle is a zip archive that carries a workflow together with everything
+//! it needs to run on another machine:
+//!
+//! ```text
+//! manifest.json
+//! workflow.json
+//! subflows/<id>.json
+//! element_libraries/<id>.json
+//! plugins/<file>.lua
+//! ```
+//!
+//! The manifest records the bundle format version so that older builds can
+//! refuse archives they do not understand.
+
+use crate::element::ElementLibrary;
+use crate::engine::executor::Workflow;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::Path;
+use thiserror::Error;
+
+/// Current bundle format version
+pub const BUNDLE_FORMAT_VERSION: u32 = 1;
+
+const MANIFEST_FILE: &str = "manifest.json";
+const WORKFLOW_FILE: &str = "workflow.json";
+const SUBFLOWS_DIR: &str = "subflows";
+const LIBRARIES_DIR: &str = "element_libraries";
+const PLUGINS_DIR: &str = "plugins";
+
+#[derive(Error, Debug)]
+pub enum BundleError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Archive error: {0}")]
+    Archive(#[from] zip::result::ZipError),
+
+    #[error("Invalid bundle: {0}")]
+    InvalidBundle(String),
+
+    #[error("Unsupported bundle format version {found} (supported up to {supported})")]
+    UnsupportedVersion { found: u32, supported: u32 },
+
+    #[error("Serialization error: {0}")]
+    Serialization(#[from] serde_json::Error),
+}
+
+pub type BundleResult<T> = Result<T, BundleError>;
+
+/// How to handle bundle items that already exist locally
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum BundleConflictStrategy {
+    /// Import everything under new ids, keeping local items untouched
+    #[default]
+    Duplicate,
+    /// Reuse local subflows, libraries and plugins that share the bundle's ids/names
+    UseExisting,
+    /// Replace local items with the bundle's copies, keeping the original ids
+    Overwrite,
+}
+
+/// Reference to a workflow or element library stored in the bundle
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleEntry {
+    pub id: String,
+    pub name: String,
+    pub path: String,
+}
+
+/// Plugin stored in the bundle
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundlePluginEntry {
+    pub name: String,
+    pub version: String,
+    pub path: String,
+    pub node_types: Vec<String>,
+}
+
+/// Bundle manifest
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleManifest {
+    pub format_version: u32,
+    pub app_version: String,
+    pub created_at: String,
+    pub workflow: BundleEntry,
+    pub subflows: Vec<BundleEntry>,
+    pub element_libraries: Vec<BundleEntry>,
+    pub plugins: Vec<BundlePluginEntry>,
+}
+
+/// Plugin source carried by a bundle
+#[derive(Debug, Clone)]
+pub struct BundledPlugin {
+    pub entry: BundlePluginEntry,
+    pub source: String,
+}
+
+impl BundledPlugin {
+    pub fn new(name: String, version: String, node_types: Vec<String>, source: String) -> Self {
+        Self {
+            entry: BundlePluginEntry {
+                path: format!("{}/{}.lua", PLUGINS_DIR, sanitize_file_name(&name)),
+                name,
+                version,
+                node_types,
+            },
+            source,
+        }
+    }
+}
+
+/// In-memory representation of a bundle
+#[derive(Debug, Clone)]
+pub struct WorkflowBundle {
+    pub manifest: BundleManifest,
+    pub workflow: Workflow,
+    pub subflows: Vec<Workflow>,
+    pub element_libraries: Vec<ElementLibrary>,
+    pub plugins: Vec<BundledPlugin>,
+}
+
+impl WorkflowBundle {
+    /// Build a bundle and its manifest from the collected items
+    ///
+    /// Entry paths are derived from sanitized ids and names, so distinct
+    /// items that sanitize to the same file name get a numeric suffix.
+    pub fn new(
+        workflow: Workflow,
+        subflows: Vec<Workflow>,
+        element_libraries: Vec<ElementLibrary>,
+        mut plugins: Vec<BundledPlugin>,
+    ) -> Self {
+        let mut used_paths = HashSet::from([MANIFEST_FILE.to_string(), WORKFLOW_FILE.to_string()]);
+
+        let subflow_entries = subflows
+            .iter()
+            .map(|w| BundleEntry {
+                id: w.id.clone(),
+                name: w.name.clone(),
+                path: unique_path(&mut used_paths, SUBFLOWS_DIR, &w.id, "json"),
+            })
+            .collect();
+        let library_entries = element_libraries
+            .iter()
+            .map(|l| BundleEntry {
+                id: l.id.clone(),
+                name: l.name.clone(),
+                path: unique_path(&mut used_paths, LIBRARIES_DIR, &l.id, "json"),
+            })
+            .collect();
+        for plugin in &mut plugins {
+            plugin.entry.path = unique_path(&mut used_paths, PLUGINS_DIR, &plugin.entry.name, "lua");
+        }
+
+        let manifest = BundleManifest {
+            format_version: BUNDLE_FORMAT_VERSION,
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+            created_at: chrono::Utc::now().to_rfc3339(),
+            workflow: BundleEntry {
+                id: workflow.id.clone(),
+                name: workflow.name.clone(),
+                path: WORKFLOW_FILE.to_string(),
+            },
+            subflows: subflow_entries,
+            element_libraries: library_entries,
+            plugins: plugins.iter().map(|p| p.entry.clone()).collect(),
+        };
+
+        Self {
+            manifest,
+            workflow,
+            subflows,
+            element_libraries,
+            plugins,
+        }
+    }
+}
+
+/// Items to store when importing a bundle, with ids already remapped
+#[derive(Debug, Clone)]
+pub struct ImportPlan {
+    pub workflow: Workflow,
+    pub subflows: Vec<Workflow>,
+    pub element_libraries: Vec<ElementLibrary>,
+}
+
+/// Decide which bundle items to import and under which ids
+///
+/// `existing_workflows` and `existing_libraries` are the ids stored locally.
+pub fn plan_import(
+    bundle: &WorkflowBundle,
+    strategy: BundleConflictStrategy,
+    existing_workflows: &HashSet<String>,
+    existing_libraries: &HashSet<String>,
+) -> ImportPlan {
+    let mut remap = IdRemap::default();
+    let mut subflows = Vec::new();
+    let mut element_libraries = Vec::new();
+
+    for subflow in &bundle.subflows {
+        let exists = existing_workflows.contains(&subflow.id);
+        match strategy {
+            BundleConflictStrategy::Duplicate => {
+                remap.workflows.insert(subflow.id.clone(), uuid::Uuid::new_v4().to_string());
+                subflows.push(subflow.clone());
+            }
+            BundleConflictStrategy::UseExisting if exists => {}
+            BundleConflictStrategy::UseExisting | BundleConflictStrategy::Overwrite => {
+                subflows.push(subflow.clone());
+            }
+        }
+    }
+
+    for library in &bundle.element_libraries {
+        let exists = existing_libraries.contains(&library.id);
+        match strategy {
+            BundleConflictStrategy::Duplicate => {
+                remap.libraries.insert(library.id.clone(), uuid::Uuid::new_v4().to_string());
+                for element in &library.elements {
+                    remap.elements.insert(element.id.clone(), uuid::Uuid::new_v4().to_string());
+                }
+                element_libraries.push(library.clone());
+            }
+            BundleConflictStrategy::UseExisting if exists => {}
+            BundleConflictStrategy::UseExisting | BundleConflictStrategy::Overwrite => {
+                element_libraries.push(library.clone());
+            }
+        }
+    }
+
+    // The root workflow keeps its id only when overwriting
+    if strategy != BundleConflictStrategy::Overwrite {
+        remap
+            .workflows
+            .insert(bundle.workflow.id.clone(), uuid::Uuid::new_v4().to_string());
+    }
+
+    for library in &mut element_libraries {
+        remap.apply_to_library(library);
+    }
+    for subflow in &mut subflows {
+        remap.apply_to_workflow(subflow);
+    }
+    let mut workflow = bundle.workflow.clone();
+    remap.apply_to_workflow(&mut workflow);
+
+    ImportPlan {
+        workflow,
+        subflows,
+        element_libraries,
+    }
+}
+
+/// Items a workflow refers to through its node data
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WorkflowReferences {
+    pub subflow_ids: BTreeSet<String>,
+    pub library_ids: BTreeSet<String>,
+    pub element_ids: BTreeSet<String>,
+    pub node_types: BTreeSet<String>,
+}
+
+/// Collect subflows, element libraries, elements and node types used by a workflow
+pub fn collect_references(workflow: &Workflow) -> WorkflowReferences {
+    let mut refs = WorkflowReferences::default();
+
+    for node in &workflow.nodes {
+        if node.node_type == "subflow" {
+            if let Some(id) = node.data.get("workflowId").and_then(|v| v.as_str()) {
+                if !id.is_empty() {
+                    refs.subflow_ids.insert(id.to_string());
+                }
+            }
+        }
+
+        if let Some(id) = node.data.get("libraryId").and_then(|v| v.as_str()) {
+            if !id.is_empty() {
+                refs.library_ids.insert(id.to_string());
+            }
+        }
+
+        if let Some(id) = node.data.get("elementId").and_then(|v| v.as_str()) {
+            if !id.is_empty() {
+                refs.element_ids.insert(id.to_string());
+            }
+        }
+
+        refs.node_types.insert(node.node_type.clone());
+    }
+
+    refs
+}
+
+/// Old id -> new id mappings applied when importing a bundle
+#[derive(Debug, Clone, Default)]
+pub struct IdRemap {
+    pub workflows: HashMap<String, String>,
+    pub libraries: HashMap<String, String>,
+    pub elements: HashMap<String, String>,
+}
+
+impl IdRemap {
+    /// Rewrite workflow/library/element references inside the workflow's node data
+    pub fn apply_to_workflow(&self, workflow: &mut Workflow) {
+        if let Some(new_id) = self.workflows.get(&workflow.id) {
+            workflow.id = new_id.clone();
+        }
+
+        for node in &mut workflow.nodes {
+            remap_data_field(&mut node.data, "workflowId", &self.workflows);
+            remap_data_field(&mut node.data, "libraryId", &self.libraries);
+            remap_data_field(&mut node.data, "elementId", &self.elements);
+        }
+    }
+
+    /// Rewrite library and element ids, including element parent/child links
+    pub fn apply_to_library(&self, library: &mut ElementLibrary) {
+        if let Some(new_id) = self.libraries.get(&library.id) {
+            library.id = new_id.clone();
+        }
+
+        for element in &mut library.elements {
+            if let Some(new_id) = self.elements.get(&element.id) {
+                element.id = new_id.clone();
+            }
+            if let Some(parent_id) = &element.parent_id {
+                if let Some(new_id) = self.elements.get(parent_id) {
+                    element.parent_id = Some(new_id.clone());
+                }
+            }
+            for child in &mut element.children {
+                if let Some(new_id) = self.elements.get(child) {
+                    *child = new_id.clone();
+                }
+            }
+        }
+    }
+}
+
+fn remap_data_field(
+    data: &mut HashMap<String, serde_json::Value>,
+    key: &str,
+    mapping: &HashMap<String, String>,
+) {
+    if let Some(old) = data.get(key).and_then(|v| v.as_str()) {
+        if let Some(new_id) = mapping.get(old) {
+            data.insert(key.to_string(), serde_json::Value::String(new_id.clone()));
+        }
+    }
+}
+
+fn sanitize_file_name(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect()
+}
+
+/// Archive path under `dir` for `name` that no other entry uses yet
+fn unique_path(used: &mut HashSet<String>, dir: &str, name: &str, extension: &str) -> String {
+    let stem = sanitize_file_name(name);
+    let mut path = format!("{}/{}.{}", dir, stem, extension);
+    let mut suffix = 2;
+    while used.contains(&path) {
+        path = format!("{}/{}-{}.{}", dir, stem, suffix, extension);
+        suffix += 1;
+    }
+    used.insert(path.clone());
+    path
+}
+
+/// Write a bundle to a zip archive
+pub fn write_bundle(path: &Path, bundle: &WorkflowBundle) -> BundleResult<()> {
+    let file = File::create(path)?;
+    let mut zip = zip::ZipWriter::new(file);
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated);
+
+    zip.start_file(MANIFEST_FILE, options)?;
+    zip.write_all(&serde_json::to_vec_pretty(&bundle.manifest)?)?;
+
+    zip.start_file(WORKFLOW_FILE, options)?;
+    zip.write_all(&serde_json::to_vec_pretty(&bundle.workflow)?)?;
+
+    for (entry, subflow) in bundle.manifest.subflows.iter().zip(&bundle.subflows) {
+        zip.start_file(entry.path.as_str(), options)?;
+        zip.write_all(&serde_json::to_vec_pretty(subflow)?)?;
+    }
+
+    for (entry, library) in bundle
+        .manifest
+        .element_libraries
+        .iter()
+        .zip(&bundle.element_libraries)
+    {
+        zip.start_file(entry.path.as_str(), options)?;
+        zip.write_all(&serde_json::to_vec_pretty(library)?)?;
+    }
+
+    for plugin in &bundle.plugins {
+        zip.start_file(plugin.entry.path.as_str(), options)?;
+        zip.write_all(plugin.source.as_bytes())?;
+    }
+
+    zip.finish()?;
+    Ok(())
+}
+
+/// Read a bundle from a zip archive
+pub fn read_bundle(path: &Path) -> BundleResult<WorkflowBundle> {
+    let file = File::open(path)?;
+    let mut archive = zip::ZipArchive::new(file)?;
+
+    let manifest: BundleManifest = serde_json::from_str(&read_entry(&mut archive, MANIFEST_FILE)?)?;
+
+    if manifest.format_version > BUNDLE_FORMAT_VERSION {
+        return Err(BundleError::UnsupportedVersion {
+            found: manifest.format_version,
+            supported: BUNDLE_FORMAT_VERSION,
+        });
+    }
+
+    let workflow: Workflow =
+        serde_json::from_str(&read_entry(&mut archive, &manifest.workflow.path)?)?;
+
+    let mut subflows = Vec::new();
+    for entry in &manifest.subflows {
+        subflows.push(serde_json::from_str(&read_entry(&mut archive, &entry.path)?)?);
+    }
+
+    let mut element_libraries = Vec::new();
+    for entry in &manifest.element_libraries {
+        element_libraries.push(serde_json::from_str(&read_entry(&mut archive, &entry.path)?)?);
+    }
+
+    let mut plugins = Vec::new();
+    for entry in &manifest.plugins {
+        plugins.push(BundledPlugin {
+            entry: entry.clone(),
+            source: read_entry(&mut archive, &entry.path)?,
+        });
+    }
+
+    Ok(WorkflowBundle {
+        manifest,
+        workflow,
+        subflows,
+        element_libraries,
+        plugins,
+    })
+}
+
+fn read_entry(archive: &mut zip::ZipArchive<File>, name: &str) -> BundleResult<String> {
+    let mut entry = archive
+        .by_name(name)
+        .map_err(|_| BundleError::InvalidBundle(format!("Missing entry: {}", name)))?;
+    let mut content = String::new();
+    entry.read_to_string(&mut content)?;
+    Ok(content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::automation::Rect;
+    use crate::element::UIElement;
+    use crate::engine::executor::{Position, WorkflowNode};
+
+    fn node(id: &str, node_type: &str, data: serde_json::Value) -> WorkflowNode {
+        WorkflowNode {
+            id: id.to_string(),
+            node_type: node_type.to_string(),
+            position: Position { x: 0.0, y: 0.0 },
+            data: serde_json::from_value(data).unwrap(),
+            label: None,
+        }
+    }
+
+    fn sample_workflow() -> Workflow {
+        Workflow {
+            id: "wf-1".to_string(),
+            name: "Main".to_string(),
+            nodes: vec![
+                node("n1", "start", serde_json::json!({})),
+                node(
+                    "n2",
+                    "click",
+                    serde_json::json!({ "libraryId": "lib-1", "elementId": "el-1" }),
+                ),
+                node("n3", "subflow", serde_json::json!({ "workflowId": "wf-2" })),
+                node("n4", "myCustomNode", serde_json::json!({})),
+            ],
+            edges: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_collect_references() {
+        let refs = collect_references(&sample_workflow());
+        assert!(refs.subflow_ids.contains("wf-2"));
+        assert!(refs.library_ids.contains("lib-1"));
+        assert!(refs.element_ids.contains("el-1"));
+        assert!(refs.node_types.contains("myCustomNode"));
+    }
+
+    #[test]
+    fn test_remap_ids() {
+        let mut remap = IdRemap::default();
+        remap.workflows.insert("wf-1".to_string(), "wf-new".to_string());
+        remap.workflows.insert("wf-2".to_string(), "wf-sub".to_string());
+        remap.libraries.insert("lib-1".to_string(), "lib-new".to_string());
+        remap.elements.insert("el-1".to_string(), "el-new".to_string());
+
+        let mut workflow = sample_workflow();
+        remap.apply_to_workflow(&mut workflow);
+        assert_eq!(workflow.id, "wf-new");
+        assert_eq!(workflow.nodes[1].data["libraryId"], "lib-new");
+        assert_eq!(workflow.nodes[1].data["elementId"], "el-new");
+        assert_eq!(workflow.nodes[2].data["workflowId"], "wf-sub");
+
+        let mut library = ElementLibrary::new("Lib".to_string());
+        library.id = "lib-1".to_string();
+        let mut element = UIElement::new(
+            "OK".to_string(),
+            "Button".to_string(),
+            Rect { x: 0, y: 0, width: 10, height: 10 },
+        );
+        element.id = "el-1".to_string();
+        library.add_element(element);
+
+        remap.apply_to_library(&mut library);
+        assert_eq!(library.id, "lib-new");
+        assert_eq!(library.elements[0].id, "el-new");
+    }
+
+    #[test]
+    fn test_bundle_round_trip() {
+        let dir = std::env::temp_dir().join(format!("batata-bundle-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("bundle.zip");
+
+        let bundle = WorkflowBundle::new(
+            sample_workflow(),
+            Vec::new(),
+            vec![ElementLibrary::new("Lib".to_string())],
+            vec![BundledPlugin::new(
+                "my_plugin".to_string(),
+                "1.0.0".to_string(),
+                vec!["myCustomNode".to_string()],
+                "return {}".to_string(),
+            )],
+        );
+        write_bundle(&path, &bundle).unwrap();
+
+        let loaded = read_bundle(&path).unwrap();
+        assert_eq!(loaded.manifest.format_version, BUNDLE_FORMAT_VERSION);
+        assert_eq!(loaded.workflow.id, "wf-1");
+        assert_eq!(loaded.element_libraries.len(), 1);
+        assert_eq!(loaded.plugins[0].entry.name, "my_plugin");
+        assert_eq!(loaded.plugins[0].source, "return {}");
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    fn sample_library() -> ElementLibrary {
+        let mut library = ElementLibrary::new("Lib".to_string());
+        library.id = "lib-1".to_string();
+        let mut element = UIElement::new(
+            "OK".to_string(),
+            "Button".to_string(),
+            Rect { x: 0, y: 0, width: 10, height: 10 },
+        );
+        element.id = "el-1".to_string();
+        library.add_element(element);
+        library
+    }
+
+    fn round_trip(bundle: &WorkflowBundle) -> WorkflowBundle {
+        let dir = std::env::temp_dir().join(format!("batata-bundle-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("bundle.zip");
+        write_bundle(&path, bundle).unwrap();
+        let loaded = read_bundle(&path).unwrap();
+        std::fs::remove_dir_all(&dir).ok();
+        loaded
+    }
+
+    fn conflicting_bundle() -> WorkflowBundle {
+        let subflow = Workflow {
+            id: "wf-2".to_string(),
+            name: "Sub".to_string(),
+            nodes: vec![node(
+                "s1",
+                "click",
+                serde_json::json!({ "libraryId": "lib-1", "elementId": "el-1" }),
+            )],
+            edges: Vec::new(),
+        };
+        round_trip(&WorkflowBundle::new(
+            sample_workflow(),
+            vec![subflow],
+            vec![sample_library()],
+            Vec::new(),
+        ))
+    }
+
+    #[test]
+    fn test_import_duplicate_remaps_conflicting_ids() {
+        let bundle = conflicting_bundle();
+        let existing_workflows = HashSet::from(["wf-1".to_string(), "wf-2".to_string()]);
+        let existing_libraries = HashSet::from(["lib-1".to_string()]);
+
+        let plan = plan_import(
+            &bundle,
+            BundleConflictStrategy::Duplicate,
+            &existing_workflows,
+            &existing_libraries,
+        );
+
+        let library = &plan.element_libraries[0];
+        let element_id = &library.elements[0].id;
+        assert!(!existing_libraries.contains(&library.id));
+        assert_ne!(element_id, "el-1");
+
+        let subflow = &plan.subflows[0];
+        assert!(!existing_workflows.contains(&subflow.id));
+        assert_eq!(subflow.nodes[0].data["libraryId"], library.id.as_str());
+        assert_eq!(subflow.nodes[0].data["elementId"], element_id.as_str());
+
+        assert!(!existing_workflows.contains(&plan.workflow.id));
+        assert_eq!(plan.workflow.nodes[1].data["libraryId"], library.id.as_str());
+        assert_eq!(plan.workflow.nodes[1].data["elementId"], element_id.as_str());
+        assert_eq!(plan.workflow.nodes[2].data["workflowId"], subflow.id.as_str());
+    }
+
+    #[test]
+    fn test_import_use_existing_keeps_local_items() {
+        let bundle = conflicting_bundle();
+        let existing_workflows = HashSet::from(["wf-2".to_string()]);
+        let existing_libraries = HashSet::from(["lib-1".to_string()]);
+
+        let plan = plan_import(
+            &bundle,
+            BundleConflictStrategy::UseExisting,
+            &existing_workflows,
+            &existing_libraries,
+        );
+
+        assert!(plan.subflows.is_empty());
+        assert!(plan.element_libraries.is_empty());
+        assert_ne!(plan.workflow.id, "wf-1");
+        assert_eq!(plan.workflow.nodes[1].data["libraryId"], "lib-1");
+        assert_eq!(plan.workflow.nodes[1].data["elementId"], "el-1");
+        assert_eq!(plan.workflow.nodes[2].data["workflowId"], "wf-2");
+    }
+
+    #[test]
+    fn test_import_overwrite_keeps_ids() {
+        let bundle = conflicting_bundle();
+        let existing_workflows = HashSet::from(["wf-1".to_string(), "wf-2".to_string()]);
+        let existing_libraries = HashSet::from(["lib-1".to_string()]);
+
+        let plan = plan_import(
+            &bundle,
+            BundleConflictStrategy::Overwrite,
+            &existing_workflows,
+            &existing_libraries,
+        );
+
+        assert_eq!(plan.workflow.id, "wf-1");
+        assert_eq!(plan.subflows[0].id, "wf-2");
+        assert_eq!(plan.element_libraries[0].id, "lib-1");
+        assert_eq!(plan.element_libraries[0].elements[0].id, "el-1");
+    }
+
+    #[test]
+    fn test_colliding_names_get_distinct_paths() {
+        let plugin = |name: &str| {
+            BundledPlugin::new(name.to_string(), "1.0.0".to_string(), Vec::new(), format!("-- {}", name))
+        };
+        let bundle = WorkflowBundle::new(
+            sample_workflow(),
+            Vec::new(),
+            Vec::new(),
+            vec![plugin("a/b"), plugin("a:b")],
+        );
+        assert_ne!(bundle.manifest.plugins[0].path, bundle.manifest.plugins[1].path);
+
+        let loaded = round_trip(&bundle);
+        assert_eq!(loaded.plugins[0].source, "-- a/b");
+        assert_eq!(loaded.plugins[1].source, "-- a:b");
+    }
+}
@@ -0,0 +1,84 @@
+//! Backup and restore commands
+
+use crate::storage::{
+    self,
+    backup::{self, BackupManifest, RestoreMode, RestoreReport},
+    BackupConfig,
+};
+use serde::Serialize;
+use std::path::Path;
+
+/// Scheduled backup file for listing
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupFileInfo {
+    pub path: String,
+    pub size: u64,
+    pub manifest: Option<BackupManifest>,
+}
+
+/// Back up all application data to an archive
+#[tauri::command]
+pub async fn create_backup(path: String) -> Result<BackupManifest, String> {
+    log::info!("Creating backup: {}", path);
+
+    let db = storage::get_connection()
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+    let db_type = storage::get_database_config().await.db_type;
+
+    backup::create_backup(&db, &db_type, Path::new(&path))
+        .await
+        .map_err(|e| format!("Failed to create backup: {}", e))
+}
+
+/// Read the manifest of a backup archive without restoring it
+#[tauri::command]
+pub async fn get_backup_info(path: String) -> Result<BackupManifest, String> {
+    backup::read_manifest(Path::new(&path)).map_err(|e| format!("Failed to read backup: {}", e))
+}
+
+/// Restore a backup archive, merging with or replacing existing data
+#[tauri::command]
+pub async fn restore_backup(path: String, mode: Option<RestoreMode>) -> Result<RestoreReport, String> {
+    let mode = mode.unwrap_or_default();
+    log::info!("Restoring backup: {} ({:?})", path, mode);
+
+    let db = storage::get_connection()
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+
+    backup::restore_backup(&db, Path::new(&path), mode)
+        .await
+        .map_err(|e| format!("Failed to restore backup: {}", e))
+}
+
+/// List scheduled backups, newest first
+#[tauri::command]
+pub async fn list_backups() -> Result<Vec<BackupFileInfo>, String> {
+    let config = storage::get_backup_config().await;
+
+    let backups = backup::list_scheduled_backups(&config.get_directory())
+        .map_err(|e| format!("Failed to list backups: {}", e))?;
+
+    Ok(backups
+        .into_iter()
+        .map(|path| BackupFileInfo {
+            size: std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0),
+            manifest: backup::read_manifest(&path).ok(),
+            path: path.to_string_lossy().to_string(),
+        })
+        .collect())
+}
+
+/// Get scheduled backup configuration
+#[tauri::command]
+pub async fn get_backup_config() -> Result<BackupConfig, String> {
+    Ok(storage::get_backup_config().await)
+}
+
+/// Update scheduled backup configuration
+#[tauri::command]
+pub async fn set_backup_config(config: BackupConfig) -> Result<(), String> {
+    storage::update_backup_config(config).await
+}
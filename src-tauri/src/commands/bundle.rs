@@ -0,0 +1,423 @@
+//! Workflow bundle export/import commands
+
+use crate::bundle::{self, BundleConflictStrategy, BundleManifest, BundledPlugin, WorkflowBundle};
+use crate::element::ElementLibrary;
+use crate::engine::executor::Workflow;
+use crate::plugin::PluginLoader;
+use crate::storage::{self, entities::{element_library, workflow}};
+use crate::PluginState;
+use sea_orm::EntityTrait;
+use serde::Serialize;
+use std::collections::{HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+use tauri::{command, AppHandle, Manager, State};
+
+use super::element::{load_element_library, save_element_library};
+use super::workflow::{load_workflow, save_workflow};
+
+/// A bundle item that collides with something already stored locally
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleConflict {
+    /// "workflow", "subflow", "elementLibrary" or "plugin"
+    pub kind: String,
+    pub id: String,
+    pub name: String,
+    pub existing_id: String,
+    pub reason: String,
+}
+
+/// Bundle contents and the conflicts an import would run into
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundlePreview {
+    pub manifest: BundleManifest,
+    pub conflicts: Vec<BundleConflict>,
+    pub warnings: Vec<String>,
+}
+
+/// Result of importing a bundle
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleImportReport {
+    pub workflow: Workflow,
+    pub imported_subflows: Vec<String>,
+    pub imported_libraries: Vec<String>,
+    pub installed_plugins: Vec<String>,
+    pub conflicts: Vec<BundleConflict>,
+    pub warnings: Vec<String>,
+}
+
+/// Export a workflow with its subflows, element libraries and plugins as a zip bundle
+#[command]
+pub async fn export_workflow_bundle(
+    id: String,
+    path: String,
+    plugin_state: State<'_, PluginState>,
+) -> Result<BundleManifest, String> {
+    log::info!("Exporting workflow bundle {} to {}", id, path);
+
+    let root = load_workflow(id).await?;
+
+    // Walk subflow references breadth-first, guarding against cycles
+    let mut subflows: Vec<Workflow> = Vec::new();
+    let mut visited: HashSet<String> = HashSet::from([root.id.clone()]);
+    let mut queue: VecDeque<String> = bundle::collect_references(&root).subflow_ids.into_iter().collect();
+    while let Some(subflow_id) = queue.pop_front() {
+        if !visited.insert(subflow_id.clone()) {
+            continue;
+        }
+        let subflow = load_workflow(subflow_id.clone())
+            .await
+            .map_err(|e| format!("Failed to load subflow '{}': {}", subflow_id, e))?;
+        queue.extend(bundle::collect_references(&subflow).subflow_ids);
+        subflows.push(subflow);
+    }
+
+    let mut library_ids: HashSet<String> = HashSet::new();
+    let mut element_ids: HashSet<String> = HashSet::new();
+    let mut node_types: HashSet<String> = HashSet::new();
+    for wf in std::iter::once(&root).chain(subflows.iter()) {
+        let refs = bundle::collect_references(wf);
+        library_ids.extend(refs.library_ids);
+        element_ids.extend(refs.element_ids);
+        node_types.extend(refs.node_types);
+    }
+
+    // Elements referenced without a library id are looked up across all libraries
+    let all_libraries = list_all_libraries().await?;
+    for library in &all_libraries {
+        if library.elements.iter().any(|e| element_ids.contains(&e.id)) {
+            library_ids.insert(library.id.clone());
+        }
+    }
+
+    let mut libraries = Vec::new();
+    for library_id in &library_ids {
+        libraries.push(
+            load_element_library(library_id.clone())
+                .await
+                .map_err(|e| format!("Failed to load element library '{}': {}", library_id, e))?,
+        );
+    }
+
+    // Collect the Lua sources of plugins providing non built-in node types
+    let registry = plugin_state.registry.clone();
+    let mut plugin_names: HashSet<String> = HashSet::new();
+    for node_type in &node_types {
+        if let Some(name) = registry.get_plugin_for_node(node_type).await {
+            plugin_names.insert(name);
+        }
+    }
+
+    let mut plugins = Vec::new();
+    for name in plugin_names {
+        let plugin = registry
+            .get_plugin(&name)
+            .await
+            .ok_or_else(|| format!("Plugin not found: {}", name))?;
+        let source = std::fs::read_to_string(&plugin.source_path)
+            .map_err(|e| format!("Failed to read plugin '{}': {}", name, e))?;
+        plugins.push(BundledPlugin::new(
+            plugin.metadata.name,
+            plugin.metadata.version,
+            plugin.nodes.into_iter().map(|n| n.node_type).collect(),
+            source,
+        ));
+    }
+
+    let workflow_bundle = WorkflowBundle::new(root, subflows, libraries, plugins);
+    bundle::write_bundle(Path::new(&path), &workflow_bundle)
+        .map_err(|e| format!("Failed to write bundle: {}", e))?;
+
+    log::info!(
+        "Workflow bundle exported: {} subflows, {} element libraries, {} plugins",
+        workflow_bundle.subflows.len(),
+        workflow_bundle.element_libraries.len(),
+        workflow_bundle.plugins.len()
+    );
+    Ok(workflow_bundle.manifest)
+}
+
+/// Read a bundle and report conflicts with local items without importing anything
+#[command]
+pub async fn inspect_workflow_bundle(
+    path: String,
+    plugin_state: State<'_, PluginState>,
+) -> Result<BundlePreview, String> {
+    log::info!("Inspecting workflow bundle {}", path);
+
+    let workflow_bundle = bundle::read_bundle(Path::new(&path))
+        .map_err(|e| format!("Failed to read bundle: {}", e))?;
+    let (conflicts, warnings) = detect_conflicts(&workflow_bundle, &plugin_state).await?;
+
+    Ok(BundlePreview {
+        manifest: workflow_bundle.manifest,
+        conflicts,
+        warnings,
+    })
+}
+
+/// Import a workflow bundle, remapping ids and installing bundled plugins
+#[command]
+pub async fn import_workflow_bundle(
+    path: String,
+    strategy: Option<BundleConflictStrategy>,
+    app: AppHandle,
+    plugin_state: State<'_, PluginState>,
+) -> Result<BundleImportReport, String> {
+    let strategy = strategy.unwrap_or_default();
+    log::info!("Importing workflow bundle from {} ({:?})", path, strategy);
+
+    let workflow_bundle = bundle::read_bundle(Path::new(&path))
+        .map_err(|e| format!("Failed to read bundle: {}", e))?;
+    let (conflicts, mut warnings) = detect_conflicts(&workflow_bundle, &plugin_state).await?;
+
+    let existing_workflows = existing_workflow_ids().await?;
+    let existing_libraries = existing_library_ids().await?;
+    let plan = bundle::plan_import(&workflow_bundle, strategy, &existing_workflows, &existing_libraries);
+
+    let mut imported_libraries = Vec::new();
+    for mut library in plan.element_libraries {
+        library.updated_at = chrono::Utc::now().to_rfc3339();
+        imported_libraries.push(save_element_library(library).await?);
+    }
+
+    let mut imported_subflows = Vec::new();
+    for subflow in plan.subflows {
+        imported_subflows.push(save_workflow(subflow).await?);
+    }
+
+    let root = plan.workflow;
+    save_workflow(root.clone()).await?;
+
+    let installed_plugins =
+        install_plugins(&workflow_bundle.plugins, strategy, &app, &plugin_state, &mut warnings).await?;
+
+    log::info!(
+        "Workflow bundle imported: {} ({} subflows, {} libraries, {} plugins)",
+        root.id,
+        imported_subflows.len(),
+        imported_libraries.len(),
+        installed_plugins.len()
+    );
+
+    Ok(BundleImportReport {
+        workflow: root,
+        imported_subflows,
+        imported_libraries,
+        installed_plugins,
+        conflicts,
+        warnings,
+    })
+}
+
+/// Compare bundle items against the local database and plugin registry
+async fn detect_conflicts(
+    workflow_bundle: &WorkflowBundle,
+    plugin_state: &PluginState,
+) -> Result<(Vec<BundleConflict>, Vec<String>), String> {
+    let db = storage::get_connection()
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+
+    let workflows = workflow::Entity::find()
+        .all(&db)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+    let libraries = element_library::Entity::find()
+        .all(&db)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+
+    let mut conflicts = Vec::new();
+    let mut warnings = Vec::new();
+
+    let bundled_workflows = std::iter::once(("workflow", &workflow_bundle.workflow))
+        .chain(workflow_bundle.subflows.iter().map(|w| ("subflow", w)));
+    for (kind, wf) in bundled_workflows {
+        if let Some(existing) = workflows.iter().find(|m| m.id == wf.id) {
+            conflicts.push(BundleConflict {
+                kind: kind.to_string(),
+                id: wf.id.clone(),
+                name: wf.name.clone(),
+                existing_id: existing.id.clone(),
+                reason: "A workflow with the same id already exists".to_string(),
+            });
+        } else if let Some(existing) = workflows.iter().find(|m| m.name == wf.name) {
+            conflicts.push(BundleConflict {
+                kind: kind.to_string(),
+                id: wf.id.clone(),
+                name: wf.name.clone(),
+                existing_id: existing.id.clone(),
+                reason: "A workflow with the same name already exists".to_string(),
+            });
+        }
+    }
+
+    for library in &workflow_bundle.element_libraries {
+        if let Some(existing) = libraries.iter().find(|m| m.id == library.id) {
+            conflicts.push(BundleConflict {
+                kind: "elementLibrary".to_string(),
+                id: library.id.clone(),
+                name: library.name.clone(),
+                existing_id: existing.id.clone(),
+                reason: "An element library with the same id already exists".to_string(),
+            });
+        } else if let Some(existing) = libraries.iter().find(|m| m.name == library.name) {
+            conflicts.push(BundleConflict {
+                kind: "elementLibrary".to_string(),
+                id: library.id.clone(),
+                name: library.name.clone(),
+                existing_id: existing.id.clone(),
+                reason: "An element library with the same name already exists".to_string(),
+            });
+        }
+    }
+
+    let registry = plugin_state.registry.clone();
+    for plugin in &workflow_bundle.plugins {
+        let entry = &plugin.entry;
+        if let Some(installed) = registry.get_plugin(&entry.name).await {
+            if installed.metadata.version != entry.version {
+                warnings.push(format!(
+                    "Plugin '{}' version mismatch: bundle has {}, installed is {}",
+                    entry.name, entry.version, installed.metadata.version
+                ));
+            }
+            conflicts.push(BundleConflict {
+                kind: "plugin".to_string(),
+                id: entry.name.clone(),
+                name: entry.name.clone(),
+                existing_id: installed.metadata.name.clone(),
+                reason: format!("Plugin already installed (version {})", installed.metadata.version),
+            });
+        }
+
+        for node_type in &entry.node_types {
+            if let Some(owner) = registry.get_plugin_for_node(node_type).await {
+                if owner != entry.name {
+                    conflicts.push(BundleConflict {
+                        kind: "plugin".to_string(),
+                        id: entry.name.clone(),
+                        name: node_type.clone(),
+                        existing_id: owner.clone(),
+                        reason: format!("Node type '{}' is already provided by plugin '{}'", node_type, owner),
+                    });
+                }
+            }
+        }
+    }
+
+    Ok((conflicts, warnings))
+}
+
+/// Write bundled plugin sources into the plugins directory and register them
+async fn install_plugins(
+    plugins: &[BundledPlugin],
+    strategy: BundleConflictStrategy,
+    app: &AppHandle,
+    plugin_state: &PluginState,
+    warnings: &mut Vec<String>,
+) -> Result<Vec<String>, String> {
+    let plugins_dir = app
+        .path()
+        .app_data_dir()
+        .map(|p: PathBuf| p.join("plugins"))
+        .unwrap_or_else(|_| PathBuf::from("plugins"));
+    std::fs::create_dir_all(&plugins_dir)
+        .map_err(|e| format!("Failed to create plugins directory: {}", e))?;
+
+    let registry = plugin_state.registry.clone();
+    let loader = PluginLoader::new(registry.clone());
+    let mut installed = Vec::new();
+
+    for plugin in plugins {
+        let name = &plugin.entry.name;
+        if registry.get_plugin(name).await.is_some() {
+            if strategy != BundleConflictStrategy::Overwrite {
+                log::info!("Keeping installed plugin '{}'", name);
+                continue;
+            }
+            let _ = registry.unregister(name).await;
+        }
+
+        let file_name = Path::new(&plugin.entry.path)
+            .file_name()
+            .map(|f| f.to_os_string())
+            .unwrap_or_else(|| format!("{}.lua", name).into());
+        let target = plugins_dir.join(file_name);
+
+        std::fs::write(&target, &plugin.source)
+            .map_err(|e| format!("Failed to write plugin '{}': {}", name, e))?;
+
+        match loader.load_file(&target).await {
+            Ok(def) => {
+                if def.metadata.version != plugin.entry.version {
+                    warnings.push(format!(
+                        "Plugin '{}' declares version {} but the bundle manifest lists {}",
+                        name, def.metadata.version, plugin.entry.version
+                    ));
+                }
+                installed.push(def.metadata.name);
+            }
+            Err(e) => warnings.push(format!("Failed to load plugin '{}': {}", name, e)),
+        }
+    }
+
+    Ok(installed)
+}
+
+async fn list_all_libraries() -> Result<Vec<ElementLibrary>, String> {
+    let db = storage::get_connection()
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+
+    let models = element_library::Entity::find()
+        .all(&db)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+
+    Ok(models
+        .into_iter()
+        .filter_map(|m| {
+            let elements = serde_json::from_str(&m.elements).ok()?;
+            Some(ElementLibrary {
+                id: m.id,
+                name: m.name,
+                description: m.description,
+                elements,
+                created_at: m.created_at.to_rfc3339(),
+                updated_at: m.updated_at.to_rfc3339(),
+            })
+        })
+        .collect())
+}
+
+async fn existing_workflow_ids() -> Result<HashSet<String>, String> {
+    let db = storage::get_connection()
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+
+    Ok(workflow::Entity::find()
+        .all(&db)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?
+        .into_iter()
+        .map(|m| m.id)
+        .collect())
+}
+
+async fn existing_library_ids() -> Result<HashSet<String>, String> {
+    let db = storage::get_connection()
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+
+    Ok(element_library::Entity::find()
+        .all(&db)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?
+        .into_iter()
+        .map(|m| m.id)
+        .collect())
+}
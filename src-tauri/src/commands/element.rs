@@ -1,8 +1,12 @@
+use crate::automation::desktop::ElementTreeNode;
+use crate::automation::vision::{self, ImageMatch, MatchOptions};
+use crate::element::validation::{self, ElementValidation, LibraryValidationReport};
+use crate::element::capture::{self, CaptureEvent, CaptureOptions};
 use crate::element::{ElementLibrary, UIElement};
 use crate::storage::{self, entities::element_library};
 use sea_orm::{ActiveModelTrait, EntityTrait, Set, QueryOrder};
 use serde::{Deserialize, Serialize};
-use tauri::command;
+use tauri::{command, AppHandle, Emitter};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ElementLibraryInfo {
@@ -44,7 +48,14 @@ pub async fn capture_element(x: i32, y: i32) -> Result<UIElement, String> {
 
     #[cfg(target_os = "linux")]
     {
-        Err("Element capture not implemented for Linux yet".to_string())
+        use crate::automation::desktop::linux::LinuxAutomation;
+        use crate::automation::desktop::DesktopAutomation;
+
+        let automation = LinuxAutomation::new();
+        automation
+            .capture_element(x, y)
+            .await
+            .map_err(|e| format!("Failed to capture element: {}", e))
     }
 
     #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
@@ -53,17 +64,33 @@ pub async fn capture_element(x: i32, y: i32) -> Result<UIElement, String> {
     }
 }
 
+/// Enter interactive capture mode
+///
+/// Emits `element-capture-hover` with the element under the resting pointer,
+/// `element-captured` with each element captured by a hotkey and
+/// `element-capture-stopped` when Esc or `stop_element_capture` ends the mode.
 #[command]
-pub async fn start_element_capture() -> Result<(), String> {
+pub async fn start_element_capture(app: AppHandle, options: Option<CaptureOptions>) -> Result<(), String> {
     log::info!("Starting element capture mode");
-    // TODO: Enter element capture mode (global hotkey, overlay window)
-    Ok(())
+
+    capture::start(options.unwrap_or_default(), move |event| {
+        let result = match event {
+            CaptureEvent::Hovered(element) => app.emit("element-capture-hover", element),
+            CaptureEvent::Captured(element) => app.emit("element-captured", element),
+            CaptureEvent::Stopped => app.emit("element-capture-stopped", ()),
+        };
+        if let Err(e) = result {
+            log::warn!("Failed to emit capture event: {}", e);
+        }
+    })
 }
 
 #[command]
 pub async fn stop_element_capture() -> Result<(), String> {
     log::info!("Stopping element capture mode");
-    // TODO: Exit element capture mode
+    if !capture::stop() {
+        log::debug!("Element capture was not active");
+    }
     Ok(())
 }
 
@@ -75,7 +102,7 @@ pub async fn save_element_library(library: ElementLibrary) -> Result<String, Str
         .await
         .map_err(|e| format!("Database error: {}", e))?;
 
-    let now = chrono::Utc::now().to_rfc3339();
+    let now = chrono::Utc::now();
 
     // Serialize elements to JSON
     let elements_json = serde_json::to_string(&library.elements)
@@ -109,7 +136,7 @@ pub async fn save_element_library(library: ElementLibrary) -> Result<String, Str
             name: Set(library.name),
             description: Set(library.description),
             elements: Set(elements_json),
-            created_at: Set(now.clone()),
+            created_at: Set(now),
             updated_at: Set(now),
         };
 
@@ -146,8 +173,8 @@ pub async fn load_element_library(id: String) -> Result<ElementLibrary, String>
         name: model.name,
         description: model.description,
         elements,
-        created_at: model.created_at,
-        updated_at: model.updated_at,
+        created_at: model.created_at.to_rfc3339(),
+        updated_at: model.updated_at.to_rfc3339(),
     })
 }
 
@@ -177,8 +204,8 @@ pub async fn list_element_libraries() -> Result<Vec<ElementLibraryInfo>, String>
                 name: m.name,
                 description: m.description,
                 element_count,
-                created_at: m.created_at,
-                updated_at: m.updated_at,
+                created_at: m.created_at.to_rfc3339(),
+                updated_at: m.updated_at.to_rfc3339(),
             }
         })
         .collect();
@@ -214,3 +241,152 @@ pub async fn highlight_element(element: UIElement) -> Result<(), String> {
         .await
         .map_err(|e| format!("Failed to highlight element: {}", e))
 }
+
+#[command]
+pub async fn find_image(image: String, options: Option<MatchOptions>) -> Result<Vec<ImageMatch>, String> {
+    use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+
+    let encoded = image
+        .split_once("base64,")
+        .map(|(_, data)| data)
+        .unwrap_or(&image);
+    let template = BASE64
+        .decode(encoded.trim())
+        .map_err(|e| format!("Invalid image data: {}", e))?;
+
+    let options = options.unwrap_or_default();
+    let automation = crate::automation::desktop::create_automation();
+    let matches = vision::locate_on_screen(automation.as_ref(), &template, &options)
+        .await
+        .map_err(|e| format!("Failed to find image: {}", e))?;
+
+    log::info!("Image found {} time(s) on screen", matches.len());
+    Ok(matches)
+}
+
+/// Levels of the accessibility tree returned when no depth is given
+const DEFAULT_TREE_DEPTH: usize = 8;
+
+/// Element hierarchy of a window or application, for the element inspector
+///
+/// `window` matches a window title or application name; the active window is
+/// inspected when it is omitted.
+#[command]
+pub async fn get_accessibility_tree(window: Option<String>, depth: Option<usize>) -> Result<ElementTreeNode, String> {
+    let automation = crate::automation::desktop::create_automation();
+    let tree = automation
+        .get_element_tree(window.as_deref(), depth.unwrap_or(DEFAULT_TREE_DEPTH))
+        .await
+        .map_err(|e| format!("Failed to get accessibility tree: {}", e))?;
+
+    log::info!("Accessibility tree with {} element(s)", tree.count());
+    Ok(tree)
+}
+
+/// Validate that a library element still resolves to exactly one element
+///
+/// Desktop elements are checked against the live desktop. When `url` is given
+/// the element is checked in a temporary browser session on that page instead.
+#[command]
+pub async fn validate_element(
+    library_id: String,
+    element_id: String,
+    url: Option<String>,
+    highlight: Option<bool>,
+) -> Result<ElementValidation, String> {
+    let library = load_element_library(library_id.clone()).await?;
+    let element = library
+        .find_element(&element_id)
+        .cloned()
+        .ok_or_else(|| format!("Element '{}' not in library '{}'", element_id, library_id))?;
+
+    let mut results = run_validation(std::slice::from_ref(&element), url.as_deref()).await?;
+    let result = results.remove(0);
+
+    if highlight.unwrap_or(true) && url.is_none() {
+        highlight_matches(&result);
+    }
+
+    Ok(result)
+}
+
+/// Validate every element of a library and report broken or ambiguous ones
+#[command]
+pub async fn validate_element_library(
+    library_id: String,
+    url: Option<String>,
+    highlight: Option<bool>,
+) -> Result<LibraryValidationReport, String> {
+    let started = std::time::Instant::now();
+    let library = load_element_library(library_id).await?;
+
+    let results = run_validation(&library.elements, url.as_deref()).await?;
+
+    if highlight.unwrap_or(false) && url.is_none() {
+        for result in &results {
+            highlight_matches(result);
+        }
+    }
+
+    let report = LibraryValidationReport::new(&library, results, started.elapsed().as_millis() as u64);
+    log::info!(
+        "Validated library '{}': {} unique, {} fallback, {} ambiguous, {} not found",
+        report.library_name,
+        report.unique,
+        report.fallback,
+        report.ambiguous,
+        report.not_found
+    );
+    Ok(report)
+}
+
+async fn run_validation(elements: &[UIElement], url: Option<&str>) -> Result<Vec<ElementValidation>, String> {
+    let Some(url) = url else {
+        let automation = crate::automation::desktop::create_automation();
+        let mut results = Vec::with_capacity(elements.len());
+        for element in elements {
+            results.push(validation::validate_desktop(automation.as_ref(), element).await);
+        }
+        return Ok(results);
+    };
+
+    use crate::automation::web::{BrowserOptions, WebAutomation};
+
+    let web = WebAutomation::new();
+    let session_id = format!("validate-{}", uuid::Uuid::new_v4());
+    web.open_browser(&session_id, BrowserOptions::default())
+        .await
+        .map_err(|e| format!("Failed to open browser: {}", e))?;
+
+    let mut results = Vec::with_capacity(elements.len());
+    let navigated = web.navigate(&session_id, url).await;
+    if navigated.is_ok() {
+        for element in elements {
+            results.push(validation::validate_web(&web, &session_id, element).await);
+        }
+    }
+
+    let _ = web.close(&session_id).await;
+    navigated.map_err(|e| format!("Failed to open '{}': {}", url, e))?;
+    Ok(results)
+}
+
+/// Flash every match of a validated element, without waiting for the overlays
+fn highlight_matches(result: &ElementValidation) {
+    use crate::automation::highlight::{self, HighlightConfig};
+
+    // Green for a unique match, red when the locator is ambiguous
+    let config = HighlightConfig {
+        color: if result.is_ok() { (0, 200, 0) } else { (255, 0, 0) },
+        ..Default::default()
+    };
+
+    for bounds in result.matches.clone() {
+        let config = config.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = highlight::highlight_element(bounds, Some(config)).await {
+                log::warn!("Failed to highlight match: {}", e);
+            }
+        });
+    }
+}
@@ -3,9 +3,13 @@ pub mod element;
 pub mod execution;
 pub mod recorder;
 pub mod settings;
+pub mod bundle;
+pub mod backup;
 
 pub use workflow::*;
 pub use element::*;
 pub use execution::*;
 pub use recorder::*;
 pub use settings::*;
+pub use bundle::*;
+pub use backup::*;
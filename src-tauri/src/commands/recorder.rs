@@ -1,5 +1,14 @@
-use crate::recorder::{convert_to_workflow, Recorder, RecordingSession, RecordingState};
+use crate::automation::desktop::create_automation;
+use crate::commands::element::{load_element_library, save_element_library};
+use crate::element::ElementLibrary;
+use crate::recorder::{
+    convert_to_workflow, replay, ConversionOptions, Recorder, RecordingSession, RecordingSource, RecordingState,
+    ReplaySummary,
+};
 use crate::engine::executor::Workflow;
+use crate::storage::{self, entities::recording::{self, RecordingInfo}};
+use chrono::{DateTime, Utc};
+use sea_orm::{ActiveModelTrait, EntityTrait, QueryOrder, Set};
 use std::sync::Arc;
 use tauri::State;
 use tokio::sync::RwLock;
@@ -21,6 +30,7 @@ pub struct RecordingStatus {
     state: RecordingState,
     action_count: usize,
     duration_ms: u64,
+    text_capture_paused: bool,
 }
 
 #[tauri::command]
@@ -32,6 +42,17 @@ pub async fn start_recording(
     recorder.start(name).await
 }
 
+/// Start recording in a new browser window, optionally opening `url`
+#[tauri::command]
+pub async fn start_web_recording(
+    name: Option<String>,
+    url: Option<String>,
+    state: State<'_, RecorderState>,
+) -> Result<(), String> {
+    let recorder = state.recorder.read().await;
+    recorder.start_web(name, url).await
+}
+
 #[tauri::command]
 pub async fn pause_recording(state: State<'_, RecorderState>) -> Result<(), String> {
     let recorder = state.recorder.read().await;
@@ -76,6 +97,7 @@ pub async fn get_recording_state(
         state: session.state,
         action_count: session.actions.len(),
         duration_ms,
+        text_capture_paused: recorder.text_capture_paused(),
     })
 }
 
@@ -89,6 +111,7 @@ pub async fn get_recording_session(
 
 #[tauri::command]
 pub async fn convert_recording_to_workflow(
+    options: Option<ConversionOptions>,
     state: State<'_, RecorderState>,
 ) -> Result<Workflow, String> {
     let recorder = state.recorder.read().await;
@@ -98,7 +121,21 @@ pub async fn convert_recording_to_workflow(
         return Err("No actions recorded".to_string());
     }
 
-    Ok(convert_to_workflow(&session))
+    let options = options.unwrap_or_default();
+    let mut library = match &options.library_id {
+        Some(id) => load_element_library(id.clone()).await?,
+        None => ElementLibrary::new(options.library_name.clone().unwrap_or_else(|| session.name.clone())),
+    };
+    let element_count = library.elements.len();
+
+    let workflow = convert_to_workflow(&session, &options, &mut library);
+
+    // Nodes reference the captured elements by library id
+    if library.elements.len() > element_count {
+        save_element_library(library).await?;
+    }
+
+    Ok(workflow)
 }
 
 #[tauri::command]
@@ -107,3 +144,182 @@ pub async fn clear_recording(state: State<'_, RecorderState>) -> Result<(), Stri
     *recorder = Recorder::new();
     Ok(())
 }
+
+/// Save the current recording, returning its id
+#[tauri::command]
+pub async fn save_recording(
+    name: Option<String>,
+    state: State<'_, RecorderState>,
+) -> Result<String, String> {
+    let recorder = state.recorder.read().await;
+    let mut session = recorder.get_session().await;
+
+    if session.state != RecordingState::Idle {
+        return Err("Stop the recording before saving it".to_string());
+    }
+    if session.actions.is_empty() {
+        return Err("No actions recorded".to_string());
+    }
+    if let Some(name) = name {
+        session.name = name;
+    }
+
+    log::info!("Saving recording: {}", session.name);
+
+    let db = storage::get_connection()
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+
+    let actions_json = serde_json::to_string(&session.actions)
+        .map_err(|e| format!("Failed to serialize actions: {}", e))?;
+    let source = match session.source {
+        RecordingSource::Desktop => "Desktop",
+        RecordingSource::Web => "Web",
+    };
+
+    let existing = recording::Entity::find_by_id(&session.id)
+        .one(&db)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+
+    let active_model = recording::ActiveModel {
+        id: Set(session.id.clone()),
+        name: Set(session.name.clone()),
+        source: Set(source.to_string()),
+        actions: Set(actions_json),
+        started_at: Set(parse_timestamp(session.started_at.as_deref())),
+        ended_at: Set(parse_timestamp(session.ended_at.as_deref())),
+        created_at: Set(existing.as_ref().map(|m| m.created_at).unwrap_or_else(Utc::now)),
+    };
+
+    if existing.is_some() {
+        active_model
+            .update(&db)
+            .await
+            .map_err(|e| format!("Failed to update recording: {}", e))?;
+    } else {
+        active_model
+            .insert(&db)
+            .await
+            .map_err(|e| format!("Failed to save recording: {}", e))?;
+    }
+
+    // Keep a renamed session in sync with what was saved
+    recorder.load(session.clone()).await?;
+
+    log::info!("Recording saved: {}", session.id);
+    Ok(session.id)
+}
+
+#[tauri::command]
+pub async fn list_recordings() -> Result<Vec<RecordingInfo>, String> {
+    let db = storage::get_connection()
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+
+    let models = recording::Entity::find()
+        .order_by_desc(recording::Column::CreatedAt)
+        .all(&db)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+
+    Ok(models.into_iter().map(RecordingInfo::from).collect())
+}
+
+/// Load a saved recording and make it the current session
+#[tauri::command]
+pub async fn load_recording(
+    id: String,
+    state: State<'_, RecorderState>,
+) -> Result<RecordingSession, String> {
+    log::info!("Loading recording: {}", id);
+
+    let session = read_recording(&id).await?;
+    let recorder = state.recorder.read().await;
+    recorder.load(session.clone()).await?;
+
+    Ok(session)
+}
+
+#[tauri::command]
+pub async fn delete_recording(id: String) -> Result<(), String> {
+    log::info!("Deleting recording: {}", id);
+
+    let db = storage::get_connection()
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+
+    recording::Entity::delete_by_id(&id)
+        .exec(&db)
+        .await
+        .map_err(|e| format!("Failed to delete recording: {}", e))?;
+
+    Ok(())
+}
+
+/// Play a recording back with its original timing, scaled by `speed`
+///
+/// Replays the saved recording `id`, or the current session when it is not given.
+#[tauri::command]
+pub async fn replay_recording(
+    id: Option<String>,
+    speed: Option<f64>,
+    state: State<'_, RecorderState>,
+) -> Result<ReplaySummary, String> {
+    let recorder = state.recorder.read().await;
+
+    // The recorder would capture its own replay
+    if recorder.get_state().await != RecordingState::Idle {
+        return Err("Recording in progress".to_string());
+    }
+
+    let session = match id {
+        Some(id) => read_recording(&id).await?,
+        None => recorder.get_session().await,
+    };
+    drop(recorder);
+
+    if session.actions.is_empty() {
+        return Err("No actions recorded".to_string());
+    }
+
+    let automation = create_automation();
+    replay(&session, speed.unwrap_or(1.0), automation.as_ref())
+        .await
+        .map_err(|e| format!("Replay failed: {}", e))
+}
+
+async fn read_recording(id: &str) -> Result<RecordingSession, String> {
+    let db = storage::get_connection()
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+
+    let model = recording::Entity::find_by_id(id)
+        .one(&db)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or_else(|| "Recording not found".to_string())?;
+
+    let actions = serde_json::from_str(&model.actions)
+        .map_err(|e| format!("Failed to parse actions: {}", e))?;
+    let source = match model.source.as_str() {
+        "Web" => RecordingSource::Web,
+        _ => RecordingSource::Desktop,
+    };
+
+    Ok(RecordingSession {
+        id: model.id,
+        name: model.name,
+        state: RecordingState::Idle,
+        source,
+        actions,
+        started_at: model.started_at.map(|t| t.to_rfc3339()),
+        ended_at: model.ended_at.map(|t| t.to_rfc3339()),
+    })
+}
+
+fn parse_timestamp(value: Option<&str>) -> Option<DateTime<Utc>> {
+    value
+        .and_then(|v| DateTime::parse_from_rfc3339(v).ok())
+        .map(|t| t.with_timezone(&Utc))
+}
@@ -4,8 +4,10 @@ use crate::storage::{
     DatabaseConfig, DatabaseType,
     get_database_config, update_database_config, test_database_connection,
     init_database_with_config,
+    transfer::{self, TransferReport},
 };
 use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter};
 
 /// Database configuration for frontend
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -111,6 +113,51 @@ pub async fn apply_db_config(config: DatabaseConfigDto) -> Result<String, String
     Ok("Database configuration applied successfully".to_string())
 }
 
+/// Copy all data from one database to another, then switch to the target
+///
+/// Progress is emitted as `database-migration-progress` events. The application
+/// configuration only changes after every table has been copied and verified.
+#[tauri::command]
+pub async fn migrate_database(
+    from: DatabaseConfigDto,
+    to: DatabaseConfigDto,
+    batch_size: Option<u64>,
+    app: AppHandle,
+) -> Result<TransferReport, String> {
+    let from_config: DatabaseConfig = from.into();
+    let to_config: DatabaseConfig = to.into();
+
+    log::info!(
+        "Migrating data from {:?} to {:?}",
+        from_config.db_type,
+        to_config.db_type
+    );
+
+    let report = transfer::transfer_data(
+        &from_config,
+        &to_config,
+        batch_size.unwrap_or(transfer::DEFAULT_BATCH_SIZE),
+        |progress| {
+            if let Err(e) = app.emit("database-migration-progress", progress) {
+                log::warn!("Failed to emit migration progress: {}", e);
+            }
+        },
+    )
+    .await
+    .map_err(|e| format!("Database migration failed: {}", e))?;
+
+    init_database_with_config(to_config)
+        .await
+        .map_err(|e| format!("Data copied but failed to switch database: {}", e))?;
+
+    log::info!(
+        "Database migration completed: {} rows in {} ms",
+        report.total_rows,
+        report.duration_ms
+    );
+    Ok(report)
+}
+
 /// Get supported database types
 #[tauri::command]
 pub fn get_supported_db_types() -> Vec<DatabaseTypeInfo> {
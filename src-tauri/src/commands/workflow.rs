@@ -23,7 +23,7 @@ pub async fn save_workflow(workflow: Workflow) -> Result<String, String> {
         .await
         .map_err(|e| format!("Database error: {}", e))?;
 
-    let now = chrono::Utc::now().to_rfc3339();
+    let now = chrono::Utc::now();
 
     // Serialize nodes, edges to JSON
     let nodes_json = serde_json::to_string(&workflow.nodes)
@@ -63,7 +63,7 @@ pub async fn save_workflow(workflow: Workflow) -> Result<String, String> {
             nodes: Set(nodes_json),
             edges: Set(edges_json),
             variables: Set("[]".to_string()),
-            created_at: Set(now.clone()),
+            created_at: Set(now),
             updated_at: Set(now),
         };
 
@@ -131,8 +131,8 @@ pub async fn list_workflows() -> Result<Vec<WorkflowInfo>, String> {
                 name: m.name,
                 description: m.description,
                 node_count,
-                created_at: m.created_at,
-                updated_at: m.updated_at,
+                created_at: m.created_at.to_rfc3339(),
+                updated_at: m.updated_at.to_rfc3339(),
             }
         })
         .collect();
@@ -0,0 +1,585 @@
+//! Interactive element capture
+//!
+//! While capture mode is active, the element under the pointer is hit-tested
+//! and highlighted whenever the pointer comes to rest. Pressing a capture
+//! hotkey (Ctrl+click or F2 by default) captures that element along with a
+//! screenshot of it, and Esc leaves capture mode. Input is polled rather than
+//! hooked, so the click of a Ctrl+click still reaches the application below.
+
+use super::UIElement;
+use crate::automation::desktop;
+use crate::automation::highlight::{self, HighlightConfig};
+use crate::automation::Rect;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+const POLL_INTERVAL_MS: u64 = 50;
+
+/// Time the pointer has to rest before the element below it is hit-tested
+const HOVER_DELAY_MS: u64 = 120;
+
+/// Lifetime of a hover highlight; it is renewed while the pointer stays
+const HOVER_HIGHLIGHT_MS: u64 = 1000;
+
+const CAPTURED_HIGHLIGHT_MS: u64 = 600;
+
+/// Stop flag of the active capture session
+static ACTIVE: Mutex<Option<Arc<AtomicBool>>> = Mutex::new(None);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CaptureOptions {
+    /// Hotkeys capturing the hovered element, e.g. "Ctrl+Click" or "F2"
+    pub hotkeys: Vec<String>,
+    /// Attach a screenshot of the element
+    pub screenshot: bool,
+}
+
+impl Default for CaptureOptions {
+    fn default() -> Self {
+        Self {
+            hotkeys: vec!["Ctrl+Click".to_string(), "F2".to_string()],
+            screenshot: true,
+        }
+    }
+}
+
+/// Something that happened during capture mode
+#[derive(Debug, Clone)]
+pub enum CaptureEvent {
+    /// The pointer came to rest over a different element
+    Hovered(UIElement),
+    /// A hotkey captured the hovered element
+    Captured(UIElement),
+    /// Capture mode ended, by Esc or `stop`
+    Stopped,
+}
+
+/// Key completing a capture hotkey
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Trigger {
+    Click,
+    /// F1 to F12
+    Function(u8),
+}
+
+/// Capture hotkey such as "Ctrl+Click" or "Shift+F2"
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Hotkey {
+    pub ctrl: bool,
+    pub shift: bool,
+    pub alt: bool,
+    pub trigger: Trigger,
+}
+
+impl Hotkey {
+    pub fn parse(hotkey: &str) -> Result<Self, String> {
+        let mut parsed = Hotkey {
+            ctrl: false,
+            shift: false,
+            alt: false,
+            trigger: Trigger::Click,
+        };
+        let mut trigger = None;
+
+        for part in hotkey.split('+').map(|p| p.trim().to_lowercase()) {
+            match part.as_str() {
+                "ctrl" | "control" => parsed.ctrl = true,
+                "shift" => parsed.shift = true,
+                "alt" | "option" => parsed.alt = true,
+                "click" => trigger = Some(Trigger::Click),
+                _ => {
+                    let number = part
+                        .strip_prefix('f')
+                        .and_then(|n| n.parse::<u8>().ok())
+                        .filter(|n| (1..=12).contains(n))
+                        .ok_or_else(|| format!("Unsupported capture hotkey '{}'", hotkey))?;
+                    trigger = Some(Trigger::Function(number));
+                }
+            }
+        }
+
+        parsed.trigger = trigger.ok_or_else(|| format!("Capture hotkey '{}' needs Click or a function key", hotkey))?;
+        Ok(parsed)
+    }
+
+    fn is_pressed(&self, input: &InputState) -> bool {
+        let modifiers = (!self.ctrl || input.ctrl) && (!self.shift || input.shift) && (!self.alt || input.alt);
+        let trigger = match self.trigger {
+            Trigger::Click => input.left_button,
+            Trigger::Function(n) => input.function_keys & (1 << (n - 1)) != 0,
+        };
+        modifiers && trigger
+    }
+}
+
+/// Pointer and key state relevant to capture mode
+#[derive(Debug, Clone, Default)]
+struct InputState {
+    x: i32,
+    y: i32,
+    ctrl: bool,
+    shift: bool,
+    alt: bool,
+    left_button: bool,
+    escape: bool,
+    /// Bit n is set while F(n+1) is held
+    function_keys: u16,
+}
+
+/// Enter capture mode; events are reported to `on_event` until it ends
+pub fn start<F>(options: CaptureOptions, on_event: F) -> Result<(), String>
+where
+    F: Fn(CaptureEvent) + Send + Sync + 'static,
+{
+    let hotkeys = options
+        .hotkeys
+        .iter()
+        .map(|hotkey| Hotkey::parse(hotkey))
+        .collect::<Result<Vec<_>, _>>()?;
+    if hotkeys.is_empty() {
+        return Err("At least one capture hotkey is required".to_string());
+    }
+
+    let mut active = ACTIVE.lock().unwrap_or_else(|e| e.into_inner());
+    if active.is_some() {
+        return Err("Element capture is already active".to_string());
+    }
+    let probe = InputProbe::new()?;
+
+    let stop = Arc::new(AtomicBool::new(false));
+    *active = Some(stop.clone());
+
+    tauri::async_runtime::spawn(async move {
+        run(probe, &hotkeys, options.screenshot, &stop, &on_event).await;
+
+        let mut active = ACTIVE.lock().unwrap_or_else(|e| e.into_inner());
+        if active.as_ref().is_some_and(|flag| Arc::ptr_eq(flag, &stop)) {
+            *active = None;
+        }
+        drop(active);
+        on_event(CaptureEvent::Stopped);
+    });
+
+    log::info!("Element capture started");
+    Ok(())
+}
+
+/// Leave capture mode; returns false if it was not active
+pub fn stop() -> bool {
+    match ACTIVE.lock().unwrap_or_else(|e| e.into_inner()).take() {
+        Some(flag) => {
+            flag.store(true, Ordering::SeqCst);
+            true
+        }
+        None => false,
+    }
+}
+
+async fn run(
+    mut probe: InputProbe,
+    hotkeys: &[Hotkey],
+    screenshot: bool,
+    stop: &AtomicBool,
+    on_event: &(dyn Fn(CaptureEvent) + Send + Sync),
+) {
+    let automation = desktop::create_automation();
+    let mut interval = tokio::time::interval(Duration::from_millis(POLL_INTERVAL_MS));
+
+    let mut position = None;
+    let mut moved_at = Instant::now();
+    let mut hit_tested = false;
+    let mut was_pressed = false;
+    let mut hovered: Option<UIElement> = None;
+    let mut highlight: Option<(tauri::async_runtime::JoinHandle<()>, Instant)> = None;
+
+    while !stop.load(Ordering::SeqCst) {
+        interval.tick().await;
+
+        let input = match probe.poll() {
+            Ok(input) => input,
+            Err(e) => {
+                log::warn!("Element capture stopped, cannot read input: {}", e);
+                break;
+            }
+        };
+        if input.escape {
+            break;
+        }
+
+        let pressed = hotkeys.iter().any(|hotkey| hotkey.is_pressed(&input));
+        if pressed && !was_pressed {
+            // Prefer the element as it was before the click changed anything
+            let element = match hovered.clone().filter(|e| contains(&e.bounds, input.x, input.y)) {
+                Some(element) => Ok(element),
+                None => automation.capture_element(input.x, input.y).await,
+            };
+            match element {
+                Ok(mut element) => {
+                    if screenshot {
+                        match automation.screenshot(Some(element.bounds.clone())).await {
+                            Ok(png) => element.screenshot = Some(png),
+                            Err(e) => log::warn!("Failed to take element screenshot: {}", e),
+                        }
+                    }
+                    show_highlight(&element.bounds, (0, 200, 0), CAPTURED_HIGHLIGHT_MS);
+                    log::info!("Captured element '{}' ({})", element.name, element.control_type);
+                    on_event(CaptureEvent::Captured(element));
+                }
+                Err(e) => log::warn!("Failed to capture element at ({}, {}): {}", input.x, input.y, e),
+            }
+        }
+        was_pressed = pressed;
+
+        if position != Some((input.x, input.y)) {
+            position = Some((input.x, input.y));
+            moved_at = Instant::now();
+            hit_tested = false;
+            continue;
+        }
+
+        if !hit_tested && moved_at.elapsed() >= Duration::from_millis(HOVER_DELAY_MS) {
+            hit_tested = true;
+            if let Ok(element) = automation.capture_element(input.x, input.y).await {
+                let changed = hovered.as_ref().is_none_or(|h| !same_rect(&h.bounds, &element.bounds));
+                if changed {
+                    if let Some((handle, _)) = highlight.take() {
+                        handle.abort();
+                    }
+                    on_event(CaptureEvent::Hovered(element.clone()));
+                    hovered = Some(element);
+                }
+            }
+        }
+
+        // Keep the hovered element highlighted
+        let expired = highlight
+            .as_ref()
+            .is_none_or(|(_, shown)| shown.elapsed() >= Duration::from_millis(HOVER_HIGHLIGHT_MS));
+        if let (Some(element), true) = (&hovered, expired) {
+            let handle = show_highlight(&element.bounds, (30, 144, 255), HOVER_HIGHLIGHT_MS);
+            highlight = Some((handle, Instant::now()));
+        }
+    }
+
+    if let Some((handle, _)) = highlight {
+        handle.abort();
+    }
+    log::info!("Element capture stopped");
+}
+
+fn show_highlight(bounds: &Rect, color: (u8, u8, u8), duration_ms: u64) -> tauri::async_runtime::JoinHandle<()> {
+    let config = HighlightConfig {
+        color,
+        duration_ms,
+        ..Default::default()
+    };
+    let bounds = bounds.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = highlight::highlight_element(bounds, Some(config)).await {
+            log::debug!("Failed to highlight hovered element: {}", e);
+        }
+    })
+}
+
+fn contains(rect: &Rect, x: i32, y: i32) -> bool {
+    x >= rect.x && x < rect.x + rect.width && y >= rect.y && y < rect.y + rect.height
+}
+
+fn same_rect(a: &Rect, b: &Rect) -> bool {
+    a.x == b.x && a.y == b.y && a.width == b.width && a.height == b.height
+}
+
+/// Watched keys and the left button, as bits of [`InputProbe`] state
+#[cfg(target_os = "linux")]
+mod probe_bits {
+    pub const ESCAPE: u32 = 1;
+    /// F1 is this bit, F2 to F12 follow
+    pub const F1: u32 = 1 << 1;
+    pub const CTRL: u32 = 1 << 13;
+    pub const SHIFT: u32 = 1 << 14;
+    pub const ALT: u32 = 1 << 15;
+    pub const LEFT_BUTTON: u32 = 1 << 16;
+}
+
+/// Input read from XInput2 raw events, which report every key and button of
+/// the master devices whatever window has the focus
+///
+/// Presses are remembered until the next poll, so a key tapped between two
+/// polls is still seen.
+#[cfg(target_os = "linux")]
+struct InputProbe {
+    conn: x11rb::rust_connection::RustConnection,
+    root: x11rb::protocol::xproto::Window,
+    /// Bit of every keycode producing a watched key
+    keys: std::collections::HashMap<x11rb::protocol::xproto::Keycode, u32>,
+    held: u32,
+    pressed: u32,
+}
+
+#[cfg(target_os = "linux")]
+impl InputProbe {
+    fn new() -> Result<Self, String> {
+        use x11rb::connection::Connection;
+        use x11rb::protocol::xinput::{self, ConnectionExt as _, Device, XIEventMask};
+
+        let (conn, screen) = x11rb::connect(None).map_err(|e| format!("Failed to connect to X display: {}", e))?;
+        let root = conn.setup().roots[screen].root;
+
+        let version = conn
+            .xinput_xi_query_version(2, 2)
+            .map_err(|e| format!("XInput2 query failed: {}", e))?
+            .reply()
+            .map_err(|e| format!("XInput2 is not available: {}", e))?;
+        if version.major_version < 2 {
+            return Err(format!(
+                "XInput {}.{} found, 2.0 or later is required",
+                version.major_version, version.minor_version
+            ));
+        }
+        let mask = XIEventMask::RAW_KEY_PRESS
+            | XIEventMask::RAW_KEY_RELEASE
+            | XIEventMask::RAW_BUTTON_PRESS
+            | XIEventMask::RAW_BUTTON_RELEASE;
+        conn.xinput_xi_select_events(
+            root,
+            &[xinput::EventMask {
+                deviceid: Device::ALL_MASTER.into(),
+                mask: vec![mask],
+            }],
+        )
+        .map_err(|e| format!("Failed to select XInput2 events: {}", e))?;
+        conn.flush().map_err(|e| format!("Failed to select XInput2 events: {}", e))?;
+
+        let keys = Self::watched_keys(&conn)?;
+        Ok(Self {
+            conn,
+            root,
+            keys,
+            held: 0,
+            pressed: 0,
+        })
+    }
+
+    /// Bits of the keycodes producing Escape, F1 to F12 and the modifiers
+    fn watched_keys(
+        conn: &x11rb::rust_connection::RustConnection,
+    ) -> Result<std::collections::HashMap<x11rb::protocol::xproto::Keycode, u32>, String> {
+        use crate::automation::desktop::x11::{keysym_modifier, XK_ESCAPE};
+        use x11rb::connection::Connection;
+        use x11rb::protocol::xproto::ConnectionExt as _;
+
+        let setup = conn.setup();
+        let (min_keycode, max_keycode) = (setup.min_keycode, setup.max_keycode);
+        let reply = conn
+            .get_keyboard_mapping(min_keycode, max_keycode - min_keycode + 1)
+            .map_err(|e| format!("Failed to read keyboard mapping: {}", e))?
+            .reply()
+            .map_err(|e| format!("Failed to read keyboard mapping: {}", e))?;
+
+        let per_keycode = (reply.keysyms_per_keycode as usize).max(1);
+        Ok(reply
+            .keysyms
+            .chunks(per_keycode)
+            .enumerate()
+            .filter_map(|(i, row)| {
+                let bit = match row[0] {
+                    XK_ESCAPE => probe_bits::ESCAPE,
+                    keysym @ 0xffbe..=0xffc9 => probe_bits::F1 << (keysym - 0xffbe),
+                    keysym => match keysym_modifier(keysym)? {
+                        "Ctrl" => probe_bits::CTRL,
+                        "Shift" => probe_bits::SHIFT,
+                        "Alt" => probe_bits::ALT,
+                        _ => return None,
+                    },
+                };
+                Some((min_keycode + i as u8, bit))
+            })
+            .collect())
+    }
+
+    fn poll(&mut self) -> Result<InputState, String> {
+        use x11rb::connection::Connection;
+        use x11rb::protocol::xproto::{ConnectionExt as _, KeyButMask};
+        use x11rb::protocol::Event;
+
+        while let Some(event) = self.conn.poll_for_event().map_err(|e| e.to_string())? {
+            let (bit, down) = match event {
+                Event::XinputRawKeyPress(event) => (self.keys.get(&(event.detail as u8)).copied(), true),
+                Event::XinputRawKeyRelease(event) => (self.keys.get(&(event.detail as u8)).copied(), false),
+                Event::XinputRawButtonPress(event) if event.detail == 1 => (Some(probe_bits::LEFT_BUTTON), true),
+                Event::XinputRawButtonRelease(event) if event.detail == 1 => (Some(probe_bits::LEFT_BUTTON), false),
+                Event::MappingNotify(_) => {
+                    self.keys = Self::watched_keys(&self.conn)?;
+                    (None, false)
+                }
+                _ => (None, false),
+            };
+            match (bit, down) {
+                (Some(bit), true) => {
+                    self.held |= bit;
+                    self.pressed |= bit;
+                }
+                (Some(bit), false) => self.held &= !bit,
+                (None, _) => {}
+            }
+        }
+
+        let pointer = self
+            .conn
+            .query_pointer(self.root)
+            .map_err(|e| e.to_string())?
+            .reply()
+            .map_err(|e| e.to_string())?;
+        // Modifiers held since before capture started only show in the pointer mask
+        let down = self.held | std::mem::take(&mut self.pressed);
+        let is_down = |bit: u32, mask: KeyButMask| down & bit != 0 || pointer.mask.contains(mask);
+
+        Ok(InputState {
+            x: pointer.root_x as i32,
+            y: pointer.root_y as i32,
+            ctrl: is_down(probe_bits::CTRL, KeyButMask::CONTROL),
+            shift: is_down(probe_bits::SHIFT, KeyButMask::SHIFT),
+            alt: is_down(probe_bits::ALT, KeyButMask::MOD1),
+            left_button: is_down(probe_bits::LEFT_BUTTON, KeyButMask::BUTTON1),
+            escape: down & probe_bits::ESCAPE != 0,
+            function_keys: ((down >> 1) & 0xfff) as u16,
+        })
+    }
+}
+
+#[cfg(target_os = "windows")]
+struct InputProbe;
+
+#[cfg(target_os = "windows")]
+impl InputProbe {
+    fn new() -> Result<Self, String> {
+        Ok(Self)
+    }
+
+    fn poll(&mut self) -> Result<InputState, String> {
+        use windows::Win32::Foundation::POINT;
+        use windows::Win32::UI::Input::KeyboardAndMouse::GetAsyncKeyState;
+        use windows::Win32::UI::WindowsAndMessaging::GetCursorPos;
+
+        const VK_LBUTTON: i32 = 0x01;
+        const VK_SHIFT: i32 = 0x10;
+        const VK_CONTROL: i32 = 0x11;
+        const VK_MENU: i32 = 0x12;
+        const VK_ESCAPE: i32 = 0x1B;
+        const VK_F1: i32 = 0x70;
+
+        let down = |key: i32| unsafe { GetAsyncKeyState(key) } as u16 & 0x8000 != 0;
+        let mut point = POINT::default();
+        unsafe { GetCursorPos(&mut point) }.map_err(|e| format!("Failed to get cursor position: {}", e))?;
+
+        Ok(InputState {
+            x: point.x,
+            y: point.y,
+            ctrl: down(VK_CONTROL),
+            shift: down(VK_SHIFT),
+            alt: down(VK_MENU),
+            left_button: down(VK_LBUTTON),
+            escape: down(VK_ESCAPE),
+            function_keys: (0..12).fold(0u16, |bits, n| if down(VK_F1 + n) { bits | 1 << n } else { bits }),
+        })
+    }
+}
+
+#[cfg(target_os = "macos")]
+struct InputProbe;
+
+#[cfg(target_os = "macos")]
+#[link(name = "ApplicationServices", kind = "framework")]
+extern "C" {
+    fn CGEventSourceFlagsState(state_id: i32) -> u64;
+    fn CGEventSourceKeyState(state_id: i32, key: u16) -> bool;
+    fn CGEventSourceButtonState(state_id: i32, button: u32) -> bool;
+}
+
+#[cfg(target_os = "macos")]
+impl InputProbe {
+    fn new() -> Result<Self, String> {
+        Ok(Self)
+    }
+
+    fn poll(&mut self) -> Result<InputState, String> {
+        use core_graphics::event::CGEvent;
+        use core_graphics::event_source::{CGEventSource, CGEventSourceStateID};
+
+        const COMBINED_SESSION_STATE: i32 = 0;
+        const FLAG_SHIFT: u64 = 0x0002_0000;
+        const FLAG_CONTROL: u64 = 0x0004_0000;
+        const FLAG_ALTERNATE: u64 = 0x0008_0000;
+        const KEY_ESCAPE: u16 = 53;
+        const KEY_F1_TO_F12: [u16; 12] = [122, 120, 99, 118, 96, 97, 98, 100, 101, 109, 103, 111];
+
+        let source = CGEventSource::new(CGEventSourceStateID::CombinedSessionState)
+            .map_err(|_| "Failed to create event source".to_string())?;
+        let location = CGEvent::new(source)
+            .map_err(|_| "Failed to read pointer position".to_string())?
+            .location();
+
+        let flags = unsafe { CGEventSourceFlagsState(COMBINED_SESSION_STATE) };
+        let key = |code: u16| unsafe { CGEventSourceKeyState(COMBINED_SESSION_STATE, code) };
+
+        Ok(InputState {
+            x: location.x as i32,
+            y: location.y as i32,
+            ctrl: flags & FLAG_CONTROL != 0,
+            shift: flags & FLAG_SHIFT != 0,
+            alt: flags & FLAG_ALTERNATE != 0,
+            left_button: unsafe { CGEventSourceButtonState(COMBINED_SESSION_STATE, 0) },
+            escape: key(KEY_ESCAPE),
+            function_keys: KEY_F1_TO_F12
+                .iter()
+                .enumerate()
+                .fold(0u16, |bits, (n, &code)| if key(code) { bits | 1 << n } else { bits }),
+        })
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "windows", target_os = "macos")))]
+struct InputProbe;
+
+#[cfg(not(any(target_os = "linux", target_os = "windows", target_os = "macos")))]
+impl InputProbe {
+    fn new() -> Result<Self, String> {
+        Err("Element capture not supported on this platform".to_string())
+    }
+
+    fn poll(&mut self) -> Result<InputState, String> {
+        Ok(InputState::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_hotkeys() {
+        let ctrl_click = Hotkey::parse("Ctrl+Click").unwrap();
+        assert!(ctrl_click.ctrl && !ctrl_click.shift);
+        assert_eq!(ctrl_click.trigger, Trigger::Click);
+
+        assert_eq!(Hotkey::parse(" f2 ").unwrap().trigger, Trigger::Function(2));
+        assert!(Hotkey::parse("Ctrl").is_err());
+        assert!(Hotkey::parse("F13").is_err());
+    }
+
+    #[test]
+    fn test_hotkey_requires_modifiers() {
+        let hotkey = Hotkey::parse("Ctrl+Click").unwrap();
+        let mut input = InputState {
+            left_button: true,
+            ..Default::default()
+        };
+        assert!(!hotkey.is_pressed(&input));
+
+        input.ctrl = true;
+        assert!(hotkey.is_pressed(&input));
+    }
+}
@@ -1,4 +1,10 @@
+pub mod capture;
+pub mod relative;
+pub mod resolver;
+pub mod validation;
+
 use crate::automation::Rect;
+use relative::RelativeLocator;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -87,19 +93,59 @@ impl ElementLibrary {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// A single way of locating an element
+///
+/// Serialized as `{ "strategy": "...", "value": ... }` to match the frontend
+/// type. The value is a string, except for `image` and `relative`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "strategy", content = "value", rename_all = "camelCase")]
 pub enum LocatorStrategy {
     AutomationId(String),
     Name(String),
     ClassName(String),
+    #[serde(rename = "xpath")]
     XPath(String),
+    #[serde(rename = "css")]
     CssSelector(String),
+    /// Encoded template image (PNG or JPEG), serialized as an array of bytes
     Image(Vec<u8>),
+    /// Position relative to an anchor element
+    Relative(Box<RelativeLocator>),
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl LocatorStrategy {
+    /// Short strategy name, as used in serialized locators
+    pub fn kind(&self) -> &'static str {
+        match self {
+            LocatorStrategy::AutomationId(_) => "automationId",
+            LocatorStrategy::Name(_) => "name",
+            LocatorStrategy::ClassName(_) => "className",
+            LocatorStrategy::XPath(_) => "xpath",
+            LocatorStrategy::CssSelector(_) => "css",
+            LocatorStrategy::Image(_) => "image",
+            LocatorStrategy::Relative(_) => "relative",
+        }
+    }
+
+    /// Human readable description for logs and errors
+    pub fn describe(&self) -> String {
+        match self {
+            LocatorStrategy::AutomationId(v)
+            | LocatorStrategy::Name(v)
+            | LocatorStrategy::ClassName(v)
+            | LocatorStrategy::XPath(v)
+            | LocatorStrategy::CssSelector(v) => format!("{}={}", self.kind(), v),
+            LocatorStrategy::Image(data) => format!("image({} bytes)", data.len()),
+            LocatorStrategy::Relative(relative) => format!("relative({})", relative.describe()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct ElementLocator {
+    #[serde(flatten)]
     pub primary: LocatorStrategy,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub fallback: Option<Box<ElementLocator>>,
 }
 
@@ -115,4 +161,72 @@ impl ElementLocator {
         self.fallback = Some(Box::new(fallback));
         self
     }
+
+    /// Build a chain that tries the strategies in the given order
+    pub fn from_strategies(strategies: Vec<LocatorStrategy>) -> Option<Self> {
+        strategies.into_iter().rev().fold(None, |fallback, strategy| {
+            Some(ElementLocator {
+                primary: strategy,
+                fallback: fallback.map(Box::new),
+            })
+        })
+    }
+
+    /// Build a chain from the identifying properties of a captured element
+    ///
+    /// Stable identifiers come first, the screenshot is the last resort.
+    pub fn from_element(element: &UIElement) -> Option<Self> {
+        let mut strategies = Vec::new();
+        let non_empty = |v: &Option<String>| v.clone().filter(|s| !s.is_empty());
+
+        if let Some(id) = non_empty(&element.automation_id) {
+            strategies.push(LocatorStrategy::AutomationId(id));
+        }
+        if let Some(css) = non_empty(&element.css_selector) {
+            strategies.push(LocatorStrategy::CssSelector(css));
+        }
+        if let Some(xpath) = non_empty(&element.xpath) {
+            strategies.push(LocatorStrategy::XPath(xpath));
+        }
+        if !element.name.is_empty() {
+            strategies.push(LocatorStrategy::Name(element.name.clone()));
+        }
+        if let Some(class_name) = non_empty(&element.class_name) {
+            strategies.push(LocatorStrategy::ClassName(class_name));
+        }
+        if let Some(screenshot) = element.screenshot.clone().filter(|s| !s.is_empty()) {
+            strategies.push(LocatorStrategy::Image(screenshot));
+        }
+
+        Self::from_strategies(strategies)
+    }
+
+    /// Append another chain after the last fallback of this one
+    pub fn then(mut self, next: ElementLocator) -> Self {
+        self.fallback = Some(Box::new(match self.fallback.take() {
+            Some(fallback) => fallback.then(next),
+            None => next,
+        }));
+        self
+    }
+
+    /// All strategies in the order they are tried
+    pub fn strategies(&self) -> Vec<&LocatorStrategy> {
+        let mut strategies = vec![&self.primary];
+        let mut next = self.fallback.as_deref();
+        while let Some(locator) = next {
+            strategies.push(&locator.primary);
+            next = locator.fallback.as_deref();
+        }
+        strategies
+    }
+
+    /// Human readable description of the whole chain
+    pub fn describe(&self) -> String {
+        self.strategies()
+            .iter()
+            .map(|s| s.describe())
+            .collect::<Vec<_>>()
+            .join(" -> ")
+    }
 }
@@ -0,0 +1,232 @@
+//! Relative (anchor based) locators
+//!
+//! A relative locator first resolves an anchor element, e.g. the label
+//! "Invoice No.", then picks a target among candidate elements by its position
+//! relative to the anchor: the textbox to the right of the label, the third row
+//! below a header, the second button within a panel.
+
+use super::ElementLocator;
+use crate::automation::Rect;
+use serde::{Deserialize, Serialize};
+
+/// Pixels an element may overlap the anchor and still count as beside it
+const EDGE_TOLERANCE: i32 = 4;
+
+/// Default radius of the `near` relation
+const NEAR_DISTANCE: i32 = 150;
+
+/// Position of the target relative to the anchor
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum Relation {
+    LeftOf,
+    RightOf,
+    Above,
+    Below,
+    Near,
+    /// Anywhere inside the anchor, which acts as a container
+    Within,
+    /// Direct child of the anchor, counting children in reading order
+    NthChild,
+}
+
+impl Relation {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Relation::LeftOf => "leftOf",
+            Relation::RightOf => "rightOf",
+            Relation::Above => "above",
+            Relation::Below => "below",
+            Relation::Near => "near",
+            Relation::Within => "within",
+            Relation::NthChild => "nthChild",
+        }
+    }
+}
+
+/// Locate an element by its position relative to an anchor element
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RelativeLocator {
+    pub relation: Relation,
+    /// Element the relation is measured from
+    pub anchor: ElementLocator,
+    /// Candidate elements, e.g. `className=Edit`; required on the desktop,
+    /// defaults to all elements in a browser
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<ElementLocator>,
+    /// Zero-based position among the matching candidates, nearest first
+    #[serde(default)]
+    pub index: usize,
+    /// Maximum gap between anchor and target in pixels
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_distance: Option<i32>,
+}
+
+impl RelativeLocator {
+    pub fn describe(&self) -> String {
+        let mut description = format!("{} [{}]", self.relation.as_str(), self.anchor.describe());
+        if let Some(target) = &self.target {
+            description.push_str(&format!(" target [{}]", target.describe()));
+        }
+        if self.index > 0 {
+            description.push_str(&format!(" #{}", self.index));
+        }
+        description
+    }
+
+    /// Pick the target among candidates given the anchor's bounds
+    pub fn select<T>(&self, anchor: &Rect, candidates: Vec<T>, bounds: impl Fn(&T) -> &Rect) -> Option<T> {
+        let mut ranked = rank(anchor, candidates, &bounds, self.relation, self.max_distance);
+        if self.index < ranked.len() {
+            Some(ranked.swap_remove(self.index))
+        } else {
+            None
+        }
+    }
+}
+
+/// Keep the candidates satisfying the relation, ordered nearest first
+/// (reading order for `within` and `nthChild`)
+pub fn rank<T>(
+    anchor: &Rect,
+    candidates: Vec<T>,
+    bounds: &impl Fn(&T) -> &Rect,
+    relation: Relation,
+    max_distance: Option<i32>,
+) -> Vec<T> {
+    let mut scored: Vec<((i64, i64), T)> = candidates
+        .into_iter()
+        .filter(|c| {
+            let b = bounds(c);
+            b.width > 0 && b.height > 0 && !same_rect(b, anchor)
+        })
+        .filter_map(|c| {
+            let score = score(anchor, bounds(&c), relation, max_distance)?;
+            Some((score, c))
+        })
+        .collect();
+
+    if relation == Relation::NthChild {
+        // Only the outermost candidates are direct children
+        let rects: Vec<Rect> = scored.iter().map(|(_, c)| bounds(c).clone()).collect();
+        let outermost: Vec<bool> = rects
+            .iter()
+            .map(|rect| !rects.iter().any(|other| !same_rect(other, rect) && contains(other, rect)))
+            .collect();
+        scored = scored
+            .into_iter()
+            .zip(outermost)
+            .filter_map(|(candidate, keep)| keep.then_some(candidate))
+            .collect();
+    }
+
+    scored.sort_by_key(|(score, _)| *score);
+    scored.into_iter().map(|(_, c)| c).collect()
+}
+
+/// Sort key of a candidate, `None` if it does not satisfy the relation
+fn score(anchor: &Rect, rect: &Rect, relation: Relation, max_distance: Option<i32>) -> Option<(i64, i64)> {
+    let (ax, ay) = anchor.center();
+    let (cx, cy) = rect.center();
+    let overlaps_rows = rect.y < anchor.y + anchor.height && rect.y + rect.height > anchor.y;
+    let overlaps_columns = rect.x < anchor.x + anchor.width && rect.x + rect.width > anchor.x;
+
+    let (distance, offset) = match relation {
+        Relation::RightOf if overlaps_rows && rect.x >= anchor.x + anchor.width - EDGE_TOLERANCE => {
+            (rect.x - (anchor.x + anchor.width), cy - ay)
+        }
+        Relation::LeftOf if overlaps_rows && rect.x + rect.width <= anchor.x + EDGE_TOLERANCE => {
+            (anchor.x - (rect.x + rect.width), cy - ay)
+        }
+        Relation::Below if overlaps_columns && rect.y >= anchor.y + anchor.height - EDGE_TOLERANCE => {
+            (rect.y - (anchor.y + anchor.height), cx - ax)
+        }
+        Relation::Above if overlaps_columns && rect.y + rect.height <= anchor.y + EDGE_TOLERANCE => {
+            (anchor.y - (rect.y + rect.height), cx - ax)
+        }
+        Relation::Near => {
+            let distance = (((cx - ax) as f64).powi(2) + ((cy - ay) as f64).powi(2)).sqrt() as i32;
+            if distance > max_distance.unwrap_or(NEAR_DISTANCE) {
+                return None;
+            }
+            (distance, 0)
+        }
+        Relation::Within | Relation::NthChild if contains(anchor, rect) => {
+            return Some((rect.y as i64, rect.x as i64));
+        }
+        _ => return None,
+    };
+
+    let distance = distance.max(0);
+    if max_distance.is_some_and(|max| distance > max) {
+        return None;
+    }
+    Some((distance as i64, offset.abs() as i64))
+}
+
+fn contains(outer: &Rect, inner: &Rect) -> bool {
+    inner.x >= outer.x
+        && inner.y >= outer.y
+        && inner.x + inner.width <= outer.x + outer.width
+        && inner.y + inner.height <= outer.y + outer.height
+}
+
+fn same_rect(a: &Rect, b: &Rect) -> bool {
+    a.x == b.x && a.y == b.y && a.width == b.width && a.height == b.height
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::element::LocatorStrategy;
+
+    fn rect(x: i32, y: i32, width: i32, height: i32) -> Rect {
+        Rect { x, y, width, height }
+    }
+
+    fn locator(relation: Relation, index: usize) -> RelativeLocator {
+        RelativeLocator {
+            relation,
+            anchor: ElementLocator::new(LocatorStrategy::Name("Invoice No.".to_string())),
+            target: None,
+            index,
+            max_distance: None,
+        }
+    }
+
+    #[test]
+    fn test_right_of_picks_nearest_on_same_row() {
+        let label = rect(10, 100, 80, 20);
+        let candidates = vec![
+            rect(300, 100, 120, 20), // same row, further away
+            rect(100, 98, 120, 24),  // same row, nearest
+            rect(100, 200, 120, 20), // next row
+        ];
+
+        let found = locator(Relation::RightOf, 0).select(&label, candidates, |r| r);
+        assert_eq!(found.map(|r| r.x), Some(100));
+    }
+
+    #[test]
+    fn test_below_index_counts_rows() {
+        let header = rect(0, 0, 400, 30);
+        let rows: Vec<Rect> = (0..5).rev().map(|i| rect(0, 30 + i * 25, 400, 25)).collect();
+
+        let third = locator(Relation::Below, 2).select(&header, rows, |r| r);
+        assert_eq!(third.map(|r| r.y), Some(80));
+    }
+
+    #[test]
+    fn test_nth_child_skips_nested_elements() {
+        let panel = rect(0, 0, 300, 300);
+        let candidates = vec![
+            rect(10, 10, 100, 40),
+            rect(20, 15, 30, 20), // inside the first child
+            rect(10, 60, 100, 40),
+        ];
+
+        let second = locator(Relation::NthChild, 1).select(&panel, candidates, |r| r);
+        assert_eq!(second.map(|r| r.y), Some(60));
+    }
+}
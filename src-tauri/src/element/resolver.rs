@@ -0,0 +1,559 @@
+//! Runtime element resolution through locator fallback chains
+//!
+//! A resolver walks an [`ElementLocator`] chain, trying the primary strategy
+//! first and then each fallback, until one matches or the timeout expires. The
+//! result records which strategy matched so callers can log it and, when a
+//! fallback was needed, refresh the stored [`UIElement`] ("self-healing").
+
+use super::relative::RelativeLocator;
+use super::{ElementLocator, LocatorStrategy, UIElement};
+use crate::automation::desktop::DesktopAutomation;
+use crate::automation::vision::{self, MatchOptions};
+use crate::automation::web::{WebAutomation, WebElement};
+use crate::automation::{AutomationError, AutomationResult};
+use serde::Serialize;
+use std::future::Future;
+use std::pin::Pin;
+use std::time::{Duration, Instant};
+
+/// Shortest time a single desktop search may take, so that anchors resolved
+/// without a timeout still get one full pass over the element tree
+const MIN_SEARCH: Duration = Duration::from_secs(1);
+
+/// Pause between rounds over the whole chain
+const RETRY_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Browser elements considered by relative locators without a target
+const DEFAULT_WEB_CANDIDATES: &str =
+    "input, textarea, select, button, a, label, tr, td, th, li, [role]";
+
+type BoxedResolve<'a> = Pin<Box<dyn Future<Output = AutomationResult<ResolvedElement>> + Send + 'a>>;
+
+/// Element found by a locator chain
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResolvedElement {
+    pub element: UIElement,
+    /// Strategy that matched
+    pub strategy: LocatorStrategy,
+    /// Position of the matching strategy in the chain (0 = primary)
+    pub strategy_index: usize,
+    /// Selector that matched, for browser elements
+    pub selector: Option<String>,
+}
+
+impl ResolvedElement {
+    /// Whether the primary strategy failed and a fallback was used
+    pub fn used_fallback(&self) -> bool {
+        self.strategy_index > 0
+    }
+
+    /// Position of the match within a chain that was tried after `offset`
+    /// other strategies, or `None` when one of those matched
+    pub fn index_in_chain(&self, offset: usize) -> Option<usize> {
+        self.strategy_index.checked_sub(offset)
+    }
+}
+
+/// Resolve a desktop element, trying each strategy of the chain in order
+///
+/// Image strategies are matched against a screenshot using `image_options`.
+pub async fn resolve_desktop(
+    automation: &dyn DesktopAutomation,
+    locator: &ElementLocator,
+    image_options: &MatchOptions,
+    timeout_ms: u64,
+) -> AutomationResult<ResolvedElement> {
+    let strategies = locator.strategies();
+    if !strategies.iter().any(|s| {
+        matches!(s, LocatorStrategy::Image(_) | LocatorStrategy::Relative(_))
+            || desktop_criteria(s).is_some()
+    }) {
+        return Err(AutomationError::InvalidParameter(format!(
+            "No desktop strategy in locator: {}",
+            locator.describe()
+        )));
+    }
+
+    let deadline = Instant::now() + Duration::from_millis(timeout_ms);
+    loop {
+        for (index, strategy) in strategies.iter().enumerate() {
+            let found = match strategy {
+                LocatorStrategy::Image(template) => {
+                    locate_image(automation, template, image_options).await
+                }
+                LocatorStrategy::Relative(relative) => {
+                    locate_relative_desktop(automation, relative, image_options).await
+                }
+                _ => match desktop_criteria(strategy) {
+                    Some(criteria) => attempt_desktop(automation, &criteria, deadline).await,
+                    None => continue,
+                },
+            };
+
+            match found {
+                Ok(element) => {
+                    log::info!("Element resolved by {} (#{})", strategy.describe(), index);
+                    return Ok(ResolvedElement {
+                        element,
+                        strategy: (*strategy).clone(),
+                        strategy_index: index,
+                        selector: None,
+                    });
+                }
+                // A search cut short says nothing about the strategy, and
+                // the time for later ones is used up as well
+                Err(e @ AutomationError::Timeout(_)) => return Err(e),
+                Err(e) => log::debug!("Strategy {} did not match: {}", strategy.describe(), e),
+            }
+        }
+
+        if Instant::now() >= deadline {
+            break;
+        }
+        tokio::time::sleep(RETRY_INTERVAL).await;
+    }
+
+    Err(AutomationError::ElementNotFound(format!(
+        "No locator matched within {}ms: {}",
+        timeout_ms,
+        locator.describe()
+    )))
+}
+
+/// Search once for a desktop element, with the time left until `deadline`
+/// (but at least `MIN_SEARCH`) as the budget
+///
+/// Only a search that finishes without a match fails with `ElementNotFound`;
+/// one that runs out of time fails with `Timeout`.
+async fn attempt_desktop(
+    automation: &dyn DesktopAutomation,
+    criteria: &str,
+    deadline: Instant,
+) -> AutomationResult<UIElement> {
+    let budget = deadline.saturating_duration_since(Instant::now()).max(MIN_SEARCH);
+    tokio::time::timeout(budget, automation.find_element(criteria))
+        .await
+        .unwrap_or_else(|_| {
+            Err(AutomationError::Timeout(format!(
+                "Search for element '{}' did not finish within {}ms",
+                criteria,
+                budget.as_millis()
+            )))
+        })
+}
+
+/// Resolve an element in a browser session, trying each strategy of the chain in order
+pub async fn resolve_web(
+    web: &WebAutomation,
+    session_id: &str,
+    locator: &ElementLocator,
+    timeout_ms: u64,
+) -> AutomationResult<ResolvedElement> {
+    let strategies = locator.strategies();
+    if !strategies
+        .iter()
+        .any(|s| matches!(s, LocatorStrategy::Relative(_)) || web_selector(s).is_some())
+    {
+        return Err(AutomationError::InvalidParameter(format!(
+            "No browser strategy in locator: {}",
+            locator.describe()
+        )));
+    }
+
+    let deadline = Instant::now() + Duration::from_millis(timeout_ms);
+    loop {
+        for (index, strategy) in strategies.iter().enumerate() {
+            let found = match strategy {
+                LocatorStrategy::Relative(relative) => {
+                    locate_relative_web(web, session_id, relative).await
+                }
+                _ => match web_selector(strategy) {
+                    Some(selector) => web
+                        .find(session_id, &selector)
+                        .await
+                        .map(|found| (found, selector)),
+                    None => continue,
+                },
+            };
+
+            match found {
+                Ok((found, selector)) => {
+                    log::info!("Web element resolved by {} (#{})", strategy.describe(), index);
+                    return Ok(ResolvedElement {
+                        element: web_element_to_ui_element(&found),
+                        strategy: (*strategy).clone(),
+                        strategy_index: index,
+                        selector: Some(selector),
+                    });
+                }
+                Err(AutomationError::ElementNotFound(_)) => {}
+                // Session errors will not go away by retrying
+                Err(e) => return Err(e),
+            }
+        }
+
+        if Instant::now() >= deadline {
+            break;
+        }
+        tokio::time::sleep(RETRY_INTERVAL).await;
+    }
+
+    Err(AutomationError::ElementNotFound(format!(
+        "No locator matched within {}ms: {}",
+        timeout_ms,
+        locator.describe()
+    )))
+}
+
+/// Find the best on-screen match of an image template
+async fn locate_image(
+    automation: &dyn DesktopAutomation,
+    template: &[u8],
+    options: &MatchOptions,
+) -> AutomationResult<UIElement> {
+    let best = vision::locate_on_screen(automation, template, options)
+        .await?
+        .into_iter()
+        .next()
+        .ok_or_else(|| {
+            AutomationError::ElementNotFound(format!(
+                "Image not found on screen (threshold {:.2})",
+                options.threshold
+            ))
+        })?;
+
+    let mut element = UIElement::new(String::new(), "Image".to_string(), best.rect);
+    element
+        .attributes
+        .insert("confidence".to_string(), format!("{:.3}", best.confidence));
+    element
+        .attributes
+        .insert("scale".to_string(), format!("{:.2}", best.scale));
+    Ok(element)
+}
+
+/// Find the element a relative locator points to on the desktop
+///
+/// Candidates come from the first target strategy that matches anything.
+async fn locate_relative_desktop(
+    automation: &dyn DesktopAutomation,
+    relative: &RelativeLocator,
+    image_options: &MatchOptions,
+) -> AutomationResult<UIElement> {
+    let target = relative.target.as_ref().ok_or_else(|| {
+        AutomationError::InvalidParameter(
+            "Relative desktop locators need a target to select candidates".to_string(),
+        )
+    })?;
+
+    let anchor = resolve_anchor_desktop(automation, &relative.anchor, image_options).await?;
+
+    let mut candidates = Vec::new();
+    for criteria in target.strategies().into_iter().filter_map(desktop_criteria) {
+        candidates = automation.find_elements(&criteria).await?;
+        if !candidates.is_empty() {
+            break;
+        }
+    }
+
+    relative
+        .select(&anchor.element.bounds, candidates, |e| &e.bounds)
+        .ok_or_else(|| {
+            AutomationError::ElementNotFound(format!("No element {}", relative.describe()))
+        })
+}
+
+/// Find the element a relative locator points to in a browser session
+///
+/// Returns the element and a selector that identifies it.
+async fn locate_relative_web(
+    web: &WebAutomation,
+    session_id: &str,
+    relative: &RelativeLocator,
+) -> AutomationResult<(WebElement, String)> {
+    let anchor = resolve_anchor_web(web, session_id, &relative.anchor).await?;
+
+    let selectors: Vec<String> = match &relative.target {
+        Some(target) => target.strategies().into_iter().filter_map(web_selector).collect(),
+        None => vec![DEFAULT_WEB_CANDIDATES.to_string()],
+    };
+
+    let mut candidates = Vec::new();
+    for selector in &selectors {
+        candidates = web.find_all(session_id, selector).await?;
+        if !candidates.is_empty() {
+            break;
+        }
+    }
+
+    let found = relative
+        .select(&anchor.element.bounds, candidates, |e| &e.bounds)
+        .ok_or_else(|| {
+            AutomationError::ElementNotFound(format!("No element {}", relative.describe()))
+        })?;
+
+    let selector = if found.css_selector.is_empty() {
+        format!("xpath={}", found.xpath)
+    } else {
+        found.css_selector.clone()
+    };
+    Ok((found, selector))
+}
+
+/// Resolve an anchor chain once, without waiting; boxed because anchors may
+/// themselves be relative
+fn resolve_anchor_desktop<'a>(
+    automation: &'a dyn DesktopAutomation,
+    anchor: &'a ElementLocator,
+    image_options: &'a MatchOptions,
+) -> BoxedResolve<'a> {
+    Box::pin(resolve_desktop(automation, anchor, image_options, 0))
+}
+
+fn resolve_anchor_web<'a>(
+    web: &'a WebAutomation,
+    session_id: &'a str,
+    anchor: &'a ElementLocator,
+) -> BoxedResolve<'a> {
+    Box::pin(resolve_web(web, session_id, anchor, 0))
+}
+
+/// Refresh a stored element from what the resolver actually found
+///
+/// Returns `true` if the stored element changed and should be saved.
+pub fn heal_element(stored: &mut UIElement, resolved: &ResolvedElement) -> bool {
+    let found = &resolved.element;
+    let mut changed = false;
+
+    let mut update = |target: &mut Option<String>, value: &Option<String>| {
+        if let Some(value) = value.as_ref().filter(|v| !v.is_empty()) {
+            if target.as_ref() != Some(value) {
+                *target = Some(value.clone());
+                changed = true;
+            }
+        }
+    };
+
+    update(&mut stored.automation_id, &found.automation_id);
+    update(&mut stored.class_name, &found.class_name);
+    update(&mut stored.xpath, &found.xpath);
+    update(&mut stored.css_selector, &found.css_selector);
+    update(&mut stored.process_name, &found.process_name);
+    update(&mut stored.window_title, &found.window_title);
+
+    if !found.name.is_empty() && stored.name != found.name {
+        stored.name = found.name.clone();
+        changed = true;
+    }
+
+    // A moved element is saved too, but was not healed
+    let moved = found.bounds.width > 0 && found.bounds.height > 0 && stored.bounds != found.bounds;
+    if moved {
+        stored.bounds = found.bounds.clone();
+    }
+
+    if changed {
+        stored.attributes.insert(
+            "healedBy".to_string(),
+            resolved.strategy.kind().to_string(),
+        );
+        stored
+            .attributes
+            .insert("healedAt".to_string(), chrono::Utc::now().to_rfc3339());
+    }
+
+    changed || moved
+}
+
+/// Locator criteria understood by `DesktopAutomation::wait_element`
+pub(crate) fn desktop_criteria(strategy: &LocatorStrategy) -> Option<String> {
+    match strategy {
+        LocatorStrategy::AutomationId(v) => Some(format!("automationid:{}", v)),
+        LocatorStrategy::Name(v) => Some(format!("name:{}", v)),
+        LocatorStrategy::ClassName(v) => Some(format!("classname:{}", v)),
+        LocatorStrategy::XPath(_)
+        | LocatorStrategy::CssSelector(_)
+        | LocatorStrategy::Image(_)
+        | LocatorStrategy::Relative(_) => None,
+    }
+}
+
+/// Selector understood by `WebAutomation`
+pub(crate) fn web_selector(strategy: &LocatorStrategy) -> Option<String> {
+    match strategy {
+        LocatorStrategy::CssSelector(css) => Some(css.clone()),
+        LocatorStrategy::XPath(xpath) => Some(format!("xpath={}", xpath)),
+        LocatorStrategy::AutomationId(id) => Some(format!("[id=\"{}\"]", id.replace('"', "\\\""))),
+        LocatorStrategy::Name(name) => {
+            let literal = xpath_literal(name);
+            Some(format!(
+                "xpath=//*[@name={0} or @aria-label={0} or normalize-space(text())={0}]",
+                literal
+            ))
+        }
+        LocatorStrategy::ClassName(class_name) => {
+            let classes: Vec<String> = class_name
+                .split_whitespace()
+                .map(|c| format!(".{}", css_escape(c)))
+                .collect();
+            (!classes.is_empty()).then(|| classes.concat())
+        }
+        LocatorStrategy::Image(_) | LocatorStrategy::Relative(_) => None,
+    }
+}
+
+fn xpath_literal(value: &str) -> String {
+    if !value.contains('\'') {
+        format!("'{}'", value)
+    } else if !value.contains('"') {
+        format!("\"{}\"", value)
+    } else {
+        let parts: Vec<String> = value.split('\'').map(|p| format!("'{}'", p)).collect();
+        format!("concat({})", parts.join(", \"'\", "))
+    }
+}
+
+fn css_escape(value: &str) -> String {
+    value
+        .chars()
+        .flat_map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' || !c.is_ascii() {
+                vec![c]
+            } else {
+                vec!['\\', c]
+            }
+        })
+        .collect()
+}
+
+fn web_element_to_ui_element(found: &WebElement) -> UIElement {
+    let name = found
+        .attributes
+        .get("aria-label")
+        .or_else(|| found.attributes.get("name"))
+        .cloned()
+        .or_else(|| found.text.clone())
+        .unwrap_or_default();
+
+    let mut element = UIElement::new(name, found.tag_name.clone(), found.bounds.clone());
+    element.automation_id = found.attributes.get("id").cloned();
+    element.class_name = found.attributes.get("class").cloned();
+    element.xpath = Some(found.xpath.clone()).filter(|x| !x.is_empty());
+    element.css_selector = Some(found.css_selector.clone()).filter(|c| !c.is_empty());
+    element.attributes = found.attributes.clone();
+    element
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::automation::desktop::testing::FakeDesktop;
+    use crate::automation::Rect;
+
+    fn rect() -> Rect {
+        Rect { x: 10, y: 10, width: 80, height: 20 }
+    }
+
+    fn chain(names: &[&str]) -> ElementLocator {
+        ElementLocator::from_strategies(
+            names.iter().map(|n| LocatorStrategy::Name(n.to_string())).collect(),
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_resolve_tries_strategies_in_order() {
+        let desktop = FakeDesktop::with_buttons(&["Save", "OK"]);
+        let options = MatchOptions::default();
+
+        let resolved = resolve_desktop(&desktop, &chain(&["OK", "Save"]), &options, 0)
+            .await
+            .unwrap();
+        assert_eq!(resolved.element.name, "OK");
+        assert!(!resolved.used_fallback());
+
+        let resolved = resolve_desktop(&desktop, &chain(&["Missing", "Save", "OK"]), &options, 0)
+            .await
+            .unwrap();
+        assert_eq!(resolved.element.name, "Save");
+        assert_eq!(resolved.strategy, LocatorStrategy::Name("Save".to_string()));
+        assert_eq!(resolved.strategy_index, 1);
+
+        let missing = resolve_desktop(&desktop, &chain(&["Missing"]), &options, 0).await;
+        assert!(matches!(missing, Err(AutomationError::ElementNotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn test_slow_search_is_not_abandoned() {
+        let mut desktop = FakeDesktop::with_buttons(&["Deep", "OK"]);
+        desktop.search_delay = MIN_SEARCH + Duration::from_millis(300);
+        let options = MatchOptions::default();
+
+        // A search longer than the minimum runs to the end within the timeout
+        let timeout = desktop.search_delay * 3;
+        let resolved = resolve_desktop(&desktop, &chain(&["Deep", "OK"]), &options, timeout.as_millis() as u64)
+            .await
+            .unwrap();
+        assert_eq!(resolved.strategy_index, 0);
+
+        // Running out of time does not count as a miss for the fallbacks
+        let started = Instant::now();
+        let result = resolve_desktop(&desktop, &chain(&["Deep", "OK"]), &options, 0).await;
+        assert!(matches!(result, Err(AutomationError::Timeout(_))));
+        assert!(started.elapsed() < desktop.search_delay);
+    }
+
+    #[tokio::test]
+    async fn test_node_strategies_are_not_fallbacks() {
+        let desktop = FakeDesktop::with_buttons(&["OK"]);
+        // A node-level strategy that fails, followed by the element's chain
+        let locator = chain(&["Missing"]).then(chain(&["OK", "Cancel"]));
+
+        let resolved = resolve_desktop(&desktop, &locator, &MatchOptions::default(), 0)
+            .await
+            .unwrap();
+        assert!(resolved.used_fallback());
+        assert_eq!(resolved.index_in_chain(1), Some(0));
+
+        let first = ResolvedElement { strategy_index: 0, ..resolved };
+        assert_eq!(first.index_in_chain(1), None);
+    }
+
+    #[test]
+    fn test_heal_element() {
+        let mut stored = UIElement::new("Save".to_string(), "Button".to_string(), rect());
+        stored.automation_id = Some("btnSave".to_string());
+        stored.class_name = Some("Button".to_string());
+
+        let mut found = UIElement::new(String::new(), "Button".to_string(), Rect { x: 50, ..rect() });
+        found.automation_id = Some("saveButton".to_string());
+        let resolved = ResolvedElement {
+            element: found,
+            strategy: LocatorStrategy::ClassName("Button".to_string()),
+            strategy_index: 2,
+            selector: None,
+        };
+
+        assert!(heal_element(&mut stored, &resolved));
+        assert_eq!(stored.automation_id.as_deref(), Some("saveButton"));
+        // Empty values found at runtime never erase stored ones
+        assert_eq!(stored.name, "Save");
+        assert_eq!(stored.class_name.as_deref(), Some("Button"));
+        assert_eq!(stored.bounds.x, 50);
+        assert_eq!(stored.attributes.get("healedBy").map(String::as_str), Some("className"));
+
+        // Nothing left to update
+        assert!(!heal_element(&mut stored, &resolved));
+
+        // A moved element is saved without counting as healed
+        stored.attributes.remove("healedBy");
+        let moved = ResolvedElement {
+            element: UIElement::new("Save".to_string(), "Button".to_string(), Rect { y: 40, ..rect() }),
+            ..resolved
+        };
+        assert!(heal_element(&mut stored, &moved));
+        assert_eq!(stored.bounds.y, 40);
+        assert!(!stored.attributes.contains_key("healedBy"));
+    }
+}
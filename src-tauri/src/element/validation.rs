@@ -0,0 +1,273 @@
+//! Locator validation
+//!
+//! Runs every strategy of an element's locator chain once against the live
+//! desktop or a browser page and counts the matches. An element is healthy
+//! when the first strategy that matches anything matches exactly one element.
+
+use super::resolver::{desktop_criteria, web_selector};
+use super::{ElementLibrary, ElementLocator, LocatorStrategy, UIElement};
+use crate::automation::desktop::DesktopAutomation;
+use crate::automation::vision::{self, MatchOptions};
+use crate::automation::web::WebAutomation;
+use crate::automation::Rect;
+use serde::Serialize;
+use std::time::Instant;
+
+/// Overall outcome for one element
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum ValidationStatus {
+    /// The primary strategy matches exactly one element
+    Unique,
+    /// Only a fallback strategy matches, and exactly one element
+    Fallback,
+    /// The resolving strategy matches more than one element
+    Ambiguous,
+    /// No strategy matches
+    NotFound,
+}
+
+/// Result of running a single strategy
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StrategyCheck {
+    pub strategy: String,
+    pub description: String,
+    /// Number of matches, `None` if the strategy can't run on this target
+    pub match_count: Option<usize>,
+    pub duration_ms: u64,
+    pub error: Option<String>,
+}
+
+/// Validation result for one element
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ElementValidation {
+    pub element_id: String,
+    pub element_name: String,
+    pub status: ValidationStatus,
+    /// Index of the first strategy that matched anything
+    pub resolved_by: Option<usize>,
+    /// Bounds of the matches of the resolving strategy
+    pub matches: Vec<Rect>,
+    pub strategies: Vec<StrategyCheck>,
+    pub duration_ms: u64,
+}
+
+impl ElementValidation {
+    /// Whether the element resolves to a single element
+    pub fn is_ok(&self) -> bool {
+        matches!(self.status, ValidationStatus::Unique | ValidationStatus::Fallback)
+    }
+}
+
+/// Validation results for a whole library
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LibraryValidationReport {
+    pub library_id: String,
+    pub library_name: String,
+    pub total: usize,
+    pub unique: usize,
+    pub fallback: usize,
+    pub ambiguous: usize,
+    pub not_found: usize,
+    pub duration_ms: u64,
+    pub elements: Vec<ElementValidation>,
+}
+
+impl LibraryValidationReport {
+    pub fn new(library: &ElementLibrary, elements: Vec<ElementValidation>, duration_ms: u64) -> Self {
+        let count = |status| elements.iter().filter(|e| e.status == status).count();
+        Self {
+            library_id: library.id.clone(),
+            library_name: library.name.clone(),
+            total: elements.len(),
+            unique: count(ValidationStatus::Unique),
+            fallback: count(ValidationStatus::Fallback),
+            ambiguous: count(ValidationStatus::Ambiguous),
+            not_found: count(ValidationStatus::NotFound),
+            duration_ms,
+            elements,
+        }
+    }
+}
+
+/// Validate an element against the live desktop
+pub async fn validate_desktop(automation: &dyn DesktopAutomation, element: &UIElement) -> ElementValidation {
+    let started = Instant::now();
+    let mut checks = Vec::new();
+    let mut resolved: Option<(usize, Vec<Rect>)> = None;
+
+    for (index, strategy) in strategies_of(element).iter().enumerate() {
+        let attempt = Instant::now();
+        let found = match strategy {
+            LocatorStrategy::Image(template) => Some(
+                vision::locate_on_screen(automation, template, &MatchOptions::default())
+                    .await
+                    .map(|matches| matches.into_iter().map(|m| m.rect).collect::<Vec<_>>()),
+            ),
+            _ => match desktop_criteria(strategy) {
+                Some(criteria) => Some(
+                    automation
+                        .find_elements(&criteria)
+                        .await
+                        .map(|found| found.into_iter().map(|e| e.bounds).collect()),
+                ),
+                None => None,
+            },
+        };
+
+        checks.push(record(strategy, found.as_ref(), attempt));
+        if let Some(Ok(rects)) = found {
+            if resolved.is_none() && !rects.is_empty() {
+                resolved = Some((index, rects));
+            }
+        }
+    }
+
+    finish(element, checks, resolved, started)
+}
+
+/// Validate an element against the page of a browser session
+pub async fn validate_web(web: &WebAutomation, session_id: &str, element: &UIElement) -> ElementValidation {
+    let started = Instant::now();
+    let mut checks = Vec::new();
+    let mut resolved: Option<(usize, Vec<Rect>)> = None;
+
+    for (index, strategy) in strategies_of(element).iter().enumerate() {
+        let attempt = Instant::now();
+        let found = match web_selector(strategy) {
+            Some(selector) => Some(
+                web.find_all(session_id, &selector)
+                    .await
+                    .map(|found| found.into_iter().map(|e| e.bounds).collect::<Vec<_>>()),
+            ),
+            None => None,
+        };
+
+        checks.push(record(strategy, found.as_ref(), attempt));
+        if let Some(Ok(rects)) = found {
+            if resolved.is_none() && !rects.is_empty() {
+                resolved = Some((index, rects));
+            }
+        }
+    }
+
+    finish(element, checks, resolved, started)
+}
+
+fn strategies_of(element: &UIElement) -> Vec<LocatorStrategy> {
+    ElementLocator::from_element(element)
+        .map(|locator| locator.strategies().into_iter().cloned().collect())
+        .unwrap_or_default()
+}
+
+fn record<E: std::fmt::Display>(
+    strategy: &LocatorStrategy,
+    found: Option<&Result<Vec<Rect>, E>>,
+    started: Instant,
+) -> StrategyCheck {
+    let (match_count, error) = match found {
+        Some(Ok(rects)) => (Some(rects.len()), None),
+        Some(Err(e)) => (Some(0), Some(e.to_string())),
+        None => (None, None),
+    };
+
+    StrategyCheck {
+        strategy: strategy.kind().to_string(),
+        description: strategy.describe(),
+        match_count,
+        duration_ms: started.elapsed().as_millis() as u64,
+        error,
+    }
+}
+
+fn finish(
+    element: &UIElement,
+    strategies: Vec<StrategyCheck>,
+    resolved: Option<(usize, Vec<Rect>)>,
+    started: Instant,
+) -> ElementValidation {
+    let status = match &resolved {
+        None => ValidationStatus::NotFound,
+        Some((_, rects)) if rects.len() > 1 => ValidationStatus::Ambiguous,
+        Some((0, _)) => ValidationStatus::Unique,
+        Some(_) => ValidationStatus::Fallback,
+    };
+    let (resolved_by, matches) = match resolved {
+        Some((index, rects)) => (Some(index), rects),
+        None => (None, Vec::new()),
+    };
+
+    ElementValidation {
+        element_id: element.id.clone(),
+        element_name: element.name.clone(),
+        status,
+        resolved_by,
+        matches,
+        strategies,
+        duration_ms: started.elapsed().as_millis() as u64,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rect(x: i32) -> Rect {
+        Rect { x, y: 0, width: 10, height: 10 }
+    }
+
+    fn validate(resolved: Option<(usize, Vec<Rect>)>) -> ElementValidation {
+        let element = UIElement::new("OK".to_string(), "Button".to_string(), rect(0));
+        finish(&element, Vec::new(), resolved, Instant::now())
+    }
+
+    #[test]
+    fn test_finish_status() {
+        let unique = validate(Some((0, vec![rect(0)])));
+        assert_eq!(unique.status, ValidationStatus::Unique);
+        assert_eq!(unique.resolved_by, Some(0));
+        assert!(unique.is_ok());
+
+        let fallback = validate(Some((2, vec![rect(0)])));
+        assert_eq!(fallback.status, ValidationStatus::Fallback);
+        assert!(fallback.is_ok());
+
+        // More than one match is ambiguous even for the primary strategy
+        let ambiguous = validate(Some((0, vec![rect(0), rect(20)])));
+        assert_eq!(ambiguous.status, ValidationStatus::Ambiguous);
+        assert_eq!(ambiguous.matches.len(), 2);
+        assert!(!ambiguous.is_ok());
+
+        let not_found = validate(None);
+        assert_eq!(not_found.status, ValidationStatus::NotFound);
+        assert_eq!(not_found.resolved_by, None);
+        assert!(not_found.matches.is_empty());
+    }
+
+    #[test]
+    fn test_record_and_library_report() {
+        let strategy = LocatorStrategy::Name("OK".to_string());
+        let failed: Result<Vec<Rect>, String> = Err("no display".to_string());
+        let check = record(&strategy, Some(&failed), Instant::now());
+        assert_eq!(check.match_count, Some(0));
+        assert_eq!(check.error.as_deref(), Some("no display"));
+        assert_eq!(record::<String>(&strategy, None, Instant::now()).match_count, None);
+
+        let library = ElementLibrary::new("Lib".to_string());
+        let report = LibraryValidationReport::new(
+            &library,
+            vec![
+                validate(Some((0, vec![rect(0)]))),
+                validate(Some((1, vec![rect(0)]))),
+                validate(None),
+                validate(None),
+            ],
+            5,
+        );
+        assert_eq!(report.total, 4);
+        assert_eq!((report.unique, report.fallback, report.ambiguous, report.not_found), (1, 1, 0, 2));
+    }
+}
@@ -2,11 +2,16 @@ use super::runtime::{DebugMode, Runtime};
 use super::variable::VariableValue;
 use super::{EngineError, EngineResult, ExecutionLog, ExecutionStatus};
 use std::collections::HashSet;
-use crate::automation::desktop;
-use crate::automation::file::FileAutomation;
+use crate::automation::desktop::{self, ClipboardContent, WindowInfo, WindowQuery, WindowState};
+use crate::automation::file::{FileAutomation, FileWaitCondition, OverwritePolicy};
+use crate::automation::keys::{Key, KeyCombo};
+use crate::automation::process::{self, ProcessTarget};
+use crate::automation::vision::MatchOptions;
 use crate::automation::web::{BrowserOptions, WebAutomation};
-use crate::automation::ClickType;
+use crate::automation::{AutomationError, ClickType, InputMethod, Rect};
+use crate::element::{resolver, ElementLocator, LocatorStrategy, UIElement};
 use crate::plugin::{PluginRegistry, LuaPluginExecutor};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::future::Future;
@@ -83,6 +88,27 @@ impl Workflow {
     }
 }
 
+/// Default time to wait for a node's target element
+const DEFAULT_ELEMENT_TIMEOUT_MS: u64 = 10000;
+
+/// Time an application gets to put copied data on the clipboard
+const CLIPBOARD_COPY_TIMEOUT_MS: u64 = 2000;
+
+/// Time an application gets to read the clipboard after a paste, before the
+/// previous content is restored
+const CLIPBOARD_PASTE_DELAY_MS: u64 = 300;
+
+/// Time to collect the rest of a command's output once it has exited, as
+/// processes it left in the background may keep its pipes open
+const COMMAND_OUTPUT_DRAIN_MS: u64 = 500;
+
+/// Element targeted by a node
+struct NodeTarget {
+    locator: Option<ElementLocator>,
+    library_id: Option<String>,
+    element: Option<UIElement>,
+}
+
 pub struct Executor {
     workflow: Workflow,
     runtime: Arc<Runtime>,
@@ -253,6 +279,28 @@ impl Executor {
             "executeCommand" => self.execute_command(node).await,
             "listDirectory" => self.execute_list_directory(node).await,
             "openApp" => self.execute_open_app(node).await,
+            "scroll" => self.execute_scroll(node).await,
+            "mouseMove" => self.execute_mouse_move(node).await,
+            "dragDrop" => self.execute_drag_drop(node).await,
+            "activateWindow" => self.execute_activate_window(node).await,
+            "waitWindow" => self.execute_wait_window(node).await,
+            "setWindowState" => self.execute_set_window_state(node).await,
+            "closeWindow" => self.execute_close_window(node).await,
+            "listWindows" => self.execute_list_windows(node).await,
+            "launchProcess" => self.execute_launch_process(node).await,
+            "waitProcess" => self.execute_wait_process(node).await,
+            "checkProcess" => self.execute_check_process(node).await,
+            "killProcess" => self.execute_kill_process(node).await,
+            "getClipboard" => self.execute_get_clipboard(node).await,
+            "setClipboard" => self.execute_set_clipboard(node).await,
+            "copyFile" => self.execute_copy_file(node, false).await,
+            "moveFile" => self.execute_copy_file(node, true).await,
+            "renameFile" => self.execute_rename_file(node).await,
+            "deleteFile" => self.execute_delete_file(node).await,
+            "createDirectory" => self.execute_create_directory(node).await,
+            "fileInfo" => self.execute_file_info(node).await,
+            "waitFile" => self.execute_wait_file(node).await,
+            "createTempFile" => self.execute_create_temp_file(node).await,
             // Special nodes handled in execute_from_node
             "condition" | "loop" | "forEach" | "tryCatch" => Ok(()),
             _ => {
@@ -301,14 +349,39 @@ impl Executor {
             _ => ClickType::Single,
         };
 
-        // TODO: Get element from element library
+        // Recorded clicks on unrecognised elements only keep their coordinates
+        if !Self::has_element_target(node) {
+            if let Some((x, y)) = self.node_point(node).await? {
+                self.runtime
+                    .add_log(
+                        ExecutionLog::info(format!("Click ({}, {}) with type {:?}", x, y, click_type))
+                            .with_node(&node.id),
+                    )
+                    .await;
+
+                let point = UIElement::new(
+                    format!("({}, {})", x, y),
+                    "Point".to_string(),
+                    Rect { x, y, width: 0, height: 0 },
+                );
+                self.automation.click(&point, click_type).await?;
+                return Ok(());
+            }
+        }
+
+        let element = self
+            .resolve_desktop_element(node, Self::element_timeout(node))
+            .await?;
+
         self.runtime
             .add_log(
-                ExecutionLog::info(format!("Click with type {:?}", click_type))
+                ExecutionLog::info(format!("Click '{}' with type {:?}", element.name, click_type))
                     .with_node(&node.id),
             )
             .await;
 
+        self.automation.click(&element, click_type).await?;
+
         Ok(())
     }
 
@@ -319,14 +392,45 @@ impl Executor {
             .and_then(|v| v.as_str())
             .unwrap_or("");
 
+        let method = match node.data.get("inputMethod").and_then(|v| v.as_str()) {
+            Some("set") => InputMethod::Set,
+            _ => InputMethod::Type,
+        };
+
         let interpolated = self.runtime.interpolate(text).await;
 
+        // Without a target the text goes to the focused element
+        if !Self::has_element_target(node) {
+            let delay = node
+                .data
+                .get("typeDelay")
+                .and_then(|v| v.as_u64())
+                .unwrap_or(0);
+
+            self.runtime
+                .add_log(
+                    ExecutionLog::info(format!("Type text into focused element: {}", interpolated))
+                        .with_node(&node.id),
+                )
+                .await;
+
+            self.automation.type_text(&interpolated, delay).await?;
+            return Ok(());
+        }
+
+        let element = self
+            .resolve_desktop_element(node, Self::element_timeout(node))
+            .await?;
+
         self.runtime
             .add_log(
-                ExecutionLog::info(format!("Input text: {}", interpolated)).with_node(&node.id),
+                ExecutionLog::info(format!("Input text into '{}': {}", element.name, interpolated))
+                    .with_node(&node.id),
             )
             .await;
 
+        self.automation.input(&element, &interpolated, method).await?;
+
         Ok(())
     }
 
@@ -337,8 +441,10 @@ impl Executor {
             .and_then(|v| v.as_str())
             .unwrap_or("result");
 
-        // TODO: Get text from element
-        let text = "placeholder_text".to_string();
+        let element = self
+            .resolve_desktop_element(node, Self::element_timeout(node))
+            .await?;
+        let text = self.automation.get_text(&element).await?;
 
         self.runtime
             .set_variable(var_name, text.clone().into())
@@ -354,1782 +460,3271 @@ impl Executor {
         Ok(())
     }
 
-    async fn execute_delay(&self, node: &WorkflowNode) -> EngineResult<()> {
-        let delay_ms = node
-            .data
-            .get("delay")
-            .and_then(|v| v.as_u64())
-            .unwrap_or(1000);
+    async fn execute_scroll(&self, node: &WorkflowNode) -> EngineResult<()> {
+        let dx = self.node_number(node, "dx").await?.unwrap_or(0);
+        let dy = self.node_number(node, "dy").await?.unwrap_or(0);
+        let at = self.node_point(node).await?;
 
         self.runtime
             .add_log(
-                ExecutionLog::info(format!("Waiting for {}ms", delay_ms)).with_node(&node.id),
+                ExecutionLog::info(format!("Scroll ({}, {}) at {:?}", dx, dy, at))
+                    .with_node(&node.id),
             )
             .await;
 
-        tokio::time::sleep(tokio::time::Duration::from_millis(delay_ms)).await;
+        self.automation.scroll(dx, dy, at).await?;
 
         Ok(())
     }
 
-    async fn execute_log(&self, node: &WorkflowNode) -> EngineResult<()> {
-        let message = node
+    async fn execute_mouse_move(&self, node: &WorkflowNode) -> EngineResult<()> {
+        let smooth = node
             .data
-            .get("message")
-            .and_then(|v| v.as_str())
-            .unwrap_or("");
+            .get("smooth")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(true);
 
-        let interpolated = self.runtime.interpolate(message).await;
+        let (x, y) = self.node_point(node).await?.ok_or_else(|| {
+            EngineError::ExecutionFailed("No target element or coordinates configured".to_string())
+        })?;
 
         self.runtime
-            .add_log(ExecutionLog::info(interpolated).with_node(&node.id))
+            .add_log(ExecutionLog::info(format!("Move mouse to ({}, {})", x, y)).with_node(&node.id))
             .await;
 
+        self.automation.mouse_move(x, y, smooth).await?;
+
         Ok(())
     }
 
-    async fn execute_set_variable(&self, node: &WorkflowNode) -> EngineResult<()> {
-        let var_name = node
-            .data
-            .get("variableName")
-            .and_then(|v| v.as_str())
-            .unwrap_or("variable");
-
-        let value_str = node
-            .data
-            .get("value")
-            .and_then(|v| v.as_str())
-            .unwrap_or("");
-
-        let value_type = node
-            .data
-            .get("valueType")
-            .and_then(|v| v.as_str())
-            .unwrap_or("string");
-
-        // Interpolate the value string
-        let interpolated = self.runtime.interpolate(value_str).await;
-
-        // Parse value based on type
-        let value: VariableValue = match value_type {
-            "number" => interpolated
-                .parse::<f64>()
-                .map(VariableValue::Number)
-                .unwrap_or(VariableValue::String(interpolated.clone())),
-            "boolean" => VariableValue::Bool(
-                interpolated.to_lowercase() == "true" || interpolated == "1",
-            ),
-            "json" => serde_json::from_str(&interpolated)
-                .unwrap_or(VariableValue::String(interpolated.clone())),
-            _ => VariableValue::String(interpolated.clone()),
+    async fn execute_drag_drop(&self, node: &WorkflowNode) -> EngineResult<()> {
+        let from = self.node_point(node).await?.ok_or_else(|| {
+            EngineError::ExecutionFailed("No drag source configured".to_string())
+        })?;
+
+        // The drop target is a nested target under `dropTarget`, or else `toX`/`toY`
+        let dropped = match node.data.get("dropTarget").and_then(|v| v.as_object()) {
+            Some(data) => {
+                let target = WorkflowNode {
+                    id: node.id.clone(),
+                    node_type: node.node_type.clone(),
+                    position: node.position.clone(),
+                    data: data.clone().into_iter().collect(),
+                    label: node.label.clone(),
+                };
+                self.node_point(&target).await?
+            }
+            None => None,
         };
-
-        self.runtime.set_variable(var_name, value.clone()).await;
+        let to = match dropped {
+            Some(point) => Some(point),
+            None => self
+                .node_number(node, "toX")
+                .await?
+                .zip(self.node_number(node, "toY").await?),
+        };
+        let to = to.ok_or_else(|| {
+            EngineError::ExecutionFailed("No drop target configured".to_string())
+        })?;
 
         self.runtime
             .add_log(
-                ExecutionLog::info(format!(
-                    "Set variable '{}' = {}",
-                    var_name,
-                    value.to_string_value()
-                ))
-                .with_node(&node.id),
+                ExecutionLog::info(format!("Drag from {:?} to {:?}", from, to)).with_node(&node.id),
             )
             .await;
 
+        self.automation.drag(from, to).await?;
+
         Ok(())
     }
 
-    async fn execute_read_file(&self, node: &WorkflowNode) -> EngineResult<()> {
-        let file_path = node
-            .data
-            .get("filePath")
-            .and_then(|v| v.as_str())
-            .unwrap_or("");
-
-        let var_name = node
-            .data
-            .get("variableName")
-            .and_then(|v| v.as_str())
-            .unwrap_or("fileContent");
-
-        // Interpolate the path
-        let interpolated_path = self.runtime.interpolate(file_path).await;
+    async fn execute_activate_window(&self, node: &WorkflowNode) -> EngineResult<()> {
+        let window = self.resolve_window(node).await?;
 
         self.runtime
-            .add_log(
-                ExecutionLog::info(format!("Reading file: {}", interpolated_path))
-                    .with_node(&node.id),
-            )
+            .add_log(ExecutionLog::info(format!("Activate window '{}'", window.title)).with_node(&node.id))
             .await;
 
-        match self.file_automation.read_file(&interpolated_path).await {
-            Ok(content) => {
-                self.runtime
-                    .set_variable(var_name, VariableValue::String(content.clone()))
-                    .await;
-
-                self.runtime
-                    .add_log(
-                        ExecutionLog::info(format!(
-                            "File read successfully, {} bytes saved to '{}'",
-                            content.len(),
-                            var_name
-                        ))
-                        .with_node(&node.id),
-                    )
-                    .await;
-            }
-            Err(e) => {
-                self.runtime
-                    .add_log(
-                        ExecutionLog::error(format!("Failed to read file: {}", e))
-                            .with_node(&node.id),
-                    )
-                    .await;
-                return Err(EngineError::ExecutionFailed(format!(
-                    "Failed to read file: {}",
-                    e
-                )));
-            }
-        }
+        self.automation.activate_window(&window).await?;
 
         Ok(())
     }
 
-    async fn execute_write_file(&self, node: &WorkflowNode) -> EngineResult<()> {
-        let file_path = node
-            .data
-            .get("filePath")
-            .and_then(|v| v.as_str())
-            .unwrap_or("");
-
-        let content = node
+    async fn execute_wait_window(&self, node: &WorkflowNode) -> EngineResult<()> {
+        let timeout = node
             .data
-            .get("content")
-            .and_then(|v| v.as_str())
-            .unwrap_or("");
+            .get("timeout")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(30000);
 
-        let write_mode = node
+        let wait_condition = node
             .data
-            .get("writeMode")
+            .get("waitCondition")
             .and_then(|v| v.as_str())
-            .unwrap_or("overwrite");
+            .unwrap_or("open");
 
-        // Interpolate path and content
-        let interpolated_path = self.runtime.interpolate(file_path).await;
-        let interpolated_content = self.runtime.interpolate(content).await;
+        let query = self.single_window_query(node).await?;
 
         self.runtime
             .add_log(
                 ExecutionLog::info(format!(
-                    "Writing to file: {} (mode: {})",
-                    interpolated_path, write_mode
+                    "Waiting for {} (condition: {}, timeout: {}ms)",
+                    query, wait_condition, timeout
                 ))
                 .with_node(&node.id),
             )
             .await;
 
-        let final_content = if write_mode == "append" {
-            // Read existing content and append
-            let existing = self
-                .file_automation
-                .read_file(&interpolated_path)
-                .await
-                .unwrap_or_default();
-            format!("{}{}", existing, interpolated_content)
+        if wait_condition == "closed" {
+            let deadline = std::time::Instant::now() + std::time::Duration::from_millis(timeout);
+            while !self.automation.find_windows(&query).await?.is_empty() {
+                if std::time::Instant::now() >= deadline {
+                    return Err(AutomationError::Timeout(format!(
+                        "{} still open after {}ms",
+                        query, timeout
+                    ))
+                    .into());
+                }
+                tokio::time::sleep(tokio::time::Duration::from_millis(200)).await;
+            }
         } else {
-            interpolated_content
+            let window = self.automation.wait_window(&query, timeout).await?;
+            if let Some(output_variable) = node
+                .data
+                .get("outputVariable")
+                .and_then(|v| v.as_str())
+                .filter(|v| !v.is_empty())
+            {
+                self.save_json(output_variable, &window).await?;
+            }
+        }
+
+        self.runtime
+            .add_log(ExecutionLog::info("Window wait completed").with_node(&node.id))
+            .await;
+
+        Ok(())
+    }
+
+    async fn execute_set_window_state(&self, node: &WorkflowNode) -> EngineResult<()> {
+        let state = match node.data.get("state").and_then(|v| v.as_str()) {
+            Some(state) if !state.is_empty() => Some(
+                serde_json::from_value::<WindowState>(serde_json::Value::String(state.to_string()))
+                    .map_err(|_| {
+                        AutomationError::InvalidParameter(format!("Unknown window state: {}", state))
+                    })?,
+            ),
+            _ => None,
         };
 
-        match self
-            .file_automation
-            .write_file(&interpolated_path, &final_content)
-            .await
-        {
-            Ok(()) => {
-                self.runtime
-                    .add_log(
-                        ExecutionLog::info(format!(
-                            "File written successfully: {}",
-                            interpolated_path
-                        ))
+        let window = self.resolve_window(node).await?;
+
+        if let Some(state) = state {
+            self.runtime
+                .add_log(
+                    ExecutionLog::info(format!("Set window '{}' to {:?}", window.title, state))
                         .with_node(&node.id),
-                    )
-                    .await;
-            }
-            Err(e) => {
-                self.runtime
-                    .add_log(
-                        ExecutionLog::error(format!("Failed to write file: {}", e))
-                            .with_node(&node.id),
-                    )
-                    .await;
-                return Err(EngineError::ExecutionFailed(format!(
-                    "Failed to write file: {}",
-                    e
-                )));
-            }
+                )
+                .await;
+            self.automation.set_window_state(&window, state).await?;
+        }
+
+        // Bounds fields left empty keep their current value
+        let bounds = Rect {
+            x: self.node_number(node, "x").await?.unwrap_or(window.bounds.x),
+            y: self.node_number(node, "y").await?.unwrap_or(window.bounds.y),
+            width: self.node_number(node, "width").await?.unwrap_or(window.bounds.width),
+            height: self.node_number(node, "height").await?.unwrap_or(window.bounds.height),
+        };
+        if bounds != window.bounds {
+            self.runtime
+                .add_log(
+                    ExecutionLog::info(format!("Move window '{}' to {:?}", window.title, bounds))
+                        .with_node(&node.id),
+                )
+                .await;
+            self.automation.set_window_bounds(&window, bounds).await?;
         }
 
         Ok(())
     }
 
-    async fn execute_condition_node(&self, node: &WorkflowNode) -> EngineResult<()> {
-        // Evaluate the condition
-        let result = self.evaluate_condition(node).await;
-
-        let branch = if result { "true" } else { "false" };
+    async fn execute_close_window(&self, node: &WorkflowNode) -> EngineResult<()> {
+        let window = self.resolve_window(node).await?;
 
         self.runtime
-            .add_log(
-                ExecutionLog::info(format!("Condition evaluated to: {}", branch))
-                    .with_node(&node.id),
-            )
+            .add_log(ExecutionLog::info(format!("Close window '{}'", window.title)).with_node(&node.id))
             .await;
 
-        // Execute nodes connected to the appropriate branch
-        let next_nodes = self.workflow.find_next_nodes_by_handle(&node.id, branch);
-        for next_node in next_nodes {
-            self.execute_from_node(next_node).await?;
-        }
+        self.automation.close_window(&window).await?;
 
         Ok(())
     }
 
-    async fn evaluate_condition(&self, node: &WorkflowNode) -> bool {
-        let operator = node
-            .data
-            .get("operator")
-            .and_then(|v| v.as_str())
-            .unwrap_or("==");
+    async fn execute_list_windows(&self, node: &WorkflowNode) -> EngineResult<()> {
+        let query = self.window_query(node).await?;
+        let windows = self.automation.find_windows(&query).await?;
 
-        let left_operand = node
-            .data
-            .get("leftOperand")
-            .and_then(|v| v.as_str())
-            .unwrap_or("");
+        self.runtime
+            .add_log(
+                ExecutionLog::info(format!("Found {} windows matching {}", windows.len(), query))
+                    .with_node(&node.id),
+            )
+            .await;
 
-        let right_operand = node
+        let output_variable = node
             .data
-            .get("rightOperand")
+            .get("outputVariable")
             .and_then(|v| v.as_str())
-            .unwrap_or("");
+            .unwrap_or("windows");
+        self.save_json(output_variable, &windows).await
+    }
 
-        // Interpolate operands
-        let left = self.runtime.interpolate(left_operand).await;
-        let right = self.runtime.interpolate(right_operand).await;
+    /// Window query from a node's `title` and `process` patterns
+    async fn window_query(&self, node: &WorkflowNode) -> EngineResult<WindowQuery> {
+        let mut patterns = Vec::with_capacity(2);
+        for key in ["title", "process"] {
+            let pattern = match node.data.get(key).and_then(|v| v.as_str()) {
+                Some(pattern) => Some(self.runtime.interpolate(pattern).await),
+                None => None,
+            };
+            patterns.push(pattern);
+        }
+        Ok(WindowQuery::new(patterns[0].as_deref(), patterns[1].as_deref())?)
+    }
 
-        match operator {
-            "==" => left == right,
-            "!=" => left != right,
-            ">" => {
-                let l: f64 = left.parse().unwrap_or(0.0);
-                let r: f64 = right.parse().unwrap_or(0.0);
-                l > r
-            }
-            "<" => {
-                let l: f64 = left.parse().unwrap_or(0.0);
-                let r: f64 = right.parse().unwrap_or(0.0);
-                l < r
-            }
-            ">=" => {
-                let l: f64 = left.parse().unwrap_or(0.0);
-                let r: f64 = right.parse().unwrap_or(0.0);
-                l >= r
-            }
-            "<=" => {
-                let l: f64 = left.parse().unwrap_or(0.0);
-                let r: f64 = right.parse().unwrap_or(0.0);
-                l <= r
-            }
-            "contains" => left.contains(&right),
-            "isEmpty" => left.is_empty(),
-            "isNotEmpty" => !left.is_empty(),
-            _ => false,
+    /// Query of a node acting on a single window, which needs a criterion
+    async fn single_window_query(&self, node: &WorkflowNode) -> EngineResult<WindowQuery> {
+        let query = self.window_query(node).await?;
+        if query.is_empty() {
+            return Err(AutomationError::InvalidParameter(
+                "No window title or process configured".to_string(),
+            )
+            .into());
         }
+        Ok(query)
     }
 
-    async fn execute_loop_node(&self, node: &WorkflowNode) -> EngineResult<()> {
-        let loop_type = node
+    /// First window matching a node's query, waiting up to its timeout
+    async fn resolve_window(&self, node: &WorkflowNode) -> EngineResult<WindowInfo> {
+        let query = self.single_window_query(node).await?;
+        Ok(self
+            .automation
+            .wait_window(&query, Self::element_timeout(node))
+            .await?)
+    }
+
+    /// Store a value as JSON in a variable
+    async fn save_json<T: Serialize + ?Sized>(&self, variable: &str, value: &T) -> EngineResult<()> {
+        let json = serde_json::to_string(value)
+            .map_err(|e| EngineError::ExecutionFailed(format!("Failed to serialize output: {}", e)))?;
+        self.runtime
+            .set_variable(variable, VariableValue::String(json))
+            .await;
+        Ok(())
+    }
+
+    async fn execute_get_clipboard(&self, node: &WorkflowNode) -> EngineResult<()> {
+        let format = node
             .data
-            .get("loopType")
+            .get("format")
             .and_then(|v| v.as_str())
-            .unwrap_or("count");
+            .unwrap_or("text");
 
-        let index_variable = node
+        let output_variable = node
             .data
-            .get("indexVariable")
+            .get("outputVariable")
             .and_then(|v| v.as_str())
-            .unwrap_or("index");
+            .unwrap_or("clipboard");
 
-        match loop_type {
-            "count" => {
-                let count = node
-                    .data
-                    .get("count")
-                    .and_then(|v| v.as_u64())
-                    .unwrap_or(1) as usize;
+        let copy_first = node
+            .data
+            .get("copyFirst")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
 
-                self.runtime
-                    .add_log(
-                        ExecutionLog::info(format!("Starting count loop: {} iterations", count))
-                            .with_node(&node.id),
-                    )
-                    .await;
+        let restore = node
+            .data
+            .get("restore")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
 
-                for i in 0..count {
-                    // Set the index variable
-                    self.runtime
-                        .set_variable(index_variable, VariableValue::Number(i as f64))
-                        .await;
+        let previous = self.automation.get_clipboard().await?;
+        let content = if copy_first {
+            let copied = self.copy_selection(&previous).await;
 
-                    self.runtime
-                        .add_log(
-                            ExecutionLog::info(format!("Loop iteration {}/{}", i + 1, count))
-                                .with_node(&node.id),
-                        )
-                        .await;
+            // Put the previous content back even if copying failed
+            let restored = if restore {
+                self.automation.set_clipboard(&previous).await
+            } else {
+                Ok(())
+            };
+            let content = copied?;
+            restored?;
+            content
+        } else {
+            previous
+        };
 
-                    // Execute body nodes
-                    let body_nodes = self.workflow.find_next_nodes_by_handle(&node.id, "body");
-                    for body_node in body_nodes {
-                        self.execute_from_node(body_node).await?;
+        let value = match format {
+            "text" => content.text.unwrap_or_default(),
+            "html" => content.html.unwrap_or_default(),
+            "image" => {
+                let png = content.png.ok_or_else(|| {
+                    AutomationError::ElementNotFound("Clipboard holds no image".to_string())
+                })?;
+                // Images go to a file when a path is given, else into the variable as base64
+                match node.data.get("filePath").and_then(|v| v.as_str()) {
+                    Some(path) if !path.is_empty() => {
+                        let path = self.runtime.interpolate(path).await;
+                        tokio::fs::write(&path, &png).await.map_err(|e| {
+                            EngineError::ExecutionFailed(format!("Failed to write image: {}", e))
+                        })?;
+                        path
                     }
+                    _ => BASE64.encode(&png),
                 }
             }
-            "while" => {
-                let condition_str = node
-                    .data
-                    .get("condition")
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("false");
-
-                self.runtime
-                    .add_log(
-                        ExecutionLog::info(format!("Starting while loop: {}", condition_str))
-                            .with_node(&node.id),
-                    )
-                    .await;
-
-                let mut iteration = 0;
-                let max_iterations = 10000; // Safety limit
-
-                while iteration < max_iterations {
-                    // Evaluate condition
-                    let interpolated = self.runtime.interpolate(condition_str).await;
-                    let condition_result = self.evaluate_simple_expression(&interpolated);
+            other => {
+                return Err(AutomationError::InvalidParameter(format!(
+                    "Unknown clipboard format: {}",
+                    other
+                ))
+                .into())
+            }
+        };
 
-                    if !condition_result {
-                        break;
-                    }
+        self.runtime
+            .set_variable(output_variable, VariableValue::String(value))
+            .await;
 
-                    self.runtime
-                        .set_variable(index_variable, VariableValue::Number(iteration as f64))
-                        .await;
+        self.runtime
+            .add_log(
+                ExecutionLog::info(format!(
+                    "Clipboard {} saved to '{}'",
+                    format, output_variable
+                ))
+                .with_node(&node.id),
+            )
+            .await;
 
-                    self.runtime
-                        .add_log(
-                            ExecutionLog::info(format!("While loop iteration {}", iteration + 1))
-                                .with_node(&node.id),
-                        )
-                        .await;
+        Ok(())
+    }
 
-                    // Execute body nodes
-                    let body_nodes = self.workflow.find_next_nodes_by_handle(&node.id, "body");
-                    for body_node in body_nodes {
-                        self.execute_from_node(body_node).await?;
-                    }
+    /// Press the copy shortcut and read what it put on the clipboard
+    async fn copy_selection(&self, previous: &ClipboardContent) -> EngineResult<ClipboardContent> {
+        self.automation.press_keys(&Self::clipboard_combo('c')).await?;
 
-                    iteration += 1;
-                }
+        // Wait for the copied data, which may equal what was there before
+        let deadline = std::time::Instant::now() + std::time::Duration::from_millis(CLIPBOARD_COPY_TIMEOUT_MS);
+        loop {
+            let content = self.automation.get_clipboard().await?;
+            if content != *previous || std::time::Instant::now() >= deadline {
+                return Ok(content);
             }
-            _ => {}
-        }
-
-        // Execute done nodes
-        let done_nodes = self.workflow.find_next_nodes_by_handle(&node.id, "done");
-        for done_node in done_nodes {
-            self.execute_from_node(done_node).await?;
+            tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
         }
-
-        Ok(())
     }
 
-    async fn execute_foreach_node(&self, node: &WorkflowNode) -> EngineResult<()> {
-        let list_variable = node
+    async fn execute_set_clipboard(&self, node: &WorkflowNode) -> EngineResult<()> {
+        let format = node
             .data
-            .get("listVariable")
+            .get("format")
             .and_then(|v| v.as_str())
-            .unwrap_or("");
+            .unwrap_or("text");
 
-        let item_variable = node
+        let paste = node
             .data
-            .get("itemVariable")
-            .and_then(|v| v.as_str())
-            .unwrap_or("item");
+            .get("paste")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
 
-        let index_variable = node
+        let restore = node
             .data
-            .get("indexVariable")
+            .get("restore")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+
+        let value = node
+            .data
+            .get("value")
             .and_then(|v| v.as_str())
-            .unwrap_or("index");
+            .unwrap_or("");
+        let value = self.runtime.interpolate(value).await;
+
+        let content = match format {
+            "text" => ClipboardContent::text(value),
+            "html" => ClipboardContent {
+                html: Some(value),
+                ..Default::default()
+            },
+            "image" => {
+                let path = node
+                    .data
+                    .get("filePath")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("");
+                let path = self.runtime.interpolate(path).await;
+                let png = tokio::fs::read(&path).await.map_err(|e| {
+                    EngineError::ExecutionFailed(format!("Failed to read image {}: {}", path, e))
+                })?;
+                ClipboardContent {
+                    png: Some(png),
+                    ..Default::default()
+                }
+            }
+            other => {
+                return Err(AutomationError::InvalidParameter(format!(
+                    "Unknown clipboard format: {}",
+                    other
+                ))
+                .into())
+            }
+        };
 
-        // Get the list from variables
-        let list = self.runtime.get_variable(list_variable).await;
+        let previous = if paste && restore {
+            Some(self.automation.get_clipboard().await?)
+        } else {
+            None
+        };
 
-        if let Some(VariableValue::List(items)) = list {
-            let items = items.clone();
+        let result: EngineResult<()> = async {
+            self.automation.set_clipboard(&content).await?;
             self.runtime
-                .add_log(
-                    ExecutionLog::info(format!(
-                        "Starting forEach loop: {} items",
-                        items.len()
-                    ))
-                    .with_node(&node.id),
-                )
+                .add_log(ExecutionLog::info(format!("Clipboard set ({})", format)).with_node(&node.id))
                 .await;
 
-            for (i, item) in items.iter().enumerate() {
-                self.runtime
-                    .set_variable(index_variable, VariableValue::Number(i as f64))
-                    .await;
-                self.runtime
-                    .set_variable(item_variable, item.clone())
-                    .await;
-
-                self.runtime
-                    .add_log(
-                        ExecutionLog::info(format!(
-                            "ForEach iteration {}/{}",
-                            i + 1,
-                            items.len()
-                        ))
-                        .with_node(&node.id),
-                    )
-                    .await;
-
-                // Execute body nodes
-                let body_nodes = self.workflow.find_next_nodes_by_handle(&node.id, "body");
-                for body_node in body_nodes {
-                    self.execute_from_node(body_node).await?;
-                }
+            if paste {
+                self.automation.press_keys(&Self::clipboard_combo('v')).await?;
             }
-        } else {
-            self.runtime
-                .add_log(
-                    ExecutionLog::warn(format!(
-                        "Variable '{}' is not a list or not found",
-                        list_variable
-                    ))
-                    .with_node(&node.id),
-                )
-                .await;
+            Ok(())
         }
+        .await;
 
-        // Execute done nodes
-        let done_nodes = self.workflow.find_next_nodes_by_handle(&node.id, "done");
-        for done_node in done_nodes {
-            self.execute_from_node(done_node).await?;
-        }
+        // Put the previous content back on every path, once the target had time to paste
+        let restored = match previous {
+            Some(previous) => {
+                if result.is_ok() {
+                    tokio::time::sleep(tokio::time::Duration::from_millis(CLIPBOARD_PASTE_DELAY_MS)).await;
+                }
+                self.automation.set_clipboard(&previous).await
+            }
+            None => Ok(()),
+        };
+        result?;
+        restored?;
 
         Ok(())
     }
 
-    fn evaluate_simple_expression(&self, expr: &str) -> bool {
-        // Simple expression evaluator for conditions like "true", "false", "1", "0"
-        let expr = expr.trim().to_lowercase();
-        expr == "true" || expr == "1" || expr == "yes"
+    /// Copy or paste shortcut of the platform, like Ctrl+C or Cmd+C
+    fn clipboard_combo(key: char) -> KeyCombo {
+        let modifier = if cfg!(target_os = "macos") { Key::Meta } else { Key::Ctrl };
+        KeyCombo::new(vec![modifier, Key::Char(key)])
     }
 
-    async fn execute_try_catch_node(&self, node: &WorkflowNode) -> EngineResult<()> {
-        let error_variable = node
-            .data
-            .get("errorVariable")
-            .and_then(|v| v.as_str())
-            .unwrap_or("error");
-
-        let max_retries = node
-            .data
-            .get("maxRetries")
-            .and_then(|v| v.as_u64())
-            .unwrap_or(0) as usize;
-
-        let retry_delay = node
+    async fn execute_delay(&self, node: &WorkflowNode) -> EngineResult<()> {
+        let delay_ms = node
             .data
-            .get("retryDelay")
+            .get("delay")
             .and_then(|v| v.as_u64())
             .unwrap_or(1000);
 
         self.runtime
             .add_log(
-                ExecutionLog::info(format!(
-                    "Starting try-catch block (max retries: {})",
-                    max_retries
-                ))
-                .with_node(&node.id),
+                ExecutionLog::info(format!("Waiting for {}ms", delay_ms)).with_node(&node.id),
             )
             .await;
 
-        let try_nodes = self.workflow.find_next_nodes_by_handle(&node.id, "try");
-        let mut last_error: Option<String> = None;
-
-        // Try to execute with retries
-        for attempt in 0..=max_retries {
-            if attempt > 0 {
-                self.runtime
-                    .add_log(
-                        ExecutionLog::info(format!("Retry attempt {}/{}", attempt, max_retries))
-                            .with_node(&node.id),
-                    )
-                    .await;
-                tokio::time::sleep(tokio::time::Duration::from_millis(retry_delay)).await;
-            }
-
-            let mut success = true;
-            for try_node in &try_nodes {
-                if let Err(e) = self.execute_from_node(try_node).await {
-                    last_error = Some(e.to_string());
-                    success = false;
-                    break;
-                }
-            }
-
-            if success {
-                // Execute finally nodes and return
-                let finally_nodes = self.workflow.find_next_nodes_by_handle(&node.id, "finally");
-                for finally_node in finally_nodes {
-                    self.execute_from_node(finally_node).await?;
-                }
-                return Ok(());
-            }
-        }
-
-        // All retries failed, execute catch block
-        if let Some(error) = &last_error {
-            self.runtime
-                .set_variable(error_variable, VariableValue::String(error.clone()))
-                .await;
-
-            self.runtime
-                .add_log(
-                    ExecutionLog::warn(format!("Caught error: {}", error)).with_node(&node.id),
-                )
-                .await;
-        }
-
-        let catch_nodes = self.workflow.find_next_nodes_by_handle(&node.id, "catch");
-        for catch_node in catch_nodes {
-            self.execute_from_node(catch_node).await?;
-        }
-
-        // Execute finally nodes
-        let finally_nodes = self.workflow.find_next_nodes_by_handle(&node.id, "finally");
-        for finally_node in finally_nodes {
-            self.execute_from_node(finally_node).await?;
-        }
+        tokio::time::sleep(tokio::time::Duration::from_millis(delay_ms)).await;
 
         Ok(())
     }
 
-    async fn execute_wait_element(&self, node: &WorkflowNode) -> EngineResult<()> {
-        let timeout = node
-            .data
-            .get("timeout")
-            .and_then(|v| v.as_u64())
-            .unwrap_or(30000);
-
-        let wait_condition = node
+    async fn execute_log(&self, node: &WorkflowNode) -> EngineResult<()> {
+        let message = node
             .data
-            .get("waitCondition")
+            .get("message")
             .and_then(|v| v.as_str())
-            .unwrap_or("visible");
-
-        self.runtime
-            .add_log(
-                ExecutionLog::info(format!(
-                    "Waiting for element (condition: {}, timeout: {}ms)",
-                    wait_condition, timeout
-                ))
-                .with_node(&node.id),
-            )
-            .await;
+            .unwrap_or("");
 
-        // TODO: Implement actual element waiting with UI automation
-        // For now, just simulate waiting
-        tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+        let interpolated = self.runtime.interpolate(message).await;
 
         self.runtime
-            .add_log(ExecutionLog::info("Element wait completed").with_node(&node.id))
+            .add_log(ExecutionLog::info(interpolated).with_node(&node.id))
             .await;
 
         Ok(())
     }
 
-    async fn execute_hotkey(&self, node: &WorkflowNode) -> EngineResult<()> {
-        let keys = node
+    async fn execute_set_variable(&self, node: &WorkflowNode) -> EngineResult<()> {
+        let var_name = node
             .data
-            .get("keys")
+            .get("variableName")
             .and_then(|v| v.as_str())
-            .unwrap_or("");
+            .unwrap_or("variable");
 
-        let key = node
+        let value_str = node
             .data
-            .get("key")
+            .get("value")
             .and_then(|v| v.as_str())
             .unwrap_or("");
 
-        let modifiers: Vec<String> = node
+        let value_type = node
             .data
-            .get("modifiers")
-            .and_then(|v| v.as_array())
-            .map(|arr| {
-                arr.iter()
-                    .filter_map(|v| v.as_str().map(String::from))
-                    .collect()
-            })
-            .unwrap_or_default();
+            .get("valueType")
+            .and_then(|v| v.as_str())
+            .unwrap_or("string");
 
-        let key_combo = if keys.is_empty() {
-            if modifiers.is_empty() {
-                key.to_string()
-            } else {
-                format!("{}+{}", modifiers.join("+"), key)
-            }
-        } else {
-            keys.to_string()
+        // Interpolate the value string
+        let interpolated = self.runtime.interpolate(value_str).await;
+
+        // Parse value based on type
+        let value: VariableValue = match value_type {
+            "number" => interpolated
+                .parse::<f64>()
+                .map(VariableValue::Number)
+                .unwrap_or(VariableValue::String(interpolated.clone())),
+            "boolean" => VariableValue::Bool(
+                interpolated.to_lowercase() == "true" || interpolated == "1",
+            ),
+            "json" => serde_json::from_str(&interpolated)
+                .unwrap_or(VariableValue::String(interpolated.clone())),
+            _ => VariableValue::String(interpolated.clone()),
         };
 
+        self.runtime.set_variable(var_name, value.clone()).await;
+
         self.runtime
             .add_log(
-                ExecutionLog::info(format!("Pressing hotkey: {}", key_combo)).with_node(&node.id),
+                ExecutionLog::info(format!(
+                    "Set variable '{}' = {}",
+                    var_name,
+                    value.to_string_value()
+                ))
+                .with_node(&node.id),
             )
             .await;
 
-        // Try to use browser keyboard if there's an active browser session
-        let browser_session_id = self
-            .runtime
-            .get_variable("_browser_session_id")
-            .await
-            .and_then(|v| {
-                if let VariableValue::String(s) = v {
-                    Some(s)
-                } else {
-                    None
-                }
-            });
-
-        if let Some(session_id) = browser_session_id {
-            // Use browser's keyboard input
-            self.web_automation
-                .press_key(&session_id, &key_combo)
-                .await
-                .map_err(|e| EngineError::ExecutionFailed(format!("Failed to press key: {}", e)))?;
-        } else {
-            // TODO: Implement desktop keyboard simulation
-            log::warn!("Desktop keyboard simulation not yet implemented");
-        }
-
         Ok(())
     }
 
-    async fn execute_screenshot(&self, node: &WorkflowNode) -> EngineResult<()> {
+    async fn execute_read_file(&self, node: &WorkflowNode) -> EngineResult<()> {
         let file_path = node
             .data
             .get("filePath")
             .and_then(|v| v.as_str())
-            .unwrap_or("screenshot.png");
+            .unwrap_or("");
 
-        let screenshot_type = node
+        let var_name = node
             .data
-            .get("screenshotType")
+            .get("variableName")
             .and_then(|v| v.as_str())
-            .unwrap_or("fullPage");
+            .unwrap_or("fileContent");
 
+        // Interpolate the path
         let interpolated_path = self.runtime.interpolate(file_path).await;
 
         self.runtime
             .add_log(
-                ExecutionLog::info(format!(
-                    "Taking screenshot (type: {}) to: {}",
-                    screenshot_type, interpolated_path
-                ))
-                .with_node(&node.id),
+                ExecutionLog::info(format!("Reading file: {}", interpolated_path))
+                    .with_node(&node.id),
             )
             .await;
 
-        // TODO: Implement actual screenshot capture
-        // For now, just log the action
+        match self.file_automation.read_file(&interpolated_path).await {
+            Ok(content) => {
+                self.runtime
+                    .set_variable(var_name, VariableValue::String(content.clone()))
+                    .await;
 
-        self.runtime
-            .add_log(ExecutionLog::info("Screenshot saved").with_node(&node.id))
-            .await;
+                self.runtime
+                    .add_log(
+                        ExecutionLog::info(format!(
+                            "File read successfully, {} bytes saved to '{}'",
+                            content.len(),
+                            var_name
+                        ))
+                        .with_node(&node.id),
+                    )
+                    .await;
+            }
+            Err(e) => {
+                self.runtime
+                    .add_log(
+                        ExecutionLog::error(format!("Failed to read file: {}", e))
+                            .with_node(&node.id),
+                    )
+                    .await;
+                return Err(EngineError::ExecutionFailed(format!(
+                    "Failed to read file: {}",
+                    e
+                )));
+            }
+        }
 
         Ok(())
     }
 
-    async fn execute_open_browser(&self, node: &WorkflowNode) -> EngineResult<()> {
-        let browser_type = node
+    async fn execute_write_file(&self, node: &WorkflowNode) -> EngineResult<()> {
+        let file_path = node
             .data
-            .get("browserType")
+            .get("filePath")
             .and_then(|v| v.as_str())
-            .unwrap_or("chrome");
-
-        let headless = node
-            .data
-            .get("headless")
-            .and_then(|v| v.as_bool())
-            .unwrap_or(false);
+            .unwrap_or("");
 
-        let initial_url = node
+        let content = node
             .data
-            .get("initialUrl")
+            .get("content")
             .and_then(|v| v.as_str())
             .unwrap_or("");
 
-        let browser_variable = node
+        let write_mode = node
             .data
-            .get("browserVariable")
+            .get("writeMode")
             .and_then(|v| v.as_str())
-            .unwrap_or("browser");
+            .unwrap_or("overwrite");
 
-        self.runtime
+        // Interpolate path and content
+        let interpolated_path = self.runtime.interpolate(file_path).await;
+        let interpolated_content = self.runtime.interpolate(content).await;
+
+        self.runtime
             .add_log(
                 ExecutionLog::info(format!(
-                    "Opening {} browser (headless: {})",
-                    browser_type, headless
+                    "Writing to file: {} (mode: {})",
+                    interpolated_path, write_mode
                 ))
                 .with_node(&node.id),
             )
             .await;
 
-        // Create browser session with unique ID
-        let session_id = format!("{}_{}", browser_variable, uuid::Uuid::new_v4());
-
-        let options = BrowserOptions {
-            headless,
-            browser_path: None,
-            user_data_dir: None,
-            window_size: Some((1280, 800)),
+        let final_content = if write_mode == "append" {
+            // Read existing content and append
+            let existing = self
+                .file_automation
+                .read_file(&interpolated_path)
+                .await
+                .unwrap_or_default();
+            format!("{}{}", existing, interpolated_content)
+        } else {
+            interpolated_content
         };
 
-        // Open browser using Chrome DevTools Protocol
-        self.web_automation
-            .open_browser(&session_id, options)
+        match self
+            .file_automation
+            .write_file(&interpolated_path, &final_content)
             .await
-            .map_err(|e| EngineError::ExecutionFailed(format!("Failed to open browser: {}", e)))?;
-
-        // Store session ID in variable for later use
-        self.runtime
-            .set_variable(browser_variable, VariableValue::String(session_id.clone()))
-            .await;
-
-        // Also store in a special internal variable for hotkey/other nodes to find
-        self.runtime
-            .set_variable("_browser_session_id", VariableValue::String(session_id.clone()))
-            .await;
-
-        // Navigate to initial URL if provided
-        if !initial_url.is_empty() {
-            let interpolated_url = self.runtime.interpolate(initial_url).await;
-            self.runtime
-                .add_log(
-                    ExecutionLog::info(format!("Navigating to: {}", interpolated_url))
+        {
+            Ok(()) => {
+                self.runtime
+                    .add_log(
+                        ExecutionLog::info(format!(
+                            "File written successfully: {}",
+                            interpolated_path
+                        ))
                         .with_node(&node.id),
-                )
-                .await;
-
-            self.web_automation
-                .navigate(&session_id, &interpolated_url)
-                .await
-                .map_err(|e| EngineError::ExecutionFailed(format!("Failed to navigate: {}", e)))?;
+                    )
+                    .await;
+            }
+            Err(e) => {
+                self.runtime
+                    .add_log(
+                        ExecutionLog::error(format!("Failed to write file: {}", e))
+                            .with_node(&node.id),
+                    )
+                    .await;
+                return Err(EngineError::ExecutionFailed(format!(
+                    "Failed to write file: {}",
+                    e
+                )));
+            }
         }
 
-        self.runtime
-            .add_log(ExecutionLog::info("Browser opened successfully").with_node(&node.id))
-            .await;
-
         Ok(())
     }
 
-    async fn execute_navigate(&self, node: &WorkflowNode) -> EngineResult<()> {
-        let url = node
-            .data
-            .get("url")
-            .and_then(|v| v.as_str())
-            .unwrap_or("");
-
-        let wait_until = node
-            .data
-            .get("waitUntil")
-            .and_then(|v| v.as_str())
-            .unwrap_or("load");
-
-        let browser_variable = node
-            .data
-            .get("browserVariable")
-            .and_then(|v| v.as_str())
-            .unwrap_or("browser");
+    async fn execute_condition_node(&self, node: &WorkflowNode) -> EngineResult<()> {
+        // Evaluate the condition
+        let result = self.evaluate_condition(node).await;
 
-        let interpolated_url = self.runtime.interpolate(url).await;
+        let branch = if result { "true" } else { "false" };
 
         self.runtime
             .add_log(
-                ExecutionLog::info(format!(
-                    "Navigating to: {} (wait: {})",
-                    interpolated_url, wait_until
-                ))
-                .with_node(&node.id),
+                ExecutionLog::info(format!("Condition evaluated to: {}", branch))
+                    .with_node(&node.id),
             )
             .await;
 
-        // Get browser session ID from variable
-        let session_id = self
-            .runtime
-            .get_variable(browser_variable)
-            .await
-            .and_then(|v| {
-                if let VariableValue::String(s) = v {
-                    Some(s)
-                } else {
-                    None
-                }
-            })
-            .ok_or_else(|| {
-                EngineError::ExecutionFailed(format!(
-                    "Browser session '{}' not found. Please open a browser first.",
-                    browser_variable
-                ))
-            })?;
-
-        // Navigate using Chrome DevTools Protocol
-        self.web_automation
-            .navigate(&session_id, &interpolated_url)
-            .await
-            .map_err(|e| EngineError::ExecutionFailed(format!("Failed to navigate: {}", e)))?;
-
-        self.runtime
-            .add_log(ExecutionLog::info("Navigation completed").with_node(&node.id))
-            .await;
+        // Execute nodes connected to the appropriate branch
+        let next_nodes = self.workflow.find_next_nodes_by_handle(&node.id, branch);
+        for next_node in next_nodes {
+            self.execute_from_node(next_node).await?;
+        }
 
         Ok(())
     }
 
-    async fn execute_web_click(&self, node: &WorkflowNode) -> EngineResult<()> {
-        let browser_variable = node
+    async fn evaluate_condition(&self, node: &WorkflowNode) -> bool {
+        let operator = node
             .data
-            .get("browserVariable")
+            .get("operator")
             .and_then(|v| v.as_str())
-            .unwrap_or("browser");
+            .unwrap_or("==");
 
-        let selector = node
+        let left_operand = node
             .data
-            .get("selector")
+            .get("leftOperand")
             .and_then(|v| v.as_str())
             .unwrap_or("");
 
-        self.runtime
-            .add_log(
-                ExecutionLog::info(format!("Web clicking element: {}", selector))
-                    .with_node(&node.id),
-            )
-            .await;
-
-        // Get browser session ID from variable
-        let session_id = self.get_browser_session(browser_variable).await?;
-
-        // Click using Chrome DevTools Protocol
-        self.web_automation
-            .click(&session_id, selector)
-            .await
-            .map_err(|e| EngineError::ExecutionFailed(format!("Failed to click: {}", e)))?;
+        let right_operand = node
+            .data
+            .get("rightOperand")
+            .and_then(|v| v.as_str())
+            .unwrap_or("");
 
-        self.runtime
-            .add_log(ExecutionLog::info("Web click completed").with_node(&node.id))
-            .await;
+        // Interpolate operands
+        let left = self.runtime.interpolate(left_operand).await;
+        let right = self.runtime.interpolate(right_operand).await;
 
-        Ok(())
+        match operator {
+            "==" => left == right,
+            "!=" => left != right,
+            ">" => {
+                let l: f64 = left.parse().unwrap_or(0.0);
+                let r: f64 = right.parse().unwrap_or(0.0);
+                l > r
+            }
+            "<" => {
+                let l: f64 = left.parse().unwrap_or(0.0);
+                let r: f64 = right.parse().unwrap_or(0.0);
+                l < r
+            }
+            ">=" => {
+                let l: f64 = left.parse().unwrap_or(0.0);
+                let r: f64 = right.parse().unwrap_or(0.0);
+                l >= r
+            }
+            "<=" => {
+                let l: f64 = left.parse().unwrap_or(0.0);
+                let r: f64 = right.parse().unwrap_or(0.0);
+                l <= r
+            }
+            "contains" => left.contains(&right),
+            "isEmpty" => left.is_empty(),
+            "isNotEmpty" => !left.is_empty(),
+            _ => false,
+        }
     }
 
-    async fn execute_web_input(&self, node: &WorkflowNode) -> EngineResult<()> {
-        let browser_variable = node
-            .data
-            .get("browserVariable")
-            .and_then(|v| v.as_str())
-            .unwrap_or("browser");
-
-        let selector = node
+    async fn execute_loop_node(&self, node: &WorkflowNode) -> EngineResult<()> {
+        let loop_type = node
             .data
-            .get("selector")
+            .get("loopType")
             .and_then(|v| v.as_str())
-            .unwrap_or("");
+            .unwrap_or("count");
 
-        let text = node
+        let index_variable = node
             .data
-            .get("text")
+            .get("indexVariable")
             .and_then(|v| v.as_str())
-            .unwrap_or("");
-
-        let interpolated_text = self.runtime.interpolate(text).await;
+            .unwrap_or("index");
 
-        self.runtime
-            .add_log(
-                ExecutionLog::info(format!("Web input to element: {}", selector))
-                    .with_node(&node.id),
-            )
-            .await;
+        match loop_type {
+            "count" => {
+                let count = node
+                    .data
+                    .get("count")
+                    .and_then(|v| v.as_u64())
+                    .unwrap_or(1) as usize;
 
-        // Get browser session ID from variable
-        let session_id = self.get_browser_session(browser_variable).await?;
+                self.runtime
+                    .add_log(
+                        ExecutionLog::info(format!("Starting count loop: {} iterations", count))
+                            .with_node(&node.id),
+                    )
+                    .await;
 
-        // Input using Chrome DevTools Protocol
-        self.web_automation
-            .input(&session_id, selector, &interpolated_text)
-            .await
-            .map_err(|e| EngineError::ExecutionFailed(format!("Failed to input: {}", e)))?;
-
-        self.runtime
-            .add_log(ExecutionLog::info("Web input completed").with_node(&node.id))
-            .await;
-
-        Ok(())
-    }
-
-    async fn execute_web_get_text(&self, node: &WorkflowNode) -> EngineResult<()> {
-        let browser_variable = node
-            .data
-            .get("browserVariable")
-            .and_then(|v| v.as_str())
-            .unwrap_or("browser");
-
-        let selector = node
-            .data
-            .get("selector")
-            .and_then(|v| v.as_str())
-            .unwrap_or("");
-
-        let var_name = node
-            .data
-            .get("variableName")
-            .and_then(|v| v.as_str())
-            .unwrap_or("result");
+                for i in 0..count {
+                    // Set the index variable
+                    self.runtime
+                        .set_variable(index_variable, VariableValue::Number(i as f64))
+                        .await;
 
-        self.runtime
-            .add_log(
-                ExecutionLog::info(format!("Getting text from element: {}", selector))
-                    .with_node(&node.id),
-            )
-            .await;
+                    self.runtime
+                        .add_log(
+                            ExecutionLog::info(format!("Loop iteration {}/{}", i + 1, count))
+                                .with_node(&node.id),
+                        )
+                        .await;
 
-        // Get browser session ID from variable
-        let session_id = self.get_browser_session(browser_variable).await?;
+                    // Execute body nodes
+                    let body_nodes = self.workflow.find_next_nodes_by_handle(&node.id, "body");
+                    for body_node in body_nodes {
+                        self.execute_from_node(body_node).await?;
+                    }
+                }
+            }
+            "while" => {
+                let condition_str = node
+                    .data
+                    .get("condition")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("false");
 
-        // Get text using Chrome DevTools Protocol
-        let text = self
-            .web_automation
-            .get_text(&session_id, selector)
-            .await
-            .map_err(|e| EngineError::ExecutionFailed(format!("Failed to get text: {}", e)))?;
+                self.runtime
+                    .add_log(
+                        ExecutionLog::info(format!("Starting while loop: {}", condition_str))
+                            .with_node(&node.id),
+                    )
+                    .await;
 
-        self.runtime
-            .set_variable(var_name, VariableValue::String(text.clone()))
-            .await;
+                let mut iteration = 0;
+                let max_iterations = 10000; // Safety limit
 
-        self.runtime
-            .add_log(
-                ExecutionLog::info(format!("Got text '{}' saved to '{}'", text, var_name))
-                    .with_node(&node.id),
-            )
-            .await;
+                while iteration < max_iterations {
+                    // Evaluate condition
+                    let interpolated = self.runtime.interpolate(condition_str).await;
+                    let condition_result = self.evaluate_simple_expression(&interpolated);
 
-        Ok(())
-    }
+                    if !condition_result {
+                        break;
+                    }
 
-    async fn execute_close_browser(&self, node: &WorkflowNode) -> EngineResult<()> {
-        let browser_variable = node
-            .data
-            .get("browserVariable")
-            .and_then(|v| v.as_str())
-            .unwrap_or("browser");
+                    self.runtime
+                        .set_variable(index_variable, VariableValue::Number(iteration as f64))
+                        .await;
 
-        self.runtime
-            .add_log(
-                ExecutionLog::info(format!("Closing browser: {}", browser_variable))
-                    .with_node(&node.id),
-            )
-            .await;
+                    self.runtime
+                        .add_log(
+                            ExecutionLog::info(format!("While loop iteration {}", iteration + 1))
+                                .with_node(&node.id),
+                        )
+                        .await;
 
-        // Get browser session ID from variable
-        let session_id = self.get_browser_session(browser_variable).await?;
+                    // Execute body nodes
+                    let body_nodes = self.workflow.find_next_nodes_by_handle(&node.id, "body");
+                    for body_node in body_nodes {
+                        self.execute_from_node(body_node).await?;
+                    }
 
-        // Close browser
-        self.web_automation
-            .close(&session_id)
-            .await
-            .map_err(|e| EngineError::ExecutionFailed(format!("Failed to close browser: {}", e)))?;
+                    iteration += 1;
+                }
+            }
+            _ => {}
+        }
 
-        self.runtime
-            .add_log(ExecutionLog::info("Browser closed").with_node(&node.id))
-            .await;
+        // Execute done nodes
+        let done_nodes = self.workflow.find_next_nodes_by_handle(&node.id, "done");
+        for done_node in done_nodes {
+            self.execute_from_node(done_node).await?;
+        }
 
         Ok(())
     }
 
-    async fn execute_js(&self, node: &WorkflowNode) -> EngineResult<()> {
-        let browser_variable = node
+    async fn execute_foreach_node(&self, node: &WorkflowNode) -> EngineResult<()> {
+        let list_variable = node
             .data
-            .get("browserVariable")
+            .get("listVariable")
             .and_then(|v| v.as_str())
-            .unwrap_or("browser");
+            .unwrap_or("");
 
-        let script = node
+        let item_variable = node
             .data
-            .get("script")
+            .get("itemVariable")
             .and_then(|v| v.as_str())
-            .unwrap_or("");
+            .unwrap_or("item");
 
-        let result_variable = node
+        let index_variable = node
             .data
-            .get("resultVariable")
+            .get("indexVariable")
             .and_then(|v| v.as_str())
-            .unwrap_or("");
-
-        self.runtime
-            .add_log(ExecutionLog::info("Executing JavaScript").with_node(&node.id))
-            .await;
-
-        // Get browser session ID from variable
-        let session_id = self.get_browser_session(browser_variable).await?;
+            .unwrap_or("index");
 
-        // Execute JavaScript
-        let result = self
-            .web_automation
-            .execute_js(&session_id, script)
-            .await
-            .map_err(|e| EngineError::ExecutionFailed(format!("Failed to execute JS: {}", e)))?;
+        // Get the list from variables
+        let list = self.runtime.get_variable(list_variable).await;
 
-        // Save result to variable if specified
-        if !result_variable.is_empty() {
+        if let Some(VariableValue::List(items)) = list {
+            let items = items.clone();
             self.runtime
-                .set_variable(result_variable, VariableValue::String(result.clone()))
+                .add_log(
+                    ExecutionLog::info(format!(
+                        "Starting forEach loop: {} items",
+                        items.len()
+                    ))
+                    .with_node(&node.id),
+                )
                 .await;
 
+            for (i, item) in items.iter().enumerate() {
+                self.runtime
+                    .set_variable(index_variable, VariableValue::Number(i as f64))
+                    .await;
+                self.runtime
+                    .set_variable(item_variable, item.clone())
+                    .await;
+
+                self.runtime
+                    .add_log(
+                        ExecutionLog::info(format!(
+                            "ForEach iteration {}/{}",
+                            i + 1,
+                            items.len()
+                        ))
+                        .with_node(&node.id),
+                    )
+                    .await;
+
+                // Execute body nodes
+                let body_nodes = self.workflow.find_next_nodes_by_handle(&node.id, "body");
+                for body_node in body_nodes {
+                    self.execute_from_node(body_node).await?;
+                }
+            }
+        } else {
             self.runtime
                 .add_log(
-                    ExecutionLog::info(format!("JS result saved to '{}'", result_variable))
-                        .with_node(&node.id),
+                    ExecutionLog::warn(format!(
+                        "Variable '{}' is not a list or not found",
+                        list_variable
+                    ))
+                    .with_node(&node.id),
                 )
                 .await;
         }
 
-        self.runtime
-            .add_log(ExecutionLog::info("JavaScript executed").with_node(&node.id))
-            .await;
+        // Execute done nodes
+        let done_nodes = self.workflow.find_next_nodes_by_handle(&node.id, "done");
+        for done_node in done_nodes {
+            self.execute_from_node(done_node).await?;
+        }
 
         Ok(())
     }
 
-    async fn get_browser_session(&self, browser_variable: &str) -> EngineResult<String> {
-        self.runtime
-            .get_variable(browser_variable)
-            .await
-            .and_then(|v| {
-                if let VariableValue::String(s) = v {
-                    Some(s)
-                } else {
-                    None
-                }
-            })
-            .ok_or_else(|| {
-                EngineError::ExecutionFailed(format!(
-                    "Browser session '{}' not found. Please open a browser first.",
-                    browser_variable
-                ))
-            })
+    fn evaluate_simple_expression(&self, expr: &str) -> bool {
+        // Simple expression evaluator for conditions like "true", "false", "1", "0"
+        let expr = expr.trim().to_lowercase();
+        expr == "true" || expr == "1" || expr == "yes"
     }
 
-    async fn execute_read_excel(&self, node: &WorkflowNode) -> EngineResult<()> {
-        let file_path = node
+    async fn execute_try_catch_node(&self, node: &WorkflowNode) -> EngineResult<()> {
+        let error_variable = node
             .data
-            .get("filePath")
+            .get("errorVariable")
             .and_then(|v| v.as_str())
-            .unwrap_or("");
-
-        let sheet_name = node
-            .data
-            .get("sheetName")
-            .and_then(|v| v.as_str())
-            .unwrap_or("");
+            .unwrap_or("error");
 
-        let var_name = node
+        let max_retries = node
             .data
-            .get("variableName")
-            .and_then(|v| v.as_str())
-            .unwrap_or("excelData");
+            .get("maxRetries")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(0) as usize;
 
-        let read_mode = node
+        let retry_delay = node
             .data
-            .get("readMode")
-            .and_then(|v| v.as_str())
-            .unwrap_or("all");
-
-        let interpolated_path = self.runtime.interpolate(file_path).await;
+            .get("retryDelay")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(1000);
 
         self.runtime
             .add_log(
-                ExecutionLog::info(format!("Reading Excel file: {}", interpolated_path))
-                    .with_node(&node.id),
+                ExecutionLog::info(format!(
+                    "Starting try-catch block (max retries: {})",
+                    max_retries
+                ))
+                .with_node(&node.id),
             )
             .await;
 
-        match read_mode {
-            "cell" => {
-                let cell_position = node
-                    .data
-                    .get("cellPosition")
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("A1");
-
-                // Parse cell position (e.g., "A1" -> (0, 0), "B2" -> (1, 1))
-                let (col, row) = self.parse_cell_position(cell_position);
-
-                let target_sheet = if sheet_name.is_empty() {
-                    // Get first sheet name
-                    self.file_automation
-                        .get_excel_sheet_names(&interpolated_path)
-                        .map_err(|e| EngineError::ExecutionFailed(e.to_string()))?
-                        .first()
-                        .cloned()
-                        .unwrap_or_else(|| "Sheet1".to_string())
-                } else {
-                    sheet_name.to_string()
-                };
-
-                let value = self
-                    .file_automation
-                    .read_excel_cell(&interpolated_path, &target_sheet, row, col)
-                    .map_err(|e| EngineError::ExecutionFailed(e.to_string()))?;
-
-                self.runtime
-                    .set_variable(var_name, VariableValue::String(value.clone()))
-                    .await;
+        let try_nodes = self.workflow.find_next_nodes_by_handle(&node.id, "try");
+        let mut last_error: Option<String> = None;
 
+        // Try to execute with retries
+        for attempt in 0..=max_retries {
+            if attempt > 0 {
                 self.runtime
                     .add_log(
-                        ExecutionLog::info(format!(
-                            "Read cell {} = '{}' saved to '{}'",
-                            cell_position, value, var_name
-                        ))
-                        .with_node(&node.id),
+                        ExecutionLog::info(format!("Retry attempt {}/{}", attempt, max_retries))
+                            .with_node(&node.id),
                     )
                     .await;
+                tokio::time::sleep(tokio::time::Duration::from_millis(retry_delay)).await;
             }
-            _ => {
-                // Read all data
-                let data = if sheet_name.is_empty() {
-                    self.file_automation
-                        .read_excel(&interpolated_path)
-                        .map_err(|e| EngineError::ExecutionFailed(e.to_string()))?
-                } else {
-                    let sheet = self
-                        .file_automation
-                        .read_excel_sheet(&interpolated_path, sheet_name)
-                        .map_err(|e| EngineError::ExecutionFailed(e.to_string()))?;
 
-                    crate::automation::file::ExcelData {
-                        sheets: vec![sheet],
-                    }
-                };
+            let mut success = true;
+            for try_node in &try_nodes {
+                if let Err(e) = self.execute_from_node(try_node).await {
+                    last_error = Some(e.to_string());
+                    success = false;
+                    break;
+                }
+            }
 
-                // Convert to JSON and store as string
-                let json_data = serde_json::to_string(&data)
-                    .map_err(|e| EngineError::ExecutionFailed(format!("Failed to serialize Excel data: {}", e)))?;
+            if success {
+                // Execute finally nodes and return
+                let finally_nodes = self.workflow.find_next_nodes_by_handle(&node.id, "finally");
+                for finally_node in finally_nodes {
+                    self.execute_from_node(finally_node).await?;
+                }
+                return Ok(());
+            }
+        }
 
-                self.runtime
-                    .set_variable(var_name, VariableValue::String(json_data))
-                    .await;
+        // All retries failed, execute catch block
+        if let Some(error) = &last_error {
+            self.runtime
+                .set_variable(error_variable, VariableValue::String(error.clone()))
+                .await;
 
-                let row_count: usize = data.sheets.iter().map(|s| s.rows.len()).sum();
-                self.runtime
-                    .add_log(
-                        ExecutionLog::info(format!(
-                            "Read {} sheets with {} rows, saved to '{}'",
-                            data.sheets.len(),
-                            row_count,
-                            var_name
-                        ))
-                        .with_node(&node.id),
-                    )
-                    .await;
+            self.runtime
+                .add_log(
+                    ExecutionLog::warn(format!("Caught error: {}", error)).with_node(&node.id),
+                )
+                .await;
+        }
+
+        let catch_nodes = self.workflow.find_next_nodes_by_handle(&node.id, "catch");
+        for catch_node in catch_nodes {
+            self.execute_from_node(catch_node).await?;
+        }
+
+        // Execute finally nodes
+        let finally_nodes = self.workflow.find_next_nodes_by_handle(&node.id, "finally");
+        for finally_node in finally_nodes {
+            self.execute_from_node(finally_node).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn execute_wait_element(&self, node: &WorkflowNode) -> EngineResult<()> {
+        let timeout = node
+            .data
+            .get("timeout")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(30000);
+
+        let wait_condition = node
+            .data
+            .get("waitCondition")
+            .and_then(|v| v.as_str())
+            .unwrap_or("visible");
+
+        self.runtime
+            .add_log(
+                ExecutionLog::info(format!(
+                    "Waiting for element (condition: {}, timeout: {}ms)",
+                    wait_condition, timeout
+                ))
+                .with_node(&node.id),
+            )
+            .await;
+
+        // Nodes with a CSS/XPath selector wait inside a browser session
+        let web_session = match node.data.get("selector").and_then(|v| v.as_str()) {
+            Some(selector) if !selector.is_empty() => {
+                let browser_variable = node
+                    .data
+                    .get("browserVariable")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("browser");
+                Some(self.get_browser_session(browser_variable).await?)
+            }
+            _ => None,
+        };
+
+        if wait_condition == "hidden" {
+            let deadline = std::time::Instant::now() + std::time::Duration::from_millis(timeout);
+            loop {
+                let found = match &web_session {
+                    Some(session_id) => self.resolve_web_selector(node, session_id, 0).await.is_ok(),
+                    None => self.resolve_desktop_element(node, 0).await.is_ok(),
+                };
+                if !found {
+                    break;
+                }
+                if std::time::Instant::now() >= deadline {
+                    return Err(AutomationError::Timeout(format!(
+                        "Element still present after {}ms",
+                        timeout
+                    ))
+                    .into());
+                }
+                tokio::time::sleep(tokio::time::Duration::from_millis(200)).await;
             }
+        } else if let Some(session_id) = &web_session {
+            self.resolve_web_selector(node, session_id, timeout).await?;
+        } else {
+            self.resolve_desktop_element(node, timeout).await?;
         }
 
+        self.runtime
+            .add_log(ExecutionLog::info("Element wait completed").with_node(&node.id))
+            .await;
+
         Ok(())
     }
 
-    async fn execute_write_excel(&self, node: &WorkflowNode) -> EngineResult<()> {
-        let file_path = node
+    async fn execute_hotkey(&self, node: &WorkflowNode) -> EngineResult<()> {
+        let keys = node
             .data
-            .get("filePath")
+            .get("keys")
             .and_then(|v| v.as_str())
             .unwrap_or("");
 
-        let sheet_name = node
+        let key = node
             .data
-            .get("sheetName")
+            .get("key")
             .and_then(|v| v.as_str())
-            .unwrap_or("Sheet1");
+            .unwrap_or("");
 
-        let interpolated_path = self.runtime.interpolate(file_path).await;
+        let modifiers: Vec<String> = node
+            .data
+            .get("modifiers")
+            .and_then(|v| v.as_array())
+            .map(|arr| {
+                arr.iter()
+                    .filter_map(|v| v.as_str().map(String::from))
+                    .collect()
+            })
+            .unwrap_or_default();
 
-        // Expand ~ to home directory
-        let expanded_path = if interpolated_path.starts_with('~') {
-            if let Some(base_dirs) = directories::BaseDirs::new() {
-                interpolated_path.replacen('~', base_dirs.home_dir().to_str().unwrap_or(""), 1)
+        let key_combo = if keys.is_empty() {
+            if modifiers.is_empty() {
+                key.to_string()
             } else {
-                interpolated_path.clone()
+                format!("{}+{}", modifiers.join("+"), key)
             }
         } else {
-            interpolated_path.clone()
+            keys.to_string()
         };
+        let combo: KeyCombo = self.runtime.interpolate(&key_combo).await.parse()?;
+
+        // "press" taps the combination, "down" and "up" hold and release it
+        let key_action = node
+            .data
+            .get("keyAction")
+            .and_then(|v| v.as_str())
+            .unwrap_or("press");
+
+        let target = node
+            .data
+            .get("target")
+            .and_then(|v| v.as_str())
+            .filter(|t| !t.is_empty())
+            .unwrap_or("desktop");
 
         self.runtime
             .add_log(
-                ExecutionLog::info(format!("Writing Excel file: {}", expanded_path))
+                ExecutionLog::info(format!("Hotkey {} ({}) on {}", combo, key_action, target))
                     .with_node(&node.id),
             )
             .await;
 
-        // Get data - either from inline "data" field or from "dataVariable" reference
-        let rows: Vec<Vec<String>> = if let Some(inline_data) = node.data.get("data") {
-            // Inline data array in node config
-            if let Some(arr) = inline_data.as_array() {
-                let mut result = Vec::new();
-                for row in arr {
-                    if let Some(row_arr) = row.as_array() {
-                        let mut row_result = Vec::new();
-                        for cell in row_arr {
-                            let cell_str = cell.as_str().unwrap_or("");
-                            // Interpolate variables in each cell
-                            let interpolated = self.runtime.interpolate(cell_str).await;
-                            row_result.push(interpolated);
-                        }
-                        result.push(row_result);
-                    }
+        match (target, key_action) {
+            ("browser", "press") => {
+                let browser_variable = node
+                    .data
+                    .get("browserVariable")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("browser");
+                let session_id = self.get_browser_session(browser_variable).await?;
+                self.web_automation.press_keys(&session_id, &combo).await?;
+            }
+            ("browser", _) => {
+                return Err(AutomationError::InvalidParameter(
+                    "Keys can only be held down on the desktop".to_string(),
+                )
+                .into())
+            }
+            ("desktop", "down") => {
+                for &key in &combo.keys {
+                    self.automation.key_down(key).await?;
                 }
-                result
-            } else {
-                return Err(EngineError::ExecutionFailed(
-                    "Inline 'data' field must be a 2D array".to_string(),
-                ));
             }
-        } else if let Some(data_variable) = node.data.get("dataVariable").and_then(|v| v.as_str()) {
-            // Data from variable reference
-            if data_variable.is_empty() {
-                return Err(EngineError::ExecutionFailed(
+            ("desktop", "up") => {
+                for &key in combo.keys.iter().rev() {
+                    self.automation.key_up(key).await?;
+                }
+            }
+            ("desktop", _) => self.automation.press_keys(&combo).await?,
+            (other, _) => {
+                return Err(AutomationError::InvalidParameter(format!(
+                    "Unknown hotkey target: {}",
+                    other
+                ))
+                .into())
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn execute_screenshot(&self, node: &WorkflowNode) -> EngineResult<()> {
+        let file_path = node
+            .data
+            .get("filePath")
+            .and_then(|v| v.as_str())
+            .unwrap_or("screenshot.png");
+
+        let screenshot_type = node
+            .data
+            .get("screenshotType")
+            .and_then(|v| v.as_str())
+            .unwrap_or("fullPage");
+
+        let interpolated_path = self.runtime.interpolate(file_path).await;
+
+        self.runtime
+            .add_log(
+                ExecutionLog::info(format!(
+                    "Taking screenshot (type: {}) to: {}",
+                    screenshot_type, interpolated_path
+                ))
+                .with_node(&node.id),
+            )
+            .await;
+
+        // TODO: Implement actual screenshot capture
+        // For now, just log the action
+
+        self.runtime
+            .add_log(ExecutionLog::info("Screenshot saved").with_node(&node.id))
+            .await;
+
+        Ok(())
+    }
+
+    async fn execute_open_browser(&self, node: &WorkflowNode) -> EngineResult<()> {
+        let browser_type = node
+            .data
+            .get("browserType")
+            .and_then(|v| v.as_str())
+            .unwrap_or("chrome");
+
+        let headless = node
+            .data
+            .get("headless")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+
+        let initial_url = node
+            .data
+            .get("initialUrl")
+            .and_then(|v| v.as_str())
+            .unwrap_or("");
+
+        let browser_variable = node
+            .data
+            .get("browserVariable")
+            .and_then(|v| v.as_str())
+            .unwrap_or("browser");
+
+        self.runtime
+            .add_log(
+                ExecutionLog::info(format!(
+                    "Opening {} browser (headless: {})",
+                    browser_type, headless
+                ))
+                .with_node(&node.id),
+            )
+            .await;
+
+        // Create browser session with unique ID
+        let session_id = format!("{}_{}", browser_variable, uuid::Uuid::new_v4());
+
+        let options = BrowserOptions {
+            headless,
+            browser_path: None,
+            user_data_dir: None,
+            window_size: Some((1280, 800)),
+        };
+
+        // Open browser using Chrome DevTools Protocol
+        self.web_automation
+            .open_browser(&session_id, options)
+            .await
+            .map_err(|e| EngineError::ExecutionFailed(format!("Failed to open browser: {}", e)))?;
+
+        // Store session ID in variable for later use
+        self.runtime
+            .set_variable(browser_variable, VariableValue::String(session_id.clone()))
+            .await;
+
+        // Navigate to initial URL if provided
+        if !initial_url.is_empty() {
+            let interpolated_url = self.runtime.interpolate(initial_url).await;
+            self.runtime
+                .add_log(
+                    ExecutionLog::info(format!("Navigating to: {}", interpolated_url))
+                        .with_node(&node.id),
+                )
+                .await;
+
+            self.web_automation
+                .navigate(&session_id, &interpolated_url)
+                .await
+                .map_err(|e| EngineError::ExecutionFailed(format!("Failed to navigate: {}", e)))?;
+        }
+
+        self.runtime
+            .add_log(ExecutionLog::info("Browser opened successfully").with_node(&node.id))
+            .await;
+
+        Ok(())
+    }
+
+    async fn execute_navigate(&self, node: &WorkflowNode) -> EngineResult<()> {
+        let url = node
+            .data
+            .get("url")
+            .and_then(|v| v.as_str())
+            .unwrap_or("");
+
+        let wait_until = node
+            .data
+            .get("waitUntil")
+            .and_then(|v| v.as_str())
+            .unwrap_or("load");
+
+        let browser_variable = node
+            .data
+            .get("browserVariable")
+            .and_then(|v| v.as_str())
+            .unwrap_or("browser");
+
+        let interpolated_url = self.runtime.interpolate(url).await;
+
+        self.runtime
+            .add_log(
+                ExecutionLog::info(format!(
+                    "Navigating to: {} (wait: {})",
+                    interpolated_url, wait_until
+                ))
+                .with_node(&node.id),
+            )
+            .await;
+
+        // Get browser session ID from variable
+        let session_id = self
+            .runtime
+            .get_variable(browser_variable)
+            .await
+            .and_then(|v| {
+                if let VariableValue::String(s) = v {
+                    Some(s)
+                } else {
+                    None
+                }
+            })
+            .ok_or_else(|| {
+                EngineError::ExecutionFailed(format!(
+                    "Browser session '{}' not found. Please open a browser first.",
+                    browser_variable
+                ))
+            })?;
+
+        // Navigate using Chrome DevTools Protocol
+        self.web_automation
+            .navigate(&session_id, &interpolated_url)
+            .await
+            .map_err(|e| EngineError::ExecutionFailed(format!("Failed to navigate: {}", e)))?;
+
+        self.runtime
+            .add_log(ExecutionLog::info("Navigation completed").with_node(&node.id))
+            .await;
+
+        Ok(())
+    }
+
+    async fn execute_web_click(&self, node: &WorkflowNode) -> EngineResult<()> {
+        let browser_variable = node
+            .data
+            .get("browserVariable")
+            .and_then(|v| v.as_str())
+            .unwrap_or("browser");
+
+        // Get browser session ID from variable
+        let session_id = self.get_browser_session(browser_variable).await?;
+        let selector = self
+            .resolve_web_selector(node, &session_id, Self::element_timeout(node))
+            .await?;
+        let selector = selector.as_str();
+
+        self.runtime
+            .add_log(
+                ExecutionLog::info(format!("Web clicking element: {}", selector))
+                    .with_node(&node.id),
+            )
+            .await;
+
+        // Click using Chrome DevTools Protocol
+        self.web_automation
+            .click(&session_id, selector)
+            .await
+            .map_err(|e| EngineError::ExecutionFailed(format!("Failed to click: {}", e)))?;
+
+        self.runtime
+            .add_log(ExecutionLog::info("Web click completed").with_node(&node.id))
+            .await;
+
+        Ok(())
+    }
+
+    async fn execute_web_input(&self, node: &WorkflowNode) -> EngineResult<()> {
+        let browser_variable = node
+            .data
+            .get("browserVariable")
+            .and_then(|v| v.as_str())
+            .unwrap_or("browser");
+
+        let text = node
+            .data
+            .get("text")
+            .and_then(|v| v.as_str())
+            .unwrap_or("");
+
+        let interpolated_text = self.runtime.interpolate(text).await;
+
+        // Get browser session ID from variable
+        let session_id = self.get_browser_session(browser_variable).await?;
+        let selector = self
+            .resolve_web_selector(node, &session_id, Self::element_timeout(node))
+            .await?;
+        let selector = selector.as_str();
+
+        self.runtime
+            .add_log(
+                ExecutionLog::info(format!("Web input to element: {}", selector))
+                    .with_node(&node.id),
+            )
+            .await;
+
+        // Input using Chrome DevTools Protocol
+        self.web_automation
+            .input(&session_id, selector, &interpolated_text)
+            .await
+            .map_err(|e| EngineError::ExecutionFailed(format!("Failed to input: {}", e)))?;
+
+        self.runtime
+            .add_log(ExecutionLog::info("Web input completed").with_node(&node.id))
+            .await;
+
+        Ok(())
+    }
+
+    async fn execute_web_get_text(&self, node: &WorkflowNode) -> EngineResult<()> {
+        let browser_variable = node
+            .data
+            .get("browserVariable")
+            .and_then(|v| v.as_str())
+            .unwrap_or("browser");
+
+        let var_name = node
+            .data
+            .get("variableName")
+            .and_then(|v| v.as_str())
+            .unwrap_or("result");
+
+        // Get browser session ID from variable
+        let session_id = self.get_browser_session(browser_variable).await?;
+        let selector = self
+            .resolve_web_selector(node, &session_id, Self::element_timeout(node))
+            .await?;
+        let selector = selector.as_str();
+
+        self.runtime
+            .add_log(
+                ExecutionLog::info(format!("Getting text from element: {}", selector))
+                    .with_node(&node.id),
+            )
+            .await;
+
+        // Get text using Chrome DevTools Protocol
+        let text = self
+            .web_automation
+            .get_text(&session_id, selector)
+            .await
+            .map_err(|e| EngineError::ExecutionFailed(format!("Failed to get text: {}", e)))?;
+
+        self.runtime
+            .set_variable(var_name, VariableValue::String(text.clone()))
+            .await;
+
+        self.runtime
+            .add_log(
+                ExecutionLog::info(format!("Got text '{}' saved to '{}'", text, var_name))
+                    .with_node(&node.id),
+            )
+            .await;
+
+        Ok(())
+    }
+
+    async fn execute_close_browser(&self, node: &WorkflowNode) -> EngineResult<()> {
+        let browser_variable = node
+            .data
+            .get("browserVariable")
+            .and_then(|v| v.as_str())
+            .unwrap_or("browser");
+
+        self.runtime
+            .add_log(
+                ExecutionLog::info(format!("Closing browser: {}", browser_variable))
+                    .with_node(&node.id),
+            )
+            .await;
+
+        // Get browser session ID from variable
+        let session_id = self.get_browser_session(browser_variable).await?;
+
+        // Close browser
+        self.web_automation
+            .close(&session_id)
+            .await
+            .map_err(|e| EngineError::ExecutionFailed(format!("Failed to close browser: {}", e)))?;
+
+        self.runtime
+            .add_log(ExecutionLog::info("Browser closed").with_node(&node.id))
+            .await;
+
+        Ok(())
+    }
+
+    async fn execute_js(&self, node: &WorkflowNode) -> EngineResult<()> {
+        let browser_variable = node
+            .data
+            .get("browserVariable")
+            .and_then(|v| v.as_str())
+            .unwrap_or("browser");
+
+        let script = node
+            .data
+            .get("script")
+            .and_then(|v| v.as_str())
+            .unwrap_or("");
+
+        let result_variable = node
+            .data
+            .get("resultVariable")
+            .and_then(|v| v.as_str())
+            .unwrap_or("");
+
+        self.runtime
+            .add_log(ExecutionLog::info("Executing JavaScript").with_node(&node.id))
+            .await;
+
+        // Get browser session ID from variable
+        let session_id = self.get_browser_session(browser_variable).await?;
+
+        // Execute JavaScript
+        let result = self
+            .web_automation
+            .execute_js(&session_id, script)
+            .await
+            .map_err(|e| EngineError::ExecutionFailed(format!("Failed to execute JS: {}", e)))?;
+
+        // Save result to variable if specified
+        if !result_variable.is_empty() {
+            self.runtime
+                .set_variable(result_variable, VariableValue::String(result.clone()))
+                .await;
+
+            self.runtime
+                .add_log(
+                    ExecutionLog::info(format!("JS result saved to '{}'", result_variable))
+                        .with_node(&node.id),
+                )
+                .await;
+        }
+
+        self.runtime
+            .add_log(ExecutionLog::info("JavaScript executed").with_node(&node.id))
+            .await;
+
+        Ok(())
+    }
+
+    async fn get_browser_session(&self, browser_variable: &str) -> EngineResult<String> {
+        self.runtime
+            .get_variable(browser_variable)
+            .await
+            .and_then(|v| {
+                if let VariableValue::String(s) = v {
+                    Some(s)
+                } else {
+                    None
+                }
+            })
+            .ok_or_else(|| {
+                EngineError::ExecutionFailed(format!(
+                    "Browser session '{}' not found. Please open a browser first.",
+                    browser_variable
+                ))
+            })
+    }
+
+    /// Integer from a node field given as a number or an interpolated string
+    async fn node_number(&self, node: &WorkflowNode, key: &str) -> EngineResult<Option<i32>> {
+        match node.data.get(key) {
+            Some(serde_json::Value::Number(n)) => Ok(n.as_f64().map(|n| n.round() as i32)),
+            Some(serde_json::Value::String(s)) if !s.trim().is_empty() => {
+                let value = self.runtime.interpolate(s).await;
+                value
+                    .trim()
+                    .parse::<f64>()
+                    .map(|n| Some(n.round() as i32))
+                    .map_err(|_| {
+                        AutomationError::InvalidParameter(format!(
+                            "Invalid number for '{}': {}",
+                            key, value
+                        ))
+                        .into()
+                    })
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Screen point a node targets, if it has a target
+    ///
+    /// Like clicks, a target element wins over explicit `x`/`y` coordinates,
+    /// which only apply to nodes without one; the element's center is shifted
+    /// by `offsetX`/`offsetY`.
+    async fn node_point(&self, node: &WorkflowNode) -> EngineResult<Option<(i32, i32)>> {
+        if !Self::has_element_target(node) {
+            let x = self.node_number(node, "x").await?;
+            let y = self.node_number(node, "y").await?;
+            return Ok(x.zip(y));
+        }
+
+        let element = self
+            .resolve_desktop_element(node, Self::element_timeout(node))
+            .await?;
+        let (x, y) = element.bounds.center();
+        let offset_x = self.node_number(node, "offsetX").await?.unwrap_or(0);
+        let offset_y = self.node_number(node, "offsetY").await?.unwrap_or(0);
+
+        Ok(Some((x + offset_x, y + offset_y)))
+    }
+
+    /// Whether a node has a target element configured
+    fn has_element_target(node: &WorkflowNode) -> bool {
+        ["locator", "element", "elementId", "image", "imagePath"]
+            .iter()
+            .any(|key| match node.data.get(*key) {
+                Some(serde_json::Value::String(s)) => !s.is_empty(),
+                Some(value) => !value.is_null(),
+                None => false,
+            })
+    }
+
+    /// Timeout for locating a node's target element
+    fn element_timeout(node: &WorkflowNode) -> u64 {
+        node.data
+            .get("timeout")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(DEFAULT_ELEMENT_TIMEOUT_MS)
+    }
+
+    /// Load the element a node targets from its data
+    ///
+    /// A node may carry an explicit `locator` chain, an inline `element`, or a
+    /// reference to a library element through `libraryId` and `elementId`.
+    async fn load_node_target(&self, node: &WorkflowNode) -> EngineResult<NodeTarget> {
+        let locator = match node.data.get("locator") {
+            Some(value) if !value.is_null() => Some(
+                serde_json::from_value::<ElementLocator>(value.clone()).map_err(|e| {
+                    EngineError::ExecutionFailed(format!("Invalid element locator: {}", e))
+                })?,
+            ),
+            _ => None,
+        };
+
+        let library_id = node
+            .data
+            .get("libraryId")
+            .and_then(|v| v.as_str())
+            .filter(|s| !s.is_empty())
+            .map(String::from);
+        let element_id = node
+            .data
+            .get("elementId")
+            .and_then(|v| v.as_str())
+            .filter(|s| !s.is_empty());
+
+        let element = match (node.data.get("element"), &library_id, element_id) {
+            (Some(value), _, _) if !value.is_null() => Some(
+                serde_json::from_value::<UIElement>(value.clone()).map_err(|e| {
+                    EngineError::ExecutionFailed(format!("Invalid element: {}", e))
+                })?,
+            ),
+            (_, Some(library_id), Some(element_id)) => {
+                let library = crate::commands::element::load_element_library(library_id.clone())
+                    .await
+                    .map_err(|e| {
+                        EngineError::ExecutionFailed(format!(
+                            "Failed to load element library '{}': {}",
+                            library_id, e
+                        ))
+                    })?;
+                Some(library.find_element(element_id).cloned().ok_or_else(|| {
+                    AutomationError::ElementNotFound(format!(
+                        "Element '{}' not in library '{}'",
+                        element_id, library_id
+                    ))
+                })?)
+            }
+            _ => None,
+        };
+
+        Ok(NodeTarget {
+            locator,
+            library_id,
+            element,
+        })
+    }
+
+    /// Load the reference image a node targets, if any
+    ///
+    /// The image comes from `image` (base64, optionally as a data URL) or from
+    /// a file at `imagePath`.
+    async fn load_node_image(&self, node: &WorkflowNode) -> EngineResult<Option<ElementLocator>> {
+        if let Some(encoded) = node
+            .data
+            .get("image")
+            .and_then(|v| v.as_str())
+            .filter(|s| !s.is_empty())
+        {
+            let encoded = encoded
+                .split_once("base64,")
+                .map(|(_, data)| data)
+                .unwrap_or(encoded);
+            let bytes = BASE64.decode(encoded.trim()).map_err(|e| {
+                AutomationError::InvalidParameter(format!("Invalid base64 image: {}", e))
+            })?;
+            return Ok(Some(ElementLocator::new(LocatorStrategy::Image(bytes))));
+        }
+
+        if let Some(path) = node
+            .data
+            .get("imagePath")
+            .and_then(|v| v.as_str())
+            .filter(|s| !s.is_empty())
+        {
+            let path = self.runtime.interpolate(path).await;
+            let bytes = tokio::fs::read(&path).await.map_err(|e| {
+                AutomationError::InvalidParameter(format!("Failed to read image '{}': {}", path, e))
+            })?;
+            return Ok(Some(ElementLocator::new(LocatorStrategy::Image(bytes))));
+        }
+
+        Ok(None)
+    }
+
+    /// Template matching options from a node's `confidence`, `searchRegion` and `scales`
+    fn image_match_options(node: &WorkflowNode) -> MatchOptions {
+        let mut options = MatchOptions::default();
+        if let Some(confidence) = node.data.get("confidence").and_then(|v| v.as_f64()) {
+            options.threshold = confidence as f32;
+        }
+        if let Some(region) = node
+            .data
+            .get("searchRegion")
+            .filter(|v| !v.is_null())
+            .and_then(|v| serde_json::from_value::<Rect>(v.clone()).ok())
+        {
+            options.region = Some(region);
+        }
+        if let Some(scales) = node.data.get("scales").and_then(|v| v.as_array()) {
+            let scales: Vec<f32> = scales
+                .iter()
+                .filter_map(|s| s.as_f64())
+                .map(|s| s as f32)
+                .collect();
+            if !scales.is_empty() {
+                options.scales = scales;
+            }
+        }
+        options
+    }
+
+    /// Resolve the desktop element targeted by a node through its locator chain
+    async fn resolve_desktop_element(
+        &self,
+        node: &WorkflowNode,
+        timeout_ms: u64,
+    ) -> EngineResult<UIElement> {
+        let target = self.load_node_target(node).await?;
+        let configured = target
+            .locator
+            .clone()
+            .or_else(|| target.element.as_ref().and_then(ElementLocator::from_element));
+
+        // Strategies tried before the element's own chain
+        let mut offset = 0;
+        let locator = match (self.load_node_image(node).await?, configured) {
+            (Some(image), Some(configured)) => {
+                offset = image.strategies().len();
+                image.then(configured)
+            }
+            (Some(image), None) => image,
+            (None, Some(configured)) => configured,
+            (None, None) => {
+                return Err(EngineError::ExecutionFailed(
+                    "No target element configured".to_string(),
+                ))
+            }
+        };
+
+        let resolved = resolver::resolve_desktop(
+            self.automation.as_ref(),
+            &locator,
+            &Self::image_match_options(node),
+            timeout_ms,
+        )
+        .await?;
+        self.handle_resolved(node, target, &resolved, offset).await;
+
+        Ok(resolved.element)
+    }
+
+    /// Resolve the selector of the browser element targeted by a node
+    ///
+    /// The node's `selector` is tried first, followed by the locator chain of
+    /// its configured element.
+    async fn resolve_web_selector(
+        &self,
+        node: &WorkflowNode,
+        session_id: &str,
+        timeout_ms: u64,
+    ) -> EngineResult<String> {
+        let selector = node
+            .data
+            .get("selector")
+            .and_then(|v| v.as_str())
+            .unwrap_or("");
+        let selector = self.runtime.interpolate(selector).await;
+
+        let target = self.load_node_target(node).await?;
+        let configured = target
+            .locator
+            .clone()
+            .or_else(|| target.element.as_ref().and_then(ElementLocator::from_element));
+
+        let primary = if selector.is_empty() {
+            None
+        } else if let Some(xpath) = crate::automation::web::xpath_selector(&selector) {
+            Some(ElementLocator::new(LocatorStrategy::XPath(xpath.to_string())))
+        } else {
+            Some(ElementLocator::new(LocatorStrategy::CssSelector(selector.clone())))
+        };
+
+        // Strategies tried before the element's own chain
+        let mut offset = 0;
+        let locator = match (primary, configured) {
+            (Some(primary), Some(configured)) => {
+                offset = primary.strategies().len();
+                primary.then(configured)
+            }
+            (Some(primary), None) => primary,
+            (None, Some(configured)) => configured,
+            (None, None) => {
+                return Err(EngineError::ExecutionFailed(
+                    "No selector or target element configured".to_string(),
+                ))
+            }
+        };
+
+        let resolved =
+            resolver::resolve_web(&self.web_automation, session_id, &locator, timeout_ms).await?;
+        self.handle_resolved(node, target, &resolved, offset).await;
+
+        Ok(resolved.selector.clone().unwrap_or(selector))
+    }
+
+    /// Log which strategy matched and heal the stored element if requested
+    ///
+    /// `offset` counts the node-level strategies, like its `selector`, that
+    /// were tried before the element's own chain; only a fallback within
+    /// that chain means the stored element is out of date.
+    async fn handle_resolved(
+        &self,
+        node: &WorkflowNode,
+        target: NodeTarget,
+        resolved: &resolver::ResolvedElement,
+        offset: usize,
+    ) {
+        let index = match resolved.index_in_chain(offset) {
+            Some(index) if index > 0 => index,
+            _ => return,
+        };
+
+        self.runtime
+            .add_log(
+                ExecutionLog::warn(format!(
+                    "Primary locator failed, element matched by fallback #{} ({})",
+                    index,
+                    resolved.strategy.describe()
+                ))
+                .with_node(&node.id),
+            )
+            .await;
+
+        let self_heal = node
+            .data
+            .get("selfHeal")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+
+        let (Some(library_id), Some(mut element)) = (target.library_id, target.element) else {
+            return;
+        };
+        if !self_heal || !resolver::heal_element(&mut element, resolved) {
+            return;
+        }
+
+        let healed = async {
+            let mut library = crate::commands::element::load_element_library(library_id).await?;
+            if let Some(stored) = library.elements.iter_mut().find(|e| e.id == element.id) {
+                *stored = element;
+            }
+            crate::commands::element::save_element_library(library).await
+        }
+        .await;
+
+        match healed {
+            Ok(_) => {
+                self.runtime
+                    .add_log(ExecutionLog::info("Stored element updated").with_node(&node.id))
+                    .await
+            }
+            Err(e) => {
+                self.runtime
+                    .add_log(
+                        ExecutionLog::warn(format!("Failed to update stored element: {}", e))
+                            .with_node(&node.id),
+                    )
+                    .await
+            }
+        }
+    }
+
+    async fn execute_read_excel(&self, node: &WorkflowNode) -> EngineResult<()> {
+        let file_path = node
+            .data
+            .get("filePath")
+            .and_then(|v| v.as_str())
+            .unwrap_or("");
+
+        let sheet_name = node
+            .data
+            .get("sheetName")
+            .and_then(|v| v.as_str())
+            .unwrap_or("");
+
+        let var_name = node
+            .data
+            .get("variableName")
+            .and_then(|v| v.as_str())
+            .unwrap_or("excelData");
+
+        let read_mode = node
+            .data
+            .get("readMode")
+            .and_then(|v| v.as_str())
+            .unwrap_or("all");
+
+        let interpolated_path = self.runtime.interpolate(file_path).await;
+
+        self.runtime
+            .add_log(
+                ExecutionLog::info(format!("Reading Excel file: {}", interpolated_path))
+                    .with_node(&node.id),
+            )
+            .await;
+
+        match read_mode {
+            "cell" => {
+                let cell_position = node
+                    .data
+                    .get("cellPosition")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("A1");
+
+                // Parse cell position (e.g., "A1" -> (0, 0), "B2" -> (1, 1))
+                let (col, row) = self.parse_cell_position(cell_position);
+
+                let target_sheet = if sheet_name.is_empty() {
+                    // Get first sheet name
+                    self.file_automation
+                        .get_excel_sheet_names(&interpolated_path)
+                        .map_err(|e| EngineError::ExecutionFailed(e.to_string()))?
+                        .first()
+                        .cloned()
+                        .unwrap_or_else(|| "Sheet1".to_string())
+                } else {
+                    sheet_name.to_string()
+                };
+
+                let value = self
+                    .file_automation
+                    .read_excel_cell(&interpolated_path, &target_sheet, row, col)
+                    .map_err(|e| EngineError::ExecutionFailed(e.to_string()))?;
+
+                self.runtime
+                    .set_variable(var_name, VariableValue::String(value.clone()))
+                    .await;
+
+                self.runtime
+                    .add_log(
+                        ExecutionLog::info(format!(
+                            "Read cell {} = '{}' saved to '{}'",
+                            cell_position, value, var_name
+                        ))
+                        .with_node(&node.id),
+                    )
+                    .await;
+            }
+            _ => {
+                // Read all data
+                let data = if sheet_name.is_empty() {
+                    self.file_automation
+                        .read_excel(&interpolated_path)
+                        .map_err(|e| EngineError::ExecutionFailed(e.to_string()))?
+                } else {
+                    let sheet = self
+                        .file_automation
+                        .read_excel_sheet(&interpolated_path, sheet_name)
+                        .map_err(|e| EngineError::ExecutionFailed(e.to_string()))?;
+
+                    crate::automation::file::ExcelData {
+                        sheets: vec![sheet],
+                    }
+                };
+
+                // Convert to JSON and store as string
+                let json_data = serde_json::to_string(&data)
+                    .map_err(|e| EngineError::ExecutionFailed(format!("Failed to serialize Excel data: {}", e)))?;
+
+                self.runtime
+                    .set_variable(var_name, VariableValue::String(json_data))
+                    .await;
+
+                let row_count: usize = data.sheets.iter().map(|s| s.rows.len()).sum();
+                self.runtime
+                    .add_log(
+                        ExecutionLog::info(format!(
+                            "Read {} sheets with {} rows, saved to '{}'",
+                            data.sheets.len(),
+                            row_count,
+                            var_name
+                        ))
+                        .with_node(&node.id),
+                    )
+                    .await;
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn execute_write_excel(&self, node: &WorkflowNode) -> EngineResult<()> {
+        let file_path = node
+            .data
+            .get("filePath")
+            .and_then(|v| v.as_str())
+            .unwrap_or("");
+
+        let sheet_name = node
+            .data
+            .get("sheetName")
+            .and_then(|v| v.as_str())
+            .unwrap_or("Sheet1");
+
+        let interpolated_path = self.runtime.interpolate(file_path).await;
+
+        let expanded_path = expand_home(&interpolated_path);
+
+        self.runtime
+            .add_log(
+                ExecutionLog::info(format!("Writing Excel file: {}", expanded_path))
+                    .with_node(&node.id),
+            )
+            .await;
+
+        // Get data - either from inline "data" field or from "dataVariable" reference
+        let rows: Vec<Vec<String>> = if let Some(inline_data) = node.data.get("data") {
+            // Inline data array in node config
+            if let Some(arr) = inline_data.as_array() {
+                let mut result = Vec::new();
+                for row in arr {
+                    if let Some(row_arr) = row.as_array() {
+                        let mut row_result = Vec::new();
+                        for cell in row_arr {
+                            let cell_str = cell.as_str().unwrap_or("");
+                            // Interpolate variables in each cell
+                            let interpolated = self.runtime.interpolate(cell_str).await;
+                            row_result.push(interpolated);
+                        }
+                        result.push(row_result);
+                    }
+                }
+                result
+            } else {
+                return Err(EngineError::ExecutionFailed(
+                    "Inline 'data' field must be a 2D array".to_string(),
+                ));
+            }
+        } else if let Some(data_variable) = node.data.get("dataVariable").and_then(|v| v.as_str()) {
+            // Data from variable reference
+            if data_variable.is_empty() {
+                return Err(EngineError::ExecutionFailed(
                     "Either 'data' or 'dataVariable' must be provided".to_string(),
                 ));
             }
 
-            let data_json = self
-                .runtime
-                .get_variable(data_variable)
-                .await
-                .and_then(|v| {
-                    if let VariableValue::String(s) = v {
-                        Some(s)
+            let data_json = self
+                .runtime
+                .get_variable(data_variable)
+                .await
+                .and_then(|v| {
+                    if let VariableValue::String(s) = v {
+                        Some(s)
+                    } else {
+                        None
+                    }
+                })
+                .ok_or_else(|| {
+                    EngineError::ExecutionFailed(format!(
+                        "Variable '{}' not found or not a string",
+                        data_variable
+                    ))
+                })?;
+
+            // Try to parse as ExcelData first, then as simple 2D array, then as array of objects
+            if let Ok(excel_data) =
+                serde_json::from_str::<crate::automation::file::ExcelData>(&data_json)
+            {
+                excel_data
+                    .sheets
+                    .first()
+                    .map(|s| s.rows.clone())
+                    .unwrap_or_default()
+            } else if let Ok(parsed_rows) = serde_json::from_str::<Vec<Vec<String>>>(&data_json) {
+                parsed_rows
+            } else if let Ok(objects) = serde_json::from_str::<Vec<serde_json::Value>>(&data_json) {
+                // Convert array of objects to 2D array (with header row)
+                self.convert_objects_to_rows(&objects, node)?
+            } else {
+                return Err(EngineError::ExecutionFailed(
+                    "Data variable must be valid JSON (ExcelData, 2D array, or array of objects)".to_string(),
+                ));
+            }
+        } else {
+            return Err(EngineError::ExecutionFailed(
+                "Either 'data' or 'dataVariable' must be provided".to_string(),
+            ));
+        };
+
+        self.file_automation
+            .write_excel_sheet(&expanded_path, sheet_name, &rows)
+            .map_err(|e| EngineError::ExecutionFailed(e.to_string()))?;
+
+        self.runtime
+            .add_log(
+                ExecutionLog::info(format!(
+                    "Wrote {} rows to sheet '{}'",
+                    rows.len(),
+                    sheet_name
+                ))
+                .with_node(&node.id),
+            )
+            .await;
+
+        Ok(())
+    }
+
+    fn parse_cell_position(&self, position: &str) -> (u32, u32) {
+        let position = position.to_uppercase();
+        let mut col: u32 = 0;
+        let mut row: u32 = 0;
+
+        for c in position.chars() {
+            if c.is_ascii_alphabetic() {
+                col = col * 26 + (c as u32 - 'A' as u32);
+            } else if c.is_ascii_digit() {
+                row = row * 10 + (c as u32 - '0' as u32);
+            }
+        }
+
+        // Excel rows are 1-indexed, convert to 0-indexed
+        (col, if row > 0 { row - 1 } else { 0 })
+    }
+
+    /// Execute a shell command and optionally save output to a variable
+    async fn execute_command(&self, node: &WorkflowNode) -> EngineResult<()> {
+        let command = node
+            .data
+            .get("command")
+            .and_then(|v| v.as_str())
+            .unwrap_or("");
+        let command = self.runtime.interpolate(command).await;
+        if command.trim().is_empty() {
+            return Err(AutomationError::InvalidParameter("No command configured".to_string()).into());
+        }
+
+        let args = self.node_args(node, "args").await;
+
+        let shell = node
+            .data
+            .get("shell")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+
+        let fail_on_error = node
+            .data
+            .get("failOnError")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+
+        // No timeout unless one is configured
+        let timeout = node
+            .data
+            .get("timeout")
+            .and_then(|v| v.as_u64())
+            .filter(|&ms| ms > 0);
+
+        let working_dir = match node.data.get("workingDir").and_then(|v| v.as_str()) {
+            Some(dir) if !dir.is_empty() => Some(self.runtime.interpolate(dir).await),
+            _ => None,
+        };
+
+        let stdin = match node.data.get("stdin").and_then(|v| v.as_str()) {
+            Some(input) if !input.is_empty() => Some(self.runtime.interpolate(input).await),
+            _ => None,
+        };
+
+        let env = self.command_env(node).await?;
+
+        self.runtime
+            .add_log(
+                ExecutionLog::info(format!("Executing command: {} {:?}", command, args))
+                    .with_node(&node.id),
+            )
+            .await;
+
+        // In shell mode the command and the quoted arguments form one command line
+        let mut cmd = if shell {
+            let line = std::iter::once(command.clone())
+                .chain(args.iter().map(|arg| shell_quote(arg)))
+                .collect::<Vec<_>>()
+                .join(" ");
+            shell_command(&line)
+        } else {
+            let mut cmd = tokio::process::Command::new(&command);
+            cmd.args(&args);
+            cmd
+        };
+        if let Some(dir) = &working_dir {
+            cmd.current_dir(dir);
+        }
+        cmd.envs(env)
+            .stdin(if stdin.is_some() {
+                std::process::Stdio::piped()
+            } else {
+                std::process::Stdio::null()
+            })
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped())
+            .kill_on_drop(true);
+
+        let mut child = cmd
+            .spawn()
+            .map_err(|e| EngineError::ExecutionFailed(format!("Failed to execute command: {}", e)))?;
+        let pid = child.id();
+
+        if let (Some(input), Some(mut pipe)) = (stdin, child.stdin.take()) {
+            // Written from a task so a command that prints before reading
+            // its input cannot deadlock; dropping the pipe closes it
+            tokio::spawn(async move {
+                use tokio::io::AsyncWriteExt;
+                if let Err(e) = pipe.write_all(input.as_bytes()).await {
+                    log::warn!("Failed to write command input: {}", e);
+                }
+            });
+        }
+
+        let stdout_pipe = child.stdout.take();
+        let stderr_pipe = child.stderr.take();
+        let mut stdout = String::new();
+        let mut stderr = String::new();
+        let run = async {
+            let output = async {
+                tokio::join!(
+                    self.stream_output(&node.id, stdout_pipe, false, &mut stdout),
+                    self.stream_output(&node.id, stderr_pipe, true, &mut stderr),
+                )
+            };
+            tokio::pin!(output);
+            let exited = tokio::select! {
+                _ = &mut output => None,
+                status = child.wait() => Some(status),
+            };
+            match exited {
+                Some(status) => {
+                    let drain = std::time::Duration::from_millis(COMMAND_OUTPUT_DRAIN_MS);
+                    let _ = tokio::time::timeout(drain, output).await;
+                    status
+                }
+                None => child.wait().await,
+            }
+        };
+        let finished = match timeout {
+            Some(ms) => tokio::time::timeout(std::time::Duration::from_millis(ms), run).await.ok(),
+            None => Some(run.await),
+        };
+
+        let Some(status) = finished else {
+            // Shell mode runs the command as a child of the shell, so the
+            // whole tree goes
+            if let Some(pid) = pid {
+                if let Err(e) = process::kill(pid, true).await {
+                    log::warn!("Failed to kill timed out command: {}", e);
+                }
+            }
+            let _ = child.kill().await;
+            return Err(AutomationError::Timeout(format!(
+                "Command did not finish within {}ms",
+                timeout.unwrap_or_default()
+            ))
+            .into());
+        };
+        let status = status
+            .map_err(|e| EngineError::ExecutionFailed(format!("Failed to wait for command: {}", e)))?;
+
+        for (key, value) in [
+            ("outputVariable", VariableValue::String(stdout)),
+            ("stderrVariable", VariableValue::String(stderr.clone())),
+            (
+                "exitCodeVariable",
+                status
+                    .code()
+                    .map(|code| VariableValue::Number(code as f64))
+                    .unwrap_or(VariableValue::Null),
+            ),
+        ] {
+            if let Some(variable) = node
+                .data
+                .get(key)
+                .and_then(|v| v.as_str())
+                .filter(|v| !v.is_empty())
+            {
+                self.runtime.set_variable(variable, value).await;
+            }
+        }
+
+        if !status.success() {
+            let message = format!("Command exited with {}: {}", status, stderr.trim());
+            if fail_on_error {
+                return Err(EngineError::ExecutionFailed(message));
+            }
+            self.runtime
+                .add_log(ExecutionLog::warn(message).with_node(&node.id))
+                .await;
+        }
+
+        self.runtime
+            .add_log(
+                ExecutionLog::info(format!("Command completed with status: {}", status))
+                    .with_node(&node.id),
+            )
+            .await;
+
+        Ok(())
+    }
+
+    /// Log each line of a command's output as it arrives and collect it in
+    /// `output`, which keeps what was read if the reading is cut short
+    async fn stream_output(
+        &self,
+        node_id: &str,
+        pipe: Option<impl tokio::io::AsyncRead + Unpin>,
+        is_stderr: bool,
+        output: &mut String,
+    ) {
+        use tokio::io::AsyncBufReadExt;
+
+        let Some(pipe) = pipe else {
+            return;
+        };
+        let mut reader = tokio::io::BufReader::new(pipe);
+        let mut line = Vec::new();
+        loop {
+            line.clear();
+            match reader.read_until(b'\n', &mut line).await {
+                Ok(0) => break,
+                Ok(_) => {
+                    // Output is not necessarily UTF-8
+                    let text = String::from_utf8_lossy(&line);
+                    output.push_str(&text);
+                    let text = text.trim_end_matches(['\r', '\n']);
+                    let log = if is_stderr {
+                        ExecutionLog::warn(text)
+                    } else {
+                        ExecutionLog::info(text)
+                    };
+                    self.runtime.add_log(log.with_node(node_id)).await;
+                }
+                Err(e) => {
+                    log::warn!("Failed to read command output: {}", e);
+                    break;
+                }
+            }
+        }
+    }
+
+    /// Environment variables of a command, given as an object or as
+    /// `NAME=value` lines
+    async fn command_env(&self, node: &WorkflowNode) -> EngineResult<Vec<(String, String)>> {
+        let mut env = Vec::new();
+        match node.data.get("env") {
+            Some(serde_json::Value::Object(vars)) => {
+                for (name, value) in vars {
+                    let value = match value {
+                        serde_json::Value::String(s) => self.runtime.interpolate(s).await,
+                        other => other.to_string(),
+                    };
+                    env.push((name.clone(), value));
+                }
+            }
+            Some(serde_json::Value::String(lines)) => {
+                for line in lines.lines().map(str::trim).filter(|l| !l.is_empty()) {
+                    let (name, value) = line.split_once('=').ok_or_else(|| {
+                        AutomationError::InvalidParameter(format!(
+                            "Invalid environment variable '{}', expected NAME=value",
+                            line
+                        ))
+                    })?;
+                    env.push((name.trim().to_string(), self.runtime.interpolate(value).await));
+                }
+            }
+            _ => {}
+        }
+        Ok(env)
+    }
+
+    /// List files and directories in a given path
+    async fn execute_list_directory(&self, node: &WorkflowNode) -> EngineResult<()> {
+        let path = node
+            .data
+            .get("path")
+            .and_then(|v| v.as_str())
+            .unwrap_or(".");
+
+        let output_variable = node
+            .data
+            .get("outputVariable")
+            .and_then(|v| v.as_str())
+            .unwrap_or("files");
+
+        let include_hidden = node
+            .data
+            .get("includeHidden")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+
+        let recursive = node
+            .data
+            .get("recursive")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+
+        let interpolated_path = self.runtime.interpolate(path).await;
+
+        let expanded_path = expand_home(&interpolated_path);
+
+        self.runtime
+            .add_log(
+                ExecutionLog::info(format!("Listing directory: {}", expanded_path))
+                    .with_node(&node.id),
+            )
+            .await;
+
+        let path_buf = std::path::PathBuf::from(&expanded_path);
+        if !path_buf.exists() {
+            return Err(EngineError::ExecutionFailed(format!(
+                "Directory does not exist: {}",
+                expanded_path
+            )));
+        }
+
+        let mut entries: Vec<serde_json::Value> = Vec::new();
+
+        if recursive {
+            self.list_directory_recursive(&path_buf, include_hidden, &mut entries)?;
+        } else {
+            let dir_entries = std::fs::read_dir(&path_buf)
+                .map_err(|e| EngineError::ExecutionFailed(format!("Failed to read directory: {}", e)))?;
+
+            for entry in dir_entries.flatten() {
+                let file_name = entry.file_name().to_string_lossy().to_string();
+
+                // Skip hidden files if not included
+                if !include_hidden && file_name.starts_with('.') {
+                    continue;
+                }
+
+                let metadata = entry.metadata().ok();
+                let is_dir = metadata.as_ref().map(|m| m.is_dir()).unwrap_or(false);
+                let size = metadata.as_ref().map(|m| m.len()).unwrap_or(0);
+
+                entries.push(serde_json::json!({
+                    "name": file_name,
+                    "path": entry.path().to_string_lossy().to_string(),
+                    "isDirectory": is_dir,
+                    "size": size
+                }));
+            }
+        }
+
+        // Sort entries: directories first, then by name
+        entries.sort_by(|a, b| {
+            let a_is_dir = a.get("isDirectory").and_then(|v| v.as_bool()).unwrap_or(false);
+            let b_is_dir = b.get("isDirectory").and_then(|v| v.as_bool()).unwrap_or(false);
+            if a_is_dir != b_is_dir {
+                return b_is_dir.cmp(&a_is_dir); // Directories first
+            }
+            let a_name = a.get("name").and_then(|v| v.as_str()).unwrap_or("");
+            let b_name = b.get("name").and_then(|v| v.as_str()).unwrap_or("");
+            a_name.cmp(b_name)
+        });
+
+        // Save to variable as JSON string
+        let json_str = serde_json::to_string(&entries)
+            .map_err(|e| EngineError::ExecutionFailed(format!("Failed to serialize entries: {}", e)))?;
+
+        self.runtime
+            .set_variable(output_variable, VariableValue::String(json_str))
+            .await;
+
+        self.runtime
+            .add_log(
+                ExecutionLog::info(format!(
+                    "Found {} entries, saved to '{}'",
+                    entries.len(),
+                    output_variable
+                ))
+                .with_node(&node.id),
+            )
+            .await;
+
+        Ok(())
+    }
+
+    fn list_directory_recursive(
+        &self,
+        path: &std::path::Path,
+        include_hidden: bool,
+        entries: &mut Vec<serde_json::Value>,
+    ) -> EngineResult<()> {
+        let dir_entries = std::fs::read_dir(path)
+            .map_err(|e| EngineError::ExecutionFailed(format!("Failed to read directory: {}", e)))?;
+
+        for entry in dir_entries.flatten() {
+            let file_name = entry.file_name().to_string_lossy().to_string();
+
+            // Skip hidden files if not included
+            if !include_hidden && file_name.starts_with('.') {
+                continue;
+            }
+
+            let metadata = entry.metadata().ok();
+            let is_dir = metadata.as_ref().map(|m| m.is_dir()).unwrap_or(false);
+            let size = metadata.as_ref().map(|m| m.len()).unwrap_or(0);
+
+            entries.push(serde_json::json!({
+                "name": file_name,
+                "path": entry.path().to_string_lossy().to_string(),
+                "isDirectory": is_dir,
+                "size": size
+            }));
+
+            // Recurse into directories
+            if is_dir {
+                let _ = self.list_directory_recursive(&entry.path(), include_hidden, entries);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Convert JSON array of objects to 2D array for Excel
+    fn convert_objects_to_rows(
+        &self,
+        objects: &[serde_json::Value],
+        node: &WorkflowNode,
+    ) -> EngineResult<Vec<Vec<String>>> {
+        if objects.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        // Get column names from node config or auto-detect from first object
+        let columns: Vec<String> = if let Some(cols) = node.data.get("columns").and_then(|v| v.as_array()) {
+            cols.iter()
+                .filter_map(|v| v.as_str().map(String::from))
+                .collect()
+        } else {
+            // Auto-detect columns from first object
+            if let Some(first_obj) = objects.first().and_then(|v| v.as_object()) {
+                first_obj.keys().cloned().collect()
+            } else {
+                return Err(EngineError::ExecutionFailed(
+                    "Cannot determine columns from data".to_string(),
+                ));
+            }
+        };
+
+        let mut rows: Vec<Vec<String>> = Vec::new();
+
+        // Add header row
+        let include_header = node
+            .data
+            .get("includeHeader")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(true);
+
+        if include_header {
+            rows.push(columns.clone());
+        }
+
+        // Add data rows
+        for obj in objects {
+            if let Some(obj_map) = obj.as_object() {
+                let row: Vec<String> = columns
+                    .iter()
+                    .map(|col| {
+                        obj_map
+                            .get(col)
+                            .map(|v| match v {
+                                serde_json::Value::String(s) => s.clone(),
+                                serde_json::Value::Bool(b) => b.to_string(),
+                                serde_json::Value::Number(n) => n.to_string(),
+                                serde_json::Value::Null => String::new(),
+                                _ => v.to_string(),
+                            })
+                            .unwrap_or_default()
+                    })
+                    .collect();
+                rows.push(row);
+            }
+        }
+
+        Ok(rows)
+    }
+
+    /// Open an application
+    async fn execute_open_app(&self, node: &WorkflowNode) -> EngineResult<()> {
+        let app_name = node
+            .data
+            .get("appName")
+            .and_then(|v| v.as_str())
+            .unwrap_or("");
+
+        let app_path = node
+            .data
+            .get("appPath")
+            .and_then(|v| v.as_str())
+            .unwrap_or("");
+
+        let arguments: Vec<String> = node
+            .data
+            .get("arguments")
+            .and_then(|v| v.as_array())
+            .map(|arr| {
+                arr.iter()
+                    .filter_map(|v| v.as_str().map(String::from))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let interpolated_app_name = self.runtime.interpolate(app_name).await;
+        let interpolated_app_path = self.runtime.interpolate(app_path).await;
+
+        let wait_window = node
+            .data
+            .get("waitWindow")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+
+        // Apps are started through a launcher, so their main window is the
+        // first new window rather than one owned by a known pid
+        let known_windows: HashSet<u64> = if wait_window {
+            self.automation
+                .list_windows()
+                .await?
+                .into_iter()
+                .map(|w| w.id)
+                .collect()
+        } else {
+            HashSet::new()
+        };
+
+        self.runtime
+            .add_log(
+                ExecutionLog::info(format!(
+                    "Opening application: {}",
+                    if !interpolated_app_name.is_empty() {
+                        &interpolated_app_name
                     } else {
-                        None
+                        &interpolated_app_path
                     }
-                })
-                .ok_or_else(|| {
-                    EngineError::ExecutionFailed(format!(
-                        "Variable '{}' not found or not a string",
-                        data_variable
-                    ))
-                })?;
+                ))
+                .with_node(&node.id),
+            )
+            .await;
 
-            // Try to parse as ExcelData first, then as simple 2D array, then as array of objects
-            if let Ok(excel_data) =
-                serde_json::from_str::<crate::automation::file::ExcelData>(&data_json)
-            {
-                excel_data
-                    .sheets
-                    .first()
-                    .map(|s| s.rows.clone())
-                    .unwrap_or_default()
-            } else if let Ok(parsed_rows) = serde_json::from_str::<Vec<Vec<String>>>(&data_json) {
-                parsed_rows
-            } else if let Ok(objects) = serde_json::from_str::<Vec<serde_json::Value>>(&data_json) {
-                // Convert array of objects to 2D array (with header row)
-                self.convert_objects_to_rows(&objects, node)?
+        #[cfg(target_os = "macos")]
+        {
+            let mut cmd = std::process::Command::new("open");
+
+            if !interpolated_app_name.is_empty() {
+                cmd.args(["-a", &interpolated_app_name]);
+            } else if !interpolated_app_path.is_empty() {
+                cmd.arg(&interpolated_app_path);
             } else {
                 return Err(EngineError::ExecutionFailed(
-                    "Data variable must be valid JSON (ExcelData, 2D array, or array of objects)".to_string(),
+                    "Either appName or appPath must be provided".to_string(),
                 ));
             }
-        } else {
-            return Err(EngineError::ExecutionFailed(
-                "Either 'data' or 'dataVariable' must be provided".to_string(),
-            ));
-        };
 
-        self.file_automation
-            .write_excel_sheet(&expanded_path, sheet_name, &rows)
-            .map_err(|e| EngineError::ExecutionFailed(e.to_string()))?;
+            // Add any additional arguments
+            if !arguments.is_empty() {
+                cmd.arg("--args");
+                cmd.args(&arguments);
+            }
+
+            cmd.spawn()
+                .map_err(|e| EngineError::ExecutionFailed(format!("Failed to open application: {}", e)))?;
+        }
+
+        #[cfg(target_os = "windows")]
+        {
+            let target = if !interpolated_app_path.is_empty() {
+                &interpolated_app_path
+            } else {
+                &interpolated_app_name
+            };
+
+            let mut cmd = std::process::Command::new("cmd");
+            cmd.args(["/C", "start", "", target]);
+            cmd.args(&arguments);
+
+            cmd.spawn()
+                .map_err(|e| EngineError::ExecutionFailed(format!("Failed to open application: {}", e)))?;
+        }
+
+        #[cfg(target_os = "linux")]
+        {
+            let target = if !interpolated_app_path.is_empty() {
+                &interpolated_app_path
+            } else {
+                &interpolated_app_name
+            };
+
+            let mut cmd = std::process::Command::new("xdg-open");
+            cmd.arg(target);
+            cmd.args(&arguments);
+
+            cmd.spawn()
+                .map_err(|e| EngineError::ExecutionFailed(format!("Failed to open application: {}", e)))?;
+        }
+
+        if wait_window {
+            let timeout = node
+                .data
+                .get("timeout")
+                .and_then(|v| v.as_u64())
+                .unwrap_or(30000);
+            let title = match node.data.get("windowTitle").and_then(|v| v.as_str()) {
+                Some(title) => Some(self.runtime.interpolate(title).await),
+                None => None,
+            };
+            let query = WindowQuery::new(title.as_deref(), None)?;
+
+            let deadline = std::time::Instant::now() + std::time::Duration::from_millis(timeout);
+            let window = loop {
+                let opened = self
+                    .automation
+                    .find_windows(&query)
+                    .await?
+                    .into_iter()
+                    .find(|w| !known_windows.contains(&w.id));
+                if let Some(window) = opened {
+                    break window;
+                }
+                if std::time::Instant::now() >= deadline {
+                    return Err(AutomationError::Timeout(format!(
+                        "No new {} within {}ms",
+                        query, timeout
+                    ))
+                    .into());
+                }
+                tokio::time::sleep(tokio::time::Duration::from_millis(200)).await;
+            };
+
+            self.runtime
+                .add_log(
+                    ExecutionLog::info(format!("Application window '{}' opened", window.title))
+                        .with_node(&node.id),
+                )
+                .await;
+        } else {
+            // Give the app time to start
+            tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+        }
 
         self.runtime
             .add_log(
-                ExecutionLog::info(format!(
-                    "Wrote {} rows to sheet '{}'",
-                    rows.len(),
-                    sheet_name
-                ))
-                .with_node(&node.id),
+                ExecutionLog::info("Application opened successfully").with_node(&node.id),
             )
             .await;
 
         Ok(())
     }
 
-    fn parse_cell_position(&self, position: &str) -> (u32, u32) {
-        let position = position.to_uppercase();
-        let mut col: u32 = 0;
-        let mut row: u32 = 0;
-
-        for c in position.chars() {
-            if c.is_ascii_alphabetic() {
-                col = col * 26 + (c as u32 - 'A' as u32);
-            } else if c.is_ascii_digit() {
-                row = row * 10 + (c as u32 - '0' as u32);
-            }
-        }
-
-        // Excel rows are 1-indexed, convert to 0-indexed
-        (col, if row > 0 { row - 1 } else { 0 })
-    }
-
-    /// Execute a shell command and optionally save output to a variable
-    async fn execute_command(&self, node: &WorkflowNode) -> EngineResult<()> {
+    async fn execute_launch_process(&self, node: &WorkflowNode) -> EngineResult<()> {
         let command = node
             .data
             .get("command")
             .and_then(|v| v.as_str())
             .unwrap_or("");
+        let command = self.runtime.interpolate(command).await;
+        if command.trim().is_empty() {
+            return Err(AutomationError::InvalidParameter("No command configured".to_string()).into());
+        }
 
-        let args: Vec<String> = node
-            .data
-            .get("args")
-            .and_then(|v| v.as_array())
-            .map(|arr| {
-                arr.iter()
-                    .filter_map(|v| v.as_str().map(String::from))
-                    .collect()
-            })
-            .unwrap_or_default();
+        let args = self.node_args(node, "args").await;
+        let working_dir = match node.data.get("workingDir").and_then(|v| v.as_str()) {
+            Some(dir) if !dir.is_empty() => Some(self.runtime.interpolate(dir).await),
+            _ => None,
+        };
 
         let output_variable = node
             .data
             .get("outputVariable")
             .and_then(|v| v.as_str())
-            .unwrap_or("");
-
-        let working_dir = node
-            .data
-            .get("workingDir")
-            .and_then(|v| v.as_str())
-            .unwrap_or("");
+            .unwrap_or("pid");
 
-        let interpolated_command = self.runtime.interpolate(command).await;
-        let interpolated_working_dir = if working_dir.is_empty() {
-            None
-        } else {
-            Some(self.runtime.interpolate(working_dir).await)
-        };
+        let pid = process::launch(&command, &args, working_dir.as_deref())?;
 
         self.runtime
             .add_log(
-                ExecutionLog::info(format!("Executing command: {} {:?}", interpolated_command, args))
+                ExecutionLog::info(format!("Started '{}' {:?} with pid {}", command, args, pid))
                     .with_node(&node.id),
             )
             .await;
 
-        // Build command
-        let mut cmd = std::process::Command::new(&interpolated_command);
-        cmd.args(&args);
-
-        if let Some(dir) = &interpolated_working_dir {
-            cmd.current_dir(dir);
+        if !output_variable.is_empty() {
+            self.runtime
+                .set_variable(output_variable, VariableValue::Number(pid as f64))
+                .await;
         }
 
-        // Execute command
-        let output = cmd
-            .output()
-            .map_err(|e| EngineError::ExecutionFailed(format!("Failed to execute command: {}", e)))?;
+        Ok(())
+    }
 
-        let stdout = String::from_utf8_lossy(&output.stdout).to_string();
-        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+    async fn execute_wait_process(&self, node: &WorkflowNode) -> EngineResult<()> {
+        let timeout = node
+            .data
+            .get("timeout")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(30000);
 
-        if !output.status.success() {
-            self.runtime
-                .add_log(
-                    ExecutionLog::warn(format!("Command exited with error: {}", stderr))
-                        .with_node(&node.id),
-                )
-                .await;
-        }
+        let wait_condition = node
+            .data
+            .get("waitCondition")
+            .and_then(|v| v.as_str())
+            .unwrap_or("exited");
 
-        // Save output to variable if specified
-        if !output_variable.is_empty() {
-            self.runtime
-                .set_variable(output_variable, VariableValue::String(stdout.clone()))
-                .await;
+        let target = self.process_target(node).await?;
+
+        self.runtime
+            .add_log(
+                ExecutionLog::info(format!(
+                    "Waiting for {} (condition: {}, timeout: {}ms)",
+                    target, wait_condition, timeout
+                ))
+                .with_node(&node.id),
+            )
+            .await;
+
+        let found = process::wait_for(&target, wait_condition == "started", timeout).await?;
+
+        // Waiting by name is how a pid is found for later nodes
+        if let (Some(first), Some(output_variable)) = (
+            found.first(),
+            node.data
+                .get("outputVariable")
+                .and_then(|v| v.as_str())
+                .filter(|v| !v.is_empty()),
+        ) {
             self.runtime
-                .add_log(
-                    ExecutionLog::info(format!("Command output saved to '{}'", output_variable))
-                        .with_node(&node.id),
-                )
+                .set_variable(output_variable, VariableValue::Number(first.pid as f64))
                 .await;
         }
 
         self.runtime
-            .add_log(
-                ExecutionLog::info(format!("Command completed with status: {}", output.status))
-                    .with_node(&node.id),
-            )
+            .add_log(ExecutionLog::info("Process wait completed").with_node(&node.id))
             .await;
 
         Ok(())
     }
 
-    /// List files and directories in a given path
-    async fn execute_list_directory(&self, node: &WorkflowNode) -> EngineResult<()> {
-        let path = node
-            .data
-            .get("path")
-            .and_then(|v| v.as_str())
-            .unwrap_or(".");
-
+    async fn execute_check_process(&self, node: &WorkflowNode) -> EngineResult<()> {
+        let target = self.process_target(node).await?;
         let output_variable = node
             .data
             .get("outputVariable")
             .and_then(|v| v.as_str())
-            .unwrap_or("files");
-
-        let include_hidden = node
-            .data
-            .get("includeHidden")
-            .and_then(|v| v.as_bool())
-            .unwrap_or(false);
-
-        let recursive = node
-            .data
-            .get("recursive")
-            .and_then(|v| v.as_bool())
-            .unwrap_or(false);
-
-        let interpolated_path = self.runtime.interpolate(path).await;
+            .unwrap_or("running");
 
-        // Expand ~ to home directory
-        let expanded_path = if interpolated_path.starts_with('~') {
-            if let Some(base_dirs) = directories::BaseDirs::new() {
-                interpolated_path.replacen('~', base_dirs.home_dir().to_str().unwrap_or(""), 1)
-            } else {
-                interpolated_path.clone()
-            }
-        } else {
-            interpolated_path.clone()
-        };
+        let running = process::is_running(&target).await?;
 
         self.runtime
             .add_log(
-                ExecutionLog::info(format!("Listing directory: {}", expanded_path))
-                    .with_node(&node.id),
+                ExecutionLog::info(format!(
+                    "{} is {}",
+                    target,
+                    if running { "running" } else { "not running" }
+                ))
+                .with_node(&node.id),
             )
             .await;
 
-        let path_buf = std::path::PathBuf::from(&expanded_path);
-        if !path_buf.exists() {
-            return Err(EngineError::ExecutionFailed(format!(
-                "Directory does not exist: {}",
-                expanded_path
-            )));
-        }
-
-        let mut entries: Vec<serde_json::Value> = Vec::new();
-
-        if recursive {
-            self.list_directory_recursive(&path_buf, include_hidden, &mut entries)?;
-        } else {
-            let dir_entries = std::fs::read_dir(&path_buf)
-                .map_err(|e| EngineError::ExecutionFailed(format!("Failed to read directory: {}", e)))?;
-
-            for entry in dir_entries.flatten() {
-                let file_name = entry.file_name().to_string_lossy().to_string();
+        self.runtime
+            .set_variable(output_variable, VariableValue::Bool(running))
+            .await;
 
-                // Skip hidden files if not included
-                if !include_hidden && file_name.starts_with('.') {
-                    continue;
-                }
+        Ok(())
+    }
 
-                let metadata = entry.metadata().ok();
-                let is_dir = metadata.as_ref().map(|m| m.is_dir()).unwrap_or(false);
-                let size = metadata.as_ref().map(|m| m.len()).unwrap_or(0);
+    async fn execute_kill_process(&self, node: &WorkflowNode) -> EngineResult<()> {
+        let tree = node
+            .data
+            .get("killTree")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(true);
 
-                entries.push(serde_json::json!({
-                    "name": file_name,
-                    "path": entry.path().to_string_lossy().to_string(),
-                    "isDirectory": is_dir,
-                    "size": size
-                }));
-            }
+        let target = self.process_target(node).await?;
+        let processes = process::find_processes(&target).await?;
+        if processes.is_empty() {
+            return Err(AutomationError::ExecutionFailed(format!("No {} is running", target)).into());
         }
 
-        // Sort entries: directories first, then by name
-        entries.sort_by(|a, b| {
-            let a_is_dir = a.get("isDirectory").and_then(|v| v.as_bool()).unwrap_or(false);
-            let b_is_dir = b.get("isDirectory").and_then(|v| v.as_bool()).unwrap_or(false);
-            if a_is_dir != b_is_dir {
-                return b_is_dir.cmp(&a_is_dir); // Directories first
-            }
-            let a_name = a.get("name").and_then(|v| v.as_str()).unwrap_or("");
-            let b_name = b.get("name").and_then(|v| v.as_str()).unwrap_or("");
-            a_name.cmp(b_name)
-        });
-
-        // Save to variable as JSON string
-        let json_str = serde_json::to_string(&entries)
-            .map_err(|e| EngineError::ExecutionFailed(format!("Failed to serialize entries: {}", e)))?;
-
-        self.runtime
-            .set_variable(output_variable, VariableValue::String(json_str))
-            .await;
+        let mut killed = 0;
+        for found in &processes {
+            killed += process::kill(found.pid, tree).await?;
+        }
 
         self.runtime
             .add_log(
-                ExecutionLog::info(format!(
-                    "Found {} entries, saved to '{}'",
-                    entries.len(),
-                    output_variable
-                ))
-                .with_node(&node.id),
+                ExecutionLog::info(format!("Killed {} processes for {}", killed, target))
+                    .with_node(&node.id),
             )
             .await;
 
         Ok(())
     }
 
-    fn list_directory_recursive(
-        &self,
-        path: &std::path::Path,
-        include_hidden: bool,
-        entries: &mut Vec<serde_json::Value>,
-    ) -> EngineResult<()> {
-        let dir_entries = std::fs::read_dir(path)
-            .map_err(|e| EngineError::ExecutionFailed(format!("Failed to read directory: {}", e)))?;
+    /// Process a node targets by its `pid`, or else its `processName`
+    async fn process_target(&self, node: &WorkflowNode) -> EngineResult<ProcessTarget> {
+        if let Some(pid) = self.node_number(node, "pid").await? {
+            let pid = u32::try_from(pid).map_err(|_| {
+                AutomationError::InvalidParameter(format!("Invalid pid: {}", pid))
+            })?;
+            return Ok(ProcessTarget::Pid(pid));
+        }
 
-        for entry in dir_entries.flatten() {
-            let file_name = entry.file_name().to_string_lossy().to_string();
+        let name = node
+            .data
+            .get("processName")
+            .and_then(|v| v.as_str())
+            .unwrap_or("");
+        let name = self.runtime.interpolate(name).await;
+        if name.trim().is_empty() {
+            return Err(AutomationError::InvalidParameter("No pid or process name configured".to_string()).into());
+        }
+        Ok(ProcessTarget::Name(name.trim().to_string()))
+    }
 
-            // Skip hidden files if not included
-            if !include_hidden && file_name.starts_with('.') {
-                continue;
+    /// Interpolated arguments given as an array or a whitespace-separated string
+    async fn node_args(&self, node: &WorkflowNode, key: &str) -> Vec<String> {
+        let mut args = Vec::new();
+        match node.data.get(key) {
+            Some(serde_json::Value::Array(values)) => {
+                for value in values.iter().filter_map(|v| v.as_str()) {
+                    args.push(self.runtime.interpolate(value).await);
+                }
             }
-
-            let metadata = entry.metadata().ok();
-            let is_dir = metadata.as_ref().map(|m| m.is_dir()).unwrap_or(false);
-            let size = metadata.as_ref().map(|m| m.len()).unwrap_or(0);
-
-            entries.push(serde_json::json!({
-                "name": file_name,
-                "path": entry.path().to_string_lossy().to_string(),
-                "isDirectory": is_dir,
-                "size": size
-            }));
-
-            // Recurse into directories
-            if is_dir {
-                let _ = self.list_directory_recursive(&entry.path(), include_hidden, entries);
+            Some(serde_json::Value::String(line)) => {
+                for arg in line.split_whitespace() {
+                    args.push(self.runtime.interpolate(arg).await);
+                }
             }
+            _ => {}
         }
-
-        Ok(())
+        args
     }
 
-    /// Convert JSON array of objects to 2D array for Excel
-    fn convert_objects_to_rows(
-        &self,
-        objects: &[serde_json::Value],
-        node: &WorkflowNode,
-    ) -> EngineResult<Vec<Vec<String>>> {
-        if objects.is_empty() {
-            return Ok(Vec::new());
-        }
+    /// Copy, or with `move_files` move, files matching `source` to `destination`
+    async fn execute_copy_file(&self, node: &WorkflowNode, move_files: bool) -> EngineResult<()> {
+        let source = self.node_path(node, "source").await?;
+        let destination = self.node_path(node, "destination").await?;
+        let policy: OverwritePolicy = node
+            .data
+            .get("overwrite")
+            .and_then(|v| v.as_str())
+            .unwrap_or("fail")
+            .parse()?;
 
-        // Get column names from node config or auto-detect from first object
-        let columns: Vec<String> = if let Some(cols) = node.data.get("columns").and_then(|v| v.as_array()) {
-            cols.iter()
-                .filter_map(|v| v.as_str().map(String::from))
-                .collect()
+        let action = if move_files { "Moving" } else { "Copying" };
+        self.runtime
+            .add_log(
+                ExecutionLog::info(format!("{} {} to {}", action, source, destination))
+                    .with_node(&node.id),
+            )
+            .await;
+
+        let written = if move_files {
+            self.file_automation.move_to(&source, &destination, policy).await?
         } else {
-            // Auto-detect columns from first object
-            if let Some(first_obj) = objects.first().and_then(|v| v.as_object()) {
-                first_obj.keys().cloned().collect()
-            } else {
-                return Err(EngineError::ExecutionFailed(
-                    "Cannot determine columns from data".to_string(),
-                ));
-            }
+            self.file_automation.copy(&source, &destination, policy).await?
         };
 
-        let mut rows: Vec<Vec<String>> = Vec::new();
-
-        // Add header row
-        let include_header = node
+        if let Some(output_variable) = node
             .data
-            .get("includeHeader")
-            .and_then(|v| v.as_bool())
-            .unwrap_or(true);
-
-        if include_header {
-            rows.push(columns.clone());
+            .get("outputVariable")
+            .and_then(|v| v.as_str())
+            .filter(|v| !v.is_empty())
+        {
+            let paths: Vec<String> = written.iter().map(|p| p.to_string_lossy().into_owned()).collect();
+            self.save_json(output_variable, &paths).await?;
         }
 
-        // Add data rows
-        for obj in objects {
-            if let Some(obj_map) = obj.as_object() {
-                let row: Vec<String> = columns
-                    .iter()
-                    .map(|col| {
-                        obj_map
-                            .get(col)
-                            .map(|v| match v {
-                                serde_json::Value::String(s) => s.clone(),
-                                serde_json::Value::Bool(b) => b.to_string(),
-                                serde_json::Value::Number(n) => n.to_string(),
-                                serde_json::Value::Null => String::new(),
-                                _ => v.to_string(),
-                            })
-                            .unwrap_or_default()
-                    })
-                    .collect();
-                rows.push(row);
-            }
-        }
+        self.runtime
+            .add_log(
+                ExecutionLog::info(format!("{} files written", written.len())).with_node(&node.id),
+            )
+            .await;
 
-        Ok(rows)
+        Ok(())
     }
 
-    /// Open an application
-    async fn execute_open_app(&self, node: &WorkflowNode) -> EngineResult<()> {
-        let app_name = node
+    async fn execute_rename_file(&self, node: &WorkflowNode) -> EngineResult<()> {
+        let path = self.node_path(node, "filePath").await?;
+        let new_name = node
             .data
-            .get("appName")
+            .get("newName")
             .and_then(|v| v.as_str())
             .unwrap_or("");
+        let new_name = self.runtime.interpolate(new_name).await;
+        let policy: OverwritePolicy = node
+            .data
+            .get("overwrite")
+            .and_then(|v| v.as_str())
+            .unwrap_or("fail")
+            .parse()?;
 
-        let app_path = node
+        let renamed = self.file_automation.rename(&path, new_name.trim(), policy).await?;
+
+        if let Some(output_variable) = node
             .data
-            .get("appPath")
+            .get("outputVariable")
             .and_then(|v| v.as_str())
-            .unwrap_or("");
+            .filter(|v| !v.is_empty())
+        {
+            self.runtime
+                .set_variable(
+                    output_variable,
+                    VariableValue::String(renamed.to_string_lossy().into_owned()),
+                )
+                .await;
+        }
 
-        let arguments: Vec<String> = node
+        self.runtime
+            .add_log(
+                ExecutionLog::info(format!("Renamed {} to {}", path, renamed.display()))
+                    .with_node(&node.id),
+            )
+            .await;
+
+        Ok(())
+    }
+
+    async fn execute_delete_file(&self, node: &WorkflowNode) -> EngineResult<()> {
+        let path = self.node_path(node, "filePath").await?;
+        let recursive = node
             .data
-            .get("arguments")
-            .and_then(|v| v.as_array())
-            .map(|arr| {
-                arr.iter()
-                    .filter_map(|v| v.as_str().map(String::from))
-                    .collect()
-            })
-            .unwrap_or_default();
+            .get("recursive")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
 
-        let interpolated_app_name = self.runtime.interpolate(app_name).await;
-        let interpolated_app_path = self.runtime.interpolate(app_path).await;
+        let deleted = self.file_automation.delete(&path, recursive).await?;
+
+        self.runtime
+            .add_log(
+                ExecutionLog::info(format!("Deleted {} entries matching {}", deleted, path))
+                    .with_node(&node.id),
+            )
+            .await;
+
+        Ok(())
+    }
+
+    async fn execute_create_directory(&self, node: &WorkflowNode) -> EngineResult<()> {
+        let path = self.node_path(node, "path").await?;
+
+        self.file_automation.create_directory(&path).await?;
+
+        self.runtime
+            .add_log(ExecutionLog::info(format!("Created directory: {}", path)).with_node(&node.id))
+            .await;
+
+        Ok(())
+    }
+
+    async fn execute_file_info(&self, node: &WorkflowNode) -> EngineResult<()> {
+        let path = self.node_path(node, "filePath").await?;
+        let output_variable = node
+            .data
+            .get("outputVariable")
+            .and_then(|v| v.as_str())
+            .unwrap_or("fileInfo");
+
+        let info = self.file_automation.file_info(&path).await?;
+        self.save_json(output_variable, &info).await?;
 
         self.runtime
             .add_log(
                 ExecutionLog::info(format!(
-                    "Opening application: {}",
-                    if !interpolated_app_name.is_empty() {
-                        &interpolated_app_name
-                    } else {
-                        &interpolated_app_path
-                    }
+                    "File info of {} saved to '{}' (exists: {})",
+                    path, output_variable, info.exists
                 ))
                 .with_node(&node.id),
             )
             .await;
 
-        #[cfg(target_os = "macos")]
-        {
-            let mut cmd = std::process::Command::new("open");
+        Ok(())
+    }
 
-            if !interpolated_app_name.is_empty() {
-                cmd.args(["-a", &interpolated_app_name]);
-            } else if !interpolated_app_path.is_empty() {
-                cmd.arg(&interpolated_app_path);
-            } else {
-                return Err(EngineError::ExecutionFailed(
-                    "Either appName or appPath must be provided".to_string(),
-                ));
-            }
+    async fn execute_wait_file(&self, node: &WorkflowNode) -> EngineResult<()> {
+        let path = self.node_path(node, "filePath").await?;
+        let timeout = node
+            .data
+            .get("timeout")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(30000);
+        let stable_time = node
+            .data
+            .get("stableTime")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(1000);
+        let wait_condition = node
+            .data
+            .get("waitCondition")
+            .and_then(|v| v.as_str())
+            .unwrap_or("appear");
+        let condition: FileWaitCondition = wait_condition.parse()?;
 
-            // Add any additional arguments
-            if !arguments.is_empty() {
-                cmd.arg("--args");
-                cmd.args(&arguments);
-            }
+        self.runtime
+            .add_log(
+                ExecutionLog::info(format!(
+                    "Waiting for file {} (condition: {}, timeout: {}ms)",
+                    path, wait_condition, timeout
+                ))
+                .with_node(&node.id),
+            )
+            .await;
 
-            cmd.spawn()
-                .map_err(|e| EngineError::ExecutionFailed(format!("Failed to open application: {}", e)))?;
-        }
+        let info = self
+            .file_automation
+            .wait_for_file(&path, condition, timeout, stable_time)
+            .await?;
 
-        #[cfg(target_os = "windows")]
+        if let Some(output_variable) = node
+            .data
+            .get("outputVariable")
+            .and_then(|v| v.as_str())
+            .filter(|v| !v.is_empty())
         {
-            let target = if !interpolated_app_path.is_empty() {
-                &interpolated_app_path
-            } else {
-                &interpolated_app_name
-            };
-
-            let mut cmd = std::process::Command::new("cmd");
-            cmd.args(["/C", "start", "", target]);
-            cmd.args(&arguments);
-
-            cmd.spawn()
-                .map_err(|e| EngineError::ExecutionFailed(format!("Failed to open application: {}", e)))?;
+            self.save_json(output_variable, &info).await?;
         }
 
-        #[cfg(target_os = "linux")]
-        {
-            let target = if !interpolated_app_path.is_empty() {
-                &interpolated_app_path
-            } else {
-                &interpolated_app_name
-            };
+        self.runtime
+            .add_log(ExecutionLog::info("File wait completed").with_node(&node.id))
+            .await;
 
-            let mut cmd = std::process::Command::new("xdg-open");
-            cmd.arg(target);
-            cmd.args(&arguments);
+        Ok(())
+    }
 
-            cmd.spawn()
-                .map_err(|e| EngineError::ExecutionFailed(format!("Failed to open application: {}", e)))?;
-        }
+    async fn execute_create_temp_file(&self, node: &WorkflowNode) -> EngineResult<()> {
+        let prefix = node
+            .data
+            .get("prefix")
+            .and_then(|v| v.as_str())
+            .unwrap_or("tmp");
+        let extension = node
+            .data
+            .get("extension")
+            .and_then(|v| v.as_str())
+            .unwrap_or("");
+        let content = node
+            .data
+            .get("content")
+            .and_then(|v| v.as_str())
+            .unwrap_or("");
+        let output_variable = node
+            .data
+            .get("outputVariable")
+            .and_then(|v| v.as_str())
+            .unwrap_or("tempFile");
 
-        // Give the app time to start
-        tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+        let prefix = self.runtime.interpolate(prefix).await;
+        let content = self.runtime.interpolate(content).await;
+        let path = self
+            .file_automation
+            .create_temp_file(&prefix, extension, content.as_bytes())
+            .await?;
+        let path = path.to_string_lossy().into_owned();
 
         self.runtime
             .add_log(
-                ExecutionLog::info("Application opened successfully").with_node(&node.id),
+                ExecutionLog::info(format!("Created temp file {} in '{}'", path, output_variable))
+                    .with_node(&node.id),
             )
             .await;
+        self.runtime
+            .set_variable(output_variable, VariableValue::String(path))
+            .await;
 
         Ok(())
     }
 
+    /// Interpolated path from a node field, with `~` expanded
+    async fn node_path(&self, node: &WorkflowNode, key: &str) -> EngineResult<String> {
+        let path = node.data.get(key).and_then(|v| v.as_str()).unwrap_or("");
+        let path = self.runtime.interpolate(path).await;
+        if path.trim().is_empty() {
+            return Err(AutomationError::InvalidParameter(format!("No {} configured", key)).into());
+        }
+        Ok(expand_home(path.trim()))
+    }
+
     pub async fn get_state(&self) -> super::runtime::RuntimeState {
         self.runtime.get_state().await
     }
@@ -2179,3 +3774,209 @@ impl Executor {
         self.runtime.get_all_variables().await
     }
 }
+
+/// Replace a leading `~` or `~/` with the home directory; `~user` paths
+/// are left alone
+fn expand_home(path: &str) -> String {
+    let rest = match path.strip_prefix('~') {
+        Some(rest) if rest.is_empty() || rest.starts_with(['/', '\\']) => rest,
+        _ => return path.to_string(),
+    };
+    match directories::BaseDirs::new() {
+        Some(base_dirs) => format!("{}{}", base_dirs.home_dir().display(), rest),
+        None => path.to_string(),
+    }
+}
+
+/// Command running a command line through the platform shell
+fn shell_command(line: &str) -> tokio::process::Command {
+    #[cfg(target_os = "windows")]
+    {
+        // cmd does not follow the usual argument quoting, so the line is
+        // passed as written
+        let mut cmd = tokio::process::Command::new("cmd");
+        cmd.arg("/C").raw_arg(line);
+        cmd
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        let mut cmd = tokio::process::Command::new("sh");
+        cmd.arg("-c").arg(line);
+        cmd
+    }
+}
+
+/// Quote an argument so the platform shell passes it on as one argument
+///
+/// cmd still expands `%NAME%` inside quotes.
+fn shell_quote(arg: &str) -> String {
+    let plain = |c: char| c.is_ascii_alphanumeric() || "_-./:=,+@".contains(c);
+    if !arg.is_empty() && arg.chars().all(plain) {
+        return arg.to_string();
+    }
+
+    if cfg!(target_os = "windows") {
+        // Backslashes only escape when they come before a quote
+        let mut quoted = String::from("\"");
+        let mut backslashes = 0;
+        for c in arg.chars() {
+            match c {
+                '\\' => backslashes += 1,
+                '"' => {
+                    quoted.extend(std::iter::repeat_n('\\', backslashes * 2 + 1));
+                    backslashes = 0;
+                }
+                _ => {
+                    quoted.extend(std::iter::repeat_n('\\', backslashes));
+                    backslashes = 0;
+                }
+            }
+            if c != '\\' {
+                quoted.push(c);
+            }
+        }
+        quoted.extend(std::iter::repeat_n('\\', backslashes * 2));
+        quoted.push('"');
+        quoted
+    } else {
+        format!("'{}'", arg.replace('\'', "'\\''"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::automation::desktop::testing::FakeDesktop;
+    use crate::element::ElementLibrary;
+    use crate::recorder::{convert_to_workflow, ConversionOptions, RecordedAction, RecordedActionType, RecordingSession};
+
+    #[test]
+    fn test_expand_home() {
+        let home = directories::BaseDirs::new().unwrap().home_dir().display().to_string();
+        assert_eq!(expand_home("~"), home);
+        assert_eq!(expand_home("~/reports"), format!("{}/reports", home));
+        assert_eq!(expand_home("~foo/reports"), "~foo/reports");
+        assert_eq!(expand_home("/tmp/~"), "/tmp/~");
+    }
+
+    fn command_node(data: serde_json::Value) -> WorkflowNode {
+        WorkflowNode {
+            id: "command".to_string(),
+            node_type: "command".to_string(),
+            position: Position { x: 0.0, y: 0.0 },
+            data: serde_json::from_value(data).unwrap(),
+            label: None,
+        }
+    }
+
+    fn empty_executor() -> Executor {
+        Executor::new(Workflow {
+            id: "test".to_string(),
+            name: "Test".to_string(),
+            nodes: Vec::new(),
+            edges: Vec::new(),
+        })
+    }
+
+    async fn text_variable(executor: &Executor, name: &str) -> String {
+        match executor.runtime.get_variable(name).await {
+            Some(VariableValue::String(text)) => text,
+            other => panic!("{} is {:?}", name, other),
+        }
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_command_output_variables() {
+        let executor = empty_executor();
+        let node = command_node(serde_json::json!({
+            "command": "cat; echo oops >&2; exit 3",
+            "shell": true,
+            "stdin": "hello",
+            "outputVariable": "out",
+            "stderrVariable": "err",
+            "exitCodeVariable": "code",
+        }));
+
+        executor.execute_command(&node).await.unwrap();
+        assert_eq!(text_variable(&executor, "out").await, "hello");
+        assert_eq!(text_variable(&executor, "err").await, "oops\n");
+        assert!(matches!(
+            executor.runtime.get_variable("code").await,
+            Some(VariableValue::Number(code)) if code == 3.0
+        ));
+
+        let node = command_node(serde_json::json!({
+            "command": "exit 3",
+            "shell": true,
+            "failOnError": true,
+        }));
+        assert!(matches!(
+            executor.execute_command(&node).await,
+            Err(EngineError::ExecutionFailed(_))
+        ));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_command_shell_arguments_are_quoted() {
+        let executor = empty_executor();
+        let node = command_node(serde_json::json!({
+            "command": "printf '%s|'",
+            "args": ["a b", "it's", "$HOME; exit 1", ""],
+            "shell": true,
+            "failOnError": true,
+            "outputVariable": "out",
+        }));
+
+        executor.execute_command(&node).await.unwrap();
+        assert_eq!(text_variable(&executor, "out").await, "a b|it's|$HOME; exit 1||");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_command_timeout_and_background_processes() {
+        let executor = empty_executor();
+        let started = std::time::Instant::now();
+        let node = command_node(serde_json::json!({
+            "command": "sleep",
+            "args": ["10"],
+            "timeout": 200,
+        }));
+        assert!(matches!(
+            executor.execute_command(&node).await,
+            Err(EngineError::AutomationError(AutomationError::Timeout(_)))
+        ));
+        assert!(started.elapsed() < std::time::Duration::from_secs(5));
+
+        // The background sleep keeps stdout open but the command is done
+        let started = std::time::Instant::now();
+        let node = command_node(serde_json::json!({
+            "command": "sleep 10 & echo started",
+            "shell": true,
+            "outputVariable": "out",
+        }));
+        executor.execute_command(&node).await.unwrap();
+        assert_eq!(text_variable(&executor, "out").await, "started\n");
+        assert!(started.elapsed() < std::time::Duration::from_secs(5));
+    }
+
+    #[tokio::test]
+    async fn test_recorded_click_without_element_runs_at_position() {
+        let mut session = RecordingSession::new("Test".to_string());
+        session.actions = vec![RecordedAction::new(RecordedActionType::Click).with_position(120, 45)];
+        let mut library = ElementLibrary::new("Test".to_string());
+        let workflow = convert_to_workflow(&session, &ConversionOptions::default(), &mut library);
+
+        let desktop = FakeDesktop::default();
+        let log = Arc::clone(&desktop.log);
+        let executor = Executor {
+            automation: Box::new(desktop),
+            ..Executor::new(workflow)
+        };
+
+        executor.execute().await.unwrap();
+        assert_eq!(*log.lock().unwrap(), vec!["click 120,45"]);
+    }
+}
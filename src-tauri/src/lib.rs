@@ -1,4 +1,5 @@
 pub mod automation;
+pub mod bundle;
 pub mod commands;
 pub mod element;
 pub mod engine;
@@ -44,6 +45,9 @@ pub fn run() {
                 }
             });
 
+            // Take scheduled backups in the background
+            tauri::async_runtime::spawn(storage::backup::run_scheduler());
+
             // Load plugins from plugins directory
             let plugins_dir = app
                 .path()
@@ -56,9 +60,8 @@ pub fn run() {
                 log::warn!("Failed to create plugins directory: {}", e);
             }
 
-            // Load plugins using the plugin_state we created earlier
-            let registry = Arc::new(PluginRegistry::new());
-            let registry_clone = registry.clone();
+            // Load plugins into the managed registry so commands and the executor see them
+            let registry_clone = app.state::<PluginState>().registry.clone();
             tauri::async_runtime::block_on(async move {
                 let loader = PluginLoader::new(registry_clone);
                 match loader.load_directory(&plugins_dir).await {
@@ -90,6 +93,10 @@ pub fn run() {
             commands::list_element_libraries,
             commands::delete_element_library,
             commands::highlight_element,
+            commands::find_image,
+            commands::get_accessibility_tree,
+            commands::validate_element,
+            commands::validate_element_library,
             // Execution commands
             commands::execute_workflow,
             commands::get_execution_state,
@@ -106,6 +113,7 @@ pub fn run() {
             commands::get_variables,
             // Recorder commands
             commands::start_recording,
+            commands::start_web_recording,
             commands::pause_recording,
             commands::resume_recording,
             commands::stop_recording,
@@ -113,12 +121,29 @@ pub fn run() {
             commands::get_recording_session,
             commands::convert_recording_to_workflow,
             commands::clear_recording,
+            commands::save_recording,
+            commands::list_recordings,
+            commands::load_recording,
+            commands::delete_recording,
+            commands::replay_recording,
             // Settings commands
             commands::get_db_config,
             commands::set_db_config,
             commands::test_db_connection,
             commands::apply_db_config,
             commands::get_supported_db_types,
+            commands::migrate_database,
+            // Bundle commands
+            commands::export_workflow_bundle,
+            commands::inspect_workflow_bundle,
+            commands::import_workflow_bundle,
+            // Backup commands
+            commands::create_backup,
+            commands::get_backup_info,
+            commands::restore_backup,
+            commands::list_backups,
+            commands::get_backup_config,
+            commands::set_backup_config,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
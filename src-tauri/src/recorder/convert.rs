@@ -0,0 +1,877 @@
+//! Conversion of recordings into workflows
+//!
+//! Raw recordings are noisy: keystrokes can arrive one at a time, modifier
+//! keys separately from the key they modify, and a double-click also reports
+//! the click it started with. The actions are first reduced to steps the
+//! executor can run, then laid out as nodes. Captured elements are stored in
+//! an element library and referenced from the nodes.
+//!
+//! Browser recordings become web nodes in a browser opened by the workflow.
+
+use super::{RecordedAction, RecordedActionType, RecordingSession, RecordingSource, WHEEL_DELTA};
+use crate::element::{ElementLibrary, UIElement};
+use crate::engine::executor::{Position, Workflow, WorkflowEdge, WorkflowNode};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+pub(super) const DOUBLE_CLICK_MS: u64 = 500;
+const DOUBLE_CLICK_DISTANCE: i32 = 4;
+
+/// Modifier keys recorded on their own combine with a key pressed within this time
+pub(super) const COMBO_MS: u64 = 1000;
+
+/// Wheel events this close together form one scroll
+const SCROLL_MERGE_MS: u64 = 500;
+
+/// Navigations this soon after another step are taken as caused by it
+const NAVIGATION_CAUSE_MS: u64 = 3000;
+
+const NODE_SPACING: f64 = 100.0;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ConversionOptions {
+    /// Pauses longer than this become delay nodes
+    pub delay_threshold_ms: u64,
+    /// Upper bound for delays created from pauses
+    pub max_delay_ms: u64,
+    /// Library to store captured elements in; a new one is created when unset
+    pub library_id: Option<String>,
+    /// Name of the new library, defaults to the recording name
+    pub library_name: Option<String>,
+}
+
+impl Default for ConversionOptions {
+    fn default() -> Self {
+        Self {
+            delay_threshold_ms: 2000,
+            max_delay_ms: 10000,
+            library_id: None,
+            library_name: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Step {
+    Click {
+        click_type: &'static str,
+        element: Option<UIElement>,
+        position: Option<(i32, i32)>,
+        selector: Option<String>,
+    },
+    Input {
+        text: String,
+        element: Option<UIElement>,
+        selector: Option<String>,
+    },
+    Select {
+        value: String,
+        element: Option<UIElement>,
+        selector: String,
+    },
+    Hotkey {
+        keys: String,
+    },
+    Scroll {
+        /// Accumulated wheel movement in `WHEEL_DELTA` units
+        delta: (i64, i64),
+        element: Option<UIElement>,
+        position: Option<(i32, i32)>,
+    },
+    Navigate {
+        url: String,
+    },
+    Delay {
+        ms: u64,
+    },
+}
+
+/// A step with the time span of the actions it was made from
+#[derive(Debug, Clone)]
+struct TimedStep {
+    step: Step,
+    start: u64,
+    end: u64,
+}
+
+impl TimedStep {
+    fn at(step: Step, timestamp: u64) -> Self {
+        Self {
+            step,
+            start: timestamp,
+            end: timestamp,
+        }
+    }
+}
+
+/// Convert a recording into a workflow
+///
+/// Elements referenced by the nodes are added to `library` unless it already
+/// contains an equivalent element. The caller is responsible for saving it.
+pub fn convert_to_workflow(
+    session: &RecordingSession,
+    options: &ConversionOptions,
+    library: &mut ElementLibrary,
+) -> Workflow {
+    let steps = collect_steps(&session.actions);
+    let steps = target_inputs(steps);
+    let mut steps = insert_delays(steps, options);
+
+    let mut nodes = vec![WorkflowNode {
+        id: "start_node".to_string(),
+        node_type: "start".to_string(),
+        position: Position { x: 250.0, y: 0.0 },
+        data: HashMap::new(),
+        label: Some("Start".to_string()),
+    }];
+    let mut edges = Vec::new();
+    let mut prev_node_id = "start_node".to_string();
+    let mut y_position = NODE_SPACING;
+
+    let mut node_steps: Vec<(&'static str, HashMap<String, serde_json::Value>)> = Vec::new();
+    if session.source == RecordingSource::Web {
+        // The workflow opens its own browser, at the page the recording started on
+        let initial_url = match steps.first() {
+            Some(TimedStep {
+                step: Step::Navigate { url },
+                ..
+            }) => url.clone(),
+            _ => String::new(),
+        };
+        if !initial_url.is_empty() {
+            steps.remove(0);
+        }
+        let data = HashMap::from([("initialUrl".to_string(), serde_json::json!(initial_url))]);
+        node_steps.push(("openBrowser", data));
+    }
+    node_steps.extend(steps.into_iter().map(|step| node_data(step.step, library)));
+
+    for (node_type, mut data) in node_steps {
+        let id = uuid::Uuid::new_v4().to_string();
+        if node_type == "hotkey" {
+            let target = match session.source {
+                RecordingSource::Web => "browser",
+                RecordingSource::Desktop => "desktop",
+            };
+            data.insert("target".to_string(), serde_json::json!(target));
+        }
+
+        nodes.push(WorkflowNode {
+            id: id.clone(),
+            node_type: node_type.to_string(),
+            position: Position {
+                x: 250.0,
+                y: y_position,
+            },
+            data,
+            label: None,
+        });
+        edges.push(edge(&prev_node_id, &id));
+
+        prev_node_id = id;
+        y_position += NODE_SPACING;
+    }
+
+    nodes.push(WorkflowNode {
+        id: "end_node".to_string(),
+        node_type: "end".to_string(),
+        position: Position {
+            x: 250.0,
+            y: y_position,
+        },
+        data: HashMap::new(),
+        label: Some("End".to_string()),
+    });
+    edges.push(edge(&prev_node_id, "end_node"));
+
+    Workflow {
+        id: uuid::Uuid::new_v4().to_string(),
+        name: session.name.clone(),
+        nodes,
+        edges,
+    }
+}
+
+fn edge(source: &str, target: &str) -> WorkflowEdge {
+    WorkflowEdge {
+        id: format!("edge_{}_{}", source, target),
+        source: source.to_string(),
+        target: target.to_string(),
+        source_handle: None,
+        target_handle: None,
+    }
+}
+
+fn node_data(step: Step, library: &mut ElementLibrary) -> (&'static str, HashMap<String, serde_json::Value>) {
+    let mut data = HashMap::new();
+    let mut target = |data: &mut HashMap<String, serde_json::Value>, element: UIElement| {
+        data.insert("elementName".to_string(), serde_json::json!(element.name));
+        data.insert("elementId".to_string(), serde_json::json!(store_element(library, element)));
+        data.insert("libraryId".to_string(), serde_json::json!(library.id));
+    };
+
+    let node_type = match step {
+        Step::Click {
+            click_type,
+            element,
+            position,
+            selector,
+        } => {
+            if let Some(element) = element {
+                target(&mut data, element);
+            }
+            if let Some(selector) = selector {
+                data.insert("selector".to_string(), serde_json::json!(selector));
+                return ("webClick", data);
+            }
+            data.insert("clickType".to_string(), serde_json::json!(click_type));
+            if let Some((x, y)) = position {
+                data.insert("x".to_string(), serde_json::json!(x));
+                data.insert("y".to_string(), serde_json::json!(y));
+            }
+            "click"
+        }
+        Step::Input {
+            text,
+            element,
+            selector,
+        } => {
+            data.insert("text".to_string(), serde_json::json!(text));
+            if let Some(element) = element {
+                target(&mut data, element);
+            }
+            if let Some(selector) = selector {
+                data.insert("selector".to_string(), serde_json::json!(selector));
+                return ("webInput", data);
+            }
+            "input"
+        }
+        Step::Select {
+            value,
+            element,
+            selector,
+        } => {
+            // There is no select node; set the value like `WebAutomation::select_option`
+            let script = format!(
+                "const select = document.querySelector({}); \
+                 if (select) {{ select.value = {}; select.dispatchEvent(new Event('change', {{ bubbles: true }})); }}",
+                serde_json::json!(selector),
+                serde_json::json!(value)
+            );
+            data.insert("script".to_string(), serde_json::json!(script));
+            if let Some(element) = element {
+                data.insert("elementName".to_string(), serde_json::json!(element.name));
+            }
+            "executeJs"
+        }
+        Step::Hotkey { keys } => {
+            data.insert("keys".to_string(), serde_json::json!(keys));
+            "hotkey"
+        }
+        Step::Scroll {
+            delta,
+            element,
+            position,
+        } => {
+            if let Some(element) = element {
+                target(&mut data, element);
+            }
+            let (dx, dy) = wheel_notches(delta);
+            data.insert("dx".to_string(), serde_json::json!(dx));
+            data.insert("dy".to_string(), serde_json::json!(dy));
+            if let Some((x, y)) = position {
+                data.insert("x".to_string(), serde_json::json!(x));
+                data.insert("y".to_string(), serde_json::json!(y));
+            }
+            "scroll"
+        }
+        Step::Navigate { url } => {
+            data.insert("url".to_string(), serde_json::json!(url));
+            "navigate"
+        }
+        Step::Delay { ms } => {
+            data.insert("delay".to_string(), serde_json::json!(ms));
+            "delay"
+        }
+    };
+    (node_type, data)
+}
+
+/// Id of the library element equivalent to `element`, adding it if missing
+fn store_element(library: &mut ElementLibrary, element: UIElement) -> String {
+    if let Some(existing) = library.elements.iter().find(|e| same_element(e, &element)) {
+        return existing.id.clone();
+    }
+    let id = element.id.clone();
+    library.add_element(element);
+    id
+}
+
+/// Whether two captures describe the same element; bounds may differ
+fn same_element(a: &UIElement, b: &UIElement) -> bool {
+    a.id == b.id
+        || (a.name == b.name
+            && a.control_type == b.control_type
+            && a.automation_id == b.automation_id
+            && a.class_name == b.class_name
+            && a.xpath == b.xpath
+            && a.css_selector == b.css_selector
+            && a.process_name == b.process_name
+            && a.window_title == b.window_title)
+}
+
+/// Reduce raw actions to executable steps
+///
+/// Pointer moves are dropped, wheel events are merged into scrolls,
+/// keystrokes are merged into text,
+/// modifier keys are combined with the next key into hotkeys, and the click
+/// preceding a double-click is removed. Navigations caused by a click or key
+/// are dropped, replaying the step repeats them.
+fn collect_steps(actions: &[RecordedAction]) -> Vec<TimedStep> {
+    let mut steps: Vec<TimedStep> = Vec::new();
+    // Modifier keys recorded as separate key presses, with their time
+    let mut held: Vec<(&'static str, u64)> = Vec::new();
+
+    for action in actions {
+        let timestamp = action.timestamp;
+        held.retain(|(_, at)| timestamp.saturating_sub(*at) <= COMBO_MS);
+
+        match action.action_type {
+            RecordedActionType::MouseMove => {}
+            RecordedActionType::Navigate => {
+                let url = action.data.get("url").and_then(|v| v.as_str()).unwrap_or("");
+                let caused = steps
+                    .last()
+                    .is_some_and(|last| timestamp.saturating_sub(last.end) <= NAVIGATION_CAUSE_MS);
+                if !url.is_empty() && url != "about:blank" && !caused {
+                    steps.push(TimedStep::at(Step::Navigate { url: url.to_string() }, timestamp));
+                }
+            }
+            RecordedActionType::Select => {
+                held.clear();
+                let Some(selector) = selector(action) else {
+                    continue;
+                };
+                let value = action.data.get("value").and_then(|v| v.as_str()).unwrap_or("");
+                steps.push(TimedStep::at(
+                    Step::Select {
+                        value: value.to_string(),
+                        element: action.element.clone(),
+                        selector,
+                    },
+                    timestamp,
+                ));
+            }
+            RecordedActionType::Scroll => {
+                held.clear();
+                let delta = |key: &str| action.data.get(key).and_then(|v| v.as_i64()).unwrap_or(0);
+                add_scroll(&mut steps, (delta("deltaX"), delta("deltaY")), action, timestamp);
+            }
+            RecordedActionType::Wait => {
+                let ms = action.data.get("delay").and_then(|v| v.as_u64()).unwrap_or(1000);
+                steps.push(TimedStep::at(Step::Delay { ms }, timestamp));
+            }
+            RecordedActionType::Click | RecordedActionType::DoubleClick | RecordedActionType::RightClick => {
+                held.clear();
+                let click_type = match action.action_type {
+                    RecordedActionType::DoubleClick => "double",
+                    RecordedActionType::RightClick => "right",
+                    _ => "single",
+                };
+
+                let mut step = TimedStep::at(
+                    Step::Click {
+                        click_type,
+                        element: action.element.clone(),
+                        position: action.position,
+                        selector: selector(action),
+                    },
+                    timestamp,
+                );
+
+                // Recorders report the first click of a double-click on its own
+                if click_type == "double" {
+                    if let Some(TimedStep {
+                        step: Step::Click { click_type: "single", position, .. },
+                        start,
+                        end,
+                    }) = steps.last()
+                    {
+                        if timestamp.saturating_sub(*end) <= DOUBLE_CLICK_MS && near(*position, action.position) {
+                            step.start = *start;
+                            steps.pop();
+                        }
+                    }
+                }
+
+                steps.push(step);
+            }
+            RecordedActionType::Input => {
+                let text = action.data.get("text").and_then(|v| v.as_str()).unwrap_or("");
+                if let Some(selector) = selector(action) {
+                    set_value(&mut steps, text, action.element.as_ref(), selector, timestamp);
+                    continue;
+                }
+                let modifiers: Vec<&str> = held.drain(..).map(|(name, _)| name).collect();
+
+                if modifiers.iter().any(|m| *m != "Shift") {
+                    let key = text.to_uppercase();
+                    steps.push(TimedStep::at(Step::Hotkey { keys: combo(&modifiers, &key) }, timestamp));
+                } else if modifiers.is_empty() {
+                    append_text(&mut steps, text, action.element.as_ref(), timestamp);
+                } else {
+                    append_text(&mut steps, &text.to_uppercase(), action.element.as_ref(), timestamp);
+                }
+            }
+            RecordedActionType::Hotkey => {
+                let vk_code = action.data.get("vkCode").and_then(|v| v.as_u64());
+                if let Some(modifier) = vk_code.and_then(vk_modifier) {
+                    // Held modifiers repeat; keep the latest press
+                    held.retain(|(name, _)| *name != modifier);
+                    held.push((modifier, timestamp));
+                    continue;
+                }
+
+                let key = action
+                    .data
+                    .get("key")
+                    .and_then(|v| v.as_str())
+                    .map(String::from)
+                    .or_else(|| vk_code.and_then(vk_key_name));
+                let Some(key) = key else {
+                    continue;
+                };
+
+                let recorded: Vec<&str> = action
+                    .data
+                    .get("modifiers")
+                    .and_then(|v| v.as_array())
+                    .map(|arr| arr.iter().filter_map(|v| v.as_str()).collect())
+                    .unwrap_or_default();
+                let modifiers: Vec<&str> = if recorded.is_empty() {
+                    held.drain(..).map(|(name, _)| name).collect()
+                } else {
+                    held.clear();
+                    recorded
+                };
+
+                if modifiers.is_empty() && key == "Backspace" && erase_char(&mut steps, timestamp) {
+                    continue;
+                }
+                steps.push(TimedStep::at(Step::Hotkey { keys: combo(&modifiers, &key) }, timestamp));
+            }
+        }
+    }
+
+    steps.retain(|s| match &s.step {
+        Step::Input { text, .. } => !text.is_empty(),
+        Step::Scroll { delta, .. } => wheel_notches(*delta) != (0, 0),
+        _ => true,
+    });
+    steps
+}
+
+/// Add wheel movement to the previous scroll if it continues it
+fn add_scroll(steps: &mut Vec<TimedStep>, delta: (i64, i64), action: &RecordedAction, timestamp: u64) {
+    if let Some(TimedStep {
+        step: Step::Scroll {
+            delta: total,
+            position,
+            ..
+        },
+        end,
+        ..
+    }) = steps.last_mut()
+    {
+        if timestamp.saturating_sub(*end) <= SCROLL_MERGE_MS && near(*position, action.position) {
+            total.0 += delta.0;
+            total.1 += delta.1;
+            *end = timestamp;
+            return;
+        }
+    }
+    steps.push(TimedStep::at(
+        Step::Scroll {
+            delta,
+            element: action.element.clone(),
+            position: action.position,
+        },
+        timestamp,
+    ));
+}
+
+/// Wheel notches for the scroll node, where positive `dy` scrolls down
+pub(super) fn wheel_notches((x, y): (i64, i64)) -> (i64, i64) {
+    let notches = |delta: i64| (delta as f64 / WHEEL_DELTA as f64).round() as i64;
+    (notches(x), -notches(y))
+}
+
+/// Selector of an action recorded in a browser
+fn selector(action: &RecordedAction) -> Option<String> {
+    action
+        .data
+        .get("selector")
+        .and_then(|v| v.as_str())
+        .filter(|s| !s.is_empty())
+        .map(String::from)
+}
+
+/// Record the committed value of a browser text field
+///
+/// Browser recordings report whole values, so a value replaces the previous
+/// one of the same field.
+fn set_value(steps: &mut Vec<TimedStep>, text: &str, element: Option<&UIElement>, selector: String, timestamp: u64) {
+    if let Some(TimedStep {
+        step: Step::Input {
+            text: previous,
+            selector: Some(previous_selector),
+            ..
+        },
+        end,
+        ..
+    }) = steps.last_mut()
+    {
+        if *previous_selector == selector {
+            *previous = text.to_string();
+            *end = timestamp;
+            return;
+        }
+    }
+    steps.push(TimedStep::at(
+        Step::Input {
+            text: text.to_string(),
+            element: element.cloned(),
+            selector: Some(selector),
+        },
+        timestamp,
+    ));
+}
+
+/// Add typed text to the previous input step if it targets the same element
+fn append_text(steps: &mut Vec<TimedStep>, text: &str, element: Option<&UIElement>, timestamp: u64) {
+    let continues = match steps.last() {
+        Some(TimedStep {
+            step: Step::Input {
+                element: previous,
+                selector: None,
+                ..
+            },
+            ..
+        }) => match (previous, element) {
+            (_, None) => true,
+            (Some(previous), Some(element)) => same_element(previous, element),
+            (None, Some(_)) => false,
+        },
+        _ => false,
+    };
+    if !continues {
+        steps.push(TimedStep::at(
+            Step::Input {
+                text: String::new(),
+                element: element.cloned(),
+                selector: None,
+            },
+            timestamp,
+        ));
+    }
+
+    if let Some(TimedStep {
+        step: Step::Input { text: typed, .. },
+        end,
+        ..
+    }) = steps.last_mut()
+    {
+        for ch in text.chars() {
+            match ch {
+                '\u{8}' | '\u{7f}' => {
+                    typed.pop();
+                }
+                _ => typed.push(ch),
+            }
+        }
+        *end = timestamp;
+    }
+}
+
+/// Remove the last typed character, if the previous step is typing
+fn erase_char(steps: &mut [TimedStep], timestamp: u64) -> bool {
+    match steps.last_mut() {
+        Some(TimedStep {
+            step: Step::Input {
+                text,
+                selector: None,
+                ..
+            },
+            end,
+            ..
+        }) if !text.is_empty() => {
+            text.pop();
+            *end = timestamp;
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Give input steps the element clicked before them
+///
+/// Input nodes click their element before typing, so a click directly
+/// followed by typing into the same element is dropped.
+fn target_inputs(steps: Vec<TimedStep>) -> Vec<TimedStep> {
+    let mut result: Vec<TimedStep> = Vec::with_capacity(steps.len());
+    let mut focused: Option<UIElement> = None;
+
+    for mut step in steps {
+        match &mut step.step {
+            Step::Click { click_type, element, .. } => {
+                focused = if *click_type == "single" { element.clone() } else { None };
+            }
+            Step::Input { element, .. } => {
+                if element.is_none() {
+                    *element = focused.clone();
+                }
+                if let (
+                    Some(element),
+                    Some(TimedStep {
+                        step: Step::Click {
+                            click_type: "single",
+                            element: Some(clicked),
+                            ..
+                        },
+                        start,
+                        ..
+                    }),
+                ) = (element.as_ref(), result.last())
+                {
+                    if same_element(clicked, element) {
+                        step.start = *start;
+                        result.pop();
+                    }
+                }
+            }
+            _ => {}
+        }
+        result.push(step);
+    }
+    result
+}
+
+/// Insert delay steps where the user paused longer than the threshold
+fn insert_delays(steps: Vec<TimedStep>, options: &ConversionOptions) -> Vec<TimedStep> {
+    let mut result: Vec<TimedStep> = Vec::with_capacity(steps.len());
+
+    for step in steps {
+        if let Some(previous) = result.last() {
+            let gap = step.start.saturating_sub(previous.end);
+            let is_delay = |s: &TimedStep| matches!(s.step, Step::Delay { .. });
+            if gap > options.delay_threshold_ms && !is_delay(previous) && !is_delay(&step) {
+                // Round to 100ms, recorded timings are not that precise
+                let ms = (gap / 100 * 100).min(options.max_delay_ms);
+                result.push(TimedStep::at(Step::Delay { ms }, previous.end));
+            }
+        }
+        result.push(step);
+    }
+    result
+}
+
+pub(super) fn near(a: Option<(i32, i32)>, b: Option<(i32, i32)>) -> bool {
+    match (a, b) {
+        (Some((ax, ay)), Some((bx, by))) => {
+            (ax - bx).abs() <= DOUBLE_CLICK_DISTANCE && (ay - by).abs() <= DOUBLE_CLICK_DISTANCE
+        }
+        _ => true,
+    }
+}
+
+/// Key combination in the "Ctrl+Shift+S" form used by hotkey nodes
+pub(super) fn combo(modifiers: &[&str], key: &str) -> String {
+    modifiers.iter().copied().chain(std::iter::once(key)).collect::<Vec<_>>().join("+")
+}
+
+/// Modifier name of a Windows virtual-key code
+pub(super) fn vk_modifier(vk_code: u64) -> Option<&'static str> {
+    match vk_code {
+        0x10 | 0xA0 | 0xA1 => Some("Shift"),
+        0x11 | 0xA2 | 0xA3 => Some("Ctrl"),
+        0x12 | 0xA4 | 0xA5 => Some("Alt"),
+        0x5B | 0x5C => Some("Win"),
+        _ => None,
+    }
+}
+
+/// Key name of a Windows virtual-key code that does not produce text
+pub(super) fn vk_key_name(vk_code: u64) -> Option<String> {
+    let name = match vk_code {
+        0x08 => "Backspace",
+        0x09 => "Tab",
+        0x0D => "Enter",
+        0x14 => "CapsLock",
+        0x1B => "Esc",
+        0x20 => "Space",
+        0x21 => "PageUp",
+        0x22 => "PageDown",
+        0x23 => "End",
+        0x24 => "Home",
+        0x25 => "Left",
+        0x26 => "Up",
+        0x27 => "Right",
+        0x28 => "Down",
+        0x2C => "PrintScreen",
+        0x2D => "Insert",
+        0x2E => "Delete",
+        0x70..=0x87 => return Some(format!("F{}", vk_code - 0x6F)),
+        _ => return None,
+    };
+    Some(name.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::automation::Rect;
+
+    fn action(action_type: RecordedActionType, timestamp: u64) -> RecordedAction {
+        let mut action = RecordedAction::new(action_type);
+        action.timestamp = timestamp;
+        action
+    }
+
+    fn typed(text: &str, timestamp: u64) -> RecordedAction {
+        action(RecordedActionType::Input, timestamp).with_data("text", serde_json::json!(text))
+    }
+
+    fn key(vk_code: u64, timestamp: u64) -> RecordedAction {
+        action(RecordedActionType::Hotkey, timestamp).with_data("vkCode", serde_json::json!(vk_code))
+    }
+
+    fn convert(actions: Vec<RecordedAction>, library: &mut ElementLibrary) -> Vec<WorkflowNode> {
+        let mut session = RecordingSession::new("Test".to_string());
+        session.actions = actions;
+        let workflow = convert_to_workflow(&session, &ConversionOptions::default(), library);
+        workflow.nodes[1..workflow.nodes.len() - 1].to_vec()
+    }
+
+    #[test]
+    fn test_keystrokes_merge_and_modifiers_combine() {
+        let mut library = ElementLibrary::new("Test".to_string());
+        let nodes = convert(
+            vec![
+                typed("h", 1000),
+                key(0xA0, 1050),
+                typed("i", 1100),
+                typed("x", 1150),
+                key(0x08, 1200),
+                key(0xA2, 1300),
+                typed("s", 1350),
+                key(0x0D, 1400),
+                action(RecordedActionType::MouseMove, 1450),
+                action(RecordedActionType::Scroll, 1500),
+            ],
+            &mut library,
+        );
+
+        let summary: Vec<(&str, &serde_json::Value)> = nodes
+            .iter()
+            .map(|n| (n.node_type.as_str(), n.data.get("text").or(n.data.get("keys")).unwrap()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("input", &serde_json::json!("hI")),
+                ("hotkey", &serde_json::json!("Ctrl+S")),
+                ("hotkey", &serde_json::json!("Enter")),
+            ]
+        );
+        assert_eq!(nodes[1].data["target"], "desktop");
+    }
+
+    #[test]
+    fn test_clicks_and_delays() {
+        let mut library = ElementLibrary::new("Test".to_string());
+        let button = UIElement::new("OK".to_string(), "Button".to_string(), Rect { x: 0, y: 0, width: 10, height: 10 });
+        let field = UIElement::new("Name".to_string(), "Edit".to_string(), Rect { x: 0, y: 0, width: 10, height: 10 });
+        let mut field_again = field.clone();
+        field_again.id = "recaptured".to_string();
+
+        let nodes = convert(
+            vec![
+                action(RecordedActionType::Click, 1000).with_position(10, 10).with_element(field),
+                typed("abc", 1200),
+                action(RecordedActionType::Click, 6000).with_position(50, 50).with_element(button.clone()),
+                action(RecordedActionType::DoubleClick, 6200).with_position(51, 50).with_element(button),
+                action(RecordedActionType::Click, 6500).with_position(10, 10).with_element(field_again),
+            ],
+            &mut library,
+        );
+
+        let types: Vec<&str> = nodes.iter().map(|n| n.node_type.as_str()).collect();
+        assert_eq!(types, vec!["input", "delay", "click", "click"]);
+        assert_eq!(nodes[1].data["delay"], 4800);
+        assert_eq!(nodes[2].data["clickType"], "double");
+
+        // The field was captured twice but is stored once
+        assert_eq!(library.elements.len(), 2);
+        assert_eq!(nodes[0].data["elementId"], nodes[3].data["elementId"]);
+        assert_eq!(nodes[0].data["libraryId"], serde_json::json!(library.id));
+    }
+
+    #[test]
+    fn test_wheel_events_merge_into_scrolls() {
+        let wheel = |timestamp, x, delta_y: i64| {
+            action(RecordedActionType::Scroll, timestamp)
+                .with_position(x, 100)
+                .with_data("deltaY", serde_json::json!(delta_y))
+        };
+        let mut library = ElementLibrary::new("Test".to_string());
+        let nodes = convert(
+            vec![
+                wheel(1000, 100, -120),
+                wheel(1100, 101, -120),
+                wheel(1200, 100, -120),
+                wheel(1300, 400, 240),
+                wheel(1400, 400, -30),
+            ],
+            &mut library,
+        );
+
+        let scrolls: Vec<(&str, &serde_json::Value, &serde_json::Value)> = nodes
+            .iter()
+            .map(|n| (n.node_type.as_str(), &n.data["dy"], &n.data["x"]))
+            .collect();
+        assert_eq!(
+            scrolls,
+            vec![
+                ("scroll", &serde_json::json!(3), &serde_json::json!(100)),
+                ("scroll", &serde_json::json!(-2), &serde_json::json!(400)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_browser_recording_becomes_web_nodes() {
+        let web = |action_type, timestamp, selector: &str| {
+            action(action_type, timestamp).with_data("selector", serde_json::json!(selector))
+        };
+        let mut session = RecordingSession::new("Web".to_string());
+        session.source = RecordingSource::Web;
+        session.actions = vec![
+            action(RecordedActionType::Navigate, 1000).with_data("url", serde_json::json!("https://example.com/")),
+            web(RecordedActionType::Input, 5000, "#q").with_data("text", serde_json::json!("rus")),
+            web(RecordedActionType::Input, 5500, "#q").with_data("text", serde_json::json!("rust")),
+            web(RecordedActionType::Click, 6000, "button[type=\"submit\"]"),
+            action(RecordedActionType::Navigate, 6500).with_data("url", serde_json::json!("https://example.com/?q=rust")),
+        ];
+
+        let mut library = ElementLibrary::new("Test".to_string());
+        let workflow = convert_to_workflow(&session, &ConversionOptions::default(), &mut library);
+        let nodes = &workflow.nodes[1..workflow.nodes.len() - 1];
+
+        let types: Vec<&str> = nodes.iter().map(|n| n.node_type.as_str()).collect();
+        assert_eq!(types, vec!["openBrowser", "delay", "webInput", "webClick"]);
+        assert_eq!(nodes[0].data["initialUrl"], "https://example.com/");
+        assert_eq!(nodes[2].data["text"], "rust");
+        assert_eq!(nodes[2].data["selector"], "#q");
+    }
+}
@@ -1,17 +1,50 @@
 #![cfg(target_os = "linux")]
 
+//! Linux event recording with raw XInput2 events
+//!
+//! Raw events report every button, motion and key event of the master devices,
+//! whichever window has the focus. Key presses are translated to text with the
+//! server's keyboard mapping, and each click is annotated with the element
+//! under the pointer through AT-SPI.
+
 use super::{RecordedAction, RecordedActionType};
+use crate::automation::desktop::linux::LinuxAutomation;
+use crate::automation::desktop::x11::{
+    is_keypad, is_level3_shift, keysym_char, keysym_modifier, keysym_name, XK_NUM_LOCK,
+};
+use crate::automation::desktop::DesktopAutomation;
+use std::collections::{HashMap, HashSet};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-use std::process::{Command, Stdio, Child};
-use std::io::{BufRead, BufReader};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+use x11rb::connection::Connection;
+use x11rb::protocol::xinput::{self, ConnectionExt as _, Device, XIEventMask};
+use x11rb::protocol::xproto::{ConnectionExt as _, KeyButMask, Keycode, Keysym, Window};
+use x11rb::protocol::Event;
+use x11rb::rust_connection::RustConnection;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+const DOUBLE_CLICK_MS: u32 = 500;
+const DOUBLE_CLICK_DISTANCE: i32 = 4;
+
+/// Time the pointer has to rest after moving before the move is recorded
+const MOVE_SETTLE_MS: u64 = 300;
 
-static ENABLED: AtomicBool = AtomicBool::new(false);
+/// Typed text is recorded once typing pauses this long
+const TEXT_IDLE_MS: u64 = 1000;
 
-/// Linux event recording using xinput
+const ELEMENT_LOOKUP_TIMEOUT_MS: u64 = 1500;
+
+const XK_CAPS_LOCK: Keysym = 0xffe5;
+const XK_BACKSPACE: Keysym = 0xff08;
+
+/// Linux event recording using XInput2 raw events
 pub struct EventMonitor {
-    xinput_process: Option<Child>,
     enabled: Arc<AtomicBool>,
+    running: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
 }
 
 impl EventMonitor {
@@ -19,181 +52,608 @@ impl EventMonitor {
     where
         F: Fn(RecordedAction) + Send + Sync + 'static,
     {
-        let enabled = Arc::new(AtomicBool::new(true));
-        let enabled_clone = enabled.clone();
-
-        // Start xinput test-xi2 to monitor events
-        let mut child = Command::new("xinput")
-            .args(["test-xi2", "--root"])
-            .stdout(Stdio::piped())
-            .stderr(Stdio::null())
-            .spawn()
-            .map_err(|e| format!("Failed to start xinput: {}. Please install xinput.", e))?;
-
-        let stdout = child.stdout.take()
-            .ok_or_else(|| "Failed to get xinput stdout".to_string())?;
-
-        // Process xinput output in a separate thread
-        std::thread::spawn(move || {
-            let reader = BufReader::new(stdout);
-            let mut current_event: Option<EventData> = None;
-
-            for line in reader.lines() {
-                if !enabled_clone.load(Ordering::SeqCst) {
-                    break;
-                }
+        let (conn, screen) = x11rb::connect(None).map_err(|e| format!("Failed to connect to X display: {}", e))?;
+        let root = conn.setup().roots[screen].root;
 
-                let line = match line {
-                    Ok(l) => l,
-                    Err(_) => continue,
-                };
+        let version = conn
+            .xinput_xi_query_version(2, 2)
+            .map_err(|e| format!("XInput2 query failed: {}", e))?
+            .reply()
+            .map_err(|e| format!("XInput2 is not available: {}", e))?;
+        if version.major_version < 2 {
+            return Err(format!(
+                "XInput {}.{} found, 2.0 or later is required",
+                version.major_version, version.minor_version
+            ));
+        }
 
-                // Parse xinput output
-                if let Some(event) = parse_xinput_line(&line, &mut current_event) {
-                    callback(event);
-                }
-            }
-        });
+        let mask = XIEventMask::RAW_KEY_PRESS
+            | XIEventMask::RAW_KEY_RELEASE
+            | XIEventMask::RAW_BUTTON_PRESS
+            | XIEventMask::RAW_BUTTON_RELEASE
+            | XIEventMask::RAW_MOTION;
+        conn.xinput_xi_select_events(
+            root,
+            &[xinput::EventMask {
+                deviceid: Device::ALL_MASTER.into(),
+                mask: vec![mask],
+            }],
+        )
+        .map_err(|e| format!("Failed to select XInput2 events: {}", e))?;
+        conn.flush().map_err(|e| format!("Failed to select XInput2 events: {}", e))?;
+
+        let keymap = load_keymap(&conn)?;
+        let mask = pointer(&conn, root).map(|(_, _, mask)| mask);
+        let caps_lock = mask.is_some_and(|mask| mask.contains(KeyButMask::LOCK));
+        let num_lock = match (mask, num_lock_mask(&conn, &keymap)) {
+            (Some(mask), Some(num_lock)) => u16::from(mask) & num_lock != 0,
+            _ => false,
+        };
+        let mut translator = Translator::new(keymap, caps_lock);
+        translator.num_lock = num_lock;
+
+        let enabled = Arc::new(AtomicBool::new(true));
+        let running = Arc::new(AtomicBool::new(true));
+        let thread = {
+            let enabled = enabled.clone();
+            let running = running.clone();
+            std::thread::spawn(move || run(conn, root, translator, &enabled, &running, &callback))
+        };
 
         Ok(Self {
-            xinput_process: Some(child),
             enabled,
+            running,
+            thread: Some(thread),
         })
     }
 
     pub fn enable(&self) {
         self.enabled.store(true, Ordering::SeqCst);
-        ENABLED.store(true, Ordering::SeqCst);
     }
 
     pub fn disable(&self) {
         self.enabled.store(false, Ordering::SeqCst);
-        ENABLED.store(false, Ordering::SeqCst);
     }
 }
 
 impl Drop for EventMonitor {
     fn drop(&mut self) {
-        self.enabled.store(false, Ordering::SeqCst);
-        if let Some(mut child) = self.xinput_process.take() {
-            let _ = child.kill();
+        self.running.store(false, Ordering::SeqCst);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
         }
     }
 }
 
-#[derive(Default)]
-struct EventData {
-    event_type: String,
-    x: i32,
-    y: i32,
-    button: u32,
-    keycode: u32,
-}
+/// Read XI2 events until the monitor is dropped
+fn run(
+    conn: RustConnection,
+    root: Window,
+    mut translator: Translator,
+    enabled: &AtomicBool,
+    running: &AtomicBool,
+    callback: &(dyn Fn(RecordedAction) + Send + Sync),
+) {
+    // Element lookups go through AT-SPI, which is async
+    let runtime = match tokio::runtime::Builder::new_current_thread().enable_all().build() {
+        Ok(runtime) => runtime,
+        Err(e) => {
+            log::error!("Failed to start recorder runtime: {}", e);
+            return;
+        }
+    };
+    let automation = LinuxAutomation::new();
 
-fn parse_xinput_line(line: &str, current_event: &mut Option<EventData>) -> Option<RecordedAction> {
-    let line = line.trim();
-
-    // Event start
-    if line.starts_with("EVENT type") {
-        let mut event = EventData::default();
-
-        // Parse event type
-        if line.contains("ButtonPress") {
-            event.event_type = "ButtonPress".to_string();
-        } else if line.contains("ButtonRelease") {
-            event.event_type = "ButtonRelease".to_string();
-        } else if line.contains("KeyPress") {
-            event.event_type = "KeyPress".to_string();
-        } else if line.contains("KeyRelease") {
-            event.event_type = "KeyRelease".to_string();
-        } else if line.contains("Motion") {
-            event.event_type = "Motion".to_string();
-        }
-
-        *current_event = Some(event);
-        return None;
-    }
-
-    // Parse event details
-    if let Some(ref mut event) = current_event {
-        if line.starts_with("root:") {
-            // Parse coordinates: root: 123.45/678.90
-            if let Some(coords) = line.strip_prefix("root:") {
-                let parts: Vec<&str> = coords.trim().split('/').collect();
-                if parts.len() >= 2 {
-                    event.x = parts[0].trim().parse::<f64>().unwrap_or(0.0) as i32;
-                    event.y = parts[1].trim().parse::<f64>().unwrap_or(0.0) as i32;
+    let mut moved_at: Option<Instant> = None;
+    let mut typed_at = Instant::now();
+    let mut last_position = None;
+
+    while running.load(Ordering::SeqCst) {
+        let event = match conn.poll_for_event() {
+            Ok(Some(event)) => event,
+            Ok(None) => {
+                let mut actions = Vec::new();
+
+                if moved_at.is_some_and(|at| at.elapsed() >= Duration::from_millis(MOVE_SETTLE_MS)) {
+                    moved_at = None;
+                    if let Some((x, y, _)) = pointer(&conn, root) {
+                        if last_position != Some((x, y)) {
+                            last_position = Some((x, y));
+                            actions.push(RecordedAction::new(RecordedActionType::MouseMove).with_position(x, y));
+                        }
+                    }
+                }
+                if typed_at.elapsed() >= Duration::from_millis(TEXT_IDLE_MS) {
+                    actions.extend(translator.flush_text());
                 }
+
+                if enabled.load(Ordering::SeqCst) {
+                    actions.into_iter().for_each(callback);
+                }
+                std::thread::sleep(POLL_INTERVAL);
+                continue;
+            }
+            Err(e) => {
+                log::error!("Lost connection to X display: {}", e);
+                break;
             }
-        } else if line.starts_with("detail:") {
-            // Parse button or keycode
-            if let Some(detail) = line.strip_prefix("detail:") {
-                let num = detail.trim().parse::<u32>().unwrap_or(0);
-                if event.event_type.contains("Button") {
-                    event.button = num;
-                } else if event.event_type.contains("Key") {
-                    event.keycode = num;
+        };
+
+        let input = match event {
+            Event::XinputRawButtonPress(event) => {
+                moved_at = None;
+                let Some((x, y, _)) = pointer(&conn, root) else {
+                    continue;
+                };
+                last_position = Some((x, y));
+                RawInput::Button {
+                    button: event.detail,
+                    x,
+                    y,
+                    time: event.time,
                 }
             }
+            Event::XinputRawMotion(_) => {
+                moved_at = Some(Instant::now());
+                continue;
+            }
+            Event::XinputRawKeyPress(event) => {
+                typed_at = Instant::now();
+                RawInput::KeyPress(event.detail as Keycode)
+            }
+            Event::XinputRawKeyRelease(event) => RawInput::KeyRelease(event.detail as Keycode),
+            Event::MappingNotify(_) => {
+                match load_keymap(&conn) {
+                    Ok(keymap) => translator.keymap = keymap,
+                    Err(e) => log::warn!("Failed to reload keyboard mapping: {}", e),
+                }
+                continue;
+            }
+            _ => continue,
+        };
+
+        let actions = translator.handle(input);
+        if !enabled.load(Ordering::SeqCst) {
+            // Keep tracking modifiers while paused, but record nothing
+            translator.flush_text();
+            continue;
         }
 
-        // Check if we have enough info to create an action
-        // Only process on Press events to avoid duplicates
-        if event.event_type == "ButtonPress" && event.button > 0 {
-            let action = match event.button {
-                1 => Some(RecordedAction::new(RecordedActionType::Click)
-                    .with_position(event.x, event.y)),
-                3 => Some(RecordedAction::new(RecordedActionType::RightClick)
-                    .with_position(event.x, event.y)),
-                4 | 5 => {
-                    // Scroll wheel (4=up, 5=down)
-                    let delta = if event.button == 4 { 120 } else { -120 };
-                    Some(RecordedAction::new(RecordedActionType::Scroll)
-                        .with_position(event.x, event.y)
-                        .with_data("deltaY", serde_json::json!(delta)))
+        for action in actions {
+            let action = match (&action.action_type, action.position) {
+                (
+                    RecordedActionType::Click | RecordedActionType::DoubleClick | RecordedActionType::RightClick,
+                    Some((x, y)),
+                ) => {
+                    let lookup = tokio::time::timeout(
+                        Duration::from_millis(ELEMENT_LOOKUP_TIMEOUT_MS),
+                        automation.capture_element(x, y),
+                    );
+                    match runtime.block_on(lookup) {
+                        Ok(Ok(element)) => action.with_element(element),
+                        Ok(Err(e)) => {
+                            log::debug!("No element at ({}, {}): {}", x, y, e);
+                            action
+                        }
+                        Err(_) => {
+                            log::debug!("Element lookup at ({}, {}) timed out", x, y);
+                            action
+                        }
+                    }
                 }
-                _ => None,
+                _ => action,
             };
+            callback(action);
+        }
+    }
+}
+
+/// Pointer position and modifier state
+fn pointer(conn: &RustConnection, root: Window) -> Option<(i32, i32, KeyButMask)> {
+    let reply = conn.query_pointer(root).ok()?.reply().ok()?;
+    Some((reply.root_x as i32, reply.root_y as i32, reply.mask))
+}
+
+/// Keysyms of every keycode, on all levels the server reports
+///
+/// The core mapping lists the unshifted and shifted keysym of the first group,
+/// then those of the second group, then the third and fourth level (AltGr).
+fn load_keymap(conn: &RustConnection) -> Result<HashMap<Keycode, Vec<Keysym>>, String> {
+    let setup = conn.setup();
+    let (min_keycode, max_keycode) = (setup.min_keycode, setup.max_keycode);
+    let reply = conn
+        .get_keyboard_mapping(min_keycode, max_keycode - min_keycode + 1)
+        .map_err(|e| format!("Failed to read keyboard mapping: {}", e))?
+        .reply()
+        .map_err(|e| format!("Failed to read keyboard mapping: {}", e))?;
+
+    let per_keycode = (reply.keysyms_per_keycode as usize).max(1);
+    Ok(reply
+        .keysyms
+        .chunks(per_keycode)
+        .enumerate()
+        .map(|(i, row)| (min_keycode + i as Keycode, row.to_vec()))
+        .collect())
+}
+
+/// Modifier mask bit the Num Lock key sets, if it is mapped
+fn num_lock_mask(conn: &RustConnection, keymap: &HashMap<Keycode, Vec<Keysym>>) -> Option<u16> {
+    let reply = conn.get_modifier_mapping().ok()?.reply().ok()?;
+    let per_modifier = (reply.keycodes.len() / 8).max(1);
+    reply
+        .keycodes
+        .chunks(per_modifier)
+        .position(|keycodes| {
+            keycodes
+                .iter()
+                .any(|k| keymap.get(k).and_then(|row| row.first()) == Some(&XK_NUM_LOCK))
+        })
+        .map(|index| 1 << index)
+}
+
+/// Keysym a key produces at the given shift levels, following the core
+/// protocol rules: a missing shifted keysym repeats the unshifted one, and
+/// with Num Lock on, Shift picks the unshifted keysym of keypad keys
+fn keysym_at(row: &[Keysym], shift: bool, level3: bool, num_lock: bool) -> Keysym {
+    let at = |i: usize| row.get(i).copied().unwrap_or(0);
+    let (first, second) = match level3 {
+        // XKB servers report the third level after the second group,
+        // Mode_switch servers as the second group
+        true if at(4) != 0 => (at(4), at(5)),
+        true if at(2) != 0 => (at(2), at(3)),
+        _ => (at(0), at(1)),
+    };
+    let second = if second == 0 { first } else { second };
+
+    if num_lock && is_keypad(second) {
+        return if shift { first } else { second };
+    }
+    if shift {
+        second
+    } else {
+        first
+    }
+}
+
+/// Input relevant to recording, with the pointer position of button presses
+#[derive(Debug, Clone, Copy)]
+enum RawInput {
+    Button { button: u32, x: i32, y: i32, time: u32 },
+    KeyPress(Keycode),
+    KeyRelease(Keycode),
+}
+
+/// Turns raw input into recorded actions: clicks, scrolls, typed text and hotkeys
+struct Translator {
+    keymap: HashMap<Keycode, Vec<Keysym>>,
+    /// Modifier and level shift keys held down
+    held: HashSet<Keycode>,
+    caps_lock: bool,
+    num_lock: bool,
+    pending_text: String,
+    /// Time and position of the last left click, for double-click detection
+    last_click: Option<(u32, i32, i32)>,
+}
+
+impl Translator {
+    fn new(keymap: HashMap<Keycode, Vec<Keysym>>, caps_lock: bool) -> Self {
+        Self {
+            keymap,
+            held: HashSet::new(),
+            caps_lock,
+            num_lock: false,
+            pending_text: String::new(),
+            last_click: None,
+        }
+    }
+
+    fn handle(&mut self, input: RawInput) -> Vec<RecordedAction> {
+        match input {
+            RawInput::Button { button, x, y, time } => self.button(button, x, y, time),
+            RawInput::KeyPress(keycode) => self.key_press(keycode),
+            RawInput::KeyRelease(keycode) => {
+                self.held.remove(&keycode);
+                Vec::new()
+            }
+        }
+    }
+
+    fn button(&mut self, button: u32, x: i32, y: i32, time: u32) -> Vec<RecordedAction> {
+        let mut actions: Vec<RecordedAction> = self.flush_text().into_iter().collect();
+
+        let action = match button {
+            1 => {
+                let is_double_click = self.last_click.is_some_and(|(at, cx, cy)| {
+                    time.wrapping_sub(at) < DOUBLE_CLICK_MS
+                        && (x - cx).abs() <= DOUBLE_CLICK_DISTANCE
+                        && (y - cy).abs() <= DOUBLE_CLICK_DISTANCE
+                });
+                if is_double_click {
+                    self.last_click = None;
+                    RecordedAction::new(RecordedActionType::DoubleClick)
+                } else {
+                    self.last_click = Some((time, x, y));
+                    RecordedAction::new(RecordedActionType::Click)
+                }
+            }
+            3 => RecordedAction::new(RecordedActionType::RightClick),
+            // Wheel: 4 up, 5 down, 6 left, 7 right
+            4 | 5 => RecordedAction::new(RecordedActionType::Scroll)
+                .with_data("deltaX", serde_json::json!(0))
+                .with_data("deltaY", serde_json::json!(if button == 4 { 120 } else { -120 })),
+            6 | 7 => RecordedAction::new(RecordedActionType::Scroll)
+                .with_data("deltaX", serde_json::json!(if button == 6 { -120 } else { 120 }))
+                .with_data("deltaY", serde_json::json!(0)),
+            _ => return actions,
+        };
 
-            *current_event = None;
-            return action;
-        } else if event.event_type == "KeyPress" && event.keycode > 0 {
-            // Convert keycode to key name using xdotool
-            let key_name = get_key_name(event.keycode);
+        let mut action = action.with_position(x, y);
+        let modifiers = self.modifiers();
+        if !modifiers.is_empty() {
+            action = action.with_data("modifiers", serde_json::json!(modifiers));
+        }
+        actions.push(action);
+        actions
+    }
 
-            let action = if is_printable_key(&key_name) {
-                RecordedAction::new(RecordedActionType::Input)
-                    .with_data("text", serde_json::json!(key_name))
-                    .with_data("keycode", serde_json::json!(event.keycode))
+    fn key_press(&mut self, keycode: Keycode) -> Vec<RecordedAction> {
+        let Some(row) = self.keymap.get(&keycode) else {
+            return Vec::new();
+        };
+        let base = keysym_at(row, false, false, false);
+
+        if keysym_modifier(base).is_some() || is_level3_shift(base) {
+            self.held.insert(keycode);
+            return Vec::new();
+        }
+        if base == XK_CAPS_LOCK {
+            self.caps_lock = !self.caps_lock;
+            return Vec::new();
+        }
+        if base == XK_NUM_LOCK {
+            self.num_lock = !self.num_lock;
+            return Vec::new();
+        }
+
+        let modifiers = self.modifiers();
+        let shift = modifiers.contains(&"Shift");
+        let is_hotkey = modifiers.iter().any(|m| *m != "Shift");
+        let level3 = self
+            .held
+            .iter()
+            .any(|k| self.keymap.get(k).is_some_and(|row| is_level3_shift(row[0])));
+        let typed = keysym_at(row, shift, level3, self.num_lock);
+
+        if is_hotkey {
+            let key = keysym_name(base)
+                .or_else(|| keysym_char(base).map(|c| c.to_uppercase().to_string()))
+                .unwrap_or_else(|| format!("0x{:x}", base));
+            return self.hotkey(key, &modifiers);
+        }
+
+        if let Some(ch) = keysym_char(typed) {
+            // Caps Lock inverts the case of letters
+            let ch = if self.caps_lock && ch.is_alphabetic() {
+                if ch.is_lowercase() {
+                    ch.to_uppercase().next().unwrap_or(ch)
+                } else {
+                    ch.to_lowercase().next().unwrap_or(ch)
+                }
             } else {
-                RecordedAction::new(RecordedActionType::Hotkey)
-                    .with_data("key", serde_json::json!(key_name))
-                    .with_data("keycode", serde_json::json!(event.keycode))
+                ch
             };
+            self.pending_text.push(ch);
+            return Vec::new();
+        }
 
-            *current_event = None;
-            return Some(action);
+        if base == XK_BACKSPACE && self.pending_text.pop().is_some() {
+            return Vec::new();
+        }
+
+        match keysym_name(base) {
+            Some(key) => self.hotkey(key, &modifiers),
+            None => Vec::new(),
         }
     }
 
-    None
-}
+    fn hotkey(&mut self, key: String, modifiers: &[&str]) -> Vec<RecordedAction> {
+        let mut actions: Vec<RecordedAction> = self.flush_text().into_iter().collect();
+        actions.push(
+            RecordedAction::new(RecordedActionType::Hotkey)
+                .with_data("key", serde_json::json!(key))
+                .with_data("modifiers", serde_json::json!(modifiers)),
+        );
+        actions
+    }
 
-fn get_key_name(keycode: u32) -> String {
-    // Use xdotool to convert keycode to key name
-    let output = Command::new("xdotool")
-        .args(["key", "--clearmodifiers", &format!("0x{:x}", keycode)])
-        .output();
+    /// Names of the held modifiers, in a fixed order
+    fn modifiers(&self) -> Vec<&'static str> {
+        let held: HashSet<&str> = self
+            .held
+            .iter()
+            .filter_map(|keycode| self.keymap.get(keycode))
+            .filter_map(|row| keysym_modifier(row[0]))
+            .collect();
+        ["Super", "Ctrl", "Alt", "Shift"]
+            .into_iter()
+            .filter(|m| held.contains(m))
+            .collect()
+    }
 
-    match output {
-        Ok(o) if o.status.success() => {
-            String::from_utf8_lossy(&o.stdout).trim().to_string()
+    /// Typed text not yet recorded, as an input action
+    fn flush_text(&mut self) -> Option<RecordedAction> {
+        if self.pending_text.is_empty() {
+            return None;
         }
-        _ => format!("key_{}", keycode),
+        let text = std::mem::take(&mut self.pending_text);
+        Some(RecordedAction::new(RecordedActionType::Input).with_data("text", serde_json::json!(text)))
     }
 }
 
-fn is_printable_key(key: &str) -> bool {
-    // Check if it's a single printable character
-    key.chars().count() == 1 && key.chars().next().map_or(false, |c| c.is_alphanumeric() || c.is_ascii_punctuation() || c == ' ')
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SHIFT: Keycode = 50;
+    const CTRL: Keycode = 37;
+    const KEY_H: Keycode = 43;
+    const KEY_I: Keycode = 31;
+    const KEY_S: Keycode = 39;
+    const KEY_ONE: Keycode = 10;
+    const BACKSPACE: Keycode = 22;
+    const ENTER: Keycode = 36;
+    const NUM_LOCK: Keycode = 77;
+    const KP_ONE: Keycode = 87;
+    const ALT_GR: Keycode = 108;
+    const KEY_Q: Keycode = 24;
+    const KEY_E: Keycode = 26;
+
+    fn translator() -> Translator {
+        // Rows as an XKB server reports a German layout: both groups, then AltGr
+        let keymap = HashMap::from([
+            (SHIFT, vec![0xffe1, 0, 0xffe1]),
+            (CTRL, vec![0xffe3, 0, 0xffe3]),
+            (KEY_H, vec![0x68, 0x48, 0x68, 0x48, 0x2b1, 0x2a1]),
+            (KEY_I, vec![0x69, 0x49, 0x69, 0x49, 0x2b9, 0x2a9]),
+            (KEY_S, vec![0x73, 0x53, 0x73, 0x53, 0xdf, 0x1001e9e]),
+            (KEY_ONE, vec![0x31, 0x21, 0x31, 0x21, 0xb9, 0xa1]),
+            (BACKSPACE, vec![0xff08, 0xff08]),
+            (ENTER, vec![0xff0d, 0, 0xff0d]),
+            (NUM_LOCK, vec![0xff7f, 0, 0xff7f]),
+            (KP_ONE, vec![0xff9c, 0xffb1, 0xff9c, 0xffb1]),
+            (ALT_GR, vec![0xfe03, 0, 0xfe03]),
+            (KEY_Q, vec![0x71, 0x51, 0x71, 0x51, 0x40, 0x7d9]),
+            (KEY_E, vec![0x65, 0x45, 0x65, 0x45, 0x10020ac, 0x45]),
+        ]);
+        Translator::new(keymap, false)
+    }
+
+    fn feed(translator: &mut Translator, inputs: &[RawInput]) -> Vec<RecordedAction> {
+        inputs.iter().flat_map(|&input| translator.handle(input)).collect()
+    }
+
+    #[test]
+    fn test_typing_with_shift_and_backspace() {
+        use RawInput::*;
+        let mut t = translator();
+
+        let actions = feed(
+            &mut t,
+            &[
+                KeyPress(SHIFT),
+                KeyPress(KEY_H),
+                KeyRelease(SHIFT),
+                KeyPress(KEY_I),
+                KeyPress(KEY_S),
+                KeyPress(BACKSPACE),
+                KeyPress(SHIFT),
+                KeyPress(KEY_ONE),
+                KeyRelease(SHIFT),
+                KeyPress(ENTER),
+            ],
+        );
+
+        assert_eq!(actions.len(), 2);
+        assert!(matches!(actions[0].action_type, RecordedActionType::Input));
+        assert_eq!(actions[0].data["text"], "Hi!");
+        assert!(matches!(actions[1].action_type, RecordedActionType::Hotkey));
+        assert_eq!(actions[1].data["key"], "Enter");
+    }
+
+    #[test]
+    fn test_ctrl_combination_is_hotkey() {
+        use RawInput::*;
+        let mut t = translator();
+
+        let actions = feed(&mut t, &[KeyPress(CTRL), KeyPress(KEY_S), KeyRelease(KEY_S), KeyRelease(CTRL)]);
+
+        assert_eq!(actions.len(), 1);
+        assert_eq!(actions[0].data["key"], "S");
+        assert_eq!(actions[0].data["modifiers"], serde_json::json!(["Ctrl"]));
+    }
+
+    #[test]
+    fn test_altgr_and_keypad_levels_are_text() {
+        use RawInput::*;
+        let mut t = translator();
+
+        let actions = feed(
+            &mut t,
+            &[
+                KeyPress(ALT_GR),
+                KeyPress(KEY_Q),
+                KeyPress(KEY_E),
+                KeyRelease(ALT_GR),
+                KeyPress(KP_ONE),
+                KeyPress(NUM_LOCK),
+                KeyPress(KP_ONE),
+                KeyPress(SHIFT),
+                KeyPress(KP_ONE),
+                KeyRelease(SHIFT),
+                KeyPress(CTRL),
+                KeyPress(ALT_GR),
+                KeyPress(KEY_Q),
+            ],
+        );
+
+        assert_eq!(actions.len(), 5);
+        assert_eq!(actions[0].data["text"], "@€");
+        // Num Lock off: the keypad key is End
+        assert_eq!(actions[1].data["key"], "End");
+        assert_eq!(actions[2].data["text"], "1");
+        // Shift undoes Num Lock
+        assert_eq!(actions[3].data["key"], "End");
+        // AltGr does not count as Alt in shortcuts
+        assert_eq!(actions[4].data["modifiers"], serde_json::json!(["Ctrl"]));
+    }
+
+    /// Needs an X server, e.g. `xvfb-run cargo test -- --ignored`
+    #[test]
+    #[ignore]
+    fn test_keymap_keeps_every_level() {
+        use x11rb::wrapper::ConnectionExt as _;
+
+        let (conn, _) = x11rb::connect(None).expect("no X display");
+        let keymap = load_keymap(&conn).unwrap();
+        let (&unused, original) = keymap
+            .iter()
+            .filter(|(_, row)| row.iter().all(|&k| k == 0))
+            .max_by_key(|(&keycode, _)| keycode)
+            .expect("no unused keycode");
+        let original = original.clone();
+
+        // q, Q, q, Q, @ on an unused key
+        let mut row = vec![0; original.len().max(5)];
+        row[..5].copy_from_slice(&[0x71, 0x51, 0x71, 0x51, 0x40]);
+        conn.change_keyboard_mapping(1, unused, row.len() as u8, &row).unwrap();
+        conn.sync().unwrap();
+
+        let keymap = load_keymap(&conn);
+        conn.change_keyboard_mapping(1, unused, original.len() as u8, &original).unwrap();
+        conn.sync().unwrap();
+
+        let typed = keymap.unwrap()[&unused].clone();
+        assert_eq!(keysym_at(&typed, false, true, false), 0x40);
+        assert_eq!(keysym_at(&typed, true, false, false), 0x51);
+    }
+
+    #[test]
+    fn test_double_click_and_scroll() {
+        use RawInput::*;
+        let mut t = translator();
+
+        let actions = feed(
+            &mut t,
+            &[
+                Button { button: 1, x: 100, y: 100, time: 1000 },
+                Button { button: 1, x: 101, y: 100, time: 1200 },
+                Button { button: 5, x: 101, y: 100, time: 2000 },
+            ],
+        );
+
+        assert!(matches!(actions[0].action_type, RecordedActionType::Click));
+        assert!(matches!(actions[1].action_type, RecordedActionType::DoubleClick));
+        assert!(matches!(actions[2].action_type, RecordedActionType::Scroll));
+        assert_eq!(actions[2].data["deltaY"], -120);
+    }
 }
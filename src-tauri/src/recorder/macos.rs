@@ -1,6 +1,6 @@
 #![cfg(target_os = "macos")]
 
-use super::{RecordedAction, RecordedActionType};
+use super::{RecordedAction, RecordedActionType, WHEEL_DELTA};
 use crate::element::UIElement;
 use std::ffi::c_void;
 use std::sync::atomic::{AtomicBool, Ordering};
@@ -336,10 +336,12 @@ fn process_event_raw(
                 CGEventGetIntegerValueField(event, K_CG_SCROLL_WHEEL_EVENT_DELTA_AXIS_2)
             };
 
+            // Report lines in wheel units like the other recorders, positive
+            // X to the right; macOS counts leftward scrolling as positive
             let action = RecordedAction::new(RecordedActionType::Scroll)
                 .with_position(x, y)
-                .with_data("deltaX", serde_json::Value::Number(delta_x.into()))
-                .with_data("deltaY", serde_json::Value::Number(delta_y.into()));
+                .with_data("deltaX", serde_json::json!(-delta_x * WHEEL_DELTA))
+                .with_data("deltaY", serde_json::json!(delta_y * WHEEL_DELTA));
 
             Some(action)
         }
@@ -1,8 +1,10 @@
+use crate::automation::web::{BrowserOptions, WebAutomation};
 use crate::element::UIElement;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::sync::Arc;
-use tokio::sync::RwLock;
+use privacy::Masker;
+use std::sync::{Arc, Mutex, PoisonError};
+use tokio::sync::{mpsc, RwLock};
 
 #[cfg(target_os = "macos")]
 mod macos;
@@ -13,6 +15,18 @@ mod windows;
 #[cfg(target_os = "linux")]
 mod linux;
 
+mod convert;
+mod privacy;
+mod replay;
+mod web;
+
+pub use convert::{convert_to_workflow, ConversionOptions};
+pub use replay::{replay, ReplaySummary};
+
+/// Wheel movement of one notch; scroll actions carry `deltaX`/`deltaY` in
+/// these units, positive up and to the right
+const WHEEL_DELTA: i64 = 120;
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum RecordingState {
     Idle,
@@ -29,6 +43,19 @@ pub enum RecordedActionType {
     Scroll,
     Hotkey,
     Wait,
+    MouseMove,
+    Navigate,
+    Select,
+}
+
+/// Where the actions of a recording come from
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+pub enum RecordingSource {
+    /// OS-level mouse and keyboard input
+    #[default]
+    Desktop,
+    /// A browser session opened by the recorder
+    Web,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -79,6 +106,8 @@ pub struct RecordingSession {
     pub id: String,
     pub name: String,
     pub state: RecordingState,
+    #[serde(default)]
+    pub source: RecordingSource,
     pub actions: Vec<RecordedAction>,
     pub started_at: Option<String>,
     pub ended_at: Option<String>,
@@ -90,6 +119,7 @@ impl RecordingSession {
             id: uuid::Uuid::new_v4().to_string(),
             name,
             state: RecordingState::Idle,
+            source: RecordingSource::Desktop,
             actions: Vec::new(),
             started_at: None,
             ended_at: None,
@@ -105,6 +135,10 @@ pub struct Recorder {
     event_hook: Arc<RwLock<Option<windows::EventHook>>>,
     #[cfg(target_os = "linux")]
     event_monitor: Arc<RwLock<Option<linux::EventMonitor>>>,
+    web: Arc<WebAutomation>,
+    /// Browser session of a web recording
+    web_session: Arc<RwLock<Option<String>>>,
+    masker: Arc<Mutex<Masker>>,
 }
 
 impl Recorder {
@@ -117,10 +151,13 @@ impl Recorder {
             event_hook: Arc::new(RwLock::new(None)),
             #[cfg(target_os = "linux")]
             event_monitor: Arc::new(RwLock::new(None)),
+            web: Arc::new(WebAutomation::new()),
+            web_session: Arc::new(RwLock::new(None)),
+            masker: Arc::new(Mutex::new(Masker::default())),
         }
     }
 
-    pub async fn start(&self, name: Option<String>) -> Result<(), String> {
+    async fn begin(&self, name: Option<String>, source: RecordingSource) -> Result<(), String> {
         let mut session = self.session.write().await;
 
         if session.state == RecordingState::Recording {
@@ -129,11 +166,17 @@ impl Recorder {
 
         session.name = name.unwrap_or_else(|| "Recording".to_string());
         session.state = RecordingState::Recording;
+        session.source = source;
         session.started_at = Some(chrono::Utc::now().to_rfc3339());
         session.actions.clear();
         session.ended_at = None;
+        *self.masker.lock().unwrap_or_else(PoisonError::into_inner) = Masker::default();
 
-        drop(session);
+        Ok(())
+    }
+
+    pub async fn start(&self, name: Option<String>) -> Result<(), String> {
+        self.begin(name, RecordingSource::Desktop).await?;
 
         // Start platform-specific event listening
         #[cfg(target_os = "macos")]
@@ -155,6 +198,20 @@ impl Recorder {
         Ok(())
     }
 
+    /// Record actions in a new browser window, starting at `url`
+    pub async fn start_web(&self, name: Option<String>, url: Option<String>) -> Result<(), String> {
+        self.begin(name, RecordingSource::Web).await?;
+
+        if let Err(e) = self.start_web_session(url).await {
+            self.stop_web_session().await;
+            self.session.write().await.state = RecordingState::Idle;
+            return Err(e);
+        }
+
+        log::info!("Web recording started");
+        Ok(())
+    }
+
     pub async fn pause(&self) -> Result<(), String> {
         let mut session = self.session.write().await;
 
@@ -236,6 +293,8 @@ impl Recorder {
             self.stop_linux_event_monitor().await?;
         }
 
+        self.stop_web_session().await;
+
         log::info!("Recording stopped with {} actions", session.actions.len());
 
         Ok(session.clone())
@@ -249,25 +308,91 @@ impl Recorder {
         self.session.read().await.clone()
     }
 
-    pub async fn add_action(&self, action: RecordedAction) {
+    /// Make a saved recording the current session
+    pub async fn load(&self, loaded: RecordingSession) -> Result<(), String> {
         let mut session = self.session.write().await;
-        if session.state == RecordingState::Recording {
-            session.actions.push(action);
+
+        if session.state != RecordingState::Idle {
+            return Err("Recording in progress".to_string());
         }
+
+        *session = RecordingSession {
+            state: RecordingState::Idle,
+            ..loaded
+        };
+        Ok(())
     }
 
-    #[cfg(target_os = "macos")]
-    async fn start_macos_event_tap(&self) -> Result<(), String> {
+    pub async fn add_action(&self, action: RecordedAction) {
+        record(&self.session, &self.masker, action).await;
+    }
+
+    /// Whether capture of typed text is paused by the text capture hotkey
+    pub fn text_capture_paused(&self) -> bool {
+        self.masker.lock().unwrap_or_else(PoisonError::into_inner).text_paused()
+    }
+
+    /// Channel for platform hook events, recorded one at a time in the order
+    /// they were sent; the masker follows the keyboard focus through clicks,
+    /// so a keystroke must never overtake the click before it
+    ///
+    /// The recording task ends once the hook owning the sender is dropped.
+    fn event_sink(&self) -> mpsc::UnboundedSender<RecordedAction> {
+        let (sender, events) = mpsc::unbounded_channel();
+        tauri::async_runtime::spawn(record_events(self.session.clone(), self.masker.clone(), events));
+        sender
+    }
+
+    async fn start_web_session(&self, url: Option<String>) -> Result<(), String> {
+        let session_id = format!("recorder_{}", uuid::Uuid::new_v4());
+        self.web
+            .open_browser(&session_id, BrowserOptions::default())
+            .await
+            .map_err(|e| format!("Failed to open browser: {}", e))?;
+        *self.web_session.write().await = Some(session_id.clone());
+
+        let mut events = self
+            .web
+            .start_recording(&session_id)
+            .await
+            .map_err(|e| format!("Failed to record browser: {}", e))?;
+
         let session_clone = self.session.clone();
+        let masker_clone = self.masker.clone();
+        tauri::async_runtime::spawn(async move {
+            while let Some(event) = events.recv().await {
+                record(&session_clone, &masker_clone, web::recorded_action(event)).await;
+            }
+        });
+
+        // Navigating after the listener is attached records the start page
+        if let Some(url) = url.filter(|u| !u.is_empty()) {
+            self.web
+                .navigate(&session_id, &url)
+                .await
+                .map_err(|e| format!("Failed to navigate: {}", e))?;
+        }
+
+        Ok(())
+    }
+
+    async fn stop_web_session(&self) {
+        let Some(session_id) = self.web_session.write().await.take() else {
+            return;
+        };
+        if let Err(e) = self.web.stop_recording(&session_id).await {
+            log::warn!("Failed to stop browser recording: {}", e);
+        }
+        if let Err(e) = self.web.close(&session_id).await {
+            log::warn!("Failed to close recording browser: {}", e);
+        }
+    }
 
+    #[cfg(target_os = "macos")]
+    async fn start_macos_event_tap(&self) -> Result<(), String> {
+        let events = self.event_sink();
         let tap = macos::EventTap::new(move |event| {
-            let session = session_clone.clone();
-            tokio::spawn(async move {
-                let mut session = session.write().await;
-                if session.state == RecordingState::Recording {
-                    session.actions.push(event);
-                }
-            });
+            let _ = events.send(event);
         })?;
 
         let mut event_tap = self.event_tap.write().await;
@@ -303,16 +428,9 @@ impl Recorder {
 
     #[cfg(target_os = "windows")]
     async fn start_windows_event_hook(&self) -> Result<(), String> {
-        let session_clone = self.session.clone();
-
+        let events = self.event_sink();
         let hook = windows::EventHook::new(move |event| {
-            let session = session_clone.clone();
-            tokio::spawn(async move {
-                let mut session = session.write().await;
-                if session.state == RecordingState::Recording {
-                    session.actions.push(event);
-                }
-            });
+            let _ = events.send(event);
         })?;
 
         let mut event_hook = self.event_hook.write().await;
@@ -348,16 +466,10 @@ impl Recorder {
 
     #[cfg(target_os = "linux")]
     async fn start_linux_event_monitor(&self) -> Result<(), String> {
-        let session_clone = self.session.clone();
-
+        // Called from the monitor thread, outside the async runtime
+        let events = self.event_sink();
         let monitor = linux::EventMonitor::new(move |event| {
-            let session = session_clone.clone();
-            tokio::spawn(async move {
-                let mut session = session.write().await;
-                if session.state == RecordingState::Recording {
-                    session.actions.push(event);
-                }
-            });
+            let _ = events.send(event);
         })?;
 
         let mut event_monitor = self.event_monitor.write().await;
@@ -392,187 +504,72 @@ impl Recorder {
     }
 }
 
-// Convert recorded actions to workflow nodes
-pub fn convert_to_workflow(session: &RecordingSession) -> crate::engine::executor::Workflow {
-    use crate::engine::executor::{Position, Workflow, WorkflowEdge, WorkflowNode};
-
-    let mut nodes: Vec<WorkflowNode> = Vec::new();
-    let mut edges: Vec<WorkflowEdge> = Vec::new();
-
-    // Add start node
-    let start_node = WorkflowNode {
-        id: "start_node".to_string(),
-        node_type: "start".to_string(),
-        position: Position { x: 250.0, y: 0.0 },
-        data: HashMap::new(),
-        label: Some("Start".to_string()),
-    };
-    nodes.push(start_node);
-
-    let mut prev_node_id = "start_node".to_string();
-    let mut y_position = 100.0;
-
-    for action in &session.actions {
-        let (node_type, mut data) = match &action.action_type {
-            RecordedActionType::Click => {
-                let mut d = HashMap::new();
-                d.insert(
-                    "clickType".to_string(),
-                    serde_json::Value::String("single".to_string()),
-                );
-                if let Some(element) = &action.element {
-                    d.insert(
-                        "elementId".to_string(),
-                        serde_json::Value::String(element.id.clone()),
-                    );
-                    d.insert(
-                        "elementName".to_string(),
-                        serde_json::Value::String(element.name.clone()),
-                    );
-                }
-                if let Some((x, y)) = action.position {
-                    d.insert("x".to_string(), serde_json::Value::Number(x.into()));
-                    d.insert("y".to_string(), serde_json::Value::Number(y.into()));
-                }
-                ("click".to_string(), d)
-            }
-            RecordedActionType::DoubleClick => {
-                let mut d = HashMap::new();
-                d.insert(
-                    "clickType".to_string(),
-                    serde_json::Value::String("double".to_string()),
-                );
-                if let Some(element) = &action.element {
-                    d.insert(
-                        "elementId".to_string(),
-                        serde_json::Value::String(element.id.clone()),
-                    );
-                }
-                if let Some((x, y)) = action.position {
-                    d.insert("x".to_string(), serde_json::Value::Number(x.into()));
-                    d.insert("y".to_string(), serde_json::Value::Number(y.into()));
-                }
-                ("click".to_string(), d)
-            }
-            RecordedActionType::RightClick => {
-                let mut d = HashMap::new();
-                d.insert(
-                    "clickType".to_string(),
-                    serde_json::Value::String("right".to_string()),
-                );
-                if let Some(element) = &action.element {
-                    d.insert(
-                        "elementId".to_string(),
-                        serde_json::Value::String(element.id.clone()),
-                    );
-                }
-                if let Some((x, y)) = action.position {
-                    d.insert("x".to_string(), serde_json::Value::Number(x.into()));
-                    d.insert("y".to_string(), serde_json::Value::Number(y.into()));
-                }
-                ("click".to_string(), d)
-            }
-            RecordedActionType::Input => {
-                let mut d = HashMap::new();
-                if let Some(text) = action.data.get("text") {
-                    d.insert("text".to_string(), text.clone());
-                }
-                if let Some(element) = &action.element {
-                    d.insert(
-                        "elementId".to_string(),
-                        serde_json::Value::String(element.id.clone()),
-                    );
-                }
-                ("input".to_string(), d)
-            }
-            RecordedActionType::Scroll => {
-                let mut d = HashMap::new();
-                if let Some(delta_x) = action.data.get("deltaX") {
-                    d.insert("deltaX".to_string(), delta_x.clone());
-                }
-                if let Some(delta_y) = action.data.get("deltaY") {
-                    d.insert("deltaY".to_string(), delta_y.clone());
-                }
-                ("scroll".to_string(), d)
-            }
-            RecordedActionType::Hotkey => {
-                let mut d = HashMap::new();
-                if let Some(keys) = action.data.get("keys") {
-                    d.insert("keys".to_string(), keys.clone());
-                }
-                if let Some(modifiers) = action.data.get("modifiers") {
-                    d.insert("modifiers".to_string(), modifiers.clone());
-                }
-                ("hotkey".to_string(), d)
-            }
-            RecordedActionType::Wait => {
-                let mut d = HashMap::new();
-                let delay = action.data.get("delay").and_then(|v| v.as_u64()).unwrap_or(1000);
-                d.insert("delay".to_string(), serde_json::Value::Number(delay.into()));
-                ("delay".to_string(), d)
-            }
-        };
+/// Record hook events in the order they arrive until all senders are gone
+async fn record_events(
+    session: Arc<RwLock<RecordingSession>>,
+    masker: Arc<Mutex<Masker>>,
+    mut events: mpsc::UnboundedReceiver<RecordedAction>,
+) {
+    while let Some(event) = events.recv().await {
+        record(&session, &masker, event).await;
+    }
+}
 
-        // Copy additional data
-        for (key, value) in &action.data {
-            if !data.contains_key(key) {
-                data.insert(key.clone(), value.clone());
-            }
+/// Add an action to a running recording, masking sensitive input
+async fn record(session: &RwLock<RecordingSession>, masker: &Mutex<Masker>, action: RecordedAction) {
+    let mut session = session.write().await;
+    if session.state == RecordingState::Recording {
+        let mut masker = masker.lock().unwrap_or_else(PoisonError::into_inner);
+        masker.record(&mut session.actions, action);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::automation::Rect;
+
+    fn password_click() -> RecordedAction {
+        let field = UIElement::new(
+            "Password".to_string(),
+            "password text".to_string(),
+            Rect { x: 0, y: 0, width: 100, height: 20 },
+        );
+        RecordedAction::new(RecordedActionType::Click).with_element(field)
+    }
+
+    fn key(text: &str) -> RecordedAction {
+        RecordedAction::new(RecordedActionType::Input).with_data("text", serde_json::json!(text))
+    }
+
+    fn texts(session: &RecordingSession) -> Vec<&str> {
+        session
+            .actions
+            .iter()
+            .filter_map(|a| a.data.get("text").and_then(|v| v.as_str()))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_hook_events_are_masked_in_order() {
+        let recorder = Recorder::new();
+        recorder.begin(None, RecordingSource::Desktop).await.unwrap();
+
+        let (sender, events) = mpsc::unbounded_channel();
+        sender.send(password_click()).unwrap();
+        for ch in ["s", "e", "c", "r", "e", "t"] {
+            sender.send(key(ch)).unwrap();
         }
+        drop(sender);
+        record_events(recorder.session.clone(), recorder.masker.clone(), events).await;
 
-        let node = WorkflowNode {
-            id: action.id.clone(),
-            node_type,
-            position: Position {
-                x: 250.0,
-                y: y_position,
-            },
-            data,
-            label: None,
-        };
-        nodes.push(node);
-
-        // Create edge from previous node
-        let edge = WorkflowEdge {
-            id: format!("edge_{}_{}", prev_node_id, action.id),
-            source: prev_node_id.clone(),
-            target: action.id.clone(),
-            source_handle: None,
-            target_handle: None,
-        };
-        edges.push(edge);
-
-        prev_node_id = action.id.clone();
-        y_position += 100.0;
-    }
-
-    // Add end node
-    let end_node = WorkflowNode {
-        id: "end_node".to_string(),
-        node_type: "end".to_string(),
-        position: Position {
-            x: 250.0,
-            y: y_position,
-        },
-        data: HashMap::new(),
-        label: Some("End".to_string()),
-    };
-    nodes.push(end_node);
-
-    // Edge from last action to end
-    let final_edge = WorkflowEdge {
-        id: format!("edge_{}_end_node", prev_node_id),
-        source: prev_node_id,
-        target: "end_node".to_string(),
-        source_handle: None,
-        target_handle: None,
-    };
-    edges.push(final_edge);
-
-    Workflow {
-        id: uuid::Uuid::new_v4().to_string(),
-        name: session.name.clone(),
-        nodes,
-        edges,
+        assert_eq!(texts(&*recorder.session.read().await), vec!["${password}"]);
+
+        // Keystrokes that overtake the click go to a field not known yet
+        recorder.stop().await.ok();
+        recorder.begin(None, RecordingSource::Desktop).await.unwrap();
+        recorder.add_action(key("secret")).await;
+        recorder.add_action(password_click()).await;
+        assert_eq!(texts(&*recorder.session.read().await), vec!["${typed_text}"]);
     }
 }
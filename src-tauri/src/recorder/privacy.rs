@@ -0,0 +1,468 @@
+//! Masking of sensitive input while recording
+//!
+//! Text typed into password fields never reaches the session: it is replaced
+//! by a variable reference such as `${password}`, to be set before the
+//! workflow runs, and screenshots are blurred where password fields are.
+//! Desktop keystrokes carry no element, so the keyboard focus is followed
+//! through clicks. Until a click shows where the focus is, at the start of a
+//! recording and after Tab or Enter, typed text is masked as well; the text
+//! capture hotkey covers fields not recognized as sensitive.
+
+use super::convert::{vk_key_name, vk_modifier, COMBO_MS};
+use super::{RecordedAction, RecordedActionType};
+use crate::automation::Rect;
+use crate::element::UIElement;
+use image::imageops;
+use std::io::Cursor;
+
+/// Key of the hotkey that pauses and resumes capture of typed text
+pub const TEXT_CAPTURE_KEY: &str = "F9";
+
+/// Modifiers of the text capture hotkey, Ctrl+Shift+F9
+pub const TEXT_CAPTURE_MODIFIERS: &[&str] = &["Ctrl", "Shift"];
+
+/// Variable standing in for text typed while capture is paused
+const PAUSED_TEXT_VARIABLE: &str = "masked_text";
+
+/// Variable standing in for text typed while the focused field is not known
+const UNKNOWN_FIELD_VARIABLE: &str = "typed_text";
+
+/// Recorded keystroke details that reveal the typed key
+const KEY_DATA: &[&str] = &["vkCode", "scanCode"];
+
+/// Password field seen during the recording
+struct SensitiveField {
+    key: String,
+    bounds: Rect,
+    placeholder: String,
+}
+
+/// Where the keyboard focus is, as far as the recording shows
+#[derive(Default)]
+enum Focus {
+    /// Not shown since the recording started or the focus moved by keyboard
+    #[default]
+    Unknown,
+    Plain,
+    /// Password field, with its placeholder
+    Sensitive(String),
+}
+
+/// Masks sensitive input of one recording as actions are added to it
+#[derive(Default)]
+pub(super) struct Masker {
+    focus: Focus,
+    fields: Vec<SensitiveField>,
+    text_paused: bool,
+    /// Modifier keys the Windows recorder reported on their own, with their timestamp
+    held: Vec<(&'static str, u64)>,
+}
+
+impl Masker {
+    /// Whether capture of typed text is paused by the hotkey
+    pub fn text_paused(&self) -> bool {
+        self.text_paused
+    }
+
+    /// Append `action` to `actions` with sensitive input masked
+    pub fn record(&mut self, actions: &mut Vec<RecordedAction>, mut action: RecordedAction) {
+        let mut sensitive = None;
+        if let Some(element) = action.element.as_mut() {
+            if is_sensitive(element) {
+                sensitive = Some(self.field(element));
+                mask_element(element);
+            }
+        }
+        let shown = match &sensitive {
+            Some(placeholder) => Focus::Sensitive(placeholder.clone()),
+            None => Focus::Plain,
+        };
+
+        match action.action_type {
+            RecordedActionType::Click | RecordedActionType::DoubleClick | RecordedActionType::RightClick => {
+                self.focus = shown;
+            }
+            RecordedActionType::Navigate => {
+                self.focus = Focus::Unknown;
+            }
+            RecordedActionType::Hotkey => {
+                if self.is_text_capture_hotkey(&action) {
+                    self.text_paused = !self.text_paused;
+                    log::info!(
+                        "Text capture {}",
+                        if self.text_paused { "paused" } else { "resumed" }
+                    );
+                    // The hotkey is not part of the recording, nor its modifiers
+                    while actions.last().is_some_and(|a| held_modifier(a).is_some()) {
+                        actions.pop();
+                    }
+                    self.held.clear();
+                    return;
+                }
+                // Corrections inside a masked text
+                if self.masking() && is_edit_key(&action) {
+                    return;
+                }
+                if is_focus_key(&action) {
+                    self.focus = Focus::Unknown;
+                }
+            }
+            RecordedActionType::Input => {
+                if action.element.is_some() {
+                    self.focus = shown;
+                }
+                let placeholder = match &self.focus {
+                    Focus::Sensitive(placeholder) => Some(placeholder.clone()),
+                    Focus::Unknown => Some(format!("${{{}}}", UNKNOWN_FIELD_VARIABLE)),
+                    Focus::Plain => None,
+                }
+                .or_else(|| {
+                    self.text_paused
+                        .then(|| format!("${{{}}}", PAUSED_TEXT_VARIABLE))
+                });
+                if let Some(placeholder) = placeholder {
+                    // Keystrokes of a masked text collapse into a single reference
+                    if actions.last().is_some_and(|last| is_masked_input(last, &placeholder)) {
+                        return;
+                    }
+                    action.data.insert("text".to_string(), serde_json::json!(placeholder));
+                    for key in KEY_DATA {
+                        action.data.remove(*key);
+                    }
+                }
+            }
+            _ => {}
+        }
+
+        if let Some(screenshot) = action.screenshot.take() {
+            let regions: Vec<Rect> = self.fields.iter().map(|f| f.bounds.clone()).collect();
+            action.screenshot = if regions.is_empty() {
+                Some(screenshot)
+            } else {
+                blur_regions(&screenshot, &regions)
+            };
+        }
+
+        actions.push(action);
+    }
+
+    fn masking(&self) -> bool {
+        !matches!(self.focus, Focus::Plain) || self.text_paused
+    }
+
+    /// Placeholder of a password field, numbered in order of appearance
+    fn field(&mut self, element: &UIElement) -> String {
+        let key = field_key(element);
+        if let Some(field) = self.fields.iter_mut().find(|f| f.key == key) {
+            field.bounds = element.bounds.clone();
+            return field.placeholder.clone();
+        }
+
+        let placeholder = match self.fields.len() {
+            0 => "${password}".to_string(),
+            n => format!("${{password_{}}}", n + 1),
+        };
+        self.fields.push(SensitiveField {
+            key,
+            bounds: element.bounds.clone(),
+            placeholder: placeholder.clone(),
+        });
+        placeholder
+    }
+
+    fn is_text_capture_hotkey(&mut self, action: &RecordedAction) -> bool {
+        let vk_code = action.data.get("vkCode").and_then(|v| v.as_u64());
+
+        // Windows reports modifiers as separate key presses
+        if let Some(modifier) = held_modifier(action) {
+            self.held.retain(|(name, _)| *name != modifier);
+            self.held.push((modifier, action.timestamp));
+            return false;
+        }
+
+        let (key, modifiers) = match vk_code {
+            Some(vk_code) => {
+                self.held
+                    .retain(|(_, at)| action.timestamp.saturating_sub(*at) <= COMBO_MS);
+                let modifiers = self.held.drain(..).map(|(name, _)| name.to_string()).collect();
+                (vk_key_name(vk_code).unwrap_or_default(), modifiers)
+            }
+            None => {
+                let key = action.data.get("key").and_then(|v| v.as_str()).unwrap_or_default();
+                let modifiers: Vec<String> = action
+                    .data
+                    .get("modifiers")
+                    .and_then(|v| v.as_array())
+                    .map(|arr| arr.iter().filter_map(|v| v.as_str()).map(String::from).collect())
+                    .unwrap_or_default();
+                (key.to_string(), modifiers)
+            }
+        };
+
+        key.eq_ignore_ascii_case(TEXT_CAPTURE_KEY)
+            && modifiers.len() == TEXT_CAPTURE_MODIFIERS.len()
+            && TEXT_CAPTURE_MODIFIERS.iter().all(|m| modifiers.iter().any(|held| held == m))
+    }
+}
+
+/// Whether an element is a password field
+///
+/// Covers the AT-SPI "password text" role, UI Automation's IsPassword,
+/// macOS secure text fields and web inputs of type password.
+pub fn is_sensitive(element: &UIElement) -> bool {
+    let attribute = |name: &str| element.attributes.get(name).map(String::as_str);
+
+    element.control_type.eq_ignore_ascii_case("password text")
+        || element.control_type == "AXSecureTextField"
+        || attribute("subrole") == Some("AXSecureTextField")
+        || attribute("isPassword") == Some("true")
+        || attribute("type").is_some_and(|t| t.eq_ignore_ascii_case("password"))
+}
+
+/// Drop the value and blur the screenshot of a password field
+fn mask_element(element: &mut UIElement) {
+    element.attributes.remove("value");
+    if let Some(screenshot) = element.screenshot.take() {
+        let whole = Rect {
+            x: 0,
+            y: 0,
+            width: i32::MAX,
+            height: i32::MAX,
+        };
+        element.screenshot = blur_regions(&screenshot, &[whole]);
+    }
+}
+
+/// Identity of a field across the actions recorded on it
+fn field_key(element: &UIElement) -> String {
+    element
+        .css_selector
+        .clone()
+        .or_else(|| element.automation_id.clone())
+        .or_else(|| element.attributes.get("path").cloned())
+        .unwrap_or_else(|| format!("{}@{},{}", element.name, element.bounds.x, element.bounds.y))
+}
+
+fn held_modifier(action: &RecordedAction) -> Option<&'static str> {
+    if !matches!(action.action_type, RecordedActionType::Hotkey) {
+        return None;
+    }
+    action.data.get("vkCode").and_then(|v| v.as_u64()).and_then(vk_modifier)
+}
+
+/// Backspace or Delete without modifiers
+fn is_edit_key(action: &RecordedAction) -> bool {
+    let has_modifiers = action
+        .data
+        .get("modifiers")
+        .and_then(|v| v.as_array())
+        .is_some_and(|m| !m.is_empty());
+
+    !has_modifiers && matches!(key_name(action).as_deref(), Some("Backspace" | "Delete"))
+}
+
+/// Tab or Enter, with any modifiers, which may move the focus to another field
+fn is_focus_key(action: &RecordedAction) -> bool {
+    matches!(key_name(action).as_deref(), Some("Tab" | "Enter"))
+}
+
+fn key_name(action: &RecordedAction) -> Option<String> {
+    action
+        .data
+        .get("key")
+        .and_then(|v| v.as_str())
+        .map(String::from)
+        .or_else(|| action.data.get("vkCode").and_then(|v| v.as_u64()).and_then(vk_key_name))
+}
+
+fn is_masked_input(action: &RecordedAction, placeholder: &str) -> bool {
+    matches!(action.action_type, RecordedActionType::Input)
+        && action.data.get("text").and_then(|v| v.as_str()) == Some(placeholder)
+}
+
+/// Blur regions of a PNG image, given in pixels of the image
+///
+/// Returns `None` when the image cannot be decoded, so that it is dropped
+/// rather than kept unmasked.
+fn blur_regions(png: &[u8], regions: &[Rect]) -> Option<Vec<u8>> {
+    let mut image = match image::load_from_memory(png) {
+        Ok(image) => image.to_rgba8(),
+        Err(e) => {
+            log::warn!("Dropping screenshot that could not be masked: {}", e);
+            return None;
+        }
+    };
+    let (width, height) = image.dimensions();
+
+    for region in regions {
+        let clamp_x = |x: i32| x.clamp(0, width as i32) as u32;
+        let clamp_y = |y: i32| y.clamp(0, height as i32) as u32;
+        let (left, top) = (clamp_x(region.x), clamp_y(region.y));
+        let right = clamp_x(region.x.saturating_add(region.width));
+        let bottom = clamp_y(region.y.saturating_add(region.height));
+        if right <= left || bottom <= top {
+            continue;
+        }
+
+        let area = imageops::crop_imm(&image, left, top, right - left, bottom - top).to_image();
+        // Strong enough to wash out characters of the field's height
+        let sigma = ((bottom - top).max(right - left) as f32 / 4.0).max(4.0);
+        let blurred = imageops::blur(&area, sigma);
+        imageops::replace(&mut image, &blurred, left as i64, top as i64);
+    }
+
+    let mut encoded = Vec::new();
+    image
+        .write_to(&mut Cursor::new(&mut encoded), image::ImageFormat::Png)
+        .ok()?;
+    Some(encoded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn action(action_type: RecordedActionType, timestamp: u64) -> RecordedAction {
+        let mut action = RecordedAction::new(action_type);
+        action.timestamp = timestamp;
+        action
+    }
+
+    fn field(control_type: &str, x: i32) -> UIElement {
+        let mut element = UIElement::new(
+            "field".to_string(),
+            control_type.to_string(),
+            Rect {
+                x,
+                y: 0,
+                width: 100,
+                height: 20,
+            },
+        );
+        element.attributes.insert("value".to_string(), "hunter2".to_string());
+        element
+    }
+
+    fn key(text: &str, timestamp: u64) -> RecordedAction {
+        action(RecordedActionType::Input, timestamp).with_data("text", serde_json::json!(text))
+    }
+
+    fn hotkey(key: &str, modifiers: &[&str], timestamp: u64) -> RecordedAction {
+        action(RecordedActionType::Hotkey, timestamp)
+            .with_data("key", serde_json::json!(key))
+            .with_data("modifiers", serde_json::json!(modifiers))
+    }
+
+    fn record(masker: &mut Masker, actions: &[RecordedAction]) -> Vec<RecordedAction> {
+        let mut recorded = Vec::new();
+        for action in actions {
+            masker.record(&mut recorded, action.clone());
+        }
+        recorded
+    }
+
+    fn texts(actions: &[RecordedAction]) -> Vec<&str> {
+        actions
+            .iter()
+            .filter_map(|a| a.data.get("text").and_then(|v| v.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn test_password_field_text_becomes_variable() {
+        let mut masker = Masker::default();
+        let recorded = record(
+            &mut masker,
+            &[
+                action(RecordedActionType::Click, 0).with_element(field("text", 0)),
+                key("bob", 100),
+                action(RecordedActionType::Click, 1000).with_element(field("password text", 200)),
+                key("h", 1100).with_data("vkCode", serde_json::json!(0x48)),
+                key("u", 1200),
+                action(RecordedActionType::Hotkey, 1300).with_data("vkCode", serde_json::json!(0x08)),
+                key("n", 1400),
+                hotkey("Enter", &[], 1500),
+            ],
+        );
+
+        assert_eq!(texts(&recorded), vec!["bob", "${password}"]);
+        assert!(!recorded[3].data.contains_key("vkCode"));
+        assert!(!recorded[2].element.as_ref().unwrap().attributes.contains_key("value"));
+        // Enter still submits the form
+        assert_eq!(recorded.len(), 5);
+    }
+
+    #[test]
+    fn test_hotkey_pauses_text_capture() {
+        let mut masker = Masker::default();
+        let recorded = record(
+            &mut masker,
+            &[
+                action(RecordedActionType::Click, 0).with_element(field("text", 0)),
+                key("user", 50),
+                // Windows: modifiers first, then the key
+                action(RecordedActionType::Hotkey, 100).with_data("vkCode", serde_json::json!(0xA2)),
+                action(RecordedActionType::Hotkey, 110).with_data("vkCode", serde_json::json!(0xA0)),
+                action(RecordedActionType::Hotkey, 120).with_data("vkCode", serde_json::json!(0x78)),
+                key("secret", 500),
+                hotkey("F9", &["Shift", "Ctrl"], 900),
+                key("visible", 1000),
+            ],
+        );
+
+        assert_eq!(texts(&recorded), vec!["user", "${masked_text}", "visible"]);
+        assert_eq!(recorded.len(), 4);
+        assert!(!masker.text_paused());
+    }
+
+    #[test]
+    fn test_text_is_masked_until_a_click_shows_the_focus() {
+        let mut masker = Masker::default();
+        let recorded = record(
+            &mut masker,
+            &[
+                key("early", 0),
+                action(RecordedActionType::Click, 100).with_element(field("text", 0)),
+                key("bob", 200),
+                hotkey("Tab", &[], 300),
+                key("s", 400),
+                hotkey("Backspace", &[], 450),
+                key("ecret", 500),
+                action(RecordedActionType::Click, 600).with_element(field("text", 0)),
+                key("!", 700),
+            ],
+        );
+
+        assert_eq!(texts(&recorded), vec!["${typed_text}", "bob", "${typed_text}", "!"]);
+        // Tab still moves the focus on replay
+        assert_eq!(recorded.len(), 7);
+    }
+
+    #[test]
+    fn test_screenshots_are_blurred_over_password_fields() {
+        let mut image = image::RgbaImage::new(40, 10);
+        for (x, _, pixel) in image.enumerate_pixels_mut() {
+            *pixel = if x % 2 == 0 {
+                image::Rgba([0, 0, 0, 255])
+            } else {
+                image::Rgba([255, 255, 255, 255])
+            };
+        }
+        let mut png = Vec::new();
+        image.write_to(&mut Cursor::new(&mut png), image::ImageFormat::Png).unwrap();
+
+        let region = Rect {
+            x: 0,
+            y: 0,
+            width: 20,
+            height: 10,
+        };
+        let blurred = blur_regions(&png, &[region]).unwrap();
+        let blurred = image::load_from_memory(&blurred).unwrap().to_rgba8();
+
+        // Stripes are washed out inside the region and untouched outside it
+        assert_ne!(blurred.get_pixel(4, 5), image.get_pixel(4, 5));
+        assert_eq!(blurred.get_pixel(30, 5), image.get_pixel(30, 5));
+        assert!(blur_regions(b"not an image", &[]).is_none());
+    }
+}
@@ -0,0 +1,397 @@
+//! Direct replay of recorded actions
+//!
+//! Plays a recording back through `DesktopAutomation` with its original
+//! timing, without converting it to a workflow first. Clicks go to the
+//! recorded screen positions and keystrokes to whatever has the focus, so
+//! this is meant for quick smoke tests on the machine the recording was made
+//! on.
+
+use super::convert::{combo, near, vk_key_name, vk_modifier, wheel_notches, COMBO_MS, DOUBLE_CLICK_MS};
+use super::{RecordedAction, RecordedActionType, RecordingSession, RecordingSource};
+use crate::automation::desktop::DesktopAutomation;
+use crate::automation::keys::KeyCombo;
+use crate::automation::{AutomationError, AutomationResult, ClickType, Rect};
+use crate::element::UIElement;
+use serde::Serialize;
+use std::time::Duration;
+
+/// Outcome of a replay
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplaySummary {
+    /// Actions played back
+    pub played: usize,
+    /// Actions that cannot be replayed directly, such as clicks without a position
+    pub skipped: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum ReplayAction {
+    Click { point: (i32, i32), click_type: ClickKind },
+    Type { text: String },
+    Keys { combo: KeyCombo },
+    Scroll { point: Option<(i32, i32)>, notches: (i32, i32) },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ClickKind {
+    Single,
+    Double,
+    Right,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct ReplayStep {
+    /// Pause before the step, already scaled by the replay speed
+    wait_ms: u64,
+    action: ReplayAction,
+    /// Recorded actions folded into this step
+    actions: usize,
+    /// Timestamp of the last recorded action folded into this step
+    end: u64,
+}
+
+/// Play `session` back; `speed` 2.0 plays twice as fast as recorded
+pub async fn replay(
+    session: &RecordingSession,
+    speed: f64,
+    automation: &dyn DesktopAutomation,
+) -> AutomationResult<ReplaySummary> {
+    if session.source == RecordingSource::Web {
+        return Err(AutomationError::InvalidParameter(
+            "Browser recordings cannot be replayed directly, convert them to a workflow".to_string(),
+        ));
+    }
+    if !speed.is_finite() || speed <= 0.0 {
+        return Err(AutomationError::InvalidParameter(format!("Invalid replay speed: {}", speed)));
+    }
+
+    let (steps, skipped) = plan(&session.actions, speed);
+    let mut summary = ReplaySummary {
+        played: 0,
+        skipped,
+    };
+
+    for step in steps {
+        if step.wait_ms > 0 {
+            tokio::time::sleep(Duration::from_millis(step.wait_ms)).await;
+        }
+
+        match &step.action {
+            ReplayAction::Click { point, click_type } => {
+                let click_type = match click_type {
+                    ClickKind::Single => ClickType::Single,
+                    ClickKind::Double => ClickType::Double,
+                    ClickKind::Right => ClickType::Right,
+                };
+                automation.click(&point_element(*point), click_type).await?;
+            }
+            ReplayAction::Type { text } => {
+                automation.type_text(text, 0).await?;
+            }
+            ReplayAction::Keys { combo } => {
+                automation.press_keys(combo).await?;
+            }
+            ReplayAction::Scroll { point, notches } => {
+                automation.scroll(notches.0, notches.1, *point).await?;
+            }
+        }
+        summary.played += step.actions;
+    }
+
+    log::info!(
+        "Replayed {} actions of '{}', skipped {}",
+        summary.played,
+        session.name,
+        summary.skipped
+    );
+    Ok(summary)
+}
+
+/// Turn recorded actions into timed steps, counting the actions left out
+///
+/// Keystrokes typed one after another become a single step, modifiers the
+/// Windows recorder reports on their own join the key that follows them, and
+/// the click a recorder reports before a double-click is dropped so it is not
+/// played three times.
+fn plan(actions: &[RecordedAction], speed: f64) -> (Vec<ReplayStep>, usize) {
+    let mut steps: Vec<ReplayStep> = Vec::new();
+    let mut skipped = 0;
+    let mut last_time = actions.first().map(|a| a.timestamp).unwrap_or(0);
+    let mut typing = false;
+    // Modifier keys recorded as separate key presses, with their time
+    let mut held: Vec<(&'static str, u64)> = Vec::new();
+
+    for action in actions {
+        let wait_ms = (action.timestamp.saturating_sub(last_time) as f64 / speed).round() as u64;
+        last_time = action.timestamp;
+
+        let before = held.len();
+        held.retain(|(_, at)| action.timestamp.saturating_sub(*at) <= COMBO_MS);
+        skipped += before - held.len();
+
+        let click_type = match action.action_type {
+            RecordedActionType::Click => Some(ClickKind::Single),
+            RecordedActionType::DoubleClick => Some(ClickKind::Double),
+            RecordedActionType::RightClick => Some(ClickKind::Right),
+            _ => None,
+        };
+
+        if let Some(click_type) = click_type {
+            typing = false;
+            skipped += held.len();
+            held.clear();
+            let Some(point) = action_point(action) else {
+                skipped += 1;
+                continue;
+            };
+
+            let mut step = ReplayStep {
+                wait_ms,
+                action: ReplayAction::Click { point, click_type },
+                actions: 1,
+                end: action.timestamp,
+            };
+            if click_type == ClickKind::Double {
+                if let Some(previous) = steps.last() {
+                    if let ReplayAction::Click { point: first, click_type: ClickKind::Single } = previous.action {
+                        if action.timestamp.saturating_sub(previous.end) <= DOUBLE_CLICK_MS
+                            && near(Some(first), Some(point))
+                        {
+                            step.wait_ms = previous.wait_ms;
+                            step.actions += previous.actions;
+                            steps.pop();
+                        }
+                    }
+                }
+            }
+            steps.push(step);
+            continue;
+        }
+
+        match action.action_type {
+            RecordedActionType::Input => {
+                let text = action.data.get("text").and_then(|v| v.as_str()).unwrap_or("");
+                let modifiers: Vec<&str> = held.drain(..).map(|(name, _)| name).collect();
+                if modifiers.iter().any(|m| *m != "Shift") {
+                    typing = false;
+                    let keys = combo(&modifiers, &text.to_uppercase());
+                    skipped += push_keys(&mut steps, &keys, wait_ms, modifiers.len() + 1, action.timestamp);
+                    continue;
+                }
+                let text = if modifiers.is_empty() {
+                    text.to_string()
+                } else {
+                    text.to_uppercase()
+                };
+                let folded = modifiers.len() + 1;
+
+                if typing {
+                    if let Some(ReplayStep {
+                        action: ReplayAction::Type { text: typed },
+                        actions,
+                        end,
+                        ..
+                    }) = steps.last_mut()
+                    {
+                        typed.push_str(&text);
+                        *actions += folded;
+                        *end = action.timestamp;
+                        continue;
+                    }
+                }
+
+                typing = true;
+                steps.push(ReplayStep {
+                    wait_ms,
+                    action: ReplayAction::Type { text },
+                    actions: folded,
+                    end: action.timestamp,
+                });
+            }
+            RecordedActionType::Hotkey => {
+                typing = false;
+                let vk_code = action.data.get("vkCode").and_then(|v| v.as_u64());
+                if let Some(modifier) = vk_code.and_then(vk_modifier) {
+                    // Held modifiers repeat; keep the latest press
+                    if let Some(i) = held.iter().position(|(name, _)| *name == modifier) {
+                        held.remove(i);
+                        skipped += 1;
+                    }
+                    held.push((modifier, action.timestamp));
+                    continue;
+                }
+
+                let key = action
+                    .data
+                    .get("key")
+                    .and_then(|v| v.as_str())
+                    .map(String::from)
+                    .or_else(|| vk_code.and_then(vk_key_name));
+                let recorded: Vec<&str> = action
+                    .data
+                    .get("modifiers")
+                    .and_then(|v| v.as_array())
+                    .map(|arr| arr.iter().filter_map(|v| v.as_str()).collect())
+                    .unwrap_or_default();
+                let (modifiers, folded): (Vec<&str>, usize) = if recorded.is_empty() {
+                    let modifiers: Vec<&str> = held.drain(..).map(|(name, _)| name).collect();
+                    let folded = modifiers.len() + 1;
+                    (modifiers, folded)
+                } else {
+                    skipped += held.len();
+                    held.clear();
+                    (recorded, 1)
+                };
+
+                match key {
+                    Some(key) => {
+                        skipped += push_keys(&mut steps, &combo(&modifiers, &key), wait_ms, folded, action.timestamp);
+                    }
+                    None => skipped += folded,
+                }
+            }
+            RecordedActionType::Scroll => {
+                typing = false;
+                let delta = |key: &str| action.data.get(key).and_then(|v| v.as_i64()).unwrap_or(0);
+                let (dx, dy) = wheel_notches((delta("deltaX"), delta("deltaY")));
+                if (dx, dy) == (0, 0) {
+                    skipped += 1;
+                    continue;
+                }
+                steps.push(ReplayStep {
+                    wait_ms,
+                    action: ReplayAction::Scroll {
+                        point: action_point(action),
+                        notches: (dx as i32, dy as i32),
+                    },
+                    actions: 1,
+                    end: action.timestamp,
+                });
+            }
+            RecordedActionType::Wait => {
+                // The pause itself is replayed through the timestamps
+                typing = false;
+            }
+            _ => {
+                typing = false;
+                skipped += 1;
+            }
+        }
+    }
+
+    (steps, skipped + held.len())
+}
+
+/// Add a step pressing a key combination, returning the number of actions
+/// skipped because the recorded keys are not known
+fn push_keys(steps: &mut Vec<ReplayStep>, keys: &str, wait_ms: u64, actions: usize, end: u64) -> usize {
+    match keys.parse::<KeyCombo>() {
+        Ok(combo) => {
+            steps.push(ReplayStep {
+                wait_ms,
+                action: ReplayAction::Keys { combo },
+                actions,
+                end,
+            });
+            0
+        }
+        Err(e) => {
+            log::debug!("Skipping recorded keys '{}': {}", keys, e);
+            actions
+        }
+    }
+}
+
+/// Screen point of an action, from its position or the center of its element
+fn action_point(action: &RecordedAction) -> Option<(i32, i32)> {
+    action
+        .position
+        .or_else(|| action.element.as_ref().map(|e| e.bounds.center()))
+}
+
+/// Zero-sized element at a screen point, clicked at exactly that point
+fn point_element((x, y): (i32, i32)) -> UIElement {
+    UIElement::new(
+        "Recorded position".to_string(),
+        "Point".to_string(),
+        Rect {
+            x,
+            y,
+            width: 0,
+            height: 0,
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::automation::desktop::testing::FakeDesktop;
+
+    fn action(action_type: RecordedActionType, timestamp: u64) -> RecordedAction {
+        let mut action = RecordedAction::new(action_type);
+        action.timestamp = timestamp;
+        action
+    }
+
+    fn key(text: &str, timestamp: u64) -> RecordedAction {
+        action(RecordedActionType::Input, timestamp).with_data("text", serde_json::json!(text))
+    }
+
+    #[test]
+    fn test_plan_scales_timing_and_merges_typing() {
+        let actions = vec![
+            action(RecordedActionType::Click, 1000).with_position(10, 10),
+            key("h", 2000),
+            key("i", 2200),
+            action(RecordedActionType::Hotkey, 2400).with_data("key", serde_json::json!("Enter")),
+            action(RecordedActionType::Click, 4400).with_position(50, 50),
+            action(RecordedActionType::DoubleClick, 4600).with_position(51, 50),
+        ];
+
+        let (steps, skipped) = plan(&actions, 2.0);
+
+        assert_eq!(skipped, 0);
+        let waits: Vec<u64> = steps.iter().map(|s| s.wait_ms).collect();
+        assert_eq!(waits, vec![0, 500, 100, 1000]);
+        assert_eq!(steps[1].action, ReplayAction::Type { text: "hi".to_string() });
+        assert_eq!(
+            steps[3].action,
+            ReplayAction::Click {
+                point: (51, 50),
+                click_type: ClickKind::Double
+            }
+        );
+        assert_eq!(steps.iter().map(|s| s.actions).sum::<usize>() + skipped, actions.len());
+    }
+
+    #[tokio::test]
+    async fn test_replay_types_at_focus_and_presses_hotkeys() {
+        let vk = |code: u64, timestamp| {
+            action(RecordedActionType::Hotkey, timestamp).with_data("vkCode", serde_json::json!(code))
+        };
+        let mut session = RecordingSession::new("Test".to_string());
+        session.actions = vec![
+            action(RecordedActionType::Click, 0).with_position(10, 10),
+            key("a", 10),
+            key("b", 20),
+            // Windows: Ctrl on its own, then the letter as text
+            vk(0xA2, 30),
+            key("c", 40),
+            action(RecordedActionType::Hotkey, 50)
+                .with_data("key", serde_json::json!("S"))
+                .with_data("modifiers", serde_json::json!(["Ctrl", "Shift"])),
+            vk(0x0D, 60),
+        ];
+        let desktop = FakeDesktop::default();
+
+        let summary = replay(&session, 100.0, &desktop).await.unwrap();
+
+        assert_eq!(
+            *desktop.log.lock().unwrap(),
+            vec!["click 10,10", "type ab", "keys Ctrl+C", "keys Ctrl+Shift+S", "keys Enter"]
+        );
+        assert_eq!((summary.played, summary.skipped), (7, 0));
+    }
+}
@@ -0,0 +1,68 @@
+//! Browser recording through CDP
+//!
+//! Browser actions are recorded in a session opened by the recorder itself,
+//! with selectors instead of screen positions.
+
+use super::{RecordedAction, RecordedActionType};
+use crate::automation::web::{RecordedTarget, WebRecordedEvent};
+use crate::automation::Rect;
+use crate::element::UIElement;
+
+/// Convert an event of the browser listener into a recorded action
+pub(super) fn recorded_action(event: WebRecordedEvent) -> RecordedAction {
+    match event {
+        WebRecordedEvent::Click { target } => with_target(RecordedAction::new(RecordedActionType::Click), &target),
+        WebRecordedEvent::Input { target, value } => {
+            with_target(RecordedAction::new(RecordedActionType::Input), &target)
+                .with_data("text", serde_json::json!(value))
+        }
+        WebRecordedEvent::Select { target, value, label } => {
+            with_target(RecordedAction::new(RecordedActionType::Select), &target)
+                .with_data("value", serde_json::json!(value))
+                .with_data("label", serde_json::json!(label))
+        }
+        WebRecordedEvent::Key { target, key, modifiers } => {
+            let action = RecordedAction::new(RecordedActionType::Hotkey)
+                .with_data("key", serde_json::json!(key))
+                .with_data("modifiers", serde_json::json!(modifiers));
+            match target {
+                Some(target) => action.with_data("selector", serde_json::json!(target.selector)),
+                None => action,
+            }
+        }
+        WebRecordedEvent::Navigate { url } => {
+            RecordedAction::new(RecordedActionType::Navigate).with_data("url", serde_json::json!(url))
+        }
+    }
+}
+
+fn with_target(action: RecordedAction, target: &RecordedTarget) -> RecordedAction {
+    action
+        .with_element(target_element(target))
+        .with_data("selector", serde_json::json!(target.selector))
+}
+
+/// Library element for a recorded target, located by its selectors
+fn target_element(target: &RecordedTarget) -> UIElement {
+    let name = if target.label.is_empty() {
+        target.tag_name.clone()
+    } else {
+        target.label.clone()
+    };
+    let mut element = UIElement::new(
+        name,
+        target.tag_name.clone(),
+        Rect {
+            x: 0,
+            y: 0,
+            width: 0,
+            height: 0,
+        },
+    );
+    element.css_selector = Some(target.selector.clone()).filter(|s| !s.is_empty());
+    element.xpath = Some(target.xpath.clone()).filter(|x| !x.is_empty());
+    if target.sensitive {
+        element.attributes.insert("type".to_string(), "password".to_string());
+    }
+    element
+}
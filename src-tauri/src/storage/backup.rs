@@ -0,0 +1,495 @@
+//! Backend-agnostic backup and restore
+//!
+//! A backup is a zip archive that does not depend on the database backend it
+//! was taken from:
+//!
+//! ```text
+//! manifest.json            format/schema versions, source backend, row counts
+//! tables/<table>.json      array of row objects keyed by column name
+//! ```
+//!
+//! Timestamps are stored as RFC3339 strings, so a backup taken from SQLite can
+//! be restored into MySQL or PostgreSQL and vice versa.
+
+use super::config::{BackupConfig, DatabaseType};
+use super::migration;
+use super::transfer::{self, ColumnKind, TableSpec, TABLES};
+use sea_orm::sea_query::{Alias, Expr, Query};
+use sea_orm::{ConnectionTrait, DatabaseConnection, DbErr, TransactionTrait, Value};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value as JsonValue};
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use thiserror::Error;
+
+/// Current backup archive format version
+pub const BACKUP_FORMAT_VERSION: u32 = 1;
+
+const MANIFEST_FILE: &str = "manifest.json";
+const TABLES_DIR: &str = "tables";
+
+/// File name prefix of scheduled backups, used for rotation
+const AUTO_BACKUP_PREFIX: &str = "auto-backup-";
+
+/// Rows read per query while dumping a table
+const DUMP_BATCH_SIZE: u64 = 500;
+
+/// Interval between checks whether a scheduled backup is due
+const SCHEDULER_TICK: Duration = Duration::from_secs(10 * 60);
+
+/// Backup errors
+#[derive(Error, Debug)]
+pub enum BackupError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Archive error: {0}")]
+    Archive(#[from] zip::result::ZipError),
+
+    #[error("Database error: {0}")]
+    Database(#[from] DbErr),
+
+    #[error("Serialization error: {0}")]
+    Serialization(#[from] serde_json::Error),
+
+    #[error("Invalid backup: {0}")]
+    InvalidBackup(String),
+
+    #[error("Unsupported backup format version {found} (supported: {supported})")]
+    UnsupportedVersion { found: u32, supported: u32 },
+}
+
+pub type BackupResult<T> = Result<T, BackupError>;
+
+/// Row count of a table stored in a backup
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupTableEntry {
+    pub name: String,
+    pub rows: u64,
+}
+
+/// Backup manifest stored as `manifest.json`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupManifest {
+    pub format_version: u32,
+    pub app_version: String,
+    pub schema_version: i64,
+    pub created_at: String,
+    pub source_type: DatabaseType,
+    pub tables: Vec<BackupTableEntry>,
+}
+
+/// How restored rows are combined with existing data
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum RestoreMode {
+    /// Keep existing rows, replacing those that share a primary key with the backup
+    #[default]
+    Merge,
+    /// Delete all existing rows before restoring
+    Replace,
+}
+
+/// Result of a restore
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RestoreReport {
+    pub mode: RestoreMode,
+    pub manifest: BackupManifest,
+    pub tables: Vec<BackupTableEntry>,
+    pub warnings: Vec<String>,
+}
+
+/// Write a backup of all application tables to `path`
+///
+/// The archive is written next to `path` under a hidden name and only renamed
+/// into place once complete, so a failed backup never leaves a truncated file
+/// behind or replaces an existing one.
+pub async fn create_backup(
+    db: &DatabaseConnection,
+    db_type: &DatabaseType,
+    path: &Path,
+) -> BackupResult<BackupManifest> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    let name = path.file_name().map(|n| n.to_string_lossy()).unwrap_or_default();
+    let partial = path.with_file_name(format!(".{}.partial-{}", name, uuid::Uuid::new_v4()));
+    let result = match write_backup(db, db_type, &partial).await {
+        Ok(manifest) => std::fs::rename(&partial, path).map(|_| manifest).map_err(Into::into),
+        Err(e) => Err(e),
+    };
+    if result.is_err() {
+        let _ = std::fs::remove_file(&partial);
+    }
+
+    let manifest = result?;
+    log::info!("Backup written to {}", path.display());
+    Ok(manifest)
+}
+
+/// Write the archive, reading every table inside one transaction so the backup
+/// is a consistent snapshot even while the application keeps writing
+async fn write_backup(
+    db: &DatabaseConnection,
+    db_type: &DatabaseType,
+    path: &Path,
+) -> BackupResult<BackupManifest> {
+    let file = File::create(path)?;
+    let mut zip = zip::ZipWriter::new(file);
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated);
+
+    let txn = db.begin().await?;
+    let mut tables = Vec::with_capacity(TABLES.len());
+    for table in TABLES {
+        let rows = dump_table(&txn, table).await?;
+        tables.push(BackupTableEntry {
+            name: table.name.to_string(),
+            rows: rows.len() as u64,
+        });
+
+        zip.start_file(format!("{}/{}.json", TABLES_DIR, table.name), options)?;
+        zip.write_all(&serde_json::to_vec(&rows)?)?;
+    }
+    let schema_version = migration::current_version(&txn).await?;
+    txn.commit().await?;
+
+    let manifest = BackupManifest {
+        format_version: BACKUP_FORMAT_VERSION,
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        schema_version,
+        created_at: chrono::Utc::now().to_rfc3339(),
+        source_type: db_type.clone(),
+        tables,
+    };
+
+    zip.start_file(MANIFEST_FILE, options)?;
+    zip.write_all(&serde_json::to_vec_pretty(&manifest)?)?;
+    zip.finish()?.sync_all()?;
+    Ok(manifest)
+}
+
+/// Read the manifest of a backup archive
+pub fn read_manifest(path: &Path) -> BackupResult<BackupManifest> {
+    let mut archive = zip::ZipArchive::new(File::open(path)?)?;
+    read_manifest_from(&mut archive)
+}
+
+/// Restore a backup archive into the given database
+///
+/// The whole restore runs in one transaction, so a failure leaves existing data untouched.
+pub async fn restore_backup(
+    db: &DatabaseConnection,
+    path: &Path,
+    mode: RestoreMode,
+) -> BackupResult<RestoreReport> {
+    let mut archive = zip::ZipArchive::new(File::open(path)?)?;
+    let manifest = read_manifest_from(&mut archive)?;
+
+    let mut warnings = Vec::new();
+    let current_schema = migration::current_version(db).await?;
+    if manifest.schema_version > current_schema {
+        warnings.push(format!(
+            "Backup was taken from schema version {}, this database is at version {}",
+            manifest.schema_version, current_schema
+        ));
+    }
+
+    for entry in &manifest.tables {
+        if !TABLES.iter().any(|t| t.name == entry.name) {
+            warnings.push(format!("Skipping unknown table '{}'", entry.name));
+        }
+    }
+
+    let txn = db.begin().await?;
+    let mut tables = Vec::new();
+
+    for table in TABLES {
+        let entry_name = format!("{}/{}.json", TABLES_DIR, table.name);
+        let rows: Vec<Map<String, JsonValue>> = match archive.by_name(&entry_name) {
+            Ok(mut file) => {
+                let mut content = String::new();
+                file.read_to_string(&mut content)?;
+                serde_json::from_str(&content)?
+            }
+            Err(zip::result::ZipError::FileNotFound) => {
+                warnings.push(format!("Backup does not contain table '{}'", table.name));
+                continue;
+            }
+            Err(e) => return Err(e.into()),
+        };
+
+        if mode == RestoreMode::Replace {
+            let delete = Query::delete().from_table(Alias::new(table.name)).to_owned();
+            txn.execute(txn.get_database_backend().build(&delete)).await?;
+        }
+
+        let mut restored = 0;
+        for chunk in rows.chunks(DUMP_BATCH_SIZE as usize) {
+            let values = chunk
+                .iter()
+                .map(|row| row_to_values(table, row))
+                .collect::<BackupResult<Vec<_>>>()?;
+
+            if mode == RestoreMode::Merge {
+                let keys: Vec<Value> = values.iter().map(|row| row[0].clone()).collect();
+                let delete = Query::delete()
+                    .from_table(Alias::new(table.name))
+                    .and_where(Expr::col(Alias::new(table.primary_key)).is_in(keys))
+                    .to_owned();
+                txn.execute(txn.get_database_backend().build(&delete)).await?;
+            }
+
+            restored += values.len() as u64;
+            transfer::insert_rows(&txn, table, values).await?;
+        }
+
+        tables.push(BackupTableEntry {
+            name: table.name.to_string(),
+            rows: restored,
+        });
+    }
+
+    txn.commit().await?;
+
+    log::info!("Backup restored from {} ({:?})", path.display(), mode);
+    Ok(RestoreReport {
+        mode,
+        manifest,
+        tables,
+        warnings,
+    })
+}
+
+/// List scheduled backups in a directory, newest first
+pub fn list_scheduled_backups(dir: &Path) -> BackupResult<Vec<PathBuf>> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut backups: Vec<PathBuf> = std::fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            path.file_name()
+                .and_then(|n| n.to_str())
+                .map(|n| n.starts_with(AUTO_BACKUP_PREFIX) && n.ends_with(".zip"))
+                .unwrap_or(false)
+        })
+        .collect();
+
+    // Timestamped names sort chronologically
+    backups.sort();
+    backups.reverse();
+    Ok(backups)
+}
+
+/// Delete all but the newest `keep` scheduled backups
+pub fn rotate_backups(dir: &Path, keep: usize) -> BackupResult<Vec<PathBuf>> {
+    let mut removed = Vec::new();
+    for path in list_scheduled_backups(dir)?.into_iter().skip(keep.max(1)) {
+        std::fs::remove_file(&path)?;
+        removed.push(path);
+    }
+    Ok(removed)
+}
+
+/// Take a scheduled backup into the configured directory and rotate old ones
+pub async fn run_scheduled_backup(config: &BackupConfig) -> BackupResult<PathBuf> {
+    let db = super::get_connection().await?;
+    let db_type = super::get_database_config().await.db_type;
+
+    let dir = config.get_directory();
+    let path = dir.join(format!(
+        "{}{}.zip",
+        AUTO_BACKUP_PREFIX,
+        chrono::Utc::now().format("%Y%m%d-%H%M%S")
+    ));
+
+    create_backup(&db, &db_type, &path).await?;
+
+    for removed in rotate_backups(&dir, config.keep)? {
+        log::info!("Removed old backup {}", removed.display());
+    }
+
+    Ok(path)
+}
+
+/// Periodically take scheduled backups according to the current configuration
+///
+/// The time of the last backup is taken from the newest archive on disk, so the
+/// schedule survives application restarts.
+pub async fn run_scheduler() {
+    loop {
+        let config = super::get_backup_config().await;
+
+        if config.enabled && is_backup_due(&config) {
+            match run_scheduled_backup(&config).await {
+                Ok(path) => log::info!("Scheduled backup written to {}", path.display()),
+                Err(e) => log::error!("Scheduled backup failed: {}", e),
+            }
+        }
+
+        tokio::time::sleep(SCHEDULER_TICK).await;
+    }
+}
+
+fn is_backup_due(config: &BackupConfig) -> bool {
+    let interval = Duration::from_secs(config.interval_hours.max(1) * 3600);
+
+    let newest = list_scheduled_backups(&config.get_directory())
+        .ok()
+        .and_then(|backups| backups.into_iter().next())
+        .and_then(|path| std::fs::metadata(path).and_then(|m| m.modified()).ok());
+
+    match newest {
+        Some(modified) => SystemTime::now()
+            .duration_since(modified)
+            .map(|elapsed| elapsed >= interval)
+            .unwrap_or(false),
+        None => true,
+    }
+}
+
+fn read_manifest_from(archive: &mut zip::ZipArchive<File>) -> BackupResult<BackupManifest> {
+    let mut content = String::new();
+    archive
+        .by_name(MANIFEST_FILE)
+        .map_err(|_| BackupError::InvalidBackup(format!("missing {}", MANIFEST_FILE)))?
+        .read_to_string(&mut content)?;
+
+    let manifest: BackupManifest = serde_json::from_str(&content)?;
+    if manifest.format_version > BACKUP_FORMAT_VERSION {
+        return Err(BackupError::UnsupportedVersion {
+            found: manifest.format_version,
+            supported: BACKUP_FORMAT_VERSION,
+        });
+    }
+    Ok(manifest)
+}
+
+async fn dump_table<C: ConnectionTrait>(
+    db: &C,
+    table: &TableSpec,
+) -> BackupResult<Vec<Map<String, JsonValue>>> {
+    let mut rows = Vec::new();
+    loop {
+        let batch = transfer::read_batch(db, table, rows.len() as u64, DUMP_BATCH_SIZE).await?;
+        let done = (batch.len() as u64) < DUMP_BATCH_SIZE;
+
+        rows.extend(batch.into_iter().map(|values| {
+            table
+                .columns
+                .iter()
+                .zip(values)
+                .map(|((name, _), value)| (name.to_string(), value_to_json(value)))
+                .collect::<Map<String, JsonValue>>()
+        }));
+
+        if done {
+            return Ok(rows);
+        }
+    }
+}
+
+fn value_to_json(value: Value) -> JsonValue {
+    match value {
+        Value::String(Some(s)) => JsonValue::String(*s),
+        Value::ChronoDateTimeUtc(Some(dt)) => JsonValue::String(dt.to_rfc3339()),
+        _ => JsonValue::Null,
+    }
+}
+
+/// Convert a row object into values ordered like `table.columns`
+///
+/// The primary key is always the first column of a [`TableSpec`].
+fn row_to_values(table: &TableSpec, row: &Map<String, JsonValue>) -> BackupResult<Vec<Value>> {
+    table
+        .columns
+        .iter()
+        .map(|(name, kind)| {
+            let field = row.get(*name).and_then(|v| v.as_str());
+            let invalid = || {
+                BackupError::InvalidBackup(format!("invalid value for {}.{}", table.name, name))
+            };
+
+            Ok(match kind {
+                ColumnKind::Text => field.ok_or_else(invalid)?.to_string().into(),
+                ColumnKind::NullableText => field.map(str::to_string).into(),
+                ColumnKind::Timestamp => parse_timestamp(field.ok_or_else(invalid)?)
+                    .ok_or_else(invalid)?
+                    .into(),
+                ColumnKind::NullableTimestamp => match field {
+                    Some(s) => Some(parse_timestamp(s).ok_or_else(invalid)?).into(),
+                    None => Option::<chrono::DateTime<chrono::Utc>>::None.into(),
+                },
+            })
+        })
+        .collect()
+}
+
+fn parse_timestamp(value: &str) -> Option<chrono::DateTime<chrono::Utc>> {
+    chrono::DateTime::parse_from_rfc3339(value)
+        .ok()
+        .map(|dt| dt.with_timezone(&chrono::Utc))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sea_orm::Database;
+
+    #[tokio::test]
+    async fn test_backup_round_trip() {
+        let dir = std::env::temp_dir().join(format!("rpa-backup-{}", uuid::Uuid::new_v4()));
+        let path = dir.join("backup.zip");
+
+        let db = Database::connect("sqlite::memory:").await.unwrap();
+        migration::run_migrations(&db, &DatabaseType::Sqlite).await.unwrap();
+        let now = chrono::Utc::now().to_rfc3339();
+        db.execute_unprepared(&format!(
+            "INSERT INTO workflows VALUES ('wf-1', 'Workflow', NULL, '[]', '[]', '[]', '{now}', '{now}')"
+        ))
+        .await
+        .unwrap();
+
+        let manifest = create_backup(&db, &DatabaseType::Sqlite, &path).await.unwrap();
+        assert_eq!(manifest.tables[0].rows, 1);
+
+        // Merge over the same data keeps a single row
+        restore_backup(&db, &path, RestoreMode::Merge).await.unwrap();
+        assert_eq!(transfer::count_rows(&db, "workflows").await.unwrap(), 1);
+
+        db.execute_unprepared(&format!(
+            "INSERT INTO workflows VALUES ('wf-2', 'Other', NULL, '[]', '[]', '[]', '{now}', '{now}')"
+        ))
+        .await
+        .unwrap();
+        restore_backup(&db, &path, RestoreMode::Replace).await.unwrap();
+        assert_eq!(transfer::count_rows(&db, "workflows").await.unwrap(), 1);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_failed_backup_keeps_previous_file() {
+        let dir = std::env::temp_dir().join(format!("rpa-backup-{}", uuid::Uuid::new_v4()));
+        let path = dir.join("backup.zip");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(&path, b"previous").unwrap();
+
+        // No migrations, so reading the first table fails
+        let db = Database::connect("sqlite::memory:").await.unwrap();
+        assert!(create_backup(&db, &DatabaseType::Sqlite, &path).await.is_err());
+
+        assert_eq!(std::fs::read(&path).unwrap(), b"previous");
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
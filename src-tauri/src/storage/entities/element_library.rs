@@ -10,8 +10,8 @@ pub struct Model {
     pub description: Option<String>,
     /// JSON serialized elements
     pub elements: String,
-    pub created_at: String,
-    pub updated_at: String,
+    pub created_at: DateTimeUtc,
+    pub updated_at: DateTimeUtc,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -42,8 +42,8 @@ impl From<Model> for ElementLibraryInfo {
             name: model.name,
             description: model.description,
             element_count,
-            created_at: model.created_at,
-            updated_at: model.updated_at,
+            created_at: model.created_at.to_rfc3339(),
+            updated_at: model.updated_at.to_rfc3339(),
         }
     }
 }
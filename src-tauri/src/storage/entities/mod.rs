@@ -1,2 +1,4 @@
 pub mod workflow;
 pub mod element_library;
+pub mod schema_version;
+pub mod recording;
@@ -0,0 +1,53 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "recordings")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: String,
+    pub name: String,
+    /// Recording source, `Desktop` or `Web`
+    pub source: String,
+    /// JSON serialized recorded actions
+    pub actions: String,
+    pub started_at: Option<DateTimeUtc>,
+    pub ended_at: Option<DateTimeUtc>,
+    pub created_at: DateTimeUtc,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+/// Recording info for listing
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordingInfo {
+    pub id: String,
+    pub name: String,
+    pub source: String,
+    pub action_count: usize,
+    pub started_at: Option<String>,
+    pub ended_at: Option<String>,
+    pub created_at: String,
+}
+
+impl From<Model> for RecordingInfo {
+    fn from(model: Model) -> Self {
+        // Parse actions to count them
+        let action_count = serde_json::from_str::<Vec<serde_json::Value>>(&model.actions)
+            .map(|actions| actions.len())
+            .unwrap_or(0);
+
+        Self {
+            id: model.id,
+            name: model.name,
+            source: model.source,
+            action_count,
+            started_at: model.started_at.map(|t| t.to_rfc3339()),
+            ended_at: model.ended_at.map(|t| t.to_rfc3339()),
+            created_at: model.created_at.to_rfc3339(),
+        }
+    }
+}
@@ -14,8 +14,8 @@ pub struct Model {
     pub edges: String,
     /// JSON serialized variables
     pub variables: String,
-    pub created_at: String,
-    pub updated_at: String,
+    pub created_at: DateTimeUtc,
+    pub updated_at: DateTimeUtc,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -46,8 +46,8 @@ impl From<Model> for WorkflowInfo {
             name: model.name,
             description: model.description,
             node_count,
-            created_at: model.created_at,
-            updated_at: model.updated_at,
+            created_at: model.created_at.to_rfc3339(),
+            updated_at: model.updated_at.to_rfc3339(),
         }
     }
 }
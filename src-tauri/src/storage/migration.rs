@@ -0,0 +1,410 @@
+//! Versioned schema migrations
+//!
+//! Every migration has a unique, increasing version and per-backend SQL. Applied
+//! versions are recorded in the `schema_version` table, and on startup all
+//! migrations newer than the recorded version run in order. Existing databases
+//! created before migrations existed are picked up by the idempotent initial
+//! migration and then upgraded like any other.
+//!
+//! To change the schema, append a new [`Migration`] to [`MIGRATIONS`]; never edit
+//! a migration that has already shipped.
+
+use super::config::DatabaseType;
+use super::entities::schema_version;
+use sea_orm::{
+    ActiveModelTrait, ConnectionTrait, DatabaseConnection, DbErr, EntityTrait, QueryOrder, Set,
+    Statement, TransactionTrait,
+};
+
+/// A single schema migration
+pub struct Migration {
+    pub version: i64,
+    pub description: &'static str,
+    up: fn(&DatabaseType) -> Vec<&'static str>,
+}
+
+impl Migration {
+    /// SQL statements that upgrade the schema to this version
+    pub fn up_statements(&self, db_type: &DatabaseType) -> Vec<&'static str> {
+        (self.up)(db_type)
+    }
+}
+
+/// All migrations, ordered by version
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "Initial schema",
+        up: initial_schema,
+    },
+    Migration {
+        version: 2,
+        description: "Store timestamps as native date/time columns",
+        up: native_timestamps,
+    },
+    Migration {
+        version: 3,
+        description: "Store JSON documents as text on PostgreSQL",
+        up: json_as_text,
+    },
+    Migration {
+        version: 4,
+        description: "Add recordings table",
+        up: recordings_table,
+    },
+];
+
+/// Latest schema version known to this build
+pub fn latest_version() -> i64 {
+    MIGRATIONS.last().map(|m| m.version).unwrap_or(0)
+}
+
+/// Get the schema version recorded in the database (0 if none)
+pub async fn current_version<C: ConnectionTrait>(db: &C) -> Result<i64, DbErr> {
+    let latest = schema_version::Entity::find()
+        .order_by_desc(schema_version::Column::Version)
+        .one(db)
+        .await?;
+
+    Ok(latest.map(|m| m.version).unwrap_or(0))
+}
+
+/// Apply all pending migrations, returning the resulting schema version
+pub async fn run_migrations(db: &DatabaseConnection, db_type: &DatabaseType) -> Result<i64, DbErr> {
+    let backend = db.get_database_backend();
+
+    db.execute(Statement::from_string(
+        backend,
+        schema_version_table(db_type).to_string(),
+    ))
+    .await?;
+
+    let mut version = current_version(db).await?;
+    if version > latest_version() {
+        return Err(DbErr::Custom(format!(
+            "Database schema version {} is newer than supported version {}",
+            version,
+            latest_version()
+        )));
+    }
+
+    for migration in MIGRATIONS.iter().filter(|m| m.version > version) {
+        log::info!(
+            "Applying migration {}: {}",
+            migration.version,
+            migration.description
+        );
+
+        // MySQL commits DDL implicitly, so a failed migration there may be partially applied
+        let txn = db.begin().await?;
+        for sql in migration.up_statements(db_type) {
+            txn.execute(Statement::from_string(backend, sql.to_string()))
+                .await
+                .map_err(|e| {
+                    DbErr::Migration(format!("Migration {} failed: {}", migration.version, e))
+                })?;
+        }
+
+        schema_version::ActiveModel {
+            version: Set(migration.version),
+            description: Set(migration.description.to_string()),
+            applied_at: Set(chrono::Utc::now()),
+        }
+        .insert(&txn)
+        .await?;
+
+        txn.commit().await?;
+        version = migration.version;
+    }
+
+    log::info!("Database schema is at version {}", version);
+    Ok(version)
+}
+
+fn schema_version_table(db_type: &DatabaseType) -> &'static str {
+    match db_type {
+        DatabaseType::Sqlite => r#"
+            CREATE TABLE IF NOT EXISTS schema_version (
+                version INTEGER PRIMARY KEY NOT NULL,
+                description TEXT NOT NULL,
+                applied_at TEXT NOT NULL
+            )
+        "#,
+        DatabaseType::Mysql => r#"
+            CREATE TABLE IF NOT EXISTS schema_version (
+                version BIGINT PRIMARY KEY NOT NULL,
+                description VARCHAR(255) NOT NULL,
+                applied_at DATETIME(6) NOT NULL
+            ) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci
+        "#,
+        DatabaseType::Postgres => r#"
+            CREATE TABLE IF NOT EXISTS schema_version (
+                version BIGINT PRIMARY KEY NOT NULL,
+                description VARCHAR(255) NOT NULL,
+                applied_at TIMESTAMPTZ NOT NULL
+            )
+        "#,
+    }
+}
+
+/// Version 1: the tables as they existed before versioned migrations
+fn initial_schema(db_type: &DatabaseType) -> Vec<&'static str> {
+    match db_type {
+        DatabaseType::Sqlite => vec![
+            r#"
+            CREATE TABLE IF NOT EXISTS workflows (
+                id TEXT PRIMARY KEY NOT NULL,
+                name TEXT NOT NULL,
+                description TEXT,
+                nodes TEXT NOT NULL,
+                edges TEXT NOT NULL,
+                variables TEXT NOT NULL,
+                created_at TEXT NOT NULL,
+                updated_at TEXT NOT NULL
+            )
+            "#,
+            r#"
+            CREATE TABLE IF NOT EXISTS element_libraries (
+                id TEXT PRIMARY KEY NOT NULL,
+                name TEXT NOT NULL,
+                description TEXT,
+                elements TEXT NOT NULL,
+                created_at TEXT NOT NULL,
+                updated_at TEXT NOT NULL
+            )
+            "#,
+            r#"
+            CREATE TABLE IF NOT EXISTS settings (
+                key TEXT PRIMARY KEY NOT NULL,
+                value TEXT NOT NULL
+            )
+            "#,
+            r#"
+            CREATE TABLE IF NOT EXISTS execution_logs (
+                id TEXT PRIMARY KEY NOT NULL,
+                workflow_id TEXT NOT NULL,
+                status TEXT NOT NULL,
+                started_at TEXT NOT NULL,
+                finished_at TEXT,
+                error_message TEXT,
+                log_data TEXT
+            )
+            "#,
+        ],
+        DatabaseType::Mysql => vec![
+            r#"
+            CREATE TABLE IF NOT EXISTS workflows (
+                id VARCHAR(36) PRIMARY KEY NOT NULL,
+                name VARCHAR(255) NOT NULL,
+                description TEXT,
+                nodes LONGTEXT NOT NULL,
+                edges LONGTEXT NOT NULL,
+                variables LONGTEXT NOT NULL,
+                created_at DATETIME NOT NULL,
+                updated_at DATETIME NOT NULL,
+                INDEX idx_workflows_name (name),
+                INDEX idx_workflows_updated (updated_at)
+            ) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci
+            "#,
+            r#"
+            CREATE TABLE IF NOT EXISTS element_libraries (
+                id VARCHAR(36) PRIMARY KEY NOT NULL,
+                name VARCHAR(255) NOT NULL,
+                description TEXT,
+                elements LONGTEXT NOT NULL,
+                created_at DATETIME NOT NULL,
+                updated_at DATETIME NOT NULL,
+                INDEX idx_element_libraries_name (name)
+            ) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci
+            "#,
+            r#"
+            CREATE TABLE IF NOT EXISTS settings (
+                `key` VARCHAR(255) PRIMARY KEY NOT NULL,
+                value LONGTEXT NOT NULL
+            ) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci
+            "#,
+            r#"
+            CREATE TABLE IF NOT EXISTS execution_logs (
+                id VARCHAR(36) PRIMARY KEY NOT NULL,
+                workflow_id VARCHAR(36) NOT NULL,
+                status VARCHAR(50) NOT NULL,
+                started_at DATETIME NOT NULL,
+                finished_at DATETIME,
+                error_message TEXT,
+                log_data LONGTEXT,
+                INDEX idx_execution_logs_workflow (workflow_id),
+                INDEX idx_execution_logs_status (status),
+                INDEX idx_execution_logs_started (started_at)
+            ) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci
+            "#,
+        ],
+        DatabaseType::Postgres => vec![
+            r#"
+            CREATE TABLE IF NOT EXISTS workflows (
+                id VARCHAR(36) PRIMARY KEY NOT NULL,
+                name VARCHAR(255) NOT NULL,
+                description TEXT,
+                nodes JSONB NOT NULL,
+                edges JSONB NOT NULL,
+                variables JSONB NOT NULL,
+                created_at TIMESTAMP NOT NULL,
+                updated_at TIMESTAMP NOT NULL
+            )
+            "#,
+            "CREATE INDEX IF NOT EXISTS idx_workflows_name ON workflows(name)",
+            "CREATE INDEX IF NOT EXISTS idx_workflows_updated ON workflows(updated_at)",
+            r#"
+            CREATE TABLE IF NOT EXISTS element_libraries (
+                id VARCHAR(36) PRIMARY KEY NOT NULL,
+                name VARCHAR(255) NOT NULL,
+                description TEXT,
+                elements JSONB NOT NULL,
+                created_at TIMESTAMP NOT NULL,
+                updated_at TIMESTAMP NOT NULL
+            )
+            "#,
+            "CREATE INDEX IF NOT EXISTS idx_element_libraries_name ON element_libraries(name)",
+            r#"
+            CREATE TABLE IF NOT EXISTS settings (
+                key VARCHAR(255) PRIMARY KEY NOT NULL,
+                value JSONB NOT NULL
+            )
+            "#,
+            r#"
+            CREATE TABLE IF NOT EXISTS execution_logs (
+                id VARCHAR(36) PRIMARY KEY NOT NULL,
+                workflow_id VARCHAR(36) NOT NULL,
+                status VARCHAR(50) NOT NULL,
+                started_at TIMESTAMP NOT NULL,
+                finished_at TIMESTAMP,
+                error_message TEXT,
+                log_data JSONB
+            )
+            "#,
+            "CREATE INDEX IF NOT EXISTS idx_execution_logs_workflow ON execution_logs(workflow_id)",
+            "CREATE INDEX IF NOT EXISTS idx_execution_logs_status ON execution_logs(status)",
+            "CREATE INDEX IF NOT EXISTS idx_execution_logs_started ON execution_logs(started_at)",
+        ],
+    }
+}
+
+/// Version 2: timestamps were written as RFC3339 strings; use timezone-aware columns
+fn native_timestamps(db_type: &DatabaseType) -> Vec<&'static str> {
+    match db_type {
+        // SQLite has no date/time type; RFC3339 text is already the canonical form
+        DatabaseType::Sqlite => vec![],
+        DatabaseType::Mysql => vec![
+            "ALTER TABLE workflows MODIFY created_at DATETIME(6) NOT NULL, MODIFY updated_at DATETIME(6) NOT NULL",
+            "ALTER TABLE element_libraries MODIFY created_at DATETIME(6) NOT NULL, MODIFY updated_at DATETIME(6) NOT NULL",
+            "ALTER TABLE execution_logs MODIFY started_at DATETIME(6) NOT NULL, MODIFY finished_at DATETIME(6)",
+        ],
+        DatabaseType::Postgres => vec![
+            r#"
+            ALTER TABLE workflows
+                ALTER COLUMN created_at TYPE TIMESTAMPTZ USING created_at AT TIME ZONE 'UTC',
+                ALTER COLUMN updated_at TYPE TIMESTAMPTZ USING updated_at AT TIME ZONE 'UTC'
+            "#,
+            r#"
+            ALTER TABLE element_libraries
+                ALTER COLUMN created_at TYPE TIMESTAMPTZ USING created_at AT TIME ZONE 'UTC',
+                ALTER COLUMN updated_at TYPE TIMESTAMPTZ USING updated_at AT TIME ZONE 'UTC'
+            "#,
+            r#"
+            ALTER TABLE execution_logs
+                ALTER COLUMN started_at TYPE TIMESTAMPTZ USING started_at AT TIME ZONE 'UTC',
+                ALTER COLUMN finished_at TYPE TIMESTAMPTZ USING finished_at AT TIME ZONE 'UTC'
+            "#,
+        ],
+    }
+}
+
+/// Version 3: entities map JSON documents to `String`, which PostgreSQL refuses to bind to JSONB
+fn json_as_text(db_type: &DatabaseType) -> Vec<&'static str> {
+    match db_type {
+        DatabaseType::Sqlite | DatabaseType::Mysql => vec![],
+        DatabaseType::Postgres => vec![
+            r#"
+            ALTER TABLE workflows
+                ALTER COLUMN nodes TYPE TEXT USING nodes::text,
+                ALTER COLUMN edges TYPE TEXT USING edges::text,
+                ALTER COLUMN variables TYPE TEXT USING variables::text
+            "#,
+            "ALTER TABLE element_libraries ALTER COLUMN elements TYPE TEXT USING elements::text",
+            "ALTER TABLE settings ALTER COLUMN value TYPE TEXT USING value::text",
+            "ALTER TABLE execution_logs ALTER COLUMN log_data TYPE TEXT USING log_data::text",
+        ],
+    }
+}
+
+/// Version 4: saved recorder sessions
+fn recordings_table(db_type: &DatabaseType) -> Vec<&'static str> {
+    match db_type {
+        DatabaseType::Sqlite => vec![
+            r#"
+            CREATE TABLE IF NOT EXISTS recordings (
+                id TEXT PRIMARY KEY NOT NULL,
+                name TEXT NOT NULL,
+                source TEXT NOT NULL,
+                actions TEXT NOT NULL,
+                started_at TEXT,
+                ended_at TEXT,
+                created_at TEXT NOT NULL
+            )
+            "#,
+        ],
+        DatabaseType::Mysql => vec![
+            r#"
+            CREATE TABLE IF NOT EXISTS recordings (
+                id VARCHAR(36) PRIMARY KEY NOT NULL,
+                name VARCHAR(255) NOT NULL,
+                source VARCHAR(50) NOT NULL,
+                actions LONGTEXT NOT NULL,
+                started_at DATETIME(6),
+                ended_at DATETIME(6),
+                created_at DATETIME(6) NOT NULL,
+                INDEX idx_recordings_created (created_at)
+            ) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci
+            "#,
+        ],
+        DatabaseType::Postgres => vec![
+            r#"
+            CREATE TABLE IF NOT EXISTS recordings (
+                id VARCHAR(36) PRIMARY KEY NOT NULL,
+                name VARCHAR(255) NOT NULL,
+                source VARCHAR(50) NOT NULL,
+                actions TEXT NOT NULL,
+                started_at TIMESTAMPTZ,
+                ended_at TIMESTAMPTZ,
+                created_at TIMESTAMPTZ NOT NULL
+            )
+            "#,
+            "CREATE INDEX IF NOT EXISTS idx_recordings_created ON recordings(created_at)",
+        ],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sea_orm::Database;
+
+    #[test]
+    fn test_versions_are_increasing() {
+        let versions: Vec<i64> = MIGRATIONS.iter().map(|m| m.version).collect();
+        assert!(versions.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(latest_version(), *versions.last().unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_migrations_are_idempotent() {
+        let db = Database::connect("sqlite::memory:").await.unwrap();
+
+        let version = run_migrations(&db, &DatabaseType::Sqlite).await.unwrap();
+        assert_eq!(version, latest_version());
+
+        // Running again must not reapply anything
+        let version = run_migrations(&db, &DatabaseType::Sqlite).await.unwrap();
+        assert_eq!(version, latest_version());
+        assert_eq!(current_version(&db).await.unwrap(), latest_version());
+    }
+}
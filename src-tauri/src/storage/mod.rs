@@ -1,11 +1,14 @@
 pub mod config;
 pub mod entities;
+pub mod migration;
+pub mod transfer;
+pub mod backup;
 
 use sea_orm::{Database, DatabaseConnection, DbErr, ConnectionTrait, Statement, DatabaseBackend};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
-pub use config::{AppConfig, DatabaseConfig, DatabaseType};
+pub use config::{AppConfig, BackupConfig, DatabaseConfig, DatabaseType};
 pub use entities::*;
 
 /// Global database connection
@@ -14,7 +17,7 @@ static DB_CONNECTION: std::sync::OnceLock<Arc<RwLock<Option<DatabaseConnection>>
 /// Global configuration
 static APP_CONFIG: std::sync::OnceLock<Arc<RwLock<AppConfig>>> = std::sync::OnceLock::new();
 
-/// Initialize database connection and create tables
+/// Initialize database connection and apply pending migrations
 pub async fn init_database() -> Result<(), DbErr> {
     // Load configuration
     let config = AppConfig::load();
@@ -25,8 +28,8 @@ pub async fn init_database() -> Result<(), DbErr> {
 
     let db = Database::connect(&db_url).await?;
 
-    // Create tables based on database type
-    create_tables(&db, &config.database.db_type).await?;
+    // Bring the schema up to date
+    migration::run_migrations(&db, &config.database.db_type).await?;
 
     // Store connection globally
     let connection = DB_CONNECTION.get_or_init(|| Arc::new(RwLock::new(None)));
@@ -51,8 +54,8 @@ pub async fn init_database_with_config(config: DatabaseConfig) -> Result<(), DbE
 
     let db = Database::connect(&db_url).await?;
 
-    // Create tables based on database type
-    create_tables(&db, &config.db_type).await?;
+    // Bring the schema up to date
+    migration::run_migrations(&db, &config.db_type).await?;
 
     // Store connection globally
     let connection = DB_CONNECTION.get_or_init(|| Arc::new(RwLock::new(None)));
@@ -97,193 +100,6 @@ pub async fn get_connection() -> Result<DatabaseConnection, DbErr> {
     }
 }
 
-/// Create database tables based on database type
-async fn create_tables(db: &DatabaseConnection, db_type: &DatabaseType) -> Result<(), DbErr> {
-    let backend = db.get_database_backend();
-
-    // Create workflows table
-    let workflows_sql = match db_type {
-        DatabaseType::Sqlite => r#"
-            CREATE TABLE IF NOT EXISTS workflows (
-                id TEXT PRIMARY KEY NOT NULL,
-                name TEXT NOT NULL,
-                description TEXT,
-                nodes TEXT NOT NULL,
-                edges TEXT NOT NULL,
-                variables TEXT NOT NULL,
-                created_at TEXT NOT NULL,
-                updated_at TEXT NOT NULL
-            )
-        "#.to_string(),
-        DatabaseType::Mysql => r#"
-            CREATE TABLE IF NOT EXISTS workflows (
-                id VARCHAR(36) PRIMARY KEY NOT NULL,
-                name VARCHAR(255) NOT NULL,
-                description TEXT,
-                nodes LONGTEXT NOT NULL,
-                edges LONGTEXT NOT NULL,
-                variables LONGTEXT NOT NULL,
-                created_at DATETIME NOT NULL,
-                updated_at DATETIME NOT NULL,
-                INDEX idx_workflows_name (name),
-                INDEX idx_workflows_updated (updated_at)
-            ) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci
-        "#.to_string(),
-        DatabaseType::Postgres => r#"
-            CREATE TABLE IF NOT EXISTS workflows (
-                id VARCHAR(36) PRIMARY KEY NOT NULL,
-                name VARCHAR(255) NOT NULL,
-                description TEXT,
-                nodes JSONB NOT NULL,
-                edges JSONB NOT NULL,
-                variables JSONB NOT NULL,
-                created_at TIMESTAMP NOT NULL,
-                updated_at TIMESTAMP NOT NULL
-            )
-        "#.to_string(),
-    };
-    db.execute(Statement::from_string(backend, workflows_sql)).await?;
-
-    // Create indexes for PostgreSQL (separate statements)
-    if matches!(db_type, DatabaseType::Postgres) {
-        let _ = db.execute(Statement::from_string(
-            backend,
-            "CREATE INDEX IF NOT EXISTS idx_workflows_name ON workflows(name)".to_string(),
-        )).await;
-        let _ = db.execute(Statement::from_string(
-            backend,
-            "CREATE INDEX IF NOT EXISTS idx_workflows_updated ON workflows(updated_at)".to_string(),
-        )).await;
-    }
-
-    // Create element_libraries table
-    let element_libraries_sql = match db_type {
-        DatabaseType::Sqlite => r#"
-            CREATE TABLE IF NOT EXISTS element_libraries (
-                id TEXT PRIMARY KEY NOT NULL,
-                name TEXT NOT NULL,
-                description TEXT,
-                elements TEXT NOT NULL,
-                created_at TEXT NOT NULL,
-                updated_at TEXT NOT NULL
-            )
-        "#.to_string(),
-        DatabaseType::Mysql => r#"
-            CREATE TABLE IF NOT EXISTS element_libraries (
-                id VARCHAR(36) PRIMARY KEY NOT NULL,
-                name VARCHAR(255) NOT NULL,
-                description TEXT,
-                elements LONGTEXT NOT NULL,
-                created_at DATETIME NOT NULL,
-                updated_at DATETIME NOT NULL,
-                INDEX idx_element_libraries_name (name)
-            ) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci
-        "#.to_string(),
-        DatabaseType::Postgres => r#"
-            CREATE TABLE IF NOT EXISTS element_libraries (
-                id VARCHAR(36) PRIMARY KEY NOT NULL,
-                name VARCHAR(255) NOT NULL,
-                description TEXT,
-                elements JSONB NOT NULL,
-                created_at TIMESTAMP NOT NULL,
-                updated_at TIMESTAMP NOT NULL
-            )
-        "#.to_string(),
-    };
-    db.execute(Statement::from_string(backend, element_libraries_sql)).await?;
-
-    // Create indexes for PostgreSQL element_libraries
-    if matches!(db_type, DatabaseType::Postgres) {
-        let _ = db.execute(Statement::from_string(
-            backend,
-            "CREATE INDEX IF NOT EXISTS idx_element_libraries_name ON element_libraries(name)".to_string(),
-        )).await;
-    }
-
-    // Create settings table
-    let settings_sql = match db_type {
-        DatabaseType::Sqlite => r#"
-            CREATE TABLE IF NOT EXISTS settings (
-                key TEXT PRIMARY KEY NOT NULL,
-                value TEXT NOT NULL
-            )
-        "#.to_string(),
-        DatabaseType::Mysql => r#"
-            CREATE TABLE IF NOT EXISTS settings (
-                `key` VARCHAR(255) PRIMARY KEY NOT NULL,
-                value LONGTEXT NOT NULL
-            ) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci
-        "#.to_string(),
-        DatabaseType::Postgres => r#"
-            CREATE TABLE IF NOT EXISTS settings (
-                key VARCHAR(255) PRIMARY KEY NOT NULL,
-                value JSONB NOT NULL
-            )
-        "#.to_string(),
-    };
-    db.execute(Statement::from_string(backend, settings_sql)).await?;
-
-    // Create execution_logs table for tracking workflow executions
-    let execution_logs_sql = match db_type {
-        DatabaseType::Sqlite => r#"
-            CREATE TABLE IF NOT EXISTS execution_logs (
-                id TEXT PRIMARY KEY NOT NULL,
-                workflow_id TEXT NOT NULL,
-                status TEXT NOT NULL,
-                started_at TEXT NOT NULL,
-                finished_at TEXT,
-                error_message TEXT,
-                log_data TEXT
-            )
-        "#.to_string(),
-        DatabaseType::Mysql => r#"
-            CREATE TABLE IF NOT EXISTS execution_logs (
-                id VARCHAR(36) PRIMARY KEY NOT NULL,
-                workflow_id VARCHAR(36) NOT NULL,
-                status VARCHAR(50) NOT NULL,
-                started_at DATETIME NOT NULL,
-                finished_at DATETIME,
-                error_message TEXT,
-                log_data LONGTEXT,
-                INDEX idx_execution_logs_workflow (workflow_id),
-                INDEX idx_execution_logs_status (status),
-                INDEX idx_execution_logs_started (started_at)
-            ) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci
-        "#.to_string(),
-        DatabaseType::Postgres => r#"
-            CREATE TABLE IF NOT EXISTS execution_logs (
-                id VARCHAR(36) PRIMARY KEY NOT NULL,
-                workflow_id VARCHAR(36) NOT NULL,
-                status VARCHAR(50) NOT NULL,
-                started_at TIMESTAMP NOT NULL,
-                finished_at TIMESTAMP,
-                error_message TEXT,
-                log_data JSONB
-            )
-        "#.to_string(),
-    };
-    db.execute(Statement::from_string(backend, execution_logs_sql)).await?;
-
-    // Create indexes for PostgreSQL execution_logs
-    if matches!(db_type, DatabaseType::Postgres) {
-        let _ = db.execute(Statement::from_string(
-            backend,
-            "CREATE INDEX IF NOT EXISTS idx_execution_logs_workflow ON execution_logs(workflow_id)".to_string(),
-        )).await;
-        let _ = db.execute(Statement::from_string(
-            backend,
-            "CREATE INDEX IF NOT EXISTS idx_execution_logs_status ON execution_logs(status)".to_string(),
-        )).await;
-        let _ = db.execute(Statement::from_string(
-            backend,
-            "CREATE INDEX IF NOT EXISTS idx_execution_logs_started ON execution_logs(started_at)".to_string(),
-        )).await;
-    }
-
-    log::info!("Database tables created successfully");
-    Ok(())
-}
-
 /// Get current database configuration
 pub async fn get_database_config() -> DatabaseConfig {
     let app_config = APP_CONFIG.get_or_init(|| Arc::new(RwLock::new(AppConfig::default())));
@@ -299,6 +115,21 @@ pub async fn update_database_config(config: DatabaseConfig) -> Result<(), String
     cfg.save()
 }
 
+/// Get current backup configuration
+pub async fn get_backup_config() -> BackupConfig {
+    let app_config = APP_CONFIG.get_or_init(|| Arc::new(RwLock::new(AppConfig::default())));
+    let cfg = app_config.read().await;
+    cfg.backup.clone()
+}
+
+/// Update backup configuration
+pub async fn update_backup_config(config: BackupConfig) -> Result<(), String> {
+    let app_config = APP_CONFIG.get_or_init(|| Arc::new(RwLock::new(AppConfig::default())));
+    let mut cfg = app_config.write().await;
+    cfg.backup = config;
+    cfg.save()
+}
+
 /// Test database connection without changing current connection
 pub async fn test_database_connection(config: &DatabaseConfig) -> Result<(), DbErr> {
     let db_url = config.get_connection_url();
@@ -0,0 +1,399 @@
+//! Copy application data between database backends
+//!
+//! Both databases are first brought to the latest schema version, then every
+//! table is copied in primary-key order in fixed-size batches. All batches go
+//! through a single target transaction and row counts are compared before it
+//! commits, so a failed transfer leaves the target empty and can be retried.
+
+use super::config::DatabaseConfig;
+use super::migration;
+use sea_orm::sea_query::{Alias, Expr, Order, Query, SimpleExpr};
+use sea_orm::{
+    ConnectionTrait, Database, DatabaseConnection, DbErr, QueryResult, TransactionTrait, Value,
+};
+use serde::Serialize;
+use std::time::Instant;
+
+/// Default number of rows copied per batch
+pub const DEFAULT_BATCH_SIZE: u64 = 200;
+
+/// How a column is read from the source and bound on the target
+#[derive(Debug, Clone, Copy)]
+pub enum ColumnKind {
+    Text,
+    NullableText,
+    Timestamp,
+    NullableTimestamp,
+}
+
+/// Description of a table copied by [`transfer_data`]
+#[derive(Debug)]
+pub struct TableSpec {
+    pub name: &'static str,
+    pub primary_key: &'static str,
+    pub columns: &'static [(&'static str, ColumnKind)],
+}
+
+/// Application tables, in copy order
+pub const TABLES: &[TableSpec] = &[
+    TableSpec {
+        name: "workflows",
+        primary_key: "id",
+        columns: &[
+            ("id", ColumnKind::Text),
+            ("name", ColumnKind::Text),
+            ("description", ColumnKind::NullableText),
+            ("nodes", ColumnKind::Text),
+            ("edges", ColumnKind::Text),
+            ("variables", ColumnKind::Text),
+            ("created_at", ColumnKind::Timestamp),
+            ("updated_at", ColumnKind::Timestamp),
+        ],
+    },
+    TableSpec {
+        name: "element_libraries",
+        primary_key: "id",
+        columns: &[
+            ("id", ColumnKind::Text),
+            ("name", ColumnKind::Text),
+            ("description", ColumnKind::NullableText),
+            ("elements", ColumnKind::Text),
+            ("created_at", ColumnKind::Timestamp),
+            ("updated_at", ColumnKind::Timestamp),
+        ],
+    },
+    TableSpec {
+        name: "settings",
+        primary_key: "key",
+        columns: &[("key", ColumnKind::Text), ("value", ColumnKind::Text)],
+    },
+    TableSpec {
+        name: "execution_logs",
+        primary_key: "id",
+        columns: &[
+            ("id", ColumnKind::Text),
+            ("workflow_id", ColumnKind::Text),
+            ("status", ColumnKind::Text),
+            ("started_at", ColumnKind::Timestamp),
+            ("finished_at", ColumnKind::NullableTimestamp),
+            ("error_message", ColumnKind::NullableText),
+            ("log_data", ColumnKind::NullableText),
+        ],
+    },
+    TableSpec {
+        name: "recordings",
+        primary_key: "id",
+        columns: &[
+            ("id", ColumnKind::Text),
+            ("name", ColumnKind::Text),
+            ("source", ColumnKind::Text),
+            ("actions", ColumnKind::Text),
+            ("started_at", ColumnKind::NullableTimestamp),
+            ("ended_at", ColumnKind::NullableTimestamp),
+            ("created_at", ColumnKind::Timestamp),
+        ],
+    },
+];
+
+/// Progress of a running transfer
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransferProgress {
+    pub table: String,
+    pub table_copied: u64,
+    pub table_total: u64,
+    pub copied: u64,
+    pub total: u64,
+}
+
+/// Per-table result of a transfer
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TableTransferReport {
+    pub table: String,
+    pub source_rows: u64,
+    pub target_rows: u64,
+}
+
+/// Result of a completed transfer
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransferReport {
+    pub tables: Vec<TableTransferReport>,
+    pub total_rows: u64,
+    pub duration_ms: u64,
+}
+
+/// Copy all application tables from one database to another
+///
+/// The target must not contain any application data yet.
+pub async fn transfer_data<F>(
+    from: &DatabaseConfig,
+    to: &DatabaseConfig,
+    batch_size: u64,
+    on_progress: F,
+) -> Result<TransferReport, DbErr>
+where
+    F: Fn(&TransferProgress),
+{
+    if from.get_connection_url() == to.get_connection_url() {
+        return Err(DbErr::Custom(
+            "Source and target databases are the same".to_string(),
+        ));
+    }
+
+    let started = Instant::now();
+    let batch_size = batch_size.max(1);
+
+    let source = Database::connect(&from.get_connection_url()).await?;
+    let target = Database::connect(&to.get_connection_url()).await?;
+
+    migration::run_migrations(&source, &from.db_type).await?;
+    migration::run_migrations(&target, &to.db_type).await?;
+
+    let mut totals = Vec::with_capacity(TABLES.len());
+    for table in TABLES {
+        if count_rows(&target, table.name).await? > 0 {
+            return Err(DbErr::Custom(format!(
+                "Target table '{}' is not empty",
+                table.name
+            )));
+        }
+        totals.push(count_rows(&source, table.name).await?);
+    }
+
+    let total: u64 = totals.iter().sum();
+    let mut copied = 0;
+
+    // Dropping the transaction on an early return rolls every batch back
+    let txn = target.begin().await?;
+
+    for (table, &table_total) in TABLES.iter().zip(&totals) {
+        log::info!("Copying {} rows from '{}'", table_total, table.name);
+
+        let mut table_copied = 0;
+        on_progress(&TransferProgress {
+            table: table.name.to_string(),
+            table_copied,
+            table_total,
+            copied,
+            total,
+        });
+
+        while table_copied < table_total {
+            let rows = read_batch(&source, table, table_copied, batch_size).await?;
+            if rows.is_empty() {
+                break;
+            }
+
+            let count = rows.len() as u64;
+            insert_rows(&txn, table, rows).await?;
+
+            table_copied += count;
+            copied += count;
+            on_progress(&TransferProgress {
+                table: table.name.to_string(),
+                table_copied,
+                table_total,
+                copied,
+                total,
+            });
+        }
+    }
+
+    // Verify that every row arrived
+    let mut tables = Vec::with_capacity(TABLES.len());
+    for (table, &source_rows) in TABLES.iter().zip(&totals) {
+        let target_rows = count_rows(&txn, table.name).await?;
+        if target_rows != source_rows {
+            return Err(DbErr::Custom(format!(
+                "Row count mismatch for '{}': source has {}, target has {}",
+                table.name, source_rows, target_rows
+            )));
+        }
+        tables.push(TableTransferReport {
+            table: table.name.to_string(),
+            source_rows,
+            target_rows,
+        });
+    }
+    txn.commit().await?;
+
+    let _ = source.close().await;
+    let _ = target.close().await;
+
+    Ok(TransferReport {
+        tables,
+        total_rows: total,
+        duration_ms: started.elapsed().as_millis() as u64,
+    })
+}
+
+/// Count the rows of a table
+pub async fn count_rows<C: ConnectionTrait>(db: &C, table: &str) -> Result<u64, DbErr> {
+    let query = Query::select()
+        .expr_as(Expr::cust("COUNT(*)"), Alias::new("count"))
+        .from(Alias::new(table))
+        .to_owned();
+
+    let row = db
+        .query_one(db.get_database_backend().build(&query))
+        .await?
+        .ok_or_else(|| DbErr::Custom(format!("Failed to count rows of '{}'", table)))?;
+
+    Ok(row.try_get::<i64>("", "count")?.max(0) as u64)
+}
+
+/// Read a batch of rows in primary-key order, values ordered like `table.columns`
+pub(crate) async fn read_batch<C: ConnectionTrait>(
+    db: &C,
+    table: &TableSpec,
+    offset: u64,
+    limit: u64,
+) -> Result<Vec<Vec<Value>>, DbErr> {
+    let query = Query::select()
+        .columns(table.columns.iter().map(|(name, _)| Alias::new(*name)))
+        .from(Alias::new(table.name))
+        .order_by(Alias::new(table.primary_key), Order::Asc)
+        .limit(limit)
+        .offset(offset)
+        .to_owned();
+
+    let rows = db.query_all(db.get_database_backend().build(&query)).await?;
+
+    rows.iter()
+        .map(|row| {
+            table
+                .columns
+                .iter()
+                .map(|(name, kind)| read_value(row, name, *kind))
+                .collect()
+        })
+        .collect()
+}
+
+fn read_value(row: &QueryResult, column: &str, kind: ColumnKind) -> Result<Value, DbErr> {
+    Ok(match kind {
+        ColumnKind::Text => row.try_get::<String>("", column)?.into(),
+        ColumnKind::NullableText => row.try_get::<Option<String>>("", column)?.into(),
+        ColumnKind::Timestamp => row.try_get::<chrono::DateTime<chrono::Utc>>("", column)?.into(),
+        ColumnKind::NullableTimestamp => row
+            .try_get::<Option<chrono::DateTime<chrono::Utc>>>("", column)?
+            .into(),
+    })
+}
+
+/// Insert rows with values ordered like `table.columns`
+pub(crate) async fn insert_rows<C: ConnectionTrait>(
+    db: &C,
+    table: &TableSpec,
+    rows: Vec<Vec<Value>>,
+) -> Result<(), DbErr> {
+    if rows.is_empty() {
+        return Ok(());
+    }
+
+    let mut insert = Query::insert();
+    insert
+        .into_table(Alias::new(table.name))
+        .columns(table.columns.iter().map(|(name, _)| Alias::new(*name)));
+
+    for row in rows {
+        insert
+            .values(row.into_iter().map(SimpleExpr::from))
+            .map_err(|e| DbErr::Custom(format!("Invalid row for '{}': {}", table.name, e)))?;
+    }
+
+    db.execute(db.get_database_backend().build(&insert)).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::config::DatabaseType;
+
+    fn sqlite_config(path: &std::path::Path) -> DatabaseConfig {
+        DatabaseConfig {
+            db_type: DatabaseType::Sqlite,
+            database: path.to_string_lossy().to_string(),
+            ..DatabaseConfig::sqlite()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_transfer_between_sqlite_files() {
+        let dir = std::env::temp_dir().join(format!("rpa-transfer-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let from = sqlite_config(&dir.join("from.db"));
+        let to = sqlite_config(&dir.join("to.db"));
+
+        let source = Database::connect(&from.get_connection_url()).await.unwrap();
+        migration::run_migrations(&source, &DatabaseType::Sqlite).await.unwrap();
+        for i in 0..5 {
+            let now = chrono::Utc::now().to_rfc3339();
+            source
+                .execute_unprepared(&format!(
+                    "INSERT INTO workflows VALUES ('wf-{i}', 'Workflow {i}', NULL, '[]', '[]', '[]', '{now}', '{now}')"
+                ))
+                .await
+                .unwrap();
+        }
+        source.close().await.unwrap();
+
+        let report = transfer_data(&from, &to, 2, |_| {}).await.unwrap();
+        assert_eq!(report.total_rows, 5);
+
+        let target = Database::connect(&to.get_connection_url()).await.unwrap();
+        assert_eq!(count_rows(&target, "workflows").await.unwrap(), 5);
+
+        // A second run must refuse to write into a populated target
+        assert!(transfer_data(&from, &to, 2, |_| {}).await.is_err());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_failed_transfer_leaves_target_empty() {
+        let dir = std::env::temp_dir().join(format!("rpa-transfer-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let from = sqlite_config(&dir.join("from.db"));
+        let to = sqlite_config(&dir.join("to.db"));
+
+        let source = Database::connect(&from.get_connection_url()).await.unwrap();
+        migration::run_migrations(&source, &DatabaseType::Sqlite).await.unwrap();
+        let now = chrono::Utc::now().to_rfc3339();
+        source
+            .execute_unprepared(&format!(
+                "INSERT INTO workflows VALUES ('wf-1', 'Workflow', NULL, '[]', '[]', '[]', '{now}', '{now}')"
+            ))
+            .await
+            .unwrap();
+        // Workflows are copied first; this row then fails to read
+        source
+            .execute_unprepared(&format!(
+                "INSERT INTO element_libraries VALUES ('lib-1', 'Lib', NULL, '[]', 'not a date', '{now}')"
+            ))
+            .await
+            .unwrap();
+
+        assert!(transfer_data(&from, &to, 1, |_| {}).await.is_err());
+
+        let target = Database::connect(&to.get_connection_url()).await.unwrap();
+        for table in TABLES {
+            assert_eq!(count_rows(&target, table.name).await.unwrap(), 0, "{}", table.name);
+        }
+        target.close().await.unwrap();
+
+        // Once the source is fixed the transfer can be retried
+        source
+            .execute_unprepared(&format!("UPDATE element_libraries SET created_at = '{now}'"))
+            .await
+            .unwrap();
+        source.close().await.unwrap();
+        let report = transfer_data(&from, &to, 1, |_| {}).await.unwrap();
+        assert_eq!(report.total_rows, 2);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
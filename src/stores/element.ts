@@ -1,6 +1,7 @@
 import { defineStore } from 'pinia';
 import { ref } from 'vue';
 import { invoke } from '@tauri-apps/api/core';
+import { listen, type UnlistenFn } from '@tauri-apps/api/event';
 import type { UIElement, ElementLibrary } from '@/types';
 
 export interface ElementLibraryInfo {
@@ -12,12 +13,19 @@ export interface ElementLibraryInfo {
   updated_at: string;
 }
 
+export interface CaptureOptions {
+  hotkeys?: string[];
+  screenshot?: boolean;
+}
+
 export const useElementStore = defineStore('element', () => {
   const libraries = ref<ElementLibrary[]>([]);
   const libraryList = ref<ElementLibraryInfo[]>([]);
   const currentLibrary = ref<ElementLibrary | null>(null);
   const selectedElement = ref<UIElement | null>(null);
   const isCapturing = ref(false);
+  const hoveredElement = ref<UIElement | null>(null);
+  let captureListeners: UnlistenFn[] = [];
   const isLoading = ref(false);
   const error = ref<string | null>(null);
 
@@ -62,13 +70,51 @@ export const useElementStore = defineStore('element', () => {
     selectedElement.value = element;
   }
 
-  // Start capture mode
-  function startCapture() {
-    isCapturing.value = true;
+  // Start capture mode: hover highlights elements, Ctrl+click or F2 captures, Esc exits
+  async function startCapture(
+    onCaptured?: (element: UIElement) => void,
+    options?: CaptureOptions
+  ): Promise<boolean> {
+    if (isCapturing.value) return true;
+
+    try {
+      error.value = null;
+      captureListeners = await Promise.all([
+        listen<UIElement>('element-capture-hover', (event) => {
+          hoveredElement.value = event.payload;
+        }),
+        listen<UIElement>('element-captured', (event) => {
+          selectedElement.value = event.payload;
+          onCaptured?.(event.payload);
+        }),
+        listen('element-capture-stopped', () => {
+          finishCapture();
+        }),
+      ]);
+      await invoke('start_element_capture', { options });
+      isCapturing.value = true;
+      return true;
+    } catch (e) {
+      error.value = String(e);
+      finishCapture();
+      return false;
+    }
   }
 
   // Stop capture mode
-  function stopCapture() {
+  async function stopCapture() {
+    try {
+      await invoke('stop_element_capture');
+    } catch (e) {
+      error.value = String(e);
+      finishCapture();
+    }
+  }
+
+  function finishCapture() {
+    captureListeners.forEach((unlisten) => unlisten());
+    captureListeners = [];
+    hoveredElement.value = null;
     isCapturing.value = false;
   }
 
@@ -185,6 +231,7 @@ export const useElementStore = defineStore('element', () => {
     currentLibrary,
     selectedElement,
     isCapturing,
+    hoveredElement,
     isLoading,
     error,
     createLibrary,
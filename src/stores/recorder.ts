@@ -4,6 +4,8 @@ import { invoke } from '@tauri-apps/api/core';
 
 export type RecordingState = 'Idle' | 'Recording' | 'Paused';
 
+export type RecordingSource = 'Desktop' | 'Web';
+
 export interface RecordedAction {
   id: string;
   action_type: string;
@@ -22,15 +24,39 @@ export interface RecordingSession {
   id: string;
   name: string;
   state: RecordingState;
+  source: RecordingSource;
   actions: RecordedAction[];
   started_at?: string;
   ended_at?: string;
 }
 
+export interface ConversionOptions {
+  delayThresholdMs?: number;
+  maxDelayMs?: number;
+  libraryId?: string;
+  libraryName?: string;
+}
+
+export interface RecordingInfo {
+  id: string;
+  name: string;
+  source: RecordingSource;
+  action_count: number;
+  started_at?: string;
+  ended_at?: string;
+  created_at: string;
+}
+
+export interface ReplaySummary {
+  played: number;
+  skipped: number;
+}
+
 export interface RecordingStatus {
   state: RecordingState;
   action_count: number;
   duration_ms: number;
+  text_capture_paused: boolean;
 }
 
 export const useRecorderStore = defineStore('recorder', () => {
@@ -38,7 +64,10 @@ export const useRecorderStore = defineStore('recorder', () => {
   const session = ref<RecordingSession | null>(null);
   const actionCount = ref(0);
   const durationMs = ref(0);
+  const textCapturePaused = ref(false);
   const error = ref<string | null>(null);
+  const recordings = ref<RecordingInfo[]>([]);
+  const isReplaying = ref(false);
 
   let statusInterval: ReturnType<typeof setInterval> | null = null;
 
@@ -65,6 +94,18 @@ export const useRecorderStore = defineStore('recorder', () => {
     }
   }
 
+  async function startWebRecording(name?: string, url?: string) {
+    try {
+      error.value = null;
+      await invoke('start_web_recording', { name, url });
+      state.value = 'Recording';
+      startStatusPolling();
+    } catch (e) {
+      error.value = String(e);
+      throw e;
+    }
+  }
+
   async function pauseRecording() {
     try {
       error.value = null;
@@ -107,6 +148,7 @@ export const useRecorderStore = defineStore('recorder', () => {
       state.value = status.state;
       actionCount.value = status.action_count;
       durationMs.value = status.duration_ms;
+      textCapturePaused.value = status.text_capture_paused;
     } catch (e) {
       console.error('Failed to fetch recording status:', e);
     }
@@ -123,10 +165,10 @@ export const useRecorderStore = defineStore('recorder', () => {
     }
   }
 
-  async function convertToWorkflow() {
+  async function convertToWorkflow(options?: ConversionOptions) {
     try {
       error.value = null;
-      const workflow = await invoke('convert_recording_to_workflow');
+      const workflow = await invoke('convert_recording_to_workflow', { options });
       return workflow;
     } catch (e) {
       error.value = String(e);
@@ -147,6 +189,64 @@ export const useRecorderStore = defineStore('recorder', () => {
     }
   }
 
+  async function saveRecording(name?: string): Promise<string> {
+    try {
+      error.value = null;
+      const id = await invoke<string>('save_recording', { name });
+      await fetchRecordings();
+      return id;
+    } catch (e) {
+      error.value = String(e);
+      throw e;
+    }
+  }
+
+  async function fetchRecordings() {
+    try {
+      recordings.value = await invoke<RecordingInfo[]>('list_recordings');
+    } catch (e) {
+      error.value = String(e);
+      throw e;
+    }
+  }
+
+  async function loadRecording(id: string): Promise<RecordingSession> {
+    try {
+      error.value = null;
+      const result = await invoke<RecordingSession>('load_recording', { id });
+      session.value = result;
+      actionCount.value = result.actions.length;
+      return result;
+    } catch (e) {
+      error.value = String(e);
+      throw e;
+    }
+  }
+
+  async function deleteRecording(id: string) {
+    try {
+      error.value = null;
+      await invoke('delete_recording', { id });
+      recordings.value = recordings.value.filter(r => r.id !== id);
+    } catch (e) {
+      error.value = String(e);
+      throw e;
+    }
+  }
+
+  async function replayRecording(id?: string, speed?: number): Promise<ReplaySummary> {
+    try {
+      error.value = null;
+      isReplaying.value = true;
+      return await invoke<ReplaySummary>('replay_recording', { id, speed });
+    } catch (e) {
+      error.value = String(e);
+      throw e;
+    } finally {
+      isReplaying.value = false;
+    }
+  }
+
   function startStatusPolling() {
     if (statusInterval) {
       clearInterval(statusInterval);
@@ -166,12 +266,16 @@ export const useRecorderStore = defineStore('recorder', () => {
     session,
     actionCount,
     durationMs,
+    textCapturePaused,
     error,
+    recordings,
+    isReplaying,
     isRecording,
     isPaused,
     isIdle,
     formattedDuration,
     startRecording,
+    startWebRecording,
     pauseRecording,
     resumeRecording,
     stopRecording,
@@ -179,5 +283,10 @@ export const useRecorderStore = defineStore('recorder', () => {
     fetchSession,
     convertToWorkflow,
     clearRecording,
+    saveRecording,
+    fetchRecordings,
+    loadRecording,
+    deleteRecording,
+    replayRecording,
   };
 });
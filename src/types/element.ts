@@ -23,6 +23,11 @@ export interface UIElement {
   createdAt: string;
 }
 
+export interface ElementTreeNode {
+  element: UIElement;
+  children: ElementTreeNode[];
+}
+
 export interface ElementLibrary {
   id: string;
   name: string;
@@ -32,8 +37,37 @@ export interface ElementLibrary {
   updatedAt: string;
 }
 
-export interface ElementLocator {
-  strategy: 'automationId' | 'name' | 'className' | 'xpath' | 'css' | 'image';
-  value: string;
-  fallback?: ElementLocator;
+export type LocatorRelation =
+  | 'leftOf'
+  | 'rightOf'
+  | 'above'
+  | 'below'
+  | 'near'
+  | 'within'
+  | 'nthChild';
+
+export interface RelativeLocator {
+  relation: LocatorRelation;
+  anchor: ElementLocator;
+  target?: ElementLocator;
+  index?: number;
+  maxDistance?: number;
 }
+
+export type ElementLocator =
+  | {
+      strategy: 'automationId' | 'name' | 'className' | 'xpath' | 'css';
+      value: string;
+      fallback?: ElementLocator;
+    }
+  | {
+      strategy: 'image';
+      /** Encoded PNG or JPEG bytes */
+      value: number[];
+      fallback?: ElementLocator;
+    }
+  | {
+      strategy: 'relative';
+      value: RelativeLocator;
+      fallback?: ElementLocator;
+    };
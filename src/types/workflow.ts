@@ -69,7 +69,29 @@ export type NodeType =
   | 'writeExcel'
   | 'executeCommand'
   | 'listDirectory'
-  | 'openApp';
+  | 'openApp'
+  | 'scroll'
+  | 'mouseMove'
+  | 'dragDrop'
+  | 'activateWindow'
+  | 'waitWindow'
+  | 'setWindowState'
+  | 'closeWindow'
+  | 'listWindows'
+  | 'launchProcess'
+  | 'waitProcess'
+  | 'checkProcess'
+  | 'killProcess'
+  | 'getClipboard'
+  | 'setClipboard'
+  | 'copyFile'
+  | 'moveFile'
+  | 'renameFile'
+  | 'deleteFile'
+  | 'createDirectory'
+  | 'fileInfo'
+  | 'waitFile'
+  | 'createTempFile';
 
 export interface NodeConfig {
   type: NodeType;
@@ -291,4 +313,156 @@ export const NODE_CONFIGS: Record<NodeType, NodeConfig> = {
     category: 'action',
     color: '#3b82f6',
   },
+  scroll: {
+    type: 'scroll',
+    label: '滚动',
+    icon: 'ScrollText',
+    category: 'action',
+    color: '#3b82f6',
+  },
+  mouseMove: {
+    type: 'mouseMove',
+    label: '移动鼠标',
+    icon: 'Move',
+    category: 'action',
+    color: '#3b82f6',
+  },
+  dragDrop: {
+    type: 'dragDrop',
+    label: '拖放',
+    icon: 'Hand',
+    category: 'action',
+    color: '#3b82f6',
+  },  activateWindow: {
+    type: 'activateWindow',
+    label: '激活窗口',
+    icon: 'AppWindow',
+    category: 'action',
+    color: '#3b82f6',
+  },
+  waitWindow: {
+    type: 'waitWindow',
+    label: '等待窗口',
+    icon: 'Clock',
+    category: 'action',
+    color: '#3b82f6',
+  },
+  setWindowState: {
+    type: 'setWindowState',
+    label: '设置窗口状态',
+    icon: 'Maximize2',
+    category: 'action',
+    color: '#3b82f6',
+  },
+  closeWindow: {
+    type: 'closeWindow',
+    label: '关闭窗口',
+    icon: 'X',
+    category: 'action',
+    color: '#3b82f6',
+  },
+  listWindows: {
+    type: 'listWindows',
+    label: '获取窗口列表',
+    icon: 'List',
+    category: 'action',
+    color: '#3b82f6',
+  },  launchProcess: {
+    type: 'launchProcess',
+    label: '启动进程',
+    icon: 'Play',
+    category: 'action',
+    color: '#3b82f6',
+  },
+  waitProcess: {
+    type: 'waitProcess',
+    label: '等待进程',
+    icon: 'Hourglass',
+    category: 'action',
+    color: '#3b82f6',
+  },
+  checkProcess: {
+    type: 'checkProcess',
+    label: '检查进程',
+    icon: 'Activity',
+    category: 'action',
+    color: '#3b82f6',
+  },
+  killProcess: {
+    type: 'killProcess',
+    label: '结束进程',
+    icon: 'Ban',
+    category: 'action',
+    color: '#3b82f6',
+  },
+  getClipboard: {
+    type: 'getClipboard',
+    label: '读取剪贴板',
+    icon: 'ClipboardPaste',
+    category: 'action',
+    color: '#3b82f6',
+  },
+  setClipboard: {
+    type: 'setClipboard',
+    label: '设置剪贴板',
+    icon: 'ClipboardCopy',
+    category: 'action',
+    color: '#3b82f6',
+  },
+  copyFile: {
+    type: 'copyFile',
+    label: '复制文件',
+    icon: 'Copy',
+    category: 'data',
+    color: '#22c55e',
+  },
+  moveFile: {
+    type: 'moveFile',
+    label: '移动文件',
+    icon: 'FolderInput',
+    category: 'data',
+    color: '#22c55e',
+  },
+  renameFile: {
+    type: 'renameFile',
+    label: '重命名文件',
+    icon: 'FilePen',
+    category: 'data',
+    color: '#22c55e',
+  },
+  deleteFile: {
+    type: 'deleteFile',
+    label: '删除文件',
+    icon: 'Trash2',
+    category: 'data',
+    color: '#22c55e',
+  },
+  createDirectory: {
+    type: 'createDirectory',
+    label: '创建目录',
+    icon: 'FolderPlus',
+    category: 'data',
+    color: '#22c55e',
+  },
+  fileInfo: {
+    type: 'fileInfo',
+    label: '文件信息',
+    icon: 'FileSearch',
+    category: 'data',
+    color: '#22c55e',
+  },
+  waitFile: {
+    type: 'waitFile',
+    label: '等待文件',
+    icon: 'FileClock',
+    category: 'data',
+    color: '#22c55e',
+  },
+  createTempFile: {
+    type: 'createTempFile',
+    label: '创建临时文件',
+    icon: 'FilePlus',
+    category: 'data',
+    color: '#22c55e',
+  },
 };